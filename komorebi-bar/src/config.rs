@@ -6,6 +6,7 @@ use color_eyre::eyre;
 use eframe::egui::Pos2;
 use eframe::egui::TextBuffer;
 use eframe::egui::Vec2;
+use komorebi_client::LayeredConfig;
 use komorebi_client::Rect;
 use komorebi_themes::KomobarTheme;
 use serde::Deserialize;
@@ -499,9 +500,8 @@ pub fn get_individual_spacing(
 
 impl KomobarConfig {
     pub fn read(path: &PathBuf) -> eyre::Result<Self> {
-        let content = std::fs::read_to_string(path)?;
         let mut value: Self = match path.extension().unwrap().to_string_lossy().as_str() {
-            "json" => serde_json::from_str(&content)?,
+            "json" => LayeredConfig::read(path)?.deserialize()?,
             _ => panic!("unsupported format"),
         };
 
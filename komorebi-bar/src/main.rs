@@ -16,6 +16,7 @@ use eframe::egui::ViewportBuilder;
 use font_loader::system_fonts;
 use hotwatch::EventKind;
 use hotwatch::Hotwatch;
+use komorebi_client::LayeredConfig;
 use komorebi_client::PathExt;
 use komorebi_client::SocketMessage;
 use komorebi_client::SubscribeOptions;
@@ -181,9 +182,7 @@ fn main() -> eyre::Result<()> {
         std::process::exit(0);
     }
 
-    let state = serde_json::from_str::<komorebi_client::State>(&komorebi_client::send_query(
-        &SocketMessage::State,
-    )?)?;
+    let state = komorebi_client::send_query::<komorebi_client::State>(&SocketMessage::State)?;
 
     let (usr_monitor_index, work_area_offset) = match &config.monitor {
         Some(MonitorConfigOrIndex::MonitorConfig(monitor_config)) => {
@@ -266,23 +265,34 @@ fn main() -> eyre::Result<()> {
     let (tx_config, rx_config) = crossbeam_channel::unbounded();
 
     let mut hotwatch = Hotwatch::new()?;
-    let config_path_cl = config_path.clone();
 
-    hotwatch.watch(config_path, move |event| match event.kind {
-        EventKind::Modify(_) | EventKind::Remove(_) => match KomobarConfig::read(&config_path_cl) {
-            Ok(updated) => {
-                tracing::info!("configuration file updated: {}", config_path_cl.display());
+    // changes to any of the files included by the configuration file should also be picked up
+    let watched_paths = LayeredConfig::read(&config_path)
+        .map(|layered| layered.files)
+        .unwrap_or_else(|_| vec![config_path.clone()]);
 
-                if let Err(error) = tx_config.send(updated) {
-                    tracing::error!("could not send configuration update to gui: {error}")
+    for watched_path in watched_paths {
+        let config_path_cl = config_path.clone();
+        let tx_config = tx_config.clone();
+
+        hotwatch.watch(watched_path, move |event| match event.kind {
+            EventKind::Modify(_) | EventKind::Remove(_) => {
+                match KomobarConfig::read(&config_path_cl) {
+                    Ok(updated) => {
+                        tracing::info!("configuration file updated: {}", config_path_cl.display());
+
+                        if let Err(error) = tx_config.send(updated) {
+                            tracing::error!("could not send configuration update to gui: {error}")
+                        }
+                    }
+                    Err(error) => {
+                        tracing::error!("{error}");
+                    }
                 }
             }
-            Err(error) => {
-                tracing::error!("{error}");
-            }
-        },
-        _ => {}
-    })?;
+            _ => {}
+        })?;
+    }
 
     tracing::info!("watching configuration file for changes");
 
@@ -81,6 +81,8 @@ pub struct KomorebiWorkspacesConfig {
     pub hide_empty_workspaces: bool,
     /// Display format of the workspace
     pub display: Option<WorkspacesDisplayFormat>,
+    /// Show the workspace sets from the komorebi configuration after the workspaces (default: false)
+    pub show_workspace_sets: Option<bool>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -223,6 +225,23 @@ impl Komorebi {
                     }
                 }
             }
+
+            if !bar.show_workspace_sets {
+                return;
+            }
+
+            for name in &monitor_info.workspace_sets {
+                let is_active = monitor_info.active_workspace_set.as_ref() == Some(name);
+                let response = SelectableFrame::new(is_active)
+                    .show(ui, |ui| bar.show_text(ctx, ui, name, is_active));
+
+                if response.clicked() {
+                    let message = FocusWorkspaceSet(name.clone());
+                    if Self::send_with_mouse_follow_off(monitor_info, message).is_ok() {
+                        monitor_info.active_workspace_set = Some(name.clone());
+                    }
+                }
+            }
         });
     }
 
@@ -382,6 +401,8 @@ pub struct WorkspacesBar {
     text_size: Vec2,
     /// Icon size (default: 12.5 * 1.4)
     icon_size: Vec2,
+    /// Show the workspace sets after the workspaces
+    show_workspace_sets: bool,
 }
 
 impl WorkspacesBar {
@@ -439,6 +460,7 @@ impl WorkspacesBar {
             renderer,
             icon_size: Vec2::splat(12.5),
             text_size: Vec2::splat(12.5 * 1.4),
+            show_workspace_sets: value.show_workspace_sets.unwrap_or_default(),
         })
     }
 
@@ -495,16 +517,21 @@ impl WorkspacesBar {
 
     /// Shows the workspace label (colored if selected).
     fn show_label(&self, ctx: &Context, ui: &mut Ui, ws: &WorkspaceInfo) -> Response {
+        self.show_text(ctx, ui, &ws.name, ws.is_selected)
+    }
+
+    /// Draws a label, highlighted with the selection colour when selected.
+    fn show_text(&self, ctx: &Context, ui: &mut Ui, text: &str, is_selected: bool) -> Response {
         Frame::NONE
             .inner_margin(Margin::same(ui.style().spacing.button_padding.y as i8))
             .show(ui, |ui| {
                 ui.set_min_height(self.icon_size.y);
-                if ws.is_selected {
+                if is_selected {
                     let text =
-                        RichText::new(&ws.name).color(ctx.style().visuals.selection.stroke.color);
+                        RichText::new(text).color(ctx.style().visuals.selection.stroke.color);
                     ui.add(Label::new(text).selectable(false))
                 } else {
-                    ui.add(Label::new(&ws.name).selectable(false))
+                    ui.add(Label::new(text).selectable(false))
                 }
             })
             .inner
@@ -784,6 +811,8 @@ pub struct MonitorInfo {
     pub focused_workspace_idx: Option<usize>,
     pub show_all_icons: bool,
     pub hide_empty_workspaces: bool,
+    pub workspace_sets: Vec<String>,
+    pub active_workspace_set: Option<String>,
 }
 
 impl Default for MonitorInfo {
@@ -799,6 +828,8 @@ impl Default for MonitorInfo {
             focused_workspace_idx: None,
             show_all_icons: false,
             hide_empty_workspaces: false,
+            workspace_sets: Vec::new(),
+            active_workspace_set: None,
         }
     }
 }
@@ -839,6 +870,8 @@ impl MonitorInfo {
             _ => return,
         };
         self.mouse_follows_focus = state.mouse_follows_focus;
+        self.workspace_sets = state.workspace_sets;
+        self.active_workspace_set = state.active_workspace_set;
 
         let monitor = &state.monitors.elements()[self.monitor_index];
         self.work_area_offset = monitor.work_area_offset;
@@ -2,12 +2,15 @@ pub use komorebi::DATA_DIR;
 pub use komorebi::Notification;
 pub use komorebi::NotificationEvent;
 pub use komorebi::animation::AnimationPrefix;
+pub use komorebi::config_check;
 pub use komorebi::container::Container;
 pub use komorebi::core::ApplicationIdentifier;
 pub use komorebi::core::Axis;
 pub use komorebi::core::CycleDirection;
 pub use komorebi::core::DefaultLayout;
+pub use komorebi::core::FocusFollowsMouseImplementation;
 pub use komorebi::core::Layout;
+pub use komorebi::core::MetricsFormat;
 pub use komorebi::core::MonocleFocusBehaviour;
 pub use komorebi::core::MoveBehaviour;
 pub use komorebi::core::OperationBehaviour;
@@ -15,34 +18,82 @@ pub use komorebi::core::OperationDirection;
 pub use komorebi::core::Rect;
 pub use komorebi::core::Sizing;
 pub use komorebi::core::SocketMessage;
+pub use komorebi::core::StackbarLabel;
+pub use komorebi::core::StackbarMode;
 pub use komorebi::core::StateQuery;
 pub use komorebi::core::SubscribeOptions;
 pub use komorebi::core::WindowKind;
 pub use komorebi::core::animation::AnimationStyle;
 pub use komorebi::core::asc::ApplicationSpecificConfiguration;
+use komorebi::core::ipc;
+use komorebi::core::ipc::Response;
+pub use komorebi::core::ipc::ResponseError;
+pub use komorebi::core::ipc::ResponseErrorKind;
+pub use komorebi::core::layered_config::LayeredConfig;
 pub use komorebi::core::pathext::PathExt;
 pub use komorebi::core::pathext::replace_env_in_path;
+pub use komorebi::core::state_path::StatePath;
+pub use komorebi::explain;
+pub use komorebi::metrics;
+pub use komorebi::monitor::MonitorInformation;
 pub use komorebi::monitor_reconciliator::MonitorNotification;
+pub use komorebi::profile::ProfileConfig;
+pub use komorebi::profile::ProfileMonitorConfig;
+pub use komorebi::profile::ProfileWorkspaceConfig;
+pub use komorebi::recorder;
+pub use komorebi::scratchpad::ScratchpadConfig;
+pub use komorebi::scratchpad::ScratchpadState;
 pub use komorebi::splash;
+pub use komorebi::state::GlobalState;
 pub use komorebi::state::State;
 pub use komorebi::static_config::StaticConfig;
 pub use komorebi::window::Window;
 pub use komorebi::workspace::Workspace;
 pub use komorebi::workspace::WorkspaceLayer;
+pub use komorebi::workspace_set::WorkspaceSetConfig;
+pub use komorebi::workspace_set::WorkspaceSetMember;
 pub use komorebi_themes::KomorebiTheme;
 pub use komorebi_themes::colour::Colour;
 pub use komorebi_themes::colour::Rgb;
+use serde::de::DeserializeOwned;
 use std::borrow::Borrow;
-use std::io::BufReader;
-use std::io::Read;
 use std::io::Write;
 use std::net::Shutdown;
 use std::os::unix::net::UnixListener;
 use std::os::unix::net::UnixStream;
 use std::time::Duration;
 
+#[cfg(feature = "tokio")]
+mod stream;
+#[cfg(feature = "tokio")]
+pub use stream::NOTIFICATION_BUFFER_SIZE;
+#[cfg(feature = "tokio")]
+pub use stream::NotificationEventKind;
+#[cfg(feature = "tokio")]
+pub use stream::NotificationStream;
+#[cfg(feature = "tokio")]
+pub use stream::StreamError;
+#[cfg(feature = "tokio")]
+pub use stream::subscribe_stream;
+#[cfg(feature = "tokio")]
+pub use stream::subscribe_stream_with_options;
+
 const KOMOREBI: &str = "komorebi.sock";
 
+/// How long to wait for komorebi to process a request and send back a response
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Response(#[from] ResponseError),
+}
+
+/// Sends a command without waiting to find out if it was processed successfully
 pub fn send_message(message: &SocketMessage) -> std::io::Result<()> {
     let socket = DATA_DIR.join(KOMOREBI);
     let mut stream = UnixStream::connect(socket)?;
@@ -67,20 +118,47 @@ where
     stream.write_all(msgs.as_bytes())
 }
 
-pub fn send_query(message: &SocketMessage) -> std::io::Result<String> {
+/// Sends a command and waits for komorebi to respond, returning the raw reply on success
+///
+/// The reply is empty for commands which are not queries
+pub fn send_request(message: &SocketMessage) -> Result<String, ClientError> {
     let socket = DATA_DIR.join(KOMOREBI);
 
     let mut stream = UnixStream::connect(socket)?;
-    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
     stream.set_write_timeout(Some(Duration::from_secs(1)))?;
-    stream.write_all(serde_json::to_string(message)?.as_bytes())?;
+    ipc::write_frame(&mut stream, &serde_json::to_vec(message)?)?;
     stream.shutdown(Shutdown::Write)?;
 
-    let mut reader = BufReader::new(stream);
-    let mut response = String::new();
-    reader.read_to_string(&mut response)?;
+    let (_, payload) = ipc::read_frame(&mut stream)?.ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "komorebi closed the connection without responding",
+        )
+    })?;
+
+    let response = serde_json::from_slice::<Response>(&payload)?;
+
+    Ok(Result::<String, ResponseError>::from(response)?)
+}
+
+/// Sends commands to be processed as a single [`SocketMessage::Transaction`], waiting for komorebi
+/// to respond
+///
+/// Unlike [`send_batch`], windows are only laid out once every command has been processed, and
+/// none of the commands take effect if any one of them fails
+pub fn send_transaction<Q>(messages: impl IntoIterator<Item = Q>) -> Result<String, ClientError>
+where
+    Q: Borrow<SocketMessage>,
+{
+    let steps = messages.into_iter().map(|m| m.borrow().clone()).collect();
+    send_request(&SocketMessage::Transaction(steps))
+}
 
-    Ok(response)
+/// Sends a query and deserializes the reply, e.g. into a [`State`], [`GlobalState`] or a
+/// `Vec<MonitorInformation>`
+pub fn send_query<T: DeserializeOwned>(message: &SocketMessage) -> Result<T, ClientError> {
+    Ok(serde_json::from_str(&send_request(message)?)?)
 }
 
 pub fn subscribe(name: &str) -> std::io::Result<UnixListener> {
@@ -0,0 +1,593 @@
+use crate::DATA_DIR;
+use crate::KOMOREBI;
+use crate::Notification;
+use crate::NotificationEvent;
+use crate::SocketMessage;
+use crate::SubscribeOptions;
+use futures_core::Stream;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::task::ready;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::UnixListener;
+use tokio::net::UnixStream;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::task::JoinHandle;
+
+/// How many items are buffered before further notifications are dropped and reported as
+/// [`StreamError::Lagged`]
+pub const NOTIFICATION_BUFFER_SIZE: usize = 64;
+
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the subscriber socket and komorebi's socket are checked for changes
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The variants of [`NotificationEvent`], used to filter a [`NotificationStream`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NotificationEventKind {
+    WindowManager,
+    Socket,
+    Monitor,
+}
+
+impl From<&NotificationEvent> for NotificationEventKind {
+    fn from(value: &NotificationEvent) -> Self {
+        match value {
+            NotificationEvent::WindowManager(_) => Self::WindowManager,
+            NotificationEvent::Socket(_) => Self::Socket,
+            NotificationEvent::Monitor(_) => Self::Monitor,
+        }
+    }
+}
+
+/// Something which interrupted a [`NotificationStream`]
+///
+/// None of these end the stream; notifications may have been missed, and the subscription has
+/// been registered again where needed.
+#[derive(Debug, thiserror::Error)]
+pub enum StreamError {
+    /// The consumer fell behind and this many notifications were dropped
+    #[error("{0} notifications were dropped because the stream fell behind")]
+    Lagged(u64),
+    /// komorebi removed the subscription after failing to reach the subscriber socket
+    #[error("komorebi removed the subscription as stale")]
+    Unsubscribed,
+    /// komorebi shut down or was restarted
+    #[error("komorebi was restarted")]
+    Restarted,
+    /// Reading from the subscriber socket failed
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// A [`Stream`] of notifications pushed by komorebi to a subscriber socket
+///
+/// Notifications are read from the socket by a background task as soon as they arrive, so that
+/// komorebi is never kept waiting, and are buffered until they are polled. Once
+/// [`NOTIFICATION_BUFFER_SIZE`] items are waiting to be consumed, further notifications are
+/// dropped and the number of dropped notifications is reported as a [`StreamError::Lagged`].
+///
+/// If reading from the socket fails, komorebi is restarted or komorebi removes the subscription,
+/// the error is yielded and the subscription is registered again as soon as komorebi is running.
+///
+/// Use [`NotificationStream::close`] to unregister the subscription. Dropping the stream only
+/// removes the subscriber socket, and komorebi unregisters it the next time it can't be reached.
+pub struct NotificationStream {
+    name: String,
+    directory: PathBuf,
+    receiver: mpsc::Receiver<Result<Notification, StreamError>>,
+    events: Option<Vec<NotificationEventKind>>,
+    task: JoinHandle<()>,
+    closed: bool,
+}
+
+impl NotificationStream {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Only yield notifications for the given kinds of events
+    pub fn filter_events(mut self, kinds: impl IntoIterator<Item = NotificationEventKind>) -> Self {
+        self.events = Some(kinds.into_iter().collect());
+        self
+    }
+
+    /// Unregisters the subscription and removes the subscriber socket
+    pub async fn close(mut self) -> std::io::Result<()> {
+        self.closed = true;
+        self.task.abort();
+
+        let unregistered = send(
+            &self.directory.join(KOMOREBI),
+            &SocketMessage::RemoveSubscriberSocket(self.name.clone()),
+        )
+        .await;
+
+        remove_socket(&self.directory.join(&self.name)).await?;
+
+        unregistered
+    }
+
+    fn is_wanted(&self, item: &Result<Notification, StreamError>) -> bool {
+        match item {
+            Ok(notification) => self.events.as_ref().is_none_or(|kinds| {
+                kinds.contains(&NotificationEventKind::from(&notification.event))
+            }),
+            Err(_) => true,
+        }
+    }
+}
+
+impl Stream for NotificationStream {
+    type Item = Result<Notification, StreamError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            match ready!(this.receiver.poll_recv(cx)) {
+                Some(item) if this.is_wanted(&item) => return Poll::Ready(Some(item)),
+                Some(_) => {}
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl Drop for NotificationStream {
+    fn drop(&mut self) {
+        self.task.abort();
+
+        if !self.closed {
+            let _ = std::fs::remove_file(self.directory.join(&self.name));
+        }
+    }
+}
+
+pub async fn subscribe_stream(name: &str) -> std::io::Result<NotificationStream> {
+    subscribe_stream_with_options(name, SubscribeOptions::default()).await
+}
+
+pub async fn subscribe_stream_with_options(
+    name: &str,
+    options: SubscribeOptions,
+) -> std::io::Result<NotificationStream> {
+    subscribe_in(DATA_DIR.clone(), name, options).await
+}
+
+async fn subscribe_in(
+    directory: PathBuf,
+    name: &str,
+    options: SubscribeOptions,
+) -> std::io::Result<NotificationStream> {
+    let subscription = Subscription {
+        directory: directory.clone(),
+        name: name.to_string(),
+        options,
+    };
+
+    let (listener, registration) = subscription.connect().await?;
+
+    let (sender, receiver) = mpsc::channel(NOTIFICATION_BUFFER_SIZE);
+    let task = tokio::spawn(forward_notifications(
+        subscription,
+        listener,
+        registration,
+        sender,
+    ));
+
+    Ok(NotificationStream {
+        name: name.to_string(),
+        directory,
+        receiver,
+        events: None,
+        task,
+        closed: false,
+    })
+}
+
+struct Subscription {
+    directory: PathBuf,
+    name: String,
+    options: SubscribeOptions,
+}
+
+/// The sockets a subscription was registered with, which change when komorebi is restarted or
+/// when it removes the subscriber socket
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Registration {
+    socket: u64,
+    komorebi: u64,
+}
+
+impl Subscription {
+    fn socket(&self) -> PathBuf {
+        self.directory.join(&self.name)
+    }
+
+    fn komorebi(&self) -> PathBuf {
+        self.directory.join(KOMOREBI)
+    }
+
+    /// Binds the subscriber socket and registers it with komorebi
+    async fn connect(&self) -> std::io::Result<(UnixListener, Registration)> {
+        let socket = self.socket();
+        remove_socket(&socket).await?;
+
+        let listener = UnixListener::bind(&socket)?;
+        let registration = Registration {
+            socket: inode(&socket).await?,
+            komorebi: inode(&self.komorebi()).await?,
+        };
+
+        let message =
+            SocketMessage::AddSubscriberSocketWithOptions(self.name.clone(), self.options);
+        send(&self.komorebi(), &message).await?;
+
+        Ok((listener, registration))
+    }
+
+    /// Binds and registers the subscriber socket again, retrying until komorebi can be reached,
+    /// or returns `None` once the stream has been dropped
+    async fn reconnect<T>(&self, sender: &mpsc::Sender<T>) -> Option<(UnixListener, Registration)> {
+        loop {
+            tokio::time::sleep(RECONNECT_INTERVAL).await;
+
+            if sender.is_closed() {
+                return None;
+            }
+
+            if let Ok(connected) = self.connect().await {
+                return Some(connected);
+            }
+        }
+    }
+
+    /// Compares the sockets on disk with the ones the subscription was registered with
+    async fn check(&self, registration: Registration) -> Option<StreamError> {
+        if inode(&self.socket()).await.ok() != Some(registration.socket) {
+            Some(StreamError::Unsubscribed)
+        } else if inode(&self.komorebi()).await.ok() != Some(registration.komorebi) {
+            Some(StreamError::Restarted)
+        } else {
+            None
+        }
+    }
+}
+
+async fn forward_notifications(
+    subscription: Subscription,
+    mut listener: UnixListener,
+    mut registration: Registration,
+    sender: mpsc::Sender<Result<Notification, StreamError>>,
+) {
+    let mut lagged = 0;
+    let mut health_check = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+
+    loop {
+        let interruption = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((mut connection, _)) => {
+                    let mut buffer = Vec::new();
+                    match connection.read_to_end(&mut buffer).await {
+                        // komorebi connects without sending anything when it is shutting down
+                        Ok(_) if buffer.is_empty() => Some(StreamError::Restarted),
+                        Ok(_) => {
+                            // notifications from a different version of komorebi are skipped
+                            if let Ok(notification) = serde_json::from_slice(&buffer)
+                                && !deliver(&sender, &mut lagged, Ok(notification))
+                            {
+                                return;
+                            }
+
+                            None
+                        }
+                        Err(error) => Some(StreamError::Io(error)),
+                    }
+                }
+                Err(error) => Some(StreamError::Io(error)),
+            },
+            _ = health_check.tick() => subscription.check(registration).await,
+        };
+
+        let Some(interruption) = interruption else {
+            continue;
+        };
+
+        if !deliver(&sender, &mut lagged, Err(interruption)) {
+            return;
+        }
+
+        match subscription.reconnect(&sender).await {
+            Some(connected) => (listener, registration) = connected,
+            None => return,
+        }
+    }
+}
+
+/// Hands an item to the consumer without waiting, counting it as lagged if the buffer is full,
+/// and returns `false` once the stream has been dropped
+fn deliver<T>(
+    sender: &mpsc::Sender<Result<T, StreamError>>,
+    lagged: &mut u64,
+    item: Result<T, StreamError>,
+) -> bool {
+    if *lagged > 0 {
+        match sender.try_send(Err(StreamError::Lagged(*lagged))) {
+            Ok(()) => *lagged = 0,
+            Err(TrySendError::Full(_)) => {
+                *lagged += 1;
+                return true;
+            }
+            Err(TrySendError::Closed(_)) => return false,
+        }
+    }
+
+    match sender.try_send(item) {
+        Ok(()) => true,
+        Err(TrySendError::Full(_)) => {
+            *lagged += 1;
+            true
+        }
+        Err(TrySendError::Closed(_)) => false,
+    }
+}
+
+async fn send(komorebi: &Path, message: &SocketMessage) -> std::io::Result<()> {
+    let mut stream = UnixStream::connect(komorebi).await?;
+    stream
+        .write_all(serde_json::to_string(message)?.as_bytes())
+        .await?;
+    stream.shutdown().await
+}
+
+async fn remove_socket(socket: &Path) -> std::io::Result<()> {
+    match tokio::fs::remove_file(socket).await {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+async fn inode(path: &Path) -> std::io::Result<u64> {
+    Ok(tokio::fs::metadata(path).await?.ino())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::State;
+    use komorebi::ring::Ring;
+    use std::collections::HashMap;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Stands in for komorebi's command socket in a temporary data directory
+    struct FakeKomorebi {
+        directory: PathBuf,
+        listener: UnixListener,
+    }
+
+    impl FakeKomorebi {
+        fn new() -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+            let directory = std::env::temp_dir().join(format!(
+                "kc-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::SeqCst)
+            ));
+
+            std::fs::create_dir_all(&directory).unwrap();
+            let listener = UnixListener::bind(directory.join(KOMOREBI)).unwrap();
+
+            Self {
+                directory,
+                listener,
+            }
+        }
+
+        /// Replaces the command socket, as a restarted komorebi would
+        fn restart(&mut self) {
+            let replacement = self.directory.join("komorebi.sock.new");
+            let listener = UnixListener::bind(&replacement).unwrap();
+            std::fs::rename(&replacement, self.directory.join(KOMOREBI)).unwrap();
+
+            // the old listener is kept alive until the socket has been replaced so that the new
+            // socket can't reuse its inode
+            self.listener = listener;
+        }
+
+        async fn next_message(&self) -> SocketMessage {
+            let (mut connection, _) = tokio::time::timeout(TIMEOUT, self.listener.accept())
+                .await
+                .unwrap()
+                .unwrap();
+
+            let mut buffer = Vec::new();
+            connection.read_to_end(&mut buffer).await.unwrap();
+            serde_json::from_slice(&buffer).unwrap()
+        }
+
+        async fn push(&self, name: &str, payload: &[u8]) {
+            let mut stream = UnixStream::connect(self.directory.join(name))
+                .await
+                .unwrap();
+            stream.write_all(payload).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+
+        async fn notify(&self, name: &str, message: SocketMessage) {
+            let notification = Notification {
+                event: NotificationEvent::Socket(message),
+                state: state(),
+            };
+
+            self.push(name, &serde_json::to_vec(&notification).unwrap())
+                .await;
+        }
+
+        async fn subscribe(&self, name: &str) -> NotificationStream {
+            let stream = subscribe_in(self.directory.clone(), name, SubscribeOptions::default())
+                .await
+                .unwrap();
+
+            assert!(matches!(
+                self.next_message().await,
+                SocketMessage::AddSubscriberSocketWithOptions(subscriber, _) if subscriber == name
+            ));
+
+            stream
+        }
+    }
+
+    impl Drop for FakeKomorebi {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.directory);
+        }
+    }
+
+    fn state() -> State {
+        State {
+            monitors: Ring::default(),
+            monitor_usr_idx_map: HashMap::new(),
+            is_paused: false,
+            resize_delta: 50,
+            new_window_behaviour: Default::default(),
+            float_override: false,
+            cross_monitor_move_behaviour: Default::default(),
+            monocle_focus_behaviour: Default::default(),
+            unmanaged_window_operation_behaviour: Default::default(),
+            work_area_offset: None,
+            focus_follows_mouse: None,
+            mouse_follows_focus: false,
+            scratchpads: vec![],
+            marks: HashMap::new(),
+            workspace_sets: vec![],
+            active_workspace_set: None,
+            active_profile: None,
+        }
+    }
+
+    async fn next(stream: &mut NotificationStream) -> Result<Notification, StreamError> {
+        tokio::time::timeout(
+            TIMEOUT,
+            std::future::poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)),
+        )
+        .await
+        .unwrap()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_notifications_are_filtered() {
+        let komorebi = FakeKomorebi::new();
+        let mut stream = komorebi
+            .subscribe("filtered")
+            .await
+            .filter_events([NotificationEventKind::Socket]);
+
+        komorebi.notify("filtered", SocketMessage::Retile).await;
+
+        let notification = next(&mut stream).await.unwrap();
+        assert!(matches!(
+            notification.event,
+            NotificationEvent::Socket(SocketMessage::Retile)
+        ));
+    }
+
+    #[test]
+    fn test_lagging_consumer_is_told_how_many_were_dropped() {
+        let (sender, mut receiver) = mpsc::channel::<Result<usize, StreamError>>(2);
+        let mut lagged = 0;
+
+        for n in 0..5 {
+            assert!(deliver(&sender, &mut lagged, Ok(n)));
+        }
+
+        assert_eq!(lagged, 3);
+        assert!(matches!(receiver.try_recv(), Ok(Ok(0))));
+        assert!(matches!(receiver.try_recv(), Ok(Ok(1))));
+
+        assert!(deliver(&sender, &mut lagged, Ok(5)));
+        assert_eq!(lagged, 0);
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(Err(StreamError::Lagged(3)))
+        ));
+        assert!(matches!(receiver.try_recv(), Ok(Ok(5))));
+
+        drop(receiver);
+        assert!(!deliver(&sender, &mut lagged, Ok(6)));
+    }
+
+    #[tokio::test]
+    async fn test_stale_removal_is_reported_and_registered_again() {
+        let komorebi = FakeKomorebi::new();
+        let mut stream = komorebi.subscribe("stale").await;
+
+        // this is what komorebi does when it can't connect to a subscriber socket
+        std::fs::remove_file(komorebi.directory.join("stale")).unwrap();
+
+        assert!(matches!(
+            next(&mut stream).await,
+            Err(StreamError::Unsubscribed)
+        ));
+
+        assert!(matches!(
+            komorebi.next_message().await,
+            SocketMessage::AddSubscriberSocketWithOptions(..)
+        ));
+
+        komorebi.notify("stale", SocketMessage::Retile).await;
+        assert!(next(&mut stream).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_restarts_are_reported_and_registered_again() {
+        let mut komorebi = FakeKomorebi::new();
+        let mut stream = komorebi.subscribe("restarted").await;
+
+        // a clean shutdown is signalled by connecting without sending anything
+        komorebi.push("restarted", b"").await;
+        assert!(matches!(
+            next(&mut stream).await,
+            Err(StreamError::Restarted)
+        ));
+        komorebi.next_message().await;
+
+        // a crash is noticed when komorebi's socket is replaced
+        komorebi.restart();
+        assert!(matches!(
+            next(&mut stream).await,
+            Err(StreamError::Restarted)
+        ));
+        komorebi.next_message().await;
+
+        komorebi.notify("restarted", SocketMessage::Retile).await;
+        assert!(next(&mut stream).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_close_unregisters_the_subscription() {
+        let komorebi = FakeKomorebi::new();
+        let stream = komorebi.subscribe("closed").await;
+
+        stream.close().await.unwrap();
+
+        assert!(matches!(
+            komorebi.next_message().await,
+            SocketMessage::RemoveSubscriberSocket(subscriber) if subscriber == "closed"
+        ));
+        assert!(!komorebi.directory.join("closed").exists());
+    }
+}
@@ -13,7 +13,7 @@ use strum::EnumString;
 #[serde(rename_all = "snake_case")]
 pub enum AnimationPrefix {
     Movement,
-    // Note: Transparency is not supported on macOS as there's no equivalent to Win32 layered windows
+    Transparency,
 }
 
 pub fn new_animation_key(prefix: AnimationPrefix, key: String) -> String {
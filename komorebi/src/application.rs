@@ -13,6 +13,7 @@ use crate::accessibility::notification_constants::kAXApplicationShownNotificatio
 use crate::accessibility::notification_constants::kAXMainWindowChangedNotification;
 use crate::accessibility::notification_constants::kAXUIElementDestroyedNotification;
 use crate::accessibility::notification_constants::kAXWindowCreatedNotification;
+use crate::platform;
 use crate::window::Window;
 use crate::window_manager_event::SystemNotification;
 use crate::window_manager_event::WindowManagerEvent;
@@ -121,13 +122,33 @@ impl Application {
         })
     }
 
+    /// An application without an accessibility element or observer, which only a
+    /// [`FakeBackend`](crate::platform::FakeBackend) knows about
+    pub fn stub(process_id: i32) -> Self {
+        Self {
+            element: Default::default(),
+            process_id,
+            observer: Default::default(),
+            is_observable: false,
+        }
+    }
+
     pub fn name(&self) -> Option<String> {
+        platform::backend().application_name(self)
+    }
+
+    pub(crate) fn name_native(&self) -> Option<String> {
         AccessibilityApi::copy_attribute_value::<CFString>(&self.element, kAXTitleAttribute)
             .map(|s| s.to_string())
     }
 
     #[tracing::instrument(skip_all)]
     pub fn observe(&mut self, run_loop: &CFRunLoop, refcon: Option<*mut c_void>) {
+        // stub applications have nothing to observe
+        if self.observer.0.is_none() {
+            return;
+        }
+
         tracing::info!(
             "registering observer for process: {}, name: {}",
             self.process_id,
@@ -29,10 +29,15 @@ use std::sync::atomic::AtomicI32;
 use std::sync::atomic::AtomicU32;
 use std::sync::atomic::Ordering;
 
-pub static BORDER_WIDTH: AtomicI32 = AtomicI32::new(10);
-pub static BORDER_OFFSET: AtomicI32 = AtomicI32::new(0);
-pub static BORDER_RADIUS: AtomicI32 = AtomicI32::new(10);
-pub static BORDER_ENABLED: AtomicBool = AtomicBool::new(true);
+pub const DEFAULT_BORDER_WIDTH: i32 = 10;
+pub const DEFAULT_BORDER_OFFSET: i32 = 0;
+pub const DEFAULT_BORDER_RADIUS: i32 = 10;
+pub const DEFAULT_BORDER_ENABLED: bool = true;
+
+pub static BORDER_WIDTH: AtomicI32 = AtomicI32::new(DEFAULT_BORDER_WIDTH);
+pub static BORDER_OFFSET: AtomicI32 = AtomicI32::new(DEFAULT_BORDER_OFFSET);
+pub static BORDER_RADIUS: AtomicI32 = AtomicI32::new(DEFAULT_BORDER_RADIUS);
+pub static BORDER_ENABLED: AtomicBool = AtomicBool::new(DEFAULT_BORDER_ENABLED);
 
 // Windows has a 7px invisible border around every app which the border_x
 // config options have to take into account. In order to keep the komorebi
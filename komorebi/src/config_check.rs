@@ -0,0 +1,872 @@
+//! Offline checks of a static configuration file
+//!
+//! [`check`] reads a configuration file the same way komorebi does and reports every problem it
+//! can find without a running window manager, each located by a JSON pointer into the merged
+//! configuration. Errors are problems which prevent the configuration from being loaded, while
+//! warnings are parts of the configuration which are silently ignored or can never take effect.
+
+use crate::core::ApplicationIdentifier;
+use crate::core::MAX_RATIOS;
+use crate::core::asc::ApplicationSpecificConfiguration;
+use crate::core::asc::AscApplicationRulesOrSchema;
+use crate::core::config_generation::IdWithIdentifier;
+use crate::core::config_generation::MatchingRule;
+use crate::core::config_generation::MatchingStrategy;
+use crate::core::custom_layout::CustomLayout;
+use crate::core::layered_config::LayeredConfig;
+use crate::core::layered_config::escape;
+use crate::core::layered_config::locate_error;
+use crate::core::validate_ratios;
+use crate::static_config::StaticConfig;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Formatter;
+use std::path::Path;
+use strum::Display;
+
+/// Keys of workspace configuration maps which are keyed by a container count threshold
+const THRESHOLD_KEYS: [&str; 5] = [
+    "layout_rules",
+    "custom_layout_rules",
+    "layout_options_rules",
+    "work_area_offset_rules",
+    "window_container_behaviour_rules",
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Severity {
+    /// The configuration cannot be loaded
+    Error,
+    /// Part of the configuration is ignored or can never take effect
+    Warning,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// JSON pointer to the offending value in the merged configuration
+    pub pointer: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}: {}", self.severity, self.message)
+        } else {
+            write!(f, "{}: {}: {}", self.severity, self.pointer, self.message)
+        }
+    }
+}
+
+/// Checks the static configuration file at `path`, returning every problem which was found with
+/// errors ahead of warnings
+pub fn check(path: &Path) -> Vec<Diagnostic> {
+    match LayeredConfig::read(path) {
+        Ok(layered) => check_value(&layered.value),
+        Err(error) => vec![Diagnostic {
+            severity: Severity::Error,
+            pointer: String::new(),
+            message: error.to_string(),
+        }],
+    }
+}
+
+/// Checks a static configuration which has already been read and merged
+pub fn check_value(raw: &Value) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        raw,
+        diagnostics: vec![],
+    };
+
+    match serde_json::from_value::<StaticConfig>(raw.clone()) {
+        Ok(config) => checker.check(&config),
+        Err(error) => {
+            let pointer = locate_error::<StaticConfig>(raw);
+            checker.error(pointer, error.to_string());
+        }
+    }
+
+    let mut diagnostics = checker.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| diagnostic.severity);
+    diagnostics
+}
+
+struct Checker<'a> {
+    raw: &'a Value,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn error(&mut self, pointer: String, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            pointer,
+            message,
+        });
+    }
+
+    fn warning(&mut self, pointer: String, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            pointer,
+            message,
+        });
+    }
+
+    /// Key of the ignore rules in the configuration, which may still use their old name
+    fn ignore_key(&self) -> &'static str {
+        if self.raw.get("ignore_rules").is_none() && self.raw.get("float_rules").is_some() {
+            "float_rules"
+        } else {
+            "ignore_rules"
+        }
+    }
+
+    fn check(&mut self, config: &StaticConfig) {
+        if let Ok(known) = serde_json::to_value(config) {
+            self.check_unknown_keys(self.raw, &known, "", &known);
+        }
+
+        self.check_rules(config);
+        self.check_monitors(config);
+        self.check_workspace_sets(config);
+        self.check_profiles(config);
+        self.check_ratios(self.raw, "");
+        self.check_app_specific_configuration(config);
+
+        // anything else which would stop komorebi from loading the configuration
+        if !self
+            .diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+            && let Err(error) = config.validate()
+        {
+            self.error(String::new(), format!("{error:#}"));
+        }
+    }
+
+    /// Reports keys which are present in the configuration but have no effect on it
+    fn check_unknown_keys(&mut self, raw: &Value, known: &Value, pointer: &str, root: &Value) {
+        match (raw, known) {
+            (Value::Object(raw_values), Value::Object(known_values)) => {
+                for (key, value) in raw_values {
+                    let pointer = format!("{pointer}/{}", escape(key));
+
+                    match known_values.get(key) {
+                        Some(known) => self.check_unknown_keys(value, known, &pointer, root),
+                        None => {
+                            if value.is_null() || pointer == "/$schema" {
+                                continue;
+                            }
+
+                            // aliases of renamed keys are not serialized either, but removing them
+                            // changes the configuration
+                            let mut without = self.raw.clone();
+                            remove(&mut without, &pointer);
+
+                            let unchanged = serde_json::from_value::<StaticConfig>(without)
+                                .ok()
+                                .and_then(|config| serde_json::to_value(config).ok())
+                                .is_some_and(|value| &value == root);
+
+                            if unchanged {
+                                self.warning(
+                                    pointer,
+                                    format!("{key} is not a recognised option and has no effect"),
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            (Value::Array(raw_values), Value::Array(known_values))
+                if raw_values.len() == known_values.len() =>
+            {
+                for (idx, (value, known)) in raw_values.iter().zip(known_values).enumerate() {
+                    self.check_unknown_keys(value, known, &format!("{pointer}/{idx}"), root);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_rules(&mut self, config: &StaticConfig) {
+        let ignore_key = self.ignore_key();
+
+        let ignore_rules = config.ignore_rules.as_deref().unwrap_or_default();
+        let manage_rules = config.manage_rules.as_deref().unwrap_or_default();
+
+        self.check_regexes(ignore_rules, &format!("/{ignore_key}"));
+        self.check_regexes(manage_rules, "/manage_rules");
+
+        for (idx, rule) in ignore_rules.iter().enumerate() {
+            if let Some(earlier) = ignore_rules[..idx]
+                .iter()
+                .position(|earlier| covers(earlier, rule))
+            {
+                self.warning(
+                    format!("/{ignore_key}/{idx}"),
+                    format!(
+                        "has no effect because /{ignore_key}/{earlier} already ignores every window it matches"
+                    ),
+                );
+            }
+        }
+
+        let lists = [
+            ("floating_applications", &config.floating_applications),
+            ("sticky_applications", &config.sticky_applications),
+            (
+                "transparency_ignore_rules",
+                &config.transparency_ignore_rules,
+            ),
+        ];
+
+        for (key, rules) in lists {
+            let rules = rules.as_deref().unwrap_or_default();
+            self.check_regexes(rules, &format!("/{key}"));
+            self.check_shadowed(rules, &format!("/{key}"), ignore_key, config);
+        }
+
+        let mut scratchpad_names = HashSet::new();
+        for (idx, scratchpad) in config.scratchpads.iter().flatten().enumerate() {
+            if !scratchpad_names.insert(&scratchpad.name) {
+                self.error(
+                    format!("/scratchpads/{idx}/name"),
+                    format!(
+                        "there is more than one scratchpad named {}",
+                        scratchpad.name
+                    ),
+                );
+            }
+
+            self.check_regexes(
+                &scratchpad.match_rules,
+                &format!("/scratchpads/{idx}/match_rules"),
+            );
+        }
+    }
+
+    fn check_regexes(&mut self, rules: &[MatchingRule], pointer: &str) {
+        for (idx, rule) in rules.iter().enumerate() {
+            let individual_rules = match rule {
+                MatchingRule::Simple(simple) => vec![(format!("{pointer}/{idx}"), simple)],
+                MatchingRule::Composite(composite) => composite
+                    .iter()
+                    .enumerate()
+                    .map(|(part, simple)| (format!("{pointer}/{idx}/{part}"), simple))
+                    .collect(),
+            };
+
+            for (pointer, simple) in individual_rules {
+                if matches!(simple.matching_strategy, Some(MatchingStrategy::Regex))
+                    && let Err(error) = Regex::new(&simple.id)
+                {
+                    self.error(
+                        format!("{pointer}/id"),
+                        format!("invalid regular expression: {error}"),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Reports rules which only affect managed windows but match nothing that isn't ignored
+    fn check_shadowed(
+        &mut self,
+        rules: &[MatchingRule],
+        pointer: &str,
+        ignore_key: &str,
+        config: &StaticConfig,
+    ) {
+        let ignore_rules = config.ignore_rules.as_deref().unwrap_or_default();
+        let manage_rules = config.manage_rules.as_deref().unwrap_or_default();
+
+        for (idx, rule) in rules.iter().enumerate() {
+            // windows matched by a manage rule are managed even if they are also ignored
+            if manage_rules.iter().any(|manage| !disjoint(manage, rule)) {
+                continue;
+            }
+
+            if let Some(ignore_idx) = ignore_rules.iter().position(|ignore| covers(ignore, rule)) {
+                self.warning(
+                    format!("{pointer}/{idx}"),
+                    format!(
+                        "never applies because /{ignore_key}/{ignore_idx} ignores every window it matches"
+                    ),
+                );
+            }
+        }
+    }
+
+    fn check_monitors(&mut self, config: &StaticConfig) {
+        let monitors = config.monitors.as_deref().unwrap_or_default();
+        let mut workspace_names: HashMap<&String, String> = HashMap::new();
+
+        for (monitor_idx, monitor) in monitors.iter().enumerate() {
+            for (workspace_idx, workspace) in monitor.workspaces.iter().enumerate() {
+                let pointer = format!("/monitors/{monitor_idx}/workspaces/{workspace_idx}");
+
+                if let Some(first) = workspace_names.get(&workspace.name) {
+                    self.error(
+                        format!("{pointer}/name"),
+                        format!(
+                            "there is more than one workspace named {}; only the one at {first} can be addressed by name",
+                            workspace.name
+                        ),
+                    );
+                } else {
+                    workspace_names.insert(&workspace.name, pointer.clone());
+                }
+
+                self.check_thresholds(&pointer);
+
+                for (threshold, _) in workspace.layout_rules.iter().flatten() {
+                    if workspace
+                        .custom_layout_rules
+                        .as_ref()
+                        .is_some_and(|rules| rules.contains_key(threshold))
+                    {
+                        self.warning(
+                            format!("{pointer}/layout_rules/{threshold}"),
+                            format!(
+                                "never applies because the custom layout rule at the same threshold takes precedence, see {pointer}/custom_layout_rules/{threshold}"
+                            ),
+                        );
+                    }
+                }
+
+                if let Some(path) = &workspace.custom_layout
+                    && let Err(error) = CustomLayout::from_path(path)
+                {
+                    self.error(
+                        format!("{pointer}/custom_layout"),
+                        format!("invalid custom layout at {}: {error}", path.display()),
+                    );
+                }
+
+                for (threshold, path) in workspace.custom_layout_rules.iter().flatten() {
+                    if let Err(error) = CustomLayout::from_path(path) {
+                        self.error(
+                            format!("{pointer}/custom_layout_rules/{threshold}"),
+                            format!("invalid custom layout at {}: {error}", path.display()),
+                        );
+                    }
+                }
+
+                let ignore_key = self.ignore_key();
+                for (key, rules) in [
+                    ("workspace_rules", &workspace.workspace_rules),
+                    (
+                        "initial_workspace_rules",
+                        &workspace.initial_workspace_rules,
+                    ),
+                ] {
+                    let rules = rules.as_deref().unwrap_or_default();
+                    self.check_regexes(rules, &format!("{pointer}/{key}"));
+                    self.check_shadowed(rules, &format!("{pointer}/{key}"), ignore_key, config);
+                }
+            }
+        }
+
+        for idx in config
+            .display_index_preferences
+            .iter()
+            .flat_map(|preferences| preferences.keys())
+        {
+            if *idx >= monitors.len() {
+                self.warning(
+                    format!("/display_index_preferences/{idx}"),
+                    format!("has no effect because monitor {idx} is not configured"),
+                );
+            }
+        }
+    }
+
+    /// Reports thresholds which are written differently but refer to the same container count,
+    /// of which only the last one is kept
+    fn check_thresholds(&mut self, workspace_pointer: &str) {
+        let Some(workspace) = self.raw.pointer(workspace_pointer) else {
+            return;
+        };
+
+        let mut shadowed = vec![];
+
+        for key in THRESHOLD_KEYS {
+            let Some(Value::Object(rules)) = workspace.get(key) else {
+                continue;
+            };
+
+            let thresholds = rules
+                .keys()
+                .filter_map(|raw| raw.trim().parse::<usize>().ok().map(|parsed| (raw, parsed)))
+                .collect::<Vec<_>>();
+
+            for (idx, (raw, parsed)) in thresholds.iter().enumerate() {
+                if let Some((later, _)) = thresholds[idx + 1..]
+                    .iter()
+                    .find(|(_, other)| other == parsed)
+                {
+                    shadowed.push((
+                        format!("{workspace_pointer}/{key}/{}", escape(raw)),
+                        format!("never applies because it is replaced by the threshold {later}"),
+                    ));
+                }
+            }
+        }
+
+        for (pointer, message) in shadowed {
+            self.warning(pointer, message);
+        }
+    }
+
+    fn check_workspace_sets(&mut self, config: &StaticConfig) {
+        let monitors = config.monitors.as_deref().unwrap_or_default();
+        let mut names = HashSet::new();
+
+        for (idx, set) in config.workspace_sets.iter().flatten().enumerate() {
+            let pointer = format!("/workspace_sets/{idx}");
+
+            if !names.insert(&set.name) {
+                self.error(
+                    format!("{pointer}/name"),
+                    format!("there is more than one workspace set named {}", set.name),
+                );
+            }
+
+            for (member_idx, member) in set.workspaces.iter().enumerate() {
+                let pointer = format!("{pointer}/workspaces/{member_idx}");
+
+                match monitors.get(member.monitor) {
+                    None => self.error(
+                        format!("{pointer}/monitor"),
+                        format!("monitor {} is not configured", member.monitor),
+                    ),
+                    Some(monitor) => {
+                        if !monitor
+                            .workspaces
+                            .iter()
+                            .any(|workspace| workspace.name == member.workspace)
+                        {
+                            self.error(
+                                format!("{pointer}/workspace"),
+                                format!(
+                                    "there is no workspace named {} on monitor {}",
+                                    member.workspace, member.monitor
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_profiles(&mut self, config: &StaticConfig) {
+        let monitors = config.monitors.as_deref().unwrap_or_default();
+
+        for (name, profile) in config.profiles.iter().flatten() {
+            for (idx, overrides) in profile.monitors.iter().flatten() {
+                let pointer = format!("/profiles/{}/monitors/{idx}", escape(name));
+
+                let Some(monitor) = monitors.get(*idx) else {
+                    self.error(pointer, format!("monitor {idx} is not configured"));
+                    continue;
+                };
+
+                for (workspace_idx, workspace) in overrides.workspaces.iter().flatten().enumerate()
+                {
+                    if !monitor
+                        .workspaces
+                        .iter()
+                        .any(|configured| configured.name == workspace.name)
+                    {
+                        self.error(
+                            format!("{pointer}/workspaces/{workspace_idx}/name"),
+                            format!(
+                                "there is no workspace named {} on monitor {idx}",
+                                workspace.name
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reports layout ratios which are clamped or dropped by [`validate_ratios`]
+    fn check_ratios(&mut self, raw: &Value, pointer: &str) {
+        match raw {
+            Value::Object(values) => {
+                for (key, value) in values {
+                    let pointer = format!("{pointer}/{}", escape(key));
+
+                    if matches!(key.as_str(), "column_ratios" | "row_ratios")
+                        && let Value::Array(ratios) = value
+                    {
+                        let ratios = ratios
+                            .iter()
+                            .map(|ratio| ratio.as_f64().map(|ratio| ratio as f32))
+                            .collect::<Option<Vec<_>>>();
+
+                        if let Some(ratios) = ratios {
+                            self.check_ratio_values(&ratios, &pointer);
+                        }
+                    } else {
+                        self.check_ratios(value, &pointer);
+                    }
+                }
+            }
+            Value::Array(values) => {
+                for (idx, value) in values.iter().enumerate() {
+                    self.check_ratios(value, &format!("{pointer}/{idx}"));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_ratio_values(&mut self, ratios: &[f32], pointer: &str) {
+        let validated = validate_ratios(ratios);
+
+        for (idx, ratio) in ratios.iter().enumerate() {
+            let pointer = format!("{pointer}/{idx}");
+
+            match validated.get(idx).copied().flatten() {
+                Some(validated) if validated == *ratio => {}
+                Some(validated) => self.warning(
+                    pointer,
+                    format!("the ratio {ratio} is out of range and is clamped to {validated}"),
+                ),
+                None if idx >= MAX_RATIOS => self.warning(
+                    pointer,
+                    format!("the ratio is ignored because at most {MAX_RATIOS} ratios are used"),
+                ),
+                None => self.warning(
+                    pointer,
+                    String::from(
+                        "the ratio is ignored because the ratios before it already add up to the whole area",
+                    ),
+                ),
+            }
+        }
+    }
+
+    fn check_app_specific_configuration(&mut self, config: &StaticConfig) {
+        let paths = config.app_specific_configuration_paths();
+        let single = paths.len() == 1 && self.raw["app_specific_configuration_path"].is_string();
+
+        for (idx, path) in paths.iter().enumerate() {
+            if !path.extension().is_some_and(|ext| ext == "json") {
+                continue;
+            }
+
+            let pointer = if single {
+                String::from("/app_specific_configuration_path")
+            } else {
+                format!("/app_specific_configuration_path/{idx}")
+            };
+
+            let asc = match ApplicationSpecificConfiguration::load(path) {
+                Ok(asc) => asc,
+                Err(error) => {
+                    self.error(
+                        pointer,
+                        format!("invalid applications.json at {}: {error}", path.display()),
+                    );
+                    continue;
+                }
+            };
+
+            for (application, entry) in asc.iter() {
+                let AscApplicationRulesOrSchema::AscApplicationRules(entry) = entry else {
+                    continue;
+                };
+
+                for rules in [&entry.ignore, &entry.manage, &entry.floating]
+                    .into_iter()
+                    .flatten()
+                {
+                    for simple in rules.iter().flat_map(individual_rules) {
+                        if matches!(simple.matching_strategy, Some(MatchingStrategy::Regex))
+                            && let Err(error) = Regex::new(&simple.id)
+                        {
+                            self.error(
+                                pointer.clone(),
+                                format!(
+                                    "invalid regular expression for {application} in {}: {error}",
+                                    path.display()
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Removes the value at `pointer`, which must not be the root
+fn remove(value: &mut Value, pointer: &str) {
+    let Some((parent, token)) = pointer.rsplit_once('/') else {
+        return;
+    };
+
+    let token = token.replace("~1", "/").replace("~0", "~");
+
+    match value.pointer_mut(parent) {
+        Some(Value::Object(values)) => {
+            values.remove(&token);
+        }
+        Some(Value::Array(values)) => {
+            if let Ok(idx) = token.parse::<usize>()
+                && idx < values.len()
+            {
+                values.remove(idx);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn individual_rules(rule: &MatchingRule) -> &[IdWithIdentifier] {
+    match rule {
+        MatchingRule::Simple(simple) => std::slice::from_ref(simple),
+        MatchingRule::Composite(composite) => composite.as_slice(),
+    }
+}
+
+/// Whether every window matched by `rule` is also matched by `by`
+fn covers(by: &MatchingRule, rule: &MatchingRule) -> bool {
+    individual_rules(by).iter().all(|by| {
+        individual_rules(rule)
+            .iter()
+            .any(|rule| covers_individual(by, rule))
+    })
+}
+
+fn covers_individual(by: &IdWithIdentifier, rule: &IdWithIdentifier) -> bool {
+    if by.kind != rule.kind {
+        return false;
+    }
+
+    let by_id = by.id.replace(".exe", "");
+    let rule_id = rule.id.replace(".exe", "");
+
+    match (strategy(by), strategy(rule)) {
+        (by_strategy, rule_strategy) if by_strategy == rule_strategy && by_id == rule_id => true,
+        (MatchingStrategy::StartsWith, MatchingStrategy::Equals | MatchingStrategy::StartsWith) => {
+            rule_id.starts_with(&by_id)
+        }
+        (MatchingStrategy::EndsWith, MatchingStrategy::Equals | MatchingStrategy::EndsWith) => {
+            rule_id.ends_with(&by_id)
+        }
+        (
+            MatchingStrategy::Contains,
+            MatchingStrategy::Equals
+            | MatchingStrategy::StartsWith
+            | MatchingStrategy::EndsWith
+            | MatchingStrategy::Contains,
+        ) => rule_id.contains(&by_id),
+        (MatchingStrategy::Legacy, MatchingStrategy::Equals) => {
+            rule_id.starts_with(&by_id) || rule_id.ends_with(&by_id)
+        }
+        (MatchingStrategy::Legacy, MatchingStrategy::StartsWith) => rule_id.starts_with(&by_id),
+        (MatchingStrategy::Legacy, MatchingStrategy::EndsWith) => rule_id.ends_with(&by_id),
+        (MatchingStrategy::Regex, MatchingStrategy::Equals) => {
+            Regex::new(&by_id).is_ok_and(|regex| regex.is_match(&rule_id))
+        }
+        _ => false,
+    }
+}
+
+/// Whether `a` and `b` can be shown to never match the same window
+fn disjoint(a: &MatchingRule, b: &MatchingRule) -> bool {
+    individual_rules(a).iter().any(|a| {
+        individual_rules(b).iter().any(|b| {
+            a.kind == b.kind
+                && strategy(a) == MatchingStrategy::Equals
+                && strategy(b) == MatchingStrategy::Equals
+                && a.id.replace(".exe", "") != b.id.replace(".exe", "")
+                && matches!(
+                    a.kind,
+                    ApplicationIdentifier::Exe
+                        | ApplicationIdentifier::Path
+                        | ApplicationIdentifier::Title
+                )
+        })
+    })
+}
+
+/// The matching strategy which is effectively used for an identifier, given that legacy matching
+/// compares executable names and paths for equality
+fn strategy(identifier: &IdWithIdentifier) -> MatchingStrategy {
+    match &identifier.matching_strategy {
+        None | Some(MatchingStrategy::Legacy) => match identifier.kind {
+            ApplicationIdentifier::Exe | ApplicationIdentifier::Path => MatchingStrategy::Equals,
+            _ => MatchingStrategy::Legacy,
+        },
+        Some(strategy) => strategy.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn find<'a>(diagnostics: &'a [Diagnostic], pointer: &str) -> &'a Diagnostic {
+        diagnostics
+            .iter()
+            .find(|diagnostic| diagnostic.pointer == pointer)
+            .unwrap_or_else(|| panic!("no diagnostic at {pointer}: {diagnostics:#?}"))
+    }
+
+    #[test]
+    fn test_deserialization_errors_are_located() {
+        let diagnostics = check_value(&json!({
+            "border": true,
+            "monitors": [
+                { "workspaces": [{ "name": "code" }, { "name": "web", "layout": "NotALayout" }] }
+            ]
+        }));
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].pointer, "/monitors/0/workspaces/1/layout");
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let diagnostics = check_value(&json!({
+            "$schema": "https://komorebi-for-mac.lgug2z.com/schema.json",
+            "boder": true,
+            "float_rules": [{ "kind": "Exe", "id": "Finder" }],
+            "monitors": [{ "workspaces": [{ "name": "code", "layuot": "BSP" }] }]
+        }));
+
+        assert_eq!(find(&diagnostics, "/boder").severity, Severity::Warning);
+        assert_eq!(
+            find(&diagnostics, "/monitors/0/workspaces/0/layuot").severity,
+            Severity::Warning
+        );
+
+        // renamed keys and the schema reference are understood
+        assert_eq!(diagnostics.len(), 2, "{diagnostics:#?}");
+    }
+
+    #[test]
+    fn test_rules() {
+        let diagnostics = check_value(&json!({
+            "ignore_rules": [
+                { "kind": "Exe", "id": "Steam" },
+                { "kind": "Title", "id": "Picture", "matching_strategy": "StartsWith" },
+                { "kind": "Title", "id": "Picture-in-Picture", "matching_strategy": "Equals" },
+                { "kind": "Title", "id": "(unclosed", "matching_strategy": "Regex" }
+            ],
+            "manage_rules": [{ "kind": "Exe", "id": "Finder" }],
+            "floating_applications": [
+                [
+                    { "kind": "Exe", "id": "Steam" },
+                    { "kind": "Title", "id": "Friends", "matching_strategy": "Equals" }
+                ],
+                { "kind": "Exe", "id": "Finder" },
+                { "kind": "Exe", "id": "Calculator" }
+            ]
+        }));
+
+        assert_eq!(
+            find(&diagnostics, "/ignore_rules/3/id").severity,
+            Severity::Error
+        );
+        assert_eq!(
+            find(&diagnostics, "/ignore_rules/2").message,
+            "has no effect because /ignore_rules/1 already ignores every window it matches"
+        );
+        assert_eq!(
+            find(&diagnostics, "/floating_applications/0").message,
+            "never applies because /ignore_rules/0 ignores every window it matches"
+        );
+
+        // the manage rule overrides the ignore rule, and nothing ignores the calculator
+        assert!(!diagnostics.iter().any(|diagnostic| {
+            matches!(
+                diagnostic.pointer.as_str(),
+                "/floating_applications/1" | "/floating_applications/2"
+            )
+        }));
+
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_workspaces() {
+        let diagnostics = check_value(&json!({
+            "display_index_preferences": { "0": "DELL-1", "3": "DELL-2" },
+            "monitors": [
+                {
+                    "workspaces": [
+                        {
+                            "name": "code",
+                            "layout_rules": { "2": "Columns", "02": "Rows" },
+                            "layout_options": { "column_ratios": [0.5, 0.95, 0.3] }
+                        }
+                    ]
+                },
+                { "workspaces": [{ "name": "code" }] }
+            ],
+            "workspace_sets": [
+                { "name": "coding", "workspaces": [{ "monitor": 1, "workspace": "web" }] }
+            ]
+        }));
+
+        assert_eq!(
+            find(&diagnostics, "/monitors/1/workspaces/0/name").severity,
+            Severity::Error
+        );
+        assert_eq!(
+            find(&diagnostics, "/monitors/0/workspaces/0/layout_rules/2").severity,
+            Severity::Warning
+        );
+        assert_eq!(
+            find(
+                &diagnostics,
+                "/monitors/0/workspaces/0/layout_options/column_ratios/1"
+            )
+            .severity,
+            Severity::Warning
+        );
+        assert_eq!(
+            find(&diagnostics, "/display_index_preferences/3").severity,
+            Severity::Warning
+        );
+        assert_eq!(
+            find(&diagnostics, "/workspace_sets/0/workspaces/0/workspace").severity,
+            Severity::Error
+        );
+    }
+
+    #[test]
+    fn test_valid_configuration() {
+        let diagnostics = check_value(&json!({
+            "border": true,
+            "ignore_rules": [{ "kind": "Exe", "id": "Steam" }],
+            "monitors": [
+                {
+                    "workspaces": [
+                        { "name": "code", "layout": "BSP", "layout_rules": { "4": "Rows" } },
+                        { "name": "web", "layout_options": { "column_ratios": [0.3, 0.4] } }
+                    ]
+                }
+            ]
+        }));
+
+        assert!(diagnostics.is_empty(), "{diagnostics:#?}");
+    }
+}
@@ -0,0 +1,518 @@
+use crate::core::Axis;
+use crate::core::OperationDirection;
+use crate::core::Rect;
+use color_eyre::eyre;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs::File;
+use std::io::BufReader;
+use std::num::NonZeroUsize;
+use std::ops::Deref;
+use std::path::Path;
+
+/// Width percentage given to the primary column when none is specified
+pub const DEFAULT_PRIMARY_WIDTH_PERCENTAGE: f32 = 50.0;
+
+/// A column-based layout described in a JSON or YAML file
+///
+/// The first container on a workspace is always placed in the `Primary` column. The remaining
+/// containers fill the other columns from left to right, with `Secondary` columns holding a fixed
+/// number of containers and a final `Tertiary` column holding everything that is left over.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CustomLayout(Vec<Column>);
+
+/// A column in a custom layout
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "column", content = "configuration")]
+pub enum Column {
+    /// The column holding the first container
+    Primary(Option<ColumnWidth>),
+    /// A column holding a fixed number of containers (default: 1)
+    Secondary(Option<ColumnSplitWithCapacity>),
+    /// The final column, holding all remaining containers
+    Tertiary(ColumnSplit),
+}
+
+/// Width of the primary column
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ColumnWidth {
+    /// Percentage of the work area width (exclusive range 0-100)
+    WidthPercentage(f32),
+}
+
+/// How containers are arranged within a column
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ColumnSplit {
+    /// Containers are stacked on top of each other
+    Horizontal,
+    /// Containers are placed side by side
+    Vertical,
+}
+
+/// How a fixed number of containers are arranged within a column
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ColumnSplitWithCapacity {
+    /// Up to this many containers are stacked on top of each other
+    Horizontal(usize),
+    /// Up to this many containers are placed side by side
+    Vertical(usize),
+}
+
+impl Deref for CustomLayout {
+    type Target = Vec<Column>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Column {
+    const fn capacity(&self) -> Option<usize> {
+        match self {
+            Column::Primary(_) | Column::Secondary(None) => Some(1),
+            Column::Secondary(Some(
+                ColumnSplitWithCapacity::Horizontal(capacity)
+                | ColumnSplitWithCapacity::Vertical(capacity),
+            )) => Some(*capacity),
+            Column::Tertiary(_) => None,
+        }
+    }
+
+    const fn split(&self) -> ColumnSplit {
+        match self {
+            Column::Primary(_)
+            | Column::Secondary(None | Some(ColumnSplitWithCapacity::Horizontal(_)))
+            | Column::Tertiary(ColumnSplit::Horizontal) => ColumnSplit::Horizontal,
+            Column::Secondary(Some(ColumnSplitWithCapacity::Vertical(_)))
+            | Column::Tertiary(ColumnSplit::Vertical) => ColumnSplit::Vertical,
+        }
+    }
+}
+
+impl CustomLayout {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let reader = || -> eyre::Result<BufReader<File>> { Ok(BufReader::new(File::open(path)?)) };
+
+        let layout: Self = match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml" | "yml") => serde_yaml::from_reader(reader()?)?,
+            Some("json") => serde_json::from_reader(reader()?)?,
+            _ => eyre::bail!(
+                "custom layout files must have a .json, .yaml or .yml extension: {}",
+                path.display()
+            ),
+        };
+
+        layout.validate()?;
+
+        Ok(layout)
+    }
+
+    pub fn validate(&self) -> eyre::Result<()> {
+        if self.is_empty() {
+            eyre::bail!("a custom layout must have at least one column");
+        }
+
+        let primary_count = self
+            .iter()
+            .filter(|column| matches!(column, Column::Primary(_)))
+            .count();
+
+        if primary_count != 1 {
+            eyre::bail!("a custom layout must have exactly one primary column");
+        }
+
+        for (idx, column) in self.iter().enumerate() {
+            match column {
+                Column::Primary(Some(ColumnWidth::WidthPercentage(percentage)))
+                    if *percentage <= 0.0 || *percentage >= 100.0 =>
+                {
+                    eyre::bail!(
+                        "the primary column width percentage must be between 0 and 100 (exclusive)"
+                    );
+                }
+                Column::Secondary(_) if column.capacity() == Some(0) => {
+                    eyre::bail!("secondary column {idx} must have a capacity of at least 1");
+                }
+                Column::Tertiary(_) if idx != self.len() - 1 => {
+                    eyre::bail!(
+                        "a tertiary column can only be the final column of a custom layout"
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn primary_idx(&self) -> Option<usize> {
+        self.iter()
+            .position(|column| matches!(column, Column::Primary(_)))
+    }
+
+    pub fn primary_width_percentage(&self) -> f32 {
+        self.iter()
+            .find_map(|column| match column {
+                Column::Primary(Some(ColumnWidth::WidthPercentage(percentage))) => {
+                    Some(*percentage)
+                }
+                _ => None,
+            })
+            .unwrap_or(DEFAULT_PRIMARY_WIDTH_PERCENTAGE)
+    }
+
+    /// Column indices in the order in which containers are assigned to them
+    fn fill_order(&self) -> Vec<usize> {
+        let primary_idx = self.primary_idx().unwrap_or_default();
+        std::iter::once(primary_idx)
+            .chain((0..self.len()).filter(|idx| *idx != primary_idx))
+            .collect()
+    }
+
+    /// Number of containers that each column should hold, indexed by column
+    fn container_counts(&self, len: usize) -> Vec<usize> {
+        let mut counts = vec![0; self.len()];
+        let order = self.fill_order();
+        let mut remaining = len;
+
+        for idx in &order {
+            let take = self[*idx]
+                .capacity()
+                .map_or(remaining, |capacity| capacity.min(remaining));
+            counts[*idx] = take;
+            remaining -= take;
+        }
+
+        // without a tertiary column, overflow goes to the last column to be filled
+        if remaining > 0
+            && let Some(last) = order.last()
+        {
+            counts[*last] += remaining;
+        }
+
+        counts
+    }
+
+    pub fn calculate(
+        &self,
+        area: &Rect,
+        len: NonZeroUsize,
+        container_padding: Option<i32>,
+        layout_flip: Option<Axis>,
+    ) -> Vec<Rect> {
+        let counts = self.container_counts(len.get());
+        let visible = counts.iter().filter(|count| **count > 0).count() as i32;
+
+        // column widths, left to right
+        let mut column_areas = vec![None; self.len()];
+        let primary_width = if visible > 1 {
+            (area.right as f32 * self.primary_width_percentage() / 100.0) as i32
+        } else {
+            area.right
+        };
+        let secondary_width = if visible > 1 {
+            (area.right - primary_width) / (visible - 1)
+        } else {
+            0
+        };
+
+        let mut left = area.left;
+        let mut placed = 0;
+        for (idx, column) in self.iter().enumerate() {
+            if counts[idx] == 0 {
+                continue;
+            }
+
+            placed += 1;
+            let width = if placed == visible {
+                // the final visible column absorbs any rounding remainder
+                area.left + area.right - left
+            } else if matches!(column, Column::Primary(_)) {
+                primary_width
+            } else {
+                secondary_width
+            };
+
+            column_areas[idx] = Some(Rect {
+                left,
+                top: area.top,
+                right: width,
+                bottom: area.bottom,
+            });
+
+            left += width;
+        }
+
+        let mut layouts = vec![];
+        for idx in self.fill_order() {
+            if let Some(column_area) = column_areas[idx] {
+                layouts.extend(split(&column_area, counts[idx], self[idx].split()));
+            }
+        }
+
+        for rect in &mut layouts {
+            if let Some(axis) = layout_flip {
+                flip(rect, area, axis);
+            }
+
+            if let Some(padding) = container_padding {
+                rect.add_padding(padding);
+            }
+        }
+
+        layouts
+    }
+}
+
+/// Find the index of the nearest rect in the given direction from the rect at `idx`
+///
+/// Custom layouts don't follow any fixed container ordering, so directional operations are
+/// resolved geometrically against the most recently calculated layout.
+pub fn idx_in_direction(
+    layouts: &[Rect],
+    idx: usize,
+    direction: OperationDirection,
+) -> Option<usize> {
+    let current = layouts.get(idx)?;
+    let overlaps_horizontally = |other: &Rect| {
+        other.left < current.left + current.right && current.left < other.left + other.right
+    };
+    let overlaps_vertically = |other: &Rect| {
+        other.top < current.top + current.bottom && current.top < other.top + other.bottom
+    };
+
+    let center = |rect: &Rect| (rect.left + rect.right / 2, rect.top + rect.bottom / 2);
+    let (current_x, current_y) = center(current);
+
+    layouts
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != idx)
+        .filter(|(_, other)| {
+            let (x, y) = center(other);
+            match direction {
+                OperationDirection::Left => x < current.left && overlaps_vertically(other),
+                OperationDirection::Right => {
+                    x > current.left + current.right && overlaps_vertically(other)
+                }
+                OperationDirection::Up => y < current.top && overlaps_horizontally(other),
+                OperationDirection::Down => {
+                    y > current.top + current.bottom && overlaps_horizontally(other)
+                }
+            }
+        })
+        .min_by_key(|(_, other)| {
+            let (x, y) = center(other);
+            match direction {
+                OperationDirection::Left | OperationDirection::Right => {
+                    ((x - current_x).abs(), (y - current_y).abs())
+                }
+                OperationDirection::Up | OperationDirection::Down => {
+                    ((y - current_y).abs(), (x - current_x).abs())
+                }
+            }
+        })
+        .map(|(i, _)| i)
+}
+
+fn split(area: &Rect, count: usize, split: ColumnSplit) -> Vec<Rect> {
+    let count = count as i32;
+    let mut rects = vec![];
+
+    for i in 0..count {
+        let rect = match split {
+            ColumnSplit::Horizontal => {
+                let height = area.bottom / count;
+                Rect {
+                    left: area.left,
+                    top: area.top + height * i,
+                    right: area.right,
+                    bottom: if i == count - 1 {
+                        area.bottom - height * i
+                    } else {
+                        height
+                    },
+                }
+            }
+            ColumnSplit::Vertical => {
+                let width = area.right / count;
+                Rect {
+                    left: area.left + width * i,
+                    top: area.top,
+                    right: if i == count - 1 {
+                        area.right - width * i
+                    } else {
+                        width
+                    },
+                    bottom: area.bottom,
+                }
+            }
+        };
+
+        rects.push(rect);
+    }
+
+    rects
+}
+
+fn flip(rect: &mut Rect, area: &Rect, axis: Axis) {
+    if matches!(axis, Axis::Horizontal | Axis::HorizontalAndVertical) {
+        rect.left = area.left + (area.left + area.right) - (rect.left + rect.right);
+    }
+
+    if matches!(axis, Axis::Vertical | Axis::HorizontalAndVertical) {
+        rect.top = area.top + (area.top + area.bottom) - (rect.top + rect.bottom);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area() -> Rect {
+        Rect {
+            left: 0,
+            top: 0,
+            right: 3000,
+            bottom: 1000,
+        }
+    }
+
+    fn ultrawide() -> CustomLayout {
+        CustomLayout(vec![
+            Column::Secondary(None),
+            Column::Primary(Some(ColumnWidth::WidthPercentage(50.0))),
+            Column::Tertiary(ColumnSplit::Horizontal),
+        ])
+    }
+
+    #[test]
+    fn test_deserialize_columns() {
+        let json = r#"[
+            {"column": "Secondary"},
+            {"column": "Primary", "configuration": {"WidthPercentage": 50.0}},
+            {"column": "Tertiary", "configuration": "Horizontal"}
+        ]"#;
+
+        let layout: CustomLayout = serde_json::from_str(json).unwrap();
+        assert_eq!(layout, ultrawide());
+        assert!(layout.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(CustomLayout(vec![]).validate().is_err());
+        assert!(
+            CustomLayout(vec![Column::Secondary(None)])
+                .validate()
+                .is_err()
+        );
+        assert!(
+            CustomLayout(vec![
+                Column::Tertiary(ColumnSplit::Vertical),
+                Column::Primary(None),
+            ])
+            .validate()
+            .is_err()
+        );
+        assert!(
+            CustomLayout(vec![Column::Primary(Some(ColumnWidth::WidthPercentage(
+                100.0
+            )))])
+            .validate()
+            .is_err()
+        );
+        assert!(
+            CustomLayout(vec![
+                Column::Primary(None),
+                Column::Secondary(Some(ColumnSplitWithCapacity::Vertical(0))),
+            ])
+            .validate()
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_single_container_fills_area() {
+        let layouts = ultrawide().calculate(&area(), NonZeroUsize::new(1).unwrap(), None, None);
+        assert_eq!(layouts, vec![area()]);
+    }
+
+    #[test]
+    fn test_primary_container_is_first() {
+        let layouts = ultrawide().calculate(&area(), NonZeroUsize::new(4).unwrap(), None, None);
+
+        assert_eq!(layouts.len(), 4);
+        // primary in the middle column
+        assert_eq!(
+            layouts[0],
+            Rect {
+                left: 750,
+                top: 0,
+                right: 1500,
+                bottom: 1000
+            }
+        );
+        // secondary on the left
+        assert_eq!(layouts[1].left, 0);
+        assert_eq!(layouts[1].right, 750);
+        // tertiary on the right split into rows
+        assert_eq!(layouts[2].left, 2250);
+        assert_eq!(layouts[2].bottom, 500);
+        assert_eq!(layouts[3].top, 500);
+    }
+
+    #[test]
+    fn test_overflow_without_tertiary() {
+        let layout = CustomLayout(vec![Column::Primary(None), Column::Secondary(None)]);
+        let layouts = layout.calculate(&area(), NonZeroUsize::new(3).unwrap(), None, None);
+
+        assert_eq!(layouts.len(), 3);
+        assert_eq!(layouts[1].left, 1500);
+        assert_eq!(layouts[2].left, 1500);
+        assert_eq!(layouts[2].top, 500);
+    }
+
+    #[test]
+    fn test_idx_in_direction() {
+        let layouts = ultrawide().calculate(&area(), NonZeroUsize::new(4).unwrap(), None, None);
+
+        assert_eq!(
+            idx_in_direction(&layouts, 0, OperationDirection::Left),
+            Some(1)
+        );
+        assert_eq!(
+            idx_in_direction(&layouts, 0, OperationDirection::Right),
+            Some(2)
+        );
+        assert_eq!(idx_in_direction(&layouts, 0, OperationDirection::Up), None);
+        assert_eq!(
+            idx_in_direction(&layouts, 2, OperationDirection::Down),
+            Some(3)
+        );
+        assert_eq!(
+            idx_in_direction(&layouts, 3, OperationDirection::Left),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_horizontal_flip() {
+        let layout = CustomLayout(vec![Column::Primary(None), Column::Secondary(None)]);
+        let layouts = layout.calculate(
+            &area(),
+            NonZeroUsize::new(2).unwrap(),
+            None,
+            Some(Axis::Horizontal),
+        );
+
+        assert_eq!(layouts[0].left, 1500);
+        assert_eq!(layouts[1].left, 0);
+    }
+}
@@ -0,0 +1,304 @@
+//! Framed request/response protocol spoken on `komorebi.sock`
+//!
+//! Every request and response is sent as a single frame made up of [`FRAME_MAGIC`], a protocol
+//! version byte, the payload length as a big-endian `u32` and finally the JSON payload. A request
+//! payload is a serialized [`SocketMessage`](crate::core::SocketMessage) and a response payload is
+//! a serialized [`Response`]. Multiple requests can be sent on the same connection, and each one
+//! receives exactly one response, in order.
+//!
+//! Clients which write bare JSON messages without a frame are still supported, but they never
+//! receive a response unless the message is a query.
+
+use crate::LibraryError;
+use crate::accessibility::error::AccessibilityError;
+use crate::core_graphics::error::CoreGraphicsError;
+use color_eyre::eyre;
+use serde::Deserialize;
+use serde::Serialize;
+use std::io::Chain;
+use std::io::Cursor;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::io::Write;
+
+/// Bumped whenever the framing or the shape of [`Response`] changes
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// Marks the start of a frame; a bare JSON message can never start with these bytes
+pub const FRAME_MAGIC: [u8; 3] = *b"KMB";
+
+/// Frames larger than this are rejected rather than allocated
+pub const MAX_FRAME_LENGTH: u32 = 16 * 1024 * 1024;
+
+const HEADER_LENGTH: usize = FRAME_MAGIC.len() + 1 + 4;
+
+/// Whether a connection is speaking the framed protocol, based on the first bytes received
+pub fn is_framed(buf: &[u8]) -> bool {
+    buf.starts_with(&FRAME_MAGIC)
+}
+
+/// Reads until there are enough bytes to compare against [`FRAME_MAGIC`] or the other end stops
+/// writing, so that a frame split across several reads is never mistaken for a bare message
+///
+/// Returns whether the connection is framed, alongside a reader which yields the peeked bytes
+/// again before the rest of the connection
+pub fn peek_framed<R: Read>(mut reader: R) -> std::io::Result<(bool, Chain<Cursor<Vec<u8>>, R>)> {
+    let mut prefix = Vec::with_capacity(FRAME_MAGIC.len());
+    (&mut reader)
+        .take(FRAME_MAGIC.len() as u64)
+        .read_to_end(&mut prefix)?;
+
+    Ok((is_framed(&prefix), Cursor::new(prefix).chain(reader)))
+}
+
+pub fn write_frame(writer: &mut impl Write, payload: &[u8]) -> std::io::Result<()> {
+    let length = u32::try_from(payload.len())
+        .ok()
+        .filter(|length| *length <= MAX_FRAME_LENGTH)
+        .ok_or_else(|| std::io::Error::new(ErrorKind::InvalidInput, "frame is too large"))?;
+
+    let mut frame = Vec::with_capacity(HEADER_LENGTH + payload.len());
+    frame.extend_from_slice(&FRAME_MAGIC);
+    frame.push(PROTOCOL_VERSION);
+    frame.extend_from_slice(&length.to_be_bytes());
+    frame.extend_from_slice(payload);
+
+    writer.write_all(&frame)?;
+    writer.flush()
+}
+
+/// Reads the next frame, returning its protocol version and payload, or `None` if the other end
+/// closed the connection cleanly between frames
+pub fn read_frame(reader: &mut impl Read) -> std::io::Result<Option<(u8, Vec<u8>)>> {
+    let mut header = [0; HEADER_LENGTH];
+
+    // distinguish between a clean shutdown and a frame that was cut off part of the way through
+    let mut read = 0;
+    while read < HEADER_LENGTH {
+        match reader.read(&mut header[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+            Ok(n) => read += n,
+            Err(error) if error.kind() == ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+
+    if header[..FRAME_MAGIC.len()] != FRAME_MAGIC {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            "frame does not start with the expected magic bytes",
+        ));
+    }
+
+    let version = header[FRAME_MAGIC.len()];
+    let length = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+
+    if length > MAX_FRAME_LENGTH {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("frame of {length} bytes is too large"),
+        ));
+    }
+
+    let mut payload = vec![0; length as usize];
+    reader.read_exact(&mut payload)?;
+
+    Ok(Some((version, payload)))
+}
+
+/// The response sent back for every framed request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Response {
+    /// The request was processed; queries carry their reply here, all other commands send an
+    /// empty string
+    Ok(String),
+    Err(ResponseError),
+}
+
+impl From<Result<String, ResponseError>> for Response {
+    fn from(value: Result<String, ResponseError>) -> Self {
+        match value {
+            Ok(reply) => Self::Ok(reply),
+            Err(error) => Self::Err(error),
+        }
+    }
+}
+
+impl From<Response> for Result<String, ResponseError> {
+    fn from(value: Response) -> Self {
+        match value {
+            Response::Ok(reply) => Ok(reply),
+            Response::Err(error) => Err(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, thiserror::Error)]
+#[error("{kind}: {message}")]
+pub struct ResponseError {
+    pub kind: ResponseErrorKind,
+    pub message: String,
+}
+
+impl ResponseError {
+    pub fn new(kind: ResponseErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(
+    Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, strum::Display, strum::EnumString,
+)]
+pub enum ResponseErrorKind {
+    /// The request was sent using a protocol version this instance of komorebi doesn't speak
+    UnsupportedVersion,
+    /// The request could not be deserialized into a command
+    InvalidRequest,
+    /// The request was sent over a network transport without a valid token
+    Unauthorized,
+    /// The window manager was busy and the command was not processed
+    Busy,
+    /// The window manager is paused and the command was not processed
+    Paused,
+    /// An Accessibility API call failed while processing the command
+    Accessibility,
+    /// A Core Graphics API call failed while processing the command
+    CoreGraphics,
+    /// The command failed for any other reason
+    Command,
+}
+
+impl From<&eyre::Report> for ResponseError {
+    fn from(value: &eyre::Report) -> Self {
+        let kind = if value.downcast_ref::<AccessibilityError>().is_some() {
+            ResponseErrorKind::Accessibility
+        } else if value.downcast_ref::<CoreGraphicsError>().is_some() {
+            ResponseErrorKind::CoreGraphics
+        } else {
+            match value.downcast_ref::<LibraryError>() {
+                Some(LibraryError::Accessibility(_)) => ResponseErrorKind::Accessibility,
+                Some(LibraryError::CoreGraphics(_)) => ResponseErrorKind::CoreGraphics,
+                Some(LibraryError::Eyre(_)) | None => ResponseErrorKind::Command,
+            }
+        };
+
+        Self::new(kind, value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hands out a single byte per read, like a client whose frame arrives in several segments
+    struct ByteAtATime(Cursor<Vec<u8>>);
+
+    impl Read for ByteAtATime {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(1);
+            self.0.read(&mut buf[..len])
+        }
+    }
+
+    #[test]
+    fn test_frame_roundtrip() {
+        let mut buf = vec![];
+        write_frame(&mut buf, br#"{"FocusWorkspaceNumber":5}"#).unwrap();
+        write_frame(&mut buf, b"").unwrap();
+
+        assert!(is_framed(&buf));
+
+        let mut reader = Cursor::new(buf);
+        assert_eq!(
+            read_frame(&mut reader).unwrap(),
+            Some((PROTOCOL_VERSION, br#"{"FocusWorkspaceNumber":5}"#.to_vec()))
+        );
+        assert_eq!(
+            read_frame(&mut reader).unwrap(),
+            Some((PROTOCOL_VERSION, vec![]))
+        );
+        assert_eq!(read_frame(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn test_bare_json_is_not_framed() {
+        assert!(!is_framed(br#"{"FocusWorkspaceNumber":5}"#));
+        assert!(!is_framed(br#""State""#));
+        assert!(!is_framed(b""));
+        assert!(!is_framed(b"K"));
+        assert!(!is_framed(b"KMZ\x01"));
+    }
+
+    #[test]
+    fn test_peek_framed_across_short_reads() {
+        let mut buf = vec![];
+        write_frame(&mut buf, b"payload").unwrap();
+
+        let (framed, mut reader) = peek_framed(ByteAtATime(Cursor::new(buf))).unwrap();
+        assert!(framed);
+        assert_eq!(
+            read_frame(&mut reader).unwrap(),
+            Some((PROTOCOL_VERSION, b"payload".to_vec()))
+        );
+
+        let (framed, mut reader) =
+            peek_framed(ByteAtATime(Cursor::new(br#""State""#.to_vec()))).unwrap();
+        assert!(!framed);
+        let mut bare = String::new();
+        reader.read_to_string(&mut bare).unwrap();
+        assert_eq!(bare, r#""State""#);
+
+        let (framed, _) = peek_framed(Cursor::new(b"KM".to_vec())).unwrap();
+        assert!(!framed);
+    }
+
+    #[test]
+    fn test_read_frame_rejects_bad_input() {
+        let mut truncated = vec![];
+        write_frame(&mut truncated, b"payload").unwrap();
+        truncated.truncate(truncated.len() - 2);
+        assert!(read_frame(&mut Cursor::new(truncated)).is_err());
+
+        let mut partial_header = Cursor::new(FRAME_MAGIC.to_vec());
+        assert!(read_frame(&mut partial_header).is_err());
+
+        let mut bad_magic = Cursor::new(b"XYZ\x01\x00\x00\x00\x00".to_vec());
+        assert!(read_frame(&mut bad_magic).is_err());
+
+        let mut too_large = FRAME_MAGIC.to_vec();
+        too_large.push(PROTOCOL_VERSION);
+        too_large.extend_from_slice(&(MAX_FRAME_LENGTH + 1).to_be_bytes());
+        assert!(read_frame(&mut Cursor::new(too_large)).is_err());
+    }
+
+    #[test]
+    fn test_response_error_kind_from_report() {
+        let report =
+            eyre::Report::from(LibraryError::Eyre(eyre::Report::msg("there is no monitor")));
+        assert_eq!(
+            ResponseError::from(&report).kind,
+            ResponseErrorKind::Command
+        );
+
+        let report = eyre::Report::msg("there is no workspace");
+        let error = ResponseError::from(&report);
+        assert_eq!(error.kind, ResponseErrorKind::Command);
+        assert_eq!(error.message, "there is no workspace");
+    }
+
+    #[test]
+    fn test_response_serialization() {
+        let response = Response::Err(ResponseError::new(ResponseErrorKind::Busy, "busy"));
+        let json = serde_json::to_string(&response).unwrap();
+
+        assert_eq!(serde_json::from_str::<Response>(&json).unwrap(), response);
+        assert_eq!(
+            Result::<String, ResponseError>::from(Response::Ok("{}".to_string())),
+            Ok("{}".to_string())
+        );
+    }
+}
@@ -0,0 +1,530 @@
+//! Configuration files which are layered on top of each other
+//!
+//! A configuration file may list other files in an `include` array. Included files are read in
+//! order and deep-merged, and the including file is merged on top of them, so a shared base
+//! configuration can sit underneath a per-machine overlay. Relative include paths are resolved
+//! from the directory of the including file, and environment variables are expanded with
+//! [`PathExt::replace_env`].
+//!
+//! Objects are merged key by key, while arrays and scalar values from later layers replace those
+//! of earlier layers. Merging an object with a value which is not an object is a conflict.
+//!
+//! Any layer may also define a `variables` table. Once every layer has been merged, `${name}`
+//! references in string values are replaced with the value of the variable. A string which
+//! consists of a single reference is replaced with the value as-is, which allows variables to
+//! hold numbers, arrays and objects. `$${` can be used to write a literal `${`. References are
+//! only substituted when at least one layer defines variables, so existing configuration files
+//! which contain `${` are read unchanged.
+
+use crate::core::pathext::PathExt;
+use color_eyre::eyre;
+use serde::de::DeserializeOwned;
+use serde_json::Map;
+use serde_json::Value;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Key of the array of files which are merged underneath a configuration file
+pub const INCLUDE_KEY: &str = "include";
+/// Key of the table of variables which can be referenced in a configuration file
+pub const VARIABLES_KEY: &str = "variables";
+
+/// A configuration value merged from a file and everything it includes
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    /// The merged value, with includes and variables resolved
+    pub value: Value,
+    /// Every file which contributed to the value, in the order in which they were merged
+    pub files: Vec<PathBuf>,
+}
+
+impl LayeredConfig {
+    /// Reads the configuration file at `path` and every file it includes
+    pub fn read(path: &Path) -> eyre::Result<Self> {
+        let mut files = vec![];
+        let mut variables = None;
+        let mut value = read_layer(path, &mut vec![], &mut files, &mut variables)?;
+
+        if let Some(variables) = variables {
+            substitute(&mut value, &variables, "")
+                .map_err(|error| eyre::eyre!("{}: {error}", path.display()))?;
+        }
+
+        Ok(Self { value, files })
+    }
+
+    /// Deserializes the merged value, naming the configuration file and the JSON pointer of the
+    /// value which could not be deserialized if it fails
+    pub fn deserialize<T: DeserializeOwned>(self) -> eyre::Result<T> {
+        T::deserialize(&self.value).map_err(|error| {
+            let path = self.files.last().map(|path| path.display().to_string());
+            let path = path.unwrap_or_default();
+
+            match locate_error::<T>(&self.value) {
+                pointer if pointer.is_empty() => eyre::eyre!("{path}: {error}"),
+                pointer => eyre::eyre!("{path}: {pointer}: {error}"),
+            }
+        })
+    }
+}
+
+fn read_layer(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    files: &mut Vec<PathBuf>,
+    variables: &mut Option<Map<String, Value>>,
+) -> eyre::Result<Value> {
+    let canonical = std::fs::canonicalize(path)
+        .map_err(|error| eyre::eyre!("could not read {}: {error}", path.display()))?;
+
+    if stack.contains(&canonical) {
+        eyre::bail!("{}: the file includes itself", path.display());
+    }
+
+    let content = std::fs::read_to_string(path)
+        .map_err(|error| eyre::eyre!("could not read {}: {error}", path.display()))?;
+
+    let mut layer: Value = serde_json::from_str(&content)
+        .map_err(|error| eyre::eyre!("{}: {error}", path.display()))?;
+
+    let Some(object) = layer.as_object_mut() else {
+        eyre::bail!("{}: the configuration must be an object", path.display());
+    };
+
+    let includes = match object.remove(INCLUDE_KEY) {
+        None => vec![],
+        Some(Value::Array(includes)) => includes
+            .into_iter()
+            .enumerate()
+            .map(|(idx, include)| match include {
+                Value::String(include) => Ok(include),
+                _ => Err(eyre::eyre!(
+                    "{}: /{INCLUDE_KEY}/{idx} must be a path to a file",
+                    path.display()
+                )),
+            })
+            .collect::<eyre::Result<Vec<_>>>()?,
+        Some(_) => eyre::bail!(
+            "{}: /{INCLUDE_KEY} must be an array of paths to files",
+            path.display()
+        ),
+    };
+
+    let layer_variables = match object.remove(VARIABLES_KEY) {
+        None => None,
+        Some(Value::Object(layer_variables)) => Some(layer_variables),
+        Some(_) => eyre::bail!("{}: /{VARIABLES_KEY} must be an object", path.display()),
+    };
+
+    stack.push(canonical);
+
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let mut merged = Value::Object(Map::new());
+
+    for include in includes {
+        let include = directory.join(include.replace_env());
+        let included = read_layer(&include, stack, files, variables)?;
+        merge(&mut merged, included, &include, "")?;
+    }
+
+    stack.pop();
+
+    if let Some(layer_variables) = layer_variables {
+        variables.get_or_insert_default().extend(layer_variables);
+    }
+
+    merge(&mut merged, layer, path, "")?;
+    files.push(path.to_path_buf());
+
+    Ok(merged)
+}
+
+/// Deep-merges `overlay` into `base`, naming `path` and the JSON pointer of the value in errors
+fn merge(base: &mut Value, overlay: Value, path: &Path, pointer: &str) -> eyre::Result<()> {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                let pointer = format!("{pointer}/{}", escape(&key));
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value, path, &pointer)?,
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => {
+            if base.is_object() || overlay.is_object() {
+                eyre::bail!(
+                    "{}: cannot merge {} into {} at {}",
+                    path.display(),
+                    kind(&overlay),
+                    kind(base),
+                    if pointer.is_empty() { "/" } else { pointer }
+                );
+            }
+
+            *base = overlay;
+        }
+    }
+
+    Ok(())
+}
+
+fn substitute(
+    value: &mut Value,
+    variables: &Map<String, Value>,
+    pointer: &str,
+) -> eyre::Result<()> {
+    match value {
+        Value::String(string) => {
+            if let Some(name) = string
+                .strip_prefix("${")
+                .and_then(|rest| rest.strip_suffix('}'))
+                .filter(|name| !name.contains(['{', '}', '$']))
+            {
+                *value = variable(variables, name, pointer)?.clone();
+            } else if string.contains('$') {
+                *string = interpolate(string, variables, pointer)?;
+            }
+        }
+        Value::Array(values) => {
+            for (idx, value) in values.iter_mut().enumerate() {
+                substitute(value, variables, &format!("{pointer}/{idx}"))?;
+            }
+        }
+        Value::Object(values) => {
+            for (key, value) in values.iter_mut() {
+                substitute(value, variables, &format!("{pointer}/{}", escape(key)))?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn interpolate(
+    string: &str,
+    variables: &Map<String, Value>,
+    pointer: &str,
+) -> eyre::Result<String> {
+    let mut out = String::with_capacity(string.len());
+    let mut rest = string;
+
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            out.push_str("${");
+            rest = escaped;
+        } else if let Some(reference) = rest.strip_prefix("${") {
+            let Some(end) = reference.find('}') else {
+                eyre::bail!("unterminated variable reference at {pointer}");
+            };
+
+            match variable(variables, &reference[..end], pointer)? {
+                Value::String(value) => out.push_str(value),
+                value @ (Value::Number(_) | Value::Bool(_)) => out.push_str(&value.to_string()),
+                _ => eyre::bail!(
+                    "variable {} cannot be used inside a string at {pointer}",
+                    &reference[..end]
+                ),
+            }
+
+            rest = &reference[end + 1..];
+        } else {
+            out.push('$');
+            rest = &rest[1..];
+        }
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn variable<'a>(
+    variables: &'a Map<String, Value>,
+    name: &str,
+    pointer: &str,
+) -> eyre::Result<&'a Value> {
+    variables
+        .get(name)
+        .ok_or_else(|| eyre::eyre!("unknown variable {name} at {pointer}"))
+}
+
+/// Finds the value at which deserializing the configuration fails by deserializing it again from
+/// text in which every value starts on its own line
+pub(crate) fn locate_error<T: DeserializeOwned>(raw: &Value) -> String {
+    let mut lines = vec![];
+    print_lines(raw, "", String::new(), &mut lines);
+
+    let text = lines
+        .iter()
+        .map(|(line, _)| line.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    match serde_json::from_str::<T>(&text) {
+        Ok(_) => String::new(),
+        Err(error) => error
+            .line()
+            .checked_sub(1)
+            .and_then(|line| lines.get(line))
+            .map(|(_, pointer)| pointer.clone())
+            .unwrap_or_default(),
+    }
+}
+
+/// Prints `value` as JSON with every value on its own line, along with the JSON pointer of the
+/// value which each line belongs to
+fn print_lines(value: &Value, pointer: &str, prefix: String, lines: &mut Vec<(String, String)>) {
+    let (open, close, children) = match value {
+        Value::Object(values) if !values.is_empty() => (
+            '{',
+            '}',
+            values
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        format!("{pointer}/{}", escape(key)),
+                        format!("{}: ", Value::String(key.clone())),
+                        value,
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ),
+        Value::Array(values) if !values.is_empty() => (
+            '[',
+            ']',
+            values
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| (format!("{pointer}/{idx}"), String::new(), value))
+                .collect(),
+        ),
+        _ => {
+            lines.push((format!("{prefix}{value}"), pointer.to_string()));
+            return;
+        }
+    };
+
+    lines.push((format!("{prefix}{open}"), pointer.to_string()));
+
+    let count = children.len();
+    for (idx, (pointer, prefix, value)) in children.into_iter().enumerate() {
+        print_lines(value, &pointer, prefix, lines);
+
+        if idx + 1 < count
+            && let Some((line, _)) = lines.last_mut()
+        {
+            line.push(',');
+        }
+    }
+
+    lines.push((close.to_string(), pointer.to_string()));
+}
+
+/// Escapes a key for use as a reference token in a JSON pointer
+pub(crate) fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Directory(PathBuf);
+
+    impl Directory {
+        fn new() -> Self {
+            let path = std::env::temp_dir().join(format!("komorebi-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn write(&self, name: &str, content: &str) -> PathBuf {
+            let path = self.0.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, content).unwrap();
+            path
+        }
+    }
+
+    impl Drop for Directory {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_includes_are_merged_in_order() {
+        let directory = Directory::new();
+        directory.write(
+            "shared/base.json",
+            r#"{
+                "border": true,
+                "border_width": 4,
+                "monitors": [{ "workspaces": [{ "name": "code" }] }],
+                "animation": { "enabled": true, "duration": 250 }
+            }"#,
+        );
+        directory.write(
+            "shared/team.json",
+            r#"{ "include": ["base.json"], "border_width": 6, "animation": { "duration": 100 } }"#,
+        );
+        let path = directory.write(
+            "komorebi.json",
+            r#"{
+                "include": ["shared/team.json"],
+                "border": false,
+                "monitors": [{ "workspaces": [{ "name": "web" }] }]
+            }"#,
+        );
+
+        let config = LayeredConfig::read(&path).unwrap();
+
+        assert_eq!(
+            config.value,
+            json!({
+                "border": false,
+                "border_width": 6,
+                "monitors": [{ "workspaces": [{ "name": "web" }] }],
+                "animation": { "enabled": true, "duration": 100 }
+            })
+        );
+
+        assert_eq!(
+            config.files,
+            vec![
+                directory.0.join("shared/base.json"),
+                directory.0.join("shared/team.json"),
+                path,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conflicts_name_the_file_and_pointer() {
+        let directory = Directory::new();
+        directory.write("base.json", r#"{ "animation": { "enabled": true } }"#);
+        let path = directory.write(
+            "komorebi.json",
+            r#"{ "include": ["base.json"], "animation": true }"#,
+        );
+
+        let error = LayeredConfig::read(&path).unwrap_err().to_string();
+        assert_eq!(
+            error,
+            format!(
+                "{}: cannot merge a boolean into an object at /animation",
+                path.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_deserialize_errors_name_the_file_and_pointer() {
+        let directory = Directory::new();
+        let path = directory.write(
+            "komorebi.json",
+            r#"{ "monitors": [{ "workspaces": [{ "name": "web", "container_padding": "wide" }] }] }"#,
+        );
+
+        let error = LayeredConfig::read(&path)
+            .unwrap()
+            .deserialize::<crate::static_config::StaticConfig>()
+            .unwrap_err()
+            .to_string();
+
+        assert!(
+            error.starts_with(&format!(
+                "{}: /monitors/0/workspaces/0/container_padding: invalid type",
+                path.display()
+            )),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_include_cycles_are_rejected() {
+        let directory = Directory::new();
+        directory.write("a.json", r#"{ "include": ["b.json"] }"#);
+        let path = directory.write("b.json", r#"{ "include": ["a.json"] }"#);
+
+        let error = LayeredConfig::read(&path).unwrap_err().to_string();
+        assert!(error.ends_with("the file includes itself"), "{error}");
+    }
+
+    #[test]
+    fn test_variables() {
+        let directory = Directory::new();
+        directory.write(
+            "base.json",
+            r#"{
+                "variables": { "padding": 10, "font": "JetBrains Mono" },
+                "default_workspace_padding": "${padding}",
+                "stackbar": { "tabs": { "font_family": "${font}" } }
+            }"#,
+        );
+        let path = directory.write(
+            "komorebi.json",
+            r#"{
+                "include": ["base.json"],
+                "variables": { "padding": 20, "offset": { "left": 0, "top": 40, "right": 0, "bottom": 40 } },
+                "global_work_area_offset": "${offset}",
+                "app_specific_configuration_path": "$HOME/${font}-${padding}-$${literal}.json"
+            }"#,
+        );
+
+        let config = LayeredConfig::read(&path).unwrap();
+
+        assert_eq!(
+            config.value,
+            json!({
+                "default_workspace_padding": 20,
+                "stackbar": { "tabs": { "font_family": "JetBrains Mono" } },
+                "global_work_area_offset": { "left": 0, "top": 40, "right": 0, "bottom": 40 },
+                "app_specific_configuration_path": "$HOME/JetBrains Mono-20-${literal}.json"
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_variables_are_rejected() {
+        let directory = Directory::new();
+        let path = directory.write(
+            "komorebi.json",
+            r#"{ "variables": {}, "monitors": [{ "workspaces": [{ "name": "${nmae}" }] }] }"#,
+        );
+
+        let error = LayeredConfig::read(&path).unwrap_err().to_string();
+        assert_eq!(
+            error,
+            format!(
+                "{}: unknown variable nmae at /monitors/0/workspaces/0/name",
+                path.display()
+            )
+        );
+
+        // references are left alone when no variables are defined
+        let path = directory.write("plain.json", r#"{ "command": "echo ${HOME}" }"#);
+        assert_eq!(
+            LayeredConfig::read(&path).unwrap().value,
+            json!({ "command": "echo ${HOME}" })
+        );
+    }
+}
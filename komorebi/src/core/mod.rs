@@ -37,8 +37,12 @@ pub use komorebi_layouts::validate_ratios;
 pub mod animation;
 pub mod asc;
 pub mod config_generation;
+pub mod custom_layout;
+pub mod ipc;
+pub mod layered_config;
 pub mod pathext;
 pub mod rect_ext;
+pub mod state_path;
 
 #[derive(
     Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, Display, EnumString, ValueEnum,
@@ -198,6 +202,75 @@ pub enum MonocleFocusBehaviour {
     NoOp,
 }
 
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Display,
+    EnumString,
+    ValueEnum,
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// Focus follows mouse implementation
+pub enum FocusFollowsMouseImplementation {
+    /// Focus the tiled or floating window under the cursor once the cursor comes to rest
+    #[default]
+    Komorebi,
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Display,
+    EnumString,
+    ValueEnum,
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// Stackbar mode
+pub enum StackbarMode {
+    /// Always show the stackbar, even for containers with a single window
+    Always,
+    /// Never show the stackbar
+    #[default]
+    Never,
+    /// Only show the stackbar for containers with more than one window
+    OnStack,
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Display,
+    EnumString,
+    ValueEnum,
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// Stackbar tab label
+pub enum StackbarLabel {
+    /// Label tabs with the name of the application process
+    #[default]
+    Process,
+    /// Label tabs with the window title
+    Title,
+}
+
 #[derive(
     Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Display, EnumString, ValueEnum,
 )]
@@ -254,6 +327,21 @@ pub enum StateQuery {
     FocusedWorkspaceLayout,
     FocusedContainerKind,
     Version,
+    FocusedWindowTitle,
+    FocusedWindowExe,
+    FocusedWindowId,
+    WorkspaceCountPerMonitor,
+    FloatingWindowCount,
+}
+
+#[derive(
+    Copy, Clone, Debug, Default, Serialize, Deserialize, Display, EnumString, ValueEnum, PartialEq,
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum MetricsFormat {
+    #[default]
+    Json,
+    Prometheus,
 }
 
 #[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -310,11 +398,16 @@ pub enum SocketMessage {
     PromoteFocus,
     PromoteWindow(OperationDirection),
     EagerFocus(String),
+    Mark(String),
+    FocusMark(String),
+    SwapWithMark(String),
     LockMonitorWorkspaceContainer(usize, usize, usize),
     UnlockMonitorWorkspaceContainer(usize, usize, usize),
     ToggleLock,
     ToggleFloat,
+    ToggleSticky,
     ToggleMonocle,
+    ToggleScratchpad(String),
     // ToggleMaximize,
     ToggleWindowContainerBehaviour,
     ToggleFloatOverride,
@@ -333,7 +426,7 @@ pub enum SocketMessage {
     CycleLayout(CycleDirection),
     ScrollingLayoutColumns(NonZeroUsize),
     LayoutRatios(Option<Vec<f32>>, Option<Vec<f32>>),
-    // ChangeLayoutCustom(#[serde_as(as = "ResolvedPathBuf")] PathBuf),
+    ChangeLayoutCustom(#[serde_as(as = "ResolvedPathBuf")] PathBuf),
     FlipLayout(Axis),
     ToggleWorkspaceWindowContainerBehaviour,
     ToggleWorkspaceFloatOverride,
@@ -349,6 +442,8 @@ pub enum SocketMessage {
     TogglePause,
     Retile,
     RetileWithResizeDimensions,
+    Undo,
+    Redo,
     QuickSave,
     QuickLoad,
     Save(#[serde_as(as = "ResolvedPathBuf")] PathBuf),
@@ -356,6 +451,7 @@ pub enum SocketMessage {
     CycleFocusMonitor(CycleDirection),
     CycleFocusWorkspace(CycleDirection),
     CycleFocusEmptyWorkspace(CycleDirection),
+    CycleFocusWorkspaceSet(CycleDirection),
     FocusMonitorNumber(usize),
     FocusMonitorAtCursor,
     FocusLastWorkspace,
@@ -364,6 +460,7 @@ pub enum SocketMessage {
     FocusWorkspaceNumbers(usize),
     FocusMonitorWorkspaceNumber(usize, usize),
     FocusNamedWorkspace(String),
+    FocusWorkspaceSet(String),
     ContainerPadding(usize, usize, i32),
     NamedWorkspaceContainerPadding(String, i32),
     FocusedWorkspaceContainerPadding(i32),
@@ -375,25 +472,26 @@ pub enum SocketMessage {
     WorkspaceName(usize, usize, String),
     WorkspaceLayout(usize, usize, DefaultLayout),
     NamedWorkspaceLayout(String, DefaultLayout),
-    // WorkspaceLayoutCustom(usize, usize, #[serde_as(as = "ResolvedPathBuf")] PathBuf),
-    // NamedWorkspaceLayoutCustom(String, #[serde_as(as = "ResolvedPathBuf")] PathBuf),
+    WorkspaceLayoutCustom(usize, usize, #[serde_as(as = "ResolvedPathBuf")] PathBuf),
+    NamedWorkspaceLayoutCustom(String, #[serde_as(as = "ResolvedPathBuf")] PathBuf),
     WorkspaceLayoutRule(usize, usize, usize, DefaultLayout),
     NamedWorkspaceLayoutRule(String, usize, DefaultLayout),
-    // WorkspaceLayoutCustomRule(
-    //     usize,
-    //     usize,
-    //     usize,
-    //     #[serde_as(as = "ResolvedPathBuf")] PathBuf,
-    // ),
-    // NamedWorkspaceLayoutCustomRule(String, usize, #[serde_as(as = "ResolvedPathBuf")] PathBuf),
+    WorkspaceLayoutCustomRule(
+        usize,
+        usize,
+        usize,
+        #[serde_as(as = "ResolvedPathBuf")] PathBuf,
+    ),
+    NamedWorkspaceLayoutCustomRule(String, usize, #[serde_as(as = "ResolvedPathBuf")] PathBuf),
     ClearWorkspaceLayoutRules(usize, usize),
     ClearNamedWorkspaceLayoutRules(String),
     ToggleWorkspaceLayer,
     // Configuration
-    // ReloadConfiguration,
+    ReloadConfiguration,
     ReplaceConfiguration(#[serde_as(as = "ResolvedPathBuf")] PathBuf),
     ReloadStaticConfiguration(#[serde_as(as = "ResolvedPathBuf")] PathBuf),
-    // WatchConfiguration(bool),
+    ActivateProfile(Option<String>),
+    WatchConfiguration(bool),
     // CompleteConfiguration,
     // AltFocusHack(bool),
     Theme(Box<KomorebiTheme>),
@@ -410,19 +508,19 @@ pub enum SocketMessage {
     BorderWidth(i32),
     BorderOffset(i32),
     // BorderImplementation(BorderImplementation),
-    // Transparency(bool),
-    // ToggleTransparency,
-    // TransparencyAlpha(u8),
+    Transparency(bool),
+    ToggleTransparency,
+    TransparencyAlpha(u8),
     // InvisibleBorders(Rect),
-    // StackbarMode(StackbarMode),
-    // StackbarLabel(StackbarLabel),
-    // StackbarFocusedTextColour(u32, u32, u32),
-    // StackbarUnfocusedTextColour(u32, u32, u32),
-    // StackbarBackgroundColour(u32, u32, u32),
-    // StackbarHeight(i32),
-    // StackbarTabWidth(i32),
-    // StackbarFontSize(i32),
-    // StackbarFontFamily(Option<String>),
+    StackbarMode(StackbarMode),
+    StackbarLabel(StackbarLabel),
+    StackbarFocusedTextColour(u32, u32, u32),
+    StackbarUnfocusedTextColour(u32, u32, u32),
+    StackbarBackgroundColour(u32, u32, u32),
+    StackbarHeight(i32),
+    StackbarTabWidth(i32),
+    StackbarFontSize(i32),
+    StackbarFontFamily(Option<String>),
     WorkAreaOffset(Rect),
     MonitorWorkAreaOffset(usize, Rect),
     WorkspaceWorkAreaOffset(usize, usize, Rect),
@@ -450,9 +548,12 @@ pub enum SocketMessage {
     GlobalState,
     VisibleWindows,
     MonitorInformation,
+    Metrics(MetricsFormat),
     Query(StateQuery),
-    // FocusFollowsMouse(FocusFollowsMouseImplementation, bool),
-    // ToggleFocusFollowsMouse(FocusFollowsMouseImplementation),
+    QueryJson(StateQuery),
+    QueryPath(String),
+    FocusFollowsMouse(FocusFollowsMouseImplementation, bool),
+    ToggleFocusFollowsMouse(FocusFollowsMouseImplementation),
     MouseFollowsFocus(bool),
     ToggleMouseFollowsFocus,
     // RemoveTitleBar(ApplicationIdentifier, String),
@@ -460,14 +561,15 @@ pub enum SocketMessage {
     AddSubscriberSocket(String),
     AddSubscriberSocketWithOptions(String, SubscribeOptions),
     RemoveSubscriberSocket(String),
-    // AddSubscriberPipe(String),
-    // RemoveSubscriberPipe(String),
+    AddSubscriberPipe(String),
+    RemoveSubscriberPipe(String),
     ApplicationSpecificConfigurationSchema,
     NotificationSchema,
     SocketSchema,
     StaticConfigSchema,
     GenerateStaticConfig,
     DebugWindow(u32),
+    Transaction(Vec<SocketMessage>),
 }
 
 impl SocketMessage {
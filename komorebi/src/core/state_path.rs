@@ -0,0 +1,262 @@
+//! Path expressions for selecting values from the serialized window manager [`State`](crate::State)
+//!
+//! A path is made up of field names separated by `.`, each of which can be followed by selectors
+//! in square brackets, for example `monitors[0].workspaces[*].name`:
+//!
+//! - `[n]` selects the element at index `n`
+//! - `[*]` selects every element
+//! - `[focused]` selects the focused element
+//!
+//! Selectors applied to a ring, such as `monitors`, `workspaces`, `containers` or `windows`, act on
+//! the elements of the ring, so the exe of the focused window can be selected with
+//! `monitors[focused].workspaces[focused].containers[focused].windows[focused].details.exe`.
+//!
+//! A path without any `[*]` selectors selects a single value, otherwise all matching values are
+//! collected into an array.
+
+use serde_json::Value;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid state path \"{path}\": {reason}")]
+pub struct StatePathError {
+    path: String,
+    reason: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Field(String),
+    Index(usize),
+    All,
+    Focused,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatePath {
+    raw: String,
+    segments: Vec<Segment>,
+}
+
+impl FromStr for StatePath {
+    type Err = StatePathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |reason| StatePathError {
+            path: s.to_string(),
+            reason,
+        };
+
+        // allow the root to be written out the same way as in jsonpath
+        let path = s.trim();
+        let path = path
+            .strip_prefix("$.")
+            .or_else(|| path.strip_prefix('$'))
+            .unwrap_or(path);
+
+        if path.is_empty() {
+            return Err(error("the path is empty"));
+        }
+
+        let mut segments = vec![];
+
+        for part in path.split('.') {
+            let (field, mut selectors) = part.split_at(part.find('[').unwrap_or(part.len()));
+
+            if field.is_empty() {
+                return Err(error("every selector must follow a field name"));
+            }
+
+            segments.push(Segment::Field(field.to_string()));
+
+            while !selectors.is_empty() {
+                let (selector, rest) = selectors
+                    .strip_prefix('[')
+                    .and_then(|selectors| selectors.split_once(']'))
+                    .ok_or_else(|| error("selectors must be enclosed in square brackets"))?;
+
+                segments.push(match selector.trim() {
+                    "*" => Segment::All,
+                    "focused" => Segment::Focused,
+                    index => {
+                        Segment::Index(index.parse().map_err(|_| {
+                            error("selectors must be an index, \"*\" or \"focused\"")
+                        })?)
+                    }
+                });
+
+                selectors = rest;
+            }
+        }
+
+        Ok(Self {
+            raw: s.to_string(),
+            segments,
+        })
+    }
+}
+
+impl Display for StatePath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+/// The elements of an array, or of a serialized ring
+fn elements(value: &Value) -> Option<&Vec<Value>> {
+    match value {
+        Value::Array(elements) => Some(elements),
+        Value::Object(object) => object.get("elements").and_then(Value::as_array),
+        _ => None,
+    }
+}
+
+fn focused(value: &Value) -> Option<&Value> {
+    let idx = value.get("focused").and_then(Value::as_u64)?;
+    elements(value)?.get(usize::try_from(idx).ok()?)
+}
+
+impl Segment {
+    fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        match self {
+            Self::Field(field) => value.get(field).into_iter().collect(),
+            Self::Index(idx) => elements(value)
+                .and_then(|elements| elements.get(*idx))
+                .into_iter()
+                .collect(),
+            Self::All => match (elements(value), value) {
+                (Some(elements), _) => elements.iter().collect(),
+                (None, Value::Object(object)) => object.values().collect(),
+                (None, _) => vec![],
+            },
+            Self::Focused => focused(value).into_iter().collect(),
+        }
+    }
+}
+
+impl StatePath {
+    /// Whether the path can match more than one value
+    pub fn is_multiple(&self) -> bool {
+        self.segments.contains(&Segment::All)
+    }
+
+    /// Selects the value at this path, or an array of all matching values if the path contains a
+    /// `[*]` selector
+    ///
+    /// Returns `None` if a path which selects a single value does not match anything
+    pub fn select(&self, value: &Value) -> Option<Value> {
+        let mut matches = vec![value];
+
+        for segment in &self.segments {
+            matches = matches
+                .into_iter()
+                .flat_map(|value| segment.select(value))
+                .collect();
+        }
+
+        if self.is_multiple() {
+            Some(Value::Array(matches.into_iter().cloned().collect()))
+        } else {
+            matches.first().map(|value| (*value).clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn state() -> Value {
+        json!({
+            "monitors": {
+                "elements": [
+                    {
+                        "workspaces": {
+                            "elements": [{ "name": "I" }, { "name": "II" }],
+                            "focused": 1
+                        }
+                    },
+                    {
+                        "workspaces": {
+                            "elements": [{ "name": "III" }],
+                            "focused": 0
+                        }
+                    }
+                ],
+                "focused": 0
+            },
+            "is_paused": false
+        })
+    }
+
+    fn select(path: &str) -> Option<Value> {
+        StatePath::from_str(path).unwrap().select(&state())
+    }
+
+    #[test]
+    fn test_select_fields_and_indices() {
+        assert_eq!(select("is_paused"), Some(json!(false)));
+        assert_eq!(select("monitors[1].workspaces[0].name"), Some(json!("III")));
+        assert_eq!(
+            select("$.monitors[0].workspaces[1].name"),
+            Some(json!("II"))
+        );
+        assert_eq!(select("monitors.focused"), Some(json!(0)));
+        assert_eq!(select("monitors[2].workspaces[0].name"), None);
+        assert_eq!(select("monitors[0].missing"), None);
+    }
+
+    #[test]
+    fn test_select_focused() {
+        assert_eq!(
+            select("monitors[focused].workspaces[focused].name"),
+            Some(json!("II"))
+        );
+        assert_eq!(
+            select("monitors[focused].workspaces[focused]"),
+            Some(json!({ "name": "II" }))
+        );
+    }
+
+    #[test]
+    fn test_select_wildcards() {
+        assert_eq!(
+            select("monitors[0].workspaces[*].name"),
+            Some(json!(["I", "II"]))
+        );
+        assert_eq!(
+            select("monitors[*].workspaces[*].name"),
+            Some(json!(["I", "II", "III"]))
+        );
+        assert_eq!(
+            select("monitors[*].workspaces[focused].name"),
+            Some(json!(["II", "III"]))
+        );
+        assert_eq!(select("monitors[*].missing"), Some(json!([])));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for path in [
+            "",
+            "$",
+            "monitors.",
+            "monitors[0",
+            "monitors[first]",
+            "[0].name",
+            "monitors[0]name",
+        ] {
+            assert!(StatePath::from_str(path).is_err(), "{path}");
+        }
+
+        assert_eq!(
+            StatePath::from_str("monitors[ 0 ].workspaces[*]")
+                .unwrap()
+                .to_string(),
+            "monitors[ 0 ].workspaces[*]"
+        );
+    }
+}
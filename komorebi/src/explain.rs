@@ -0,0 +1,312 @@
+//! Offline explanation of how a window would be handled by a static configuration
+//!
+//! [`explain`] evaluates the rules of a [`StaticConfig`] and its application-specific
+//! configuration files against a window which only needs to be described, using the same
+//! matching logic as the window manager, so rules can be written and tested without the
+//! application being installed or komorebi running.
+
+use crate::FLOATING_APPLICATIONS;
+use crate::IGNORE_IDENTIFIERS;
+use crate::MANAGE_IDENTIFIERS;
+use crate::PERMAIGNORE_CLASSES;
+use crate::REGEX_IDENTIFIERS;
+use crate::TABBED_APPLICATIONS;
+use crate::TITLELESS_APPLICATIONS;
+use crate::core::config_generation::WorkspaceMatchingRule;
+use crate::static_config::StaticConfig;
+use crate::static_config::handle_asc_file;
+use crate::static_config::populate_rules;
+use crate::window::EligibilityRules;
+use crate::window::RuleDebug;
+use crate::window::resolve_title;
+use crate::window::should_act;
+use crate::window::window_is_eligible_for_rules;
+use color_eyre::eyre;
+use serde::Serialize;
+
+/// The identifiers of a window which may not exist yet
+#[derive(Clone, Debug, Default)]
+pub struct HypotheticalWindow {
+    pub exe: String,
+    pub title: Option<String>,
+    pub role: String,
+    pub subrole: String,
+    pub path: String,
+}
+
+/// A workspace rule which matches the window
+#[derive(Clone, Debug, Serialize)]
+pub struct WorkspaceRuleMatch {
+    /// Name of the workspace which the rule belongs to
+    pub workspace: String,
+    #[serde(flatten)]
+    pub rule: WorkspaceMatchingRule,
+}
+
+/// How a window would be handled by a static configuration
+#[derive(Debug, Serialize)]
+pub struct Explanation {
+    pub rule_debug: RuleDebug,
+    /// Every workspace rule which matches the window in the order in which they are enforced,
+    /// the first of which decides where a managed window is moved to
+    pub workspace_rules: Vec<WorkspaceRuleMatch>,
+}
+
+/// Explains how `window` would be handled by `config`, on top of the rules which are built into
+/// komorebi
+pub fn explain(config: &StaticConfig, window: &HypotheticalWindow) -> eyre::Result<Explanation> {
+    let permaignore_classes = PERMAIGNORE_CLASSES.lock().clone();
+    let mut regex_identifiers = REGEX_IDENTIFIERS.lock().clone();
+    let mut ignore_identifiers = IGNORE_IDENTIFIERS.lock().clone();
+    let mut manage_identifiers = MANAGE_IDENTIFIERS.lock().clone();
+    let mut floating_applications = FLOATING_APPLICATIONS.lock().clone();
+    let mut titleless_applications = TITLELESS_APPLICATIONS.lock().clone();
+    let mut tabbed_applications = TABBED_APPLICATIONS.lock().clone();
+
+    let mut config = config.clone();
+
+    for (rules, identifiers) in [
+        (&mut config.ignore_rules, &mut ignore_identifiers),
+        (&mut config.manage_rules, &mut manage_identifiers),
+        (
+            &mut config.floating_applications,
+            &mut floating_applications,
+        ),
+    ] {
+        if let Some(rules) = rules {
+            populate_rules(rules, identifiers, &mut regex_identifiers)?;
+        }
+    }
+
+    for (applications, identifiers) in [
+        (&config.titleless_applications, &mut titleless_applications),
+        (&config.tabbed_applications, &mut tabbed_applications),
+    ] {
+        for application in applications.iter().flatten() {
+            if !identifiers.contains(application) {
+                identifiers.push(application.clone());
+            }
+        }
+    }
+
+    for path in config.app_specific_configuration_paths() {
+        handle_asc_file(
+            &path,
+            &mut ignore_identifiers,
+            &mut manage_identifiers,
+            &mut floating_applications,
+            &mut tabbed_applications,
+            &mut titleless_applications,
+            &mut regex_identifiers,
+        )?;
+    }
+
+    let mut workspace_rules = vec![];
+    for (monitor_index, monitor) in config.monitors.iter_mut().flatten().enumerate() {
+        for (workspace_index, workspace) in monitor.workspaces.iter_mut().enumerate() {
+            for (rules, initial_only) in [
+                (&mut workspace.workspace_rules, false),
+                (&mut workspace.initial_workspace_rules, true),
+            ] {
+                let Some(rules) = rules else {
+                    continue;
+                };
+
+                populate_rules(rules, &mut vec![], &mut regex_identifiers)?;
+
+                for rule in rules.iter() {
+                    workspace_rules.push(WorkspaceRuleMatch {
+                        workspace: workspace.name.clone(),
+                        rule: WorkspaceMatchingRule {
+                            monitor_index,
+                            workspace_index,
+                            matching_rule: rule.clone(),
+                            initial_only,
+                        },
+                    });
+                }
+            }
+        }
+    }
+
+    let mut rule_debug = RuleDebug {
+        is_window: true,
+        ..Default::default()
+    };
+
+    let Some(title) = resolve_title(
+        window.title.clone(),
+        Some(window.exe.clone()),
+        &titleless_applications,
+        &mut rule_debug,
+    ) else {
+        return Ok(Explanation {
+            rule_debug,
+            workspace_rules: vec![],
+        });
+    };
+
+    rule_debug.title = Some(title.clone());
+    rule_debug.exe_name = Some(window.exe.clone());
+    rule_debug.role = Some(window.role.clone());
+    rule_debug.subrole = Some(window.subrole.clone());
+    rule_debug.path = Some(window.path.clone());
+
+    let classes = [window.role.as_str(), window.subrole.as_str()];
+
+    let rules = EligibilityRules {
+        permaignore_classes: &permaignore_classes,
+        ignore_identifiers: &ignore_identifiers,
+        manage_identifiers: &manage_identifiers,
+        floating_applications: &floating_applications,
+        tabbed_applications: &tabbed_applications,
+        regex_identifiers: &regex_identifiers,
+    };
+
+    rule_debug.should_manage = window_is_eligible_for_rules(
+        &title,
+        &window.exe,
+        &classes,
+        &window.path,
+        &rules,
+        &mut rule_debug,
+    );
+
+    workspace_rules.retain(|workspace_rule| {
+        should_act(
+            &title,
+            &window.exe,
+            &classes,
+            &window.path,
+            std::slice::from_ref(&workspace_rule.rule.matching_rule),
+            &regex_identifiers,
+        )
+        .is_some()
+    });
+
+    Ok(Explanation {
+        rule_debug,
+        workspace_rules,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> StaticConfig {
+        StaticConfig::read_raw(
+            r#"{
+                "ignore_rules": [
+                    { "kind": "Exe", "id": "Steam" },
+                    { "kind": "Title", "id": "^Picture", "matching_strategy": "Regex" }
+                ],
+                "manage_rules": [{ "kind": "Title", "id": "Picture-in-Picture Player" }],
+                "floating_applications": [{ "kind": "Exe", "id": "Calculator" }],
+                "titleless_applications": ["Raycast"],
+                "monitors": [
+                    {
+                        "workspaces": [
+                            { "name": "code" },
+                            {
+                                "name": "web",
+                                "workspace_rules": [{ "kind": "Exe", "id": "Safari" }],
+                                "initial_workspace_rules": [
+                                    { "kind": "Title", "id": "Safari", "matching_strategy": "Contains" }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn window(exe: &str, title: Option<&str>) -> HypotheticalWindow {
+        HypotheticalWindow {
+            exe: exe.to_string(),
+            title: title.map(String::from),
+            role: String::from("AXWindow"),
+            subrole: String::from("AXStandardWindow"),
+            path: format!("/Applications/{exe}.app"),
+        }
+    }
+
+    #[test]
+    fn test_ignored_and_managed() {
+        let config = config();
+
+        let explanation = explain(&config, &window("Steam", Some("Friends"))).unwrap();
+        assert!(!explanation.rule_debug.should_manage);
+        assert!(explanation.rule_debug.matches_ignore_identifier.is_some());
+
+        // the manage rule overrides the regex ignore rule
+        let explanation = explain(
+            &config,
+            &window("Safari", Some("Picture-in-Picture Player")),
+        )
+        .unwrap();
+        assert!(explanation.rule_debug.should_manage);
+        assert!(explanation.rule_debug.matches_ignore_identifier.is_some());
+        assert!(explanation.rule_debug.matches_managed_override.is_some());
+
+        let explanation = explain(&config, &window("Calculator", Some("Calculator"))).unwrap();
+        assert!(explanation.rule_debug.should_manage);
+        assert!(
+            explanation
+                .rule_debug
+                .matches_floating_applications
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_titleless_and_permaignored() {
+        let config = config();
+
+        let explanation = explain(&config, &window("Raycast", Some(""))).unwrap();
+        assert!(explanation.rule_debug.should_manage);
+        assert_eq!(
+            explanation.rule_debug.matches_titleless_applications,
+            Some(String::from("Raycast"))
+        );
+
+        let explanation = explain(&config, &window("Finder", None)).unwrap();
+        assert!(!explanation.rule_debug.should_manage);
+        assert!(!explanation.rule_debug.has_title);
+
+        let mut dialog = window("Finder", Some("Copy"));
+        dialog.subrole = String::from("AXDialog");
+        let explanation = explain(&config, &dialog).unwrap();
+        assert!(!explanation.rule_debug.should_manage);
+        assert_eq!(
+            explanation.rule_debug.matches_permaignore_class,
+            Some(String::from("AXDialog"))
+        );
+    }
+
+    #[test]
+    fn test_workspace_rules() {
+        let config = config();
+
+        let explanation = explain(&config, &window("Safari", Some("Safari - Start Page"))).unwrap();
+        let matched = explanation
+            .workspace_rules
+            .iter()
+            .map(|workspace_rule| {
+                (
+                    workspace_rule.workspace.as_str(),
+                    workspace_rule.rule.monitor_index,
+                    workspace_rule.rule.workspace_index,
+                    workspace_rule.rule.initial_only,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(matched, vec![("web", 0, 1, false), ("web", 0, 1, true)]);
+
+        let explanation = explain(&config, &window("Terminal", Some("zsh"))).unwrap();
+        assert!(explanation.workspace_rules.is_empty());
+    }
+}
@@ -0,0 +1,547 @@
+//! A bounded history of window arrangements which can be walked with undo and redo
+//!
+//! A [`LayoutSnapshot`] is taken before every layout-altering command, identifying windows by
+//! their id. Restoring a snapshot moves the windows which are still managed back into the
+//! recorded containers; windows which have closed in the meantime are skipped, and windows which
+//! have been opened since are left on the workspace they are on.
+
+use crate::container::Container;
+use crate::core::Axis;
+use crate::core::Layout;
+use crate::core::Rect;
+use crate::core::SocketMessage;
+use crate::core::custom_layout::CustomLayout;
+use crate::monitor::Monitor;
+use crate::ring::Ring;
+use crate::window::Window;
+use crate::window_manager::WindowManager;
+use crate::workspace::Workspace;
+use color_eyre::eyre;
+use std::collections::VecDeque;
+
+/// The number of snapshots which can be undone
+pub const HISTORY_LIMIT: usize = 50;
+
+#[derive(Debug, Default)]
+pub struct History {
+    undo: VecDeque<LayoutSnapshot>,
+    redo: Vec<LayoutSnapshot>,
+}
+
+impl History {
+    /// Records the arrangement from before a layout-altering command, discarding anything which
+    /// could have been redone
+    pub fn push(&mut self, snapshot: LayoutSnapshot) {
+        if self.undo.len() == HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+
+        self.undo.push_back(snapshot);
+        self.redo.clear();
+    }
+
+    /// Exchanges the current arrangement for the most recently recorded one
+    pub fn undo(&mut self, current: LayoutSnapshot) -> Option<LayoutSnapshot> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current);
+
+        Some(previous)
+    }
+
+    /// Exchanges the current arrangement for the most recently undone one
+    pub fn redo(&mut self, current: LayoutSnapshot) -> Option<LayoutSnapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push_back(current);
+
+        Some(next)
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+}
+
+/// Commands which record a snapshot so that they can be undone
+pub fn is_undoable(message: &SocketMessage) -> bool {
+    match message {
+        SocketMessage::Transaction(steps) => steps.iter().any(is_undoable),
+        message => matches!(
+            message,
+            SocketMessage::MoveWindow(_)
+                | SocketMessage::CycleMoveWindow(_)
+                | SocketMessage::StackWindow(_)
+                | SocketMessage::UnstackWindow
+                | SocketMessage::CycleStackIndex(_)
+                | SocketMessage::StackAll
+                | SocketMessage::UnstackAll
+                | SocketMessage::ResizeWindowEdge(_, _)
+                | SocketMessage::ResizeWindowAxis(_, _)
+                | SocketMessage::MoveContainerToLastWorkspace
+                | SocketMessage::SendContainerToLastWorkspace
+                | SocketMessage::MoveContainerToMonitorNumber(_)
+                | SocketMessage::CycleMoveContainerToMonitor(_)
+                | SocketMessage::MoveContainerToWorkspaceNumber(_)
+                | SocketMessage::MoveContainerToNamedWorkspace(_)
+                | SocketMessage::CycleMoveContainerToWorkspace(_)
+                | SocketMessage::SendContainerToMonitorNumber(_)
+                | SocketMessage::CycleSendContainerToMonitor(_)
+                | SocketMessage::SendContainerToWorkspaceNumber(_)
+                | SocketMessage::CycleSendContainerToWorkspace(_)
+                | SocketMessage::SendContainerToMonitorWorkspaceNumber(_, _)
+                | SocketMessage::MoveContainerToMonitorWorkspaceNumber(_, _)
+                | SocketMessage::SendContainerToNamedWorkspace(_)
+                | SocketMessage::Promote
+                | SocketMessage::PromoteSwap
+                | SocketMessage::PromoteWindow(_)
+                | SocketMessage::SwapWithMark(_)
+                | SocketMessage::ToggleFloat
+                | SocketMessage::ToggleMonocle
+                | SocketMessage::ToggleLock
+                | SocketMessage::LockMonitorWorkspaceContainer(_, _, _)
+                | SocketMessage::UnlockMonitorWorkspaceContainer(_, _, _)
+                | SocketMessage::ChangeLayout(_)
+                | SocketMessage::CycleLayout(_)
+                | SocketMessage::ChangeLayoutCustom(_)
+                | SocketMessage::FlipLayout(_)
+                | SocketMessage::ToggleTiling
+                | SocketMessage::Retile
+        ),
+    }
+}
+
+/// The arrangement of every monitor and workspace, with windows identified by their id
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutSnapshot {
+    pub focused_monitor_idx: usize,
+    pub monitors: Vec<MonitorSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorSnapshot {
+    pub focused_workspace_idx: usize,
+    pub workspaces: Vec<WorkspaceSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceSnapshot {
+    pub layout: Layout,
+    pub custom_layout: Option<CustomLayout>,
+    pub layout_flip: Option<Axis>,
+    pub tile: bool,
+    pub focused_container_idx: usize,
+    pub containers: Vec<ContainerSnapshot>,
+    pub floating_windows: Vec<u32>,
+    pub monocle_container: Option<ContainerSnapshot>,
+    pub monocle_container_restore_idx: Option<usize>,
+    pub resize_dimensions: Vec<Option<Rect>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerSnapshot {
+    pub id: String,
+    pub locked: bool,
+    pub focused_window_idx: usize,
+    pub windows: Vec<u32>,
+}
+
+impl From<&Container> for ContainerSnapshot {
+    fn from(value: &Container) -> Self {
+        Self {
+            id: value.id.clone(),
+            locked: value.locked,
+            focused_window_idx: value.focused_window_idx(),
+            windows: value.windows().iter().map(|window| window.id).collect(),
+        }
+    }
+}
+
+impl From<&Workspace> for WorkspaceSnapshot {
+    fn from(value: &Workspace) -> Self {
+        Self {
+            layout: value.layout.clone(),
+            custom_layout: value.custom_layout.clone(),
+            layout_flip: value.layout_flip,
+            tile: value.tile,
+            focused_container_idx: value.focused_container_idx(),
+            containers: value
+                .containers()
+                .iter()
+                .map(ContainerSnapshot::from)
+                .collect(),
+            floating_windows: value
+                .floating_windows()
+                .iter()
+                .map(|window| window.id)
+                .collect(),
+            monocle_container: value
+                .monocle_container
+                .as_ref()
+                .map(ContainerSnapshot::from),
+            monocle_container_restore_idx: value.monocle_container_restore_idx,
+            resize_dimensions: value.resize_dimensions.clone(),
+        }
+    }
+}
+
+impl From<&Monitor> for MonitorSnapshot {
+    fn from(value: &Monitor) -> Self {
+        Self {
+            focused_workspace_idx: value.focused_workspace_idx(),
+            workspaces: value
+                .workspaces()
+                .iter()
+                .map(WorkspaceSnapshot::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<&Ring<Monitor>> for LayoutSnapshot {
+    fn from(value: &Ring<Monitor>) -> Self {
+        Self {
+            focused_monitor_idx: value.focused_idx(),
+            monitors: value.elements().iter().map(MonitorSnapshot::from).collect(),
+        }
+    }
+}
+
+impl From<&WindowManager> for LayoutSnapshot {
+    fn from(value: &WindowManager) -> Self {
+        Self::from(&value.monitors)
+    }
+}
+
+impl ContainerSnapshot {
+    /// Rebuilds the container from whichever of its windows can still be taken, if any
+    fn restore(&self, take: &mut impl FnMut(u32) -> Option<Window>) -> Option<Container> {
+        let windows = self
+            .windows
+            .iter()
+            .filter_map(|id| take(*id))
+            .collect::<Vec<_>>();
+
+        if windows.is_empty() {
+            return None;
+        }
+
+        let mut container = Container {
+            id: self.id.clone(),
+            locked: self.locked,
+            ..Default::default()
+        };
+
+        container.windows_mut().extend(windows);
+        container.focus_window(self.focused_window_idx.min(container.windows().len() - 1));
+
+        Some(container)
+    }
+}
+
+impl LayoutSnapshot {
+    /// Moves every managed window back into the position recorded in this snapshot
+    ///
+    /// Monitors and workspaces which no longer exist are skipped, and maximized windows are left
+    /// alone; nothing is moved on screen until the monitors are next loaded and retiled
+    pub fn restore(&self, monitors: &mut Ring<Monitor>) -> eyre::Result<()> {
+        // take every tiled, monocle and floating window out of its workspace, remembering where
+        // it came from so that windows opened after this snapshot was taken can be put back
+        let mut pool: Vec<Option<(usize, usize, bool, Window)>> = vec![];
+        for (monitor_idx, monitor) in monitors.elements_mut().iter_mut().enumerate() {
+            for (workspace_idx, workspace) in monitor.workspaces_mut().iter_mut().enumerate() {
+                let mut tiled = vec![];
+
+                for container in workspace.containers_mut().drain(..) {
+                    tiled.extend(container.windows().iter().cloned());
+                }
+
+                if let Some(container) = workspace.monocle_container.take() {
+                    tiled.extend(container.windows().iter().cloned());
+                }
+
+                for window in tiled {
+                    pool.push(Some((monitor_idx, workspace_idx, false, window)));
+                }
+
+                for window in workspace.floating_windows_mut().drain(..) {
+                    pool.push(Some((monitor_idx, workspace_idx, true, window)));
+                }
+
+                workspace.focus_container(0);
+                workspace.monocle_container_restore_idx = None;
+                workspace.resize_dimensions.clear();
+            }
+        }
+
+        let mut take = |id: u32| {
+            pool.iter_mut()
+                .find(|entry| matches!(entry, Some((_, _, _, window)) if window.id == id))
+                .and_then(Option::take)
+                .map(|(_, _, _, window)| window)
+        };
+
+        for (monitor_idx, saved_monitor) in self.monitors.iter().enumerate() {
+            let Some(monitor) = monitors.elements_mut().get_mut(monitor_idx) else {
+                continue;
+            };
+
+            for (workspace_idx, saved) in saved_monitor.workspaces.iter().enumerate() {
+                let Some(workspace) = monitor.workspaces_mut().get_mut(workspace_idx) else {
+                    continue;
+                };
+
+                workspace.layout = saved.layout.clone();
+                workspace.custom_layout = saved.custom_layout.clone();
+                workspace.layout_flip = saved.layout_flip;
+                workspace.tile = saved.tile;
+
+                for saved_container in &saved.containers {
+                    if let Some(container) = saved_container.restore(&mut take) {
+                        workspace.containers_mut().push_back(container);
+                    }
+                }
+
+                let container_count = workspace.containers().len();
+
+                if let Some(saved_container) = &saved.monocle_container
+                    && let Some(container) = saved_container.restore(&mut take)
+                {
+                    workspace.monocle_container = Some(container);
+                    workspace.monocle_container_restore_idx = Some(
+                        saved
+                            .monocle_container_restore_idx
+                            .unwrap_or_default()
+                            .min(container_count),
+                    );
+                }
+
+                for id in &saved.floating_windows {
+                    if let Some(window) = take(*id) {
+                        workspace.floating_windows_mut().push_back(window);
+                    }
+                }
+
+                // resize adjustments only make sense if every container could be restored
+                if saved.resize_dimensions.len() == container_count {
+                    workspace.resize_dimensions = saved.resize_dimensions.clone();
+                }
+
+                workspace.focus_container(
+                    saved
+                        .focused_container_idx
+                        .min(container_count.saturating_sub(1)),
+                );
+            }
+
+            if saved_monitor.focused_workspace_idx != monitor.focused_workspace_idx()
+                && saved_monitor.focused_workspace_idx < monitor.workspaces().len()
+            {
+                monitor.focus_workspace(saved_monitor.focused_workspace_idx)?;
+            }
+        }
+
+        for (monitor_idx, workspace_idx, floating, window) in pool.into_iter().flatten() {
+            let Some(workspace) = monitors
+                .elements_mut()
+                .get_mut(monitor_idx)
+                .and_then(|m| m.workspaces_mut().get_mut(workspace_idx))
+            else {
+                continue;
+            };
+
+            if floating {
+                workspace.floating_windows_mut().push_back(window);
+            } else {
+                let mut container = Container::default();
+                container.windows_mut().push_back(window);
+                workspace.containers_mut().push_back(container);
+
+                // keep the restored resize adjustments lined up with the containers
+                if !workspace.resize_dimensions.is_empty() {
+                    workspace.resize_dimensions.push(None);
+                }
+            }
+        }
+
+        if self.focused_monitor_idx < monitors.elements().len() {
+            monitors.focus(self.focused_monitor_idx);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::DefaultLayout;
+    use crate::core::WindowHidingPosition;
+    use crate::monitor;
+
+    fn snapshot(focused_monitor_idx: usize) -> LayoutSnapshot {
+        LayoutSnapshot {
+            focused_monitor_idx,
+            monitors: vec![],
+        }
+    }
+
+    fn stub_monitors(containers: &[&[u32]]) -> Ring<Monitor> {
+        let mut m = monitor::new(
+            0,
+            Rect::default(),
+            Rect::default(),
+            "TestMonitor".to_string(),
+            "TestDeviceID".to_string(),
+        );
+
+        let workspace = m.focused_workspace_mut().unwrap();
+        for ids in containers {
+            let mut container = Container::default();
+            for id in *ids {
+                container
+                    .add_window(&Window::from(*id), WindowHidingPosition::BottomLeft)
+                    .unwrap();
+            }
+
+            workspace.add_container_to_back(container);
+        }
+
+        let mut monitors = Ring::default();
+        monitors.elements_mut().push_back(m);
+
+        monitors
+    }
+
+    fn window_ids(monitors: &Ring<Monitor>) -> Vec<Vec<u32>> {
+        monitors.elements()[0]
+            .focused_workspace()
+            .unwrap()
+            .containers()
+            .iter()
+            .map(|container| container.windows().iter().map(|window| window.id).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_history_undo_redo() {
+        let mut history = History::default();
+        assert!(history.undo(snapshot(0)).is_none());
+
+        history.push(snapshot(1));
+        history.push(snapshot(2));
+
+        assert_eq!(history.undo(snapshot(3)), Some(snapshot(2)));
+        assert_eq!(history.undo(snapshot(2)), Some(snapshot(1)));
+        assert!(history.undo(snapshot(1)).is_none());
+        assert_eq!(history.redo_len(), 2);
+
+        assert_eq!(history.redo(snapshot(1)), Some(snapshot(2)));
+        assert_eq!(history.undo_len(), 1);
+
+        // a new change can't be followed by a redo of something older
+        history.push(snapshot(4));
+        assert!(history.redo(snapshot(5)).is_none());
+        assert_eq!(history.undo_len(), 2);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = History::default();
+        for idx in 0..HISTORY_LIMIT + 10 {
+            history.push(snapshot(idx));
+        }
+
+        assert_eq!(history.undo_len(), HISTORY_LIMIT);
+        assert_eq!(history.undo(snapshot(0)), Some(snapshot(HISTORY_LIMIT + 9)));
+    }
+
+    #[test]
+    fn test_is_undoable() {
+        assert!(is_undoable(&SocketMessage::StackAll));
+        assert!(is_undoable(&SocketMessage::FlipLayout(Axis::Horizontal)));
+        assert!(!is_undoable(&SocketMessage::FocusWorkspaceNumber(1)));
+        assert!(!is_undoable(&SocketMessage::Undo));
+        assert!(is_undoable(&SocketMessage::Transaction(vec![
+            SocketMessage::FocusWorkspaceNumber(1),
+            SocketMessage::ChangeLayout(DefaultLayout::Columns),
+        ])));
+    }
+
+    #[test]
+    fn test_restore_snapshot() {
+        let mut monitors = stub_monitors(&[&[1], &[2], &[3]]);
+        let before = LayoutSnapshot::from(&monitors);
+
+        // stack everything into a single container and flip the layout
+        {
+            let workspace = monitors.elements_mut()[0].focused_workspace_mut().unwrap();
+            let mut stacked = Container::default();
+            for container in workspace.containers_mut().drain(..) {
+                stacked
+                    .windows_mut()
+                    .extend(container.windows().iter().cloned());
+            }
+
+            workspace.containers_mut().push_back(stacked);
+            workspace.layout_flip = Some(Axis::Vertical);
+        }
+
+        assert_eq!(window_ids(&monitors), vec![vec![1, 2, 3]]);
+
+        before.restore(&mut monitors).unwrap();
+
+        assert_eq!(window_ids(&monitors), vec![vec![1], vec![2], vec![3]]);
+        assert_eq!(LayoutSnapshot::from(&monitors), before);
+    }
+
+    #[test]
+    fn test_restore_snapshot_skips_closed_windows_and_keeps_new_windows() {
+        let mut monitors = stub_monitors(&[&[1, 2], &[3]]);
+        let mut before = LayoutSnapshot::from(&monitors);
+        let resize = Rect {
+            left: 0,
+            top: 0,
+            right: 50,
+            bottom: 0,
+        };
+        before.monitors[0].workspaces[0].resize_dimensions = vec![Some(resize), None];
+
+        // window 2 has closed and window 4 has opened since the snapshot was taken
+        let mut monitors_now = stub_monitors(&[&[1], &[3], &[4]]);
+        before.restore(&mut monitors_now).unwrap();
+
+        assert_eq!(window_ids(&monitors_now), vec![vec![1], vec![3], vec![4]]);
+
+        // both containers were restored, and the container of the new window has no adjustment
+        let workspace = monitors_now.elements()[0].focused_workspace().unwrap();
+        assert_eq!(workspace.resize_dimensions, vec![Some(resize), None, None]);
+
+        monitors = stub_monitors(&[&[3, 1]]);
+        before.restore(&mut monitors).unwrap();
+
+        assert_eq!(window_ids(&monitors), vec![vec![1], vec![3]]);
+        assert_eq!(
+            monitors.elements()[0]
+                .focused_workspace()
+                .unwrap()
+                .resize_dimensions,
+            vec![Some(resize), None]
+        );
+
+        // the resize adjustments are for two containers, but only one of them could be restored
+        let mut monitors = stub_monitors(&[&[1], &[4]]);
+        before.restore(&mut monitors).unwrap();
+
+        assert_eq!(window_ids(&monitors), vec![vec![1], vec![4]]);
+        assert!(
+            monitors.elements()[0]
+                .focused_workspace()
+                .unwrap()
+                .resize_dimensions
+                .is_empty()
+        );
+    }
+}
@@ -1,11 +1,17 @@
 use crate::accessibility::AccessibilityApi;
+use crate::core::StackbarMode;
 use crate::macos_api::MacosApi;
 use crate::reaper;
 use crate::reaper::ReaperNotification;
+use crate::stackbar_manager;
+use crate::window_manager::WindowManager;
 use crate::window_manager_event::ManualNotification;
 use crate::window_manager_event::SystemNotification;
 use crate::window_manager_event::WindowManagerEvent;
 use crate::window_manager_event_listener;
+use crossbeam_channel::Receiver;
+use crossbeam_channel::Sender;
+use lazy_static::lazy_static;
 use objc2_core_foundation::CFMachPort;
 use objc2_core_foundation::CFRetained;
 use objc2_core_foundation::CFRunLoop;
@@ -16,8 +22,158 @@ use objc2_core_graphics::CGEventTapOptions;
 use objc2_core_graphics::CGEventTapPlacement;
 use objc2_core_graphics::CGEventTapProxy;
 use objc2_core_graphics::CGEventType;
+use parking_lot::Mutex;
 use std::ffi::c_void;
 use std::ptr::NonNull;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+pub static FOCUS_FOLLOWS_MOUSE: AtomicBool = AtomicBool::new(false);
+
+/// How long the cursor has to be at rest before the window under it is focused
+const FOCUS_FOLLOWS_MOUSE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// How long to hold off on focusing the window under the cursor after komorebi has focused a
+/// window, so that we don't immediately undo focus changes made by commands
+const FOCUS_FOLLOWS_MOUSE_COOLDOWN: Duration = Duration::from_millis(250);
+
+// milliseconds since START at which a window was last focused by komorebi
+static LAST_PROGRAMMATIC_FOCUS: AtomicU64 = AtomicU64::new(0);
+
+// milliseconds since START at which the cursor last moved
+static LAST_CURSOR_MOVEMENT: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    static ref START: Instant = Instant::now();
+}
+
+fn now() -> u64 {
+    START.elapsed().as_millis() as u64
+}
+
+/// Records that komorebi has just focused a window itself
+pub fn record_programmatic_focus() {
+    LAST_PROGRAMMATIC_FOCUS.store(now(), Ordering::SeqCst);
+}
+
+/// What focus follows mouse should do about the window under the cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusFollowsMouseAction {
+    /// The cursor hasn't come to rest yet, so check again after this long
+    Wait(Duration),
+    /// Leave the focus where it is
+    Ignore,
+    /// Focus the window with this id
+    Focus(u32),
+}
+
+/// Decides what focus follows mouse should do, given timestamps in milliseconds
+pub fn focus_follows_mouse_action(
+    now: u64,
+    last_cursor_movement: u64,
+    last_programmatic_focus: u64,
+    window_under_cursor: Option<u32>,
+    focused_window: Option<u32>,
+) -> FocusFollowsMouseAction {
+    let debounce = FOCUS_FOLLOWS_MOUSE_DEBOUNCE.as_millis() as u64;
+    let at_rest = now.saturating_sub(last_cursor_movement);
+
+    if at_rest < debounce {
+        return FocusFollowsMouseAction::Wait(Duration::from_millis(debounce - at_rest));
+    }
+
+    if now.saturating_sub(last_programmatic_focus) < FOCUS_FOLLOWS_MOUSE_COOLDOWN.as_millis() as u64
+    {
+        return FocusFollowsMouseAction::Ignore;
+    }
+
+    match window_under_cursor {
+        Some(id) if focused_window != Some(id) => FocusFollowsMouseAction::Focus(id),
+        _ => FocusFollowsMouseAction::Ignore,
+    }
+}
+
+/// Decides what focus follows mouse should do right now
+pub fn current_focus_follows_mouse_action(
+    window_under_cursor: Option<u32>,
+    focused_window: Option<u32>,
+) -> FocusFollowsMouseAction {
+    focus_follows_mouse_action(
+        now(),
+        LAST_CURSOR_MOVEMENT.load(Ordering::SeqCst),
+        LAST_PROGRAMMATIC_FOCUS.load(Ordering::SeqCst),
+        window_under_cursor,
+        focused_window,
+    )
+}
+
+static CHANNEL: OnceLock<(Sender<()>, Receiver<()>)> = OnceLock::new();
+
+pub fn channel() -> &'static (Sender<()>, Receiver<()>) {
+    CHANNEL.get_or_init(|| crossbeam_channel::bounded(50))
+}
+
+fn event_tx() -> Sender<()> {
+    channel().0.clone()
+}
+
+fn event_rx() -> Receiver<()> {
+    channel().1.clone()
+}
+
+// the cursor moves a lot, so unlike other notifications we don't warn when the channel is full;
+// any pending notification will already result in the window under the cursor being focused
+fn send_notification() {
+    let _ = event_tx().try_send(());
+}
+
+pub fn listen_for_notifications(wm: Arc<Mutex<WindowManager>>) {
+    std::thread::spawn(move || {
+        loop {
+            match handle_notifications(wm.clone()) {
+                Ok(()) => {
+                    tracing::warn!("restarting finished thread");
+                }
+                Err(error) => {
+                    tracing::warn!("restarting failed thread: {}", error);
+                }
+            }
+        }
+    });
+}
+
+fn handle_notifications(wm: Arc<Mutex<WindowManager>>) -> color_eyre::Result<()> {
+    tracing::info!("listening");
+
+    let receiver = event_rx();
+
+    while receiver.recv().is_ok() {
+        loop {
+            // the time of the latest movement is all we need, so drop any intermediate movements
+            while receiver.try_recv().is_ok() {}
+
+            if !FOCUS_FOLLOWS_MOUSE.load(Ordering::SeqCst) {
+                break;
+            }
+
+            match wm.lock().focus_window_under_cursor() {
+                Ok(FocusFollowsMouseAction::Wait(delay)) => std::thread::sleep(delay),
+                Ok(_) => break,
+                Err(error) => {
+                    tracing::warn!("could not focus the window under the cursor: {error}");
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
 
 pub struct InputEventListener {
     port: CFRetained<CFMachPort>,
@@ -29,10 +185,27 @@ extern "C-unwind" fn callback(
     mut event_ref: NonNull<CGEvent>,
     _listener: *mut c_void,
 ) -> *mut CGEvent {
+    if event_type == CGEventType::MouseMoved {
+        if FOCUS_FOLLOWS_MOUSE.load(Ordering::Relaxed) {
+            LAST_CURSOR_MOVEMENT.store(now(), Ordering::Relaxed);
+            send_notification();
+        }
+
+        return unsafe { event_ref.as_mut() };
+    }
+
     // this should cover both clicking the close button and closing out something
     // by using cmd+w or cmd+q since some apps don't send events on close
     reaper::send_notification(ReaperNotification::MouseUpKeyUp);
 
+    // stackbar tabs are hit-tested on the stackbar manager thread so that we never block here
+    if event_type == CGEventType::LeftMouseUp
+        && !matches!(stackbar_manager::STACKBAR_MODE.load(), StackbarMode::Never)
+    {
+        let point = MacosApi::cursor_pos();
+        stackbar_manager::send_click(point.x as i32, point.y as i32);
+    }
+
     // this one is only really for when people "drag" a tab out of one window
     // to create another window - we wanna make sure it gets handled because
     // events don't get sent sometimes
@@ -83,7 +256,9 @@ extern "C-unwind" fn callback(
 
 impl InputEventListener {
     pub fn init(run_loop: &CFRunLoop) -> Option<Self> {
-        let mouse_event_mask = (1 << CGEventType::LeftMouseUp.0) | (1 << CGEventType::KeyUp.0);
+        let mouse_event_mask = (1 << CGEventType::LeftMouseUp.0)
+            | (1 << CGEventType::KeyUp.0)
+            | (1 << CGEventType::MouseMoved.0);
         let mut port = None;
 
         unsafe {
@@ -118,3 +293,56 @@ impl Drop for InputEventListener {
         CGEvent::tap_enable(&self.port, false);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOLDOWN: u64 = FOCUS_FOLLOWS_MOUSE_COOLDOWN.as_millis() as u64;
+
+    #[test]
+    fn test_waits_for_the_cursor_to_come_to_rest() {
+        let now = 10_000;
+
+        assert_eq!(
+            focus_follows_mouse_action(now, now - 20, 0, Some(1), Some(2)),
+            FocusFollowsMouseAction::Wait(Duration::from_millis(30))
+        );
+
+        assert_eq!(
+            focus_follows_mouse_action(now, now - 50, 0, Some(1), Some(2)),
+            FocusFollowsMouseAction::Focus(1)
+        );
+    }
+
+    #[test]
+    fn test_cooldown_after_programmatic_focus() {
+        let now = 10_000;
+        let last_cursor_movement = now - 100;
+
+        assert_eq!(
+            focus_follows_mouse_action(now, last_cursor_movement, now - 10, Some(1), Some(2)),
+            FocusFollowsMouseAction::Ignore
+        );
+
+        assert_eq!(
+            focus_follows_mouse_action(now, last_cursor_movement, now - COOLDOWN, Some(1), Some(2)),
+            FocusFollowsMouseAction::Focus(1)
+        );
+    }
+
+    #[test]
+    fn test_window_under_cursor_is_already_focused() {
+        let now = 10_000;
+
+        assert_eq!(
+            focus_follows_mouse_action(now, now - 100, 0, Some(1), Some(1)),
+            FocusFollowsMouseAction::Ignore
+        );
+
+        assert_eq!(
+            focus_follows_mouse_action(now, now - 100, 0, None, Some(1)),
+            FocusFollowsMouseAction::Ignore
+        );
+    }
+}
@@ -13,6 +13,7 @@ use crate::core::config_generation::WorkspaceMatchingRule;
 use crate::core_graphics::error::CoreGraphicsError;
 use crate::monitor_reconciliator::MonitorNotification;
 use crate::state::State;
+use crate::subscriber_pipe::SubscriberPipe;
 use crate::window::AspectRatio;
 use crate::window::PredefinedAspectRatio;
 use crate::window_manager_event::WindowManagerEvent;
@@ -52,31 +53,46 @@ pub mod animation;
 pub mod app_kit_notification_constants;
 pub mod application;
 pub mod border_manager;
+pub mod config_check;
 pub mod container;
 pub mod core;
 pub mod core_graphics;
 pub mod display_reconfiguration_listener;
+pub mod explain;
+pub mod history;
 pub mod input_event_listener;
 pub mod ioreg;
 pub mod lockable_sequence;
 pub mod macos_api;
+pub mod metrics;
 pub mod monitor;
 pub mod monitor_reconciliator;
 pub mod notification_center_listener;
+pub mod platform;
 pub mod process_command;
 pub mod process_event;
+pub mod profile;
 pub mod reaper;
+pub mod recorder;
+pub mod remote_listener;
+pub mod scratchpad;
+pub mod session;
 pub mod skylight;
 pub mod splash;
+pub mod stackbar_manager;
 pub mod state;
 pub mod static_config;
+pub mod subscriber_pipe;
 pub mod theme_manager;
+pub mod transaction;
+pub mod transparency_manager;
 pub mod window;
 pub mod window_manager;
 pub mod window_manager_event;
 pub mod window_manager_event_listener;
 pub mod workspace;
 pub mod workspace_reconciliator;
+pub mod workspace_set;
 
 lazy_static! {
     pub static ref HOME_DIR: PathBuf = {
@@ -106,6 +122,8 @@ lazy_static! {
         Arc::new(Mutex::new(HashMap::new()));
     pub static ref SUBSCRIPTION_SOCKET_OPTIONS: Arc<Mutex<HashMap<String, SubscribeOptions>>> =
         Arc::new(Mutex::new(HashMap::new()));
+    pub static ref SUBSCRIPTION_PIPES: Arc<Mutex<HashMap<String, SubscriberPipe>>> =
+        Arc::new(Mutex::new(HashMap::new()));
     static ref FLOATING_WINDOW_TOGGLE_ASPECT_RATIO: Arc<Mutex<AspectRatio>> = Arc::new(Mutex::new(
         AspectRatio::Predefined(PredefinedAspectRatio::Widescreen)
     ));
@@ -118,6 +136,7 @@ lazy_static! {
     static ref REGEX_IDENTIFIERS: Arc<Mutex<HashMap<String, Regex>>> =
         Arc::new(Mutex::new(HashMap::new()));
     static ref MANAGE_IDENTIFIERS: Arc<Mutex<Vec<MatchingRule>>> = Arc::new(Mutex::new(vec![]));
+    static ref TRANSPARENCY_BLACKLIST: Arc<Mutex<Vec<MatchingRule>>> = Arc::new(Mutex::new(vec![]));
     static ref IGNORE_IDENTIFIERS: Arc<Mutex<Vec<MatchingRule>>> = Arc::new(Mutex::new(vec![
         MatchingRule::Simple(IdWithIdentifier {
             kind: ApplicationIdentifier::Exe,
@@ -148,12 +167,15 @@ lazy_static! {
     ]));
     static ref TITLELESS_APPLICATIONS: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
     static ref UNMANAGED_WINDOW_IDS: Arc<Mutex<Vec<u32>>> = Arc::new(Mutex::new(vec![]));
+    static ref STICKY_APPLICATIONS: Arc<Mutex<Vec<MatchingRule>>> =
+        Arc::new(Mutex::new(Vec::new()));
     pub static ref LAYOUT_DEFAULTS: Arc<Mutex<HashMap<DefaultLayout, LayoutDefaultEntry>>> =
         Arc::new(Mutex::new(HashMap::new()));
 }
 
-pub static DEFAULT_WORKSPACE_PADDING: AtomicI32 = AtomicI32::new(5);
-pub static DEFAULT_CONTAINER_PADDING: AtomicI32 = AtomicI32::new(5);
+pub const DEFAULT_PADDING: i32 = 5;
+pub static DEFAULT_WORKSPACE_PADDING: AtomicI32 = AtomicI32::new(DEFAULT_PADDING);
+pub static DEFAULT_CONTAINER_PADDING: AtomicI32 = AtomicI32::new(DEFAULT_PADDING);
 pub static DEFAULT_RESIZE_DELTA: i32 = 50;
 pub static DEFAULT_MOUSE_FOLLOWS_FOCUS: bool = true;
 
@@ -208,30 +230,39 @@ pub fn notify_subscribers(
     notification: Notification,
     state_has_been_modified: bool,
 ) -> eyre::Result<()> {
+    let _timer = metrics::time_subscriber_fanout();
+
     let is_override_event = matches!(
         notification.event,
         NotificationEvent::Monitor(_)
         | NotificationEvent::Socket(SocketMessage::AddSubscriberSocket(_))
             | NotificationEvent::Socket(SocketMessage::AddSubscriberSocketWithOptions(_, _))
+            | NotificationEvent::Socket(SocketMessage::AddSubscriberPipe(_))
             | NotificationEvent::Socket(SocketMessage::Theme(_))
             | NotificationEvent::Socket(SocketMessage::ReloadStaticConfiguration(_))
+            | NotificationEvent::Socket(SocketMessage::ReloadConfiguration)
             // | NotificationEvent::WindowManager(WindowManagerEvent::TitleUpdate(_, _))
             | NotificationEvent::WindowManager(WindowManagerEvent::Show(_, _)) // | NotificationEvent::WindowManager(WindowManagerEvent::Uncloak(_, _))
     );
 
     let notification = &serde_json::to_string(&notification)?;
-    let mut stale_sockets = vec![];
-    let mut sockets = SUBSCRIPTION_SOCKETS.lock();
     let options = SUBSCRIPTION_SOCKET_OPTIONS.lock();
 
-    for (socket, path) in &mut *sockets {
+    let should_notify = |subscriber: &str| {
         let apply_state_filter = (*options)
-            .get(socket)
+            .get(subscriber)
             .copied()
             .unwrap_or_default()
             .filter_state_changes;
 
-        if !apply_state_filter || state_has_been_modified || is_override_event {
+        !apply_state_filter || state_has_been_modified || is_override_event
+    };
+
+    let mut stale_sockets = vec![];
+    let mut sockets = SUBSCRIPTION_SOCKETS.lock();
+
+    for (socket, path) in &mut *sockets {
+        if should_notify(socket) {
             match UnixStream::connect(path) {
                 Ok(mut stream) => {
                     tracing::debug!("pushed notification to subscriber: {socket}");
@@ -244,21 +275,54 @@ pub fn notify_subscribers(
         }
     }
 
-    for socket in stale_sockets {
-        tracing::warn!("removing stale subscription: {socket}");
-        sockets.remove(&socket);
-        let socket_path = DATA_DIR.join(socket);
-        if let Err(error) = std::fs::remove_file(&socket_path) {
-            tracing::error!(
-                "could not remove stale subscriber socket file at {}: {error}",
-                socket_path.display()
-            )
+    remove_stale_subscribers(&mut sockets, stale_sockets, |socket, _| {
+        std::fs::remove_file(DATA_DIR.join(socket))
+    });
+
+    let mut stale_pipes = vec![];
+    let mut pipes = SUBSCRIPTION_PIPES.lock();
+
+    for (pipe, subscriber) in &mut *pipes {
+        if should_notify(pipe) {
+            match subscriber.push(notification) {
+                Ok(()) => {
+                    tracing::debug!("pushed notification to subscriber: {pipe}");
+                }
+                Err(error) => {
+                    if error.kind() == std::io::ErrorKind::WouldBlock {
+                        tracing::warn!("subscriber is not reading notifications: {pipe}");
+                    }
+
+                    stale_pipes.push(pipe.clone());
+                }
+            }
         }
     }
 
+    remove_stale_subscribers(&mut pipes, stale_pipes, |_, subscriber| subscriber.close());
+
+    remote_listener::push_notification(notification);
+
     Ok(())
 }
 
+/// Unregisters subscribers which could not be reached, handing each of them to `close` so that
+/// the transport can clean up after them
+fn remove_stale_subscribers<T>(
+    subscribers: &mut HashMap<String, T>,
+    stale: Vec<String>,
+    close: impl Fn(&str, T) -> std::io::Result<()>,
+) {
+    for name in stale {
+        tracing::warn!("removing stale subscription: {name}");
+        if let Some(subscriber) = subscribers.remove(&name)
+            && let Err(error) = close(&name, subscriber)
+        {
+            tracing::error!("could not clean up stale subscriber {name}: {error}");
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CoreFoundationRunLoop(pub CFRetained<CFRunLoop>);
 unsafe impl Sync for CoreFoundationRunLoop {}
@@ -99,6 +99,7 @@ impl<T: Lockable> LockableSequence<T> for VecDeque<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[derive(Debug, PartialEq)]
     struct TestItem {
@@ -360,4 +361,90 @@ mod tests {
         assert!(ring[1].locked);
         assert!(!ring[2].locked);
     }
+
+    fn locked_positions(v: &VecDeque<TestItem>) -> Vec<(usize, i32)> {
+        v.iter()
+            .enumerate()
+            .filter(|(_, x)| x.locked)
+            .map(|(idx, x)| (idx, x.val))
+            .collect()
+    }
+
+    fn arb_deque() -> impl Strategy<Value = VecDeque<TestItem>> {
+        proptest::collection::vec(any::<bool>(), 0..12).prop_map(|locks| {
+            locks
+                .into_iter()
+                .enumerate()
+                .map(|(val, locked)| TestItem {
+                    val: val as i32,
+                    locked,
+                })
+                .collect()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_insert_respecting_locks_invariants(mut ring in arb_deque(), idx in 0usize..16) {
+            let len = ring.len();
+            let locked = locked_positions(&ring);
+
+            let actual_index = ring.insert_respecting_locks(
+                idx,
+                TestItem {
+                    val: 99,
+                    locked: false,
+                },
+            );
+
+            prop_assert_eq!(ring.len(), len + 1);
+            prop_assert_eq!(ring[actual_index].val, 99);
+            // Every locked element stays where it was
+            prop_assert_eq!(locked_positions(&ring), locked);
+        }
+
+        #[test]
+        fn test_remove_respecting_locks_invariants(mut ring in arb_deque(), idx in 0usize..16) {
+            let len = ring.len();
+            let expected = ring.get(idx).map(|x| x.val);
+            let mut locked = locked_positions(&ring);
+
+            let removed = ring.remove_respecting_locks(idx).map(|x| x.val);
+            prop_assert_eq!(removed, expected);
+
+            match removed {
+                None => prop_assert_eq!(ring.len(), len),
+                Some(val) => {
+                    prop_assert_eq!(ring.len(), len - 1);
+                    locked.retain(|(_, locked_val)| *locked_val != val);
+                }
+            }
+
+            // Locked elements can only be pulled off their positions when the deque becomes too
+            // short to hold them there
+            if locked.iter().all(|(locked_idx, _)| *locked_idx < ring.len()) {
+                prop_assert_eq!(locked_positions(&ring), locked);
+            }
+        }
+
+        #[test]
+        fn test_swap_respecting_locks_invariants(
+            mut ring in arb_deque(),
+            i in any::<prop::sample::Index>(),
+            j in any::<prop::sample::Index>(),
+        ) {
+            prop_assume!(!ring.is_empty());
+
+            let i = i.index(ring.len());
+            let j = j.index(ring.len());
+            let locks = ring.iter().map(|x| x.locked).collect::<Vec<_>>();
+
+            ring.swap_respecting_locks(i, j);
+
+            prop_assert_eq!(ring[i].val, j as i32);
+            prop_assert_eq!(ring[j].val, i as i32);
+            // The lock state belongs to the position rather than the element
+            prop_assert_eq!(ring.iter().map(|x| x.locked).collect::<Vec<_>>(), locks);
+        }
+    }
 }
@@ -16,6 +16,7 @@ use crate::core_graphics::CoreGraphicsApi;
 use crate::ioreg::IoReg;
 use crate::monitor::Monitor;
 use crate::monitor::MonitorInfo;
+use crate::platform;
 use crate::window::RuleDebug;
 use crate::window::WindowInfo;
 use crate::window_manager::WindowManager;
@@ -94,7 +95,7 @@ impl MacosApi {
 impl MacosApi {
     #[tracing::instrument(skip_all)]
     pub fn load_monitor_information(wm: &mut WindowManager) -> Result<(), LibraryError> {
-        let all_devices = Self::latest_monitor_information()?;
+        let all_devices = platform::backend().monitors()?;
 
         let monitors = &mut wm.monitors;
         let monitor_usr_idx_map = &mut wm.monitor_usr_idx_map;
@@ -180,7 +181,7 @@ impl MacosApi {
     }
 
     pub fn update_monitor_work_areas(wm: &mut WindowManager) -> eyre::Result<()> {
-        let all_devices = Self::latest_monitor_information()?;
+        let all_devices = platform::backend().monitors()?;
         for device in all_devices {
             for monitor in wm.monitors_mut() {
                 if monitor.id == device.id {
@@ -10,16 +10,23 @@ use komorebi::HOME_DIR;
 use komorebi::border_manager;
 use komorebi::core::pathext::replace_env_in_path;
 use komorebi::display_reconfiguration_listener::DisplayReconfigurationListener;
+use komorebi::input_event_listener;
 use komorebi::input_event_listener::InputEventListener;
 use komorebi::monitor_reconciliator;
 use komorebi::notification_center_listener::NotificationCenterListener;
+use komorebi::platform;
 use komorebi::process_command::listen_for_commands;
 use komorebi::process_event::listen_for_events;
 use komorebi::reaper;
+use komorebi::recorder;
+use komorebi::remote_listener;
+use komorebi::remote_listener::RemoteToken;
+use komorebi::stackbar_manager;
 use komorebi::static_config::StaticConfig;
 use komorebi::theme_manager;
+use komorebi::transparency_manager;
+use komorebi::transparency_manager::SkyLightBackend;
 use komorebi::window_manager::WindowManager;
-use komorebi::window_manager_event_listener;
 use komorebi::workspace_reconciliator;
 use objc2::MainThreadMarker;
 use objc2::rc::autoreleasepool;
@@ -190,6 +197,16 @@ struct Opts {
     /// Level of log output verbosity
     #[clap(long, value_enum, default_value_t=LogLevel::Info)]
     log_level: LogLevel,
+    /// Accept commands on this localhost TCP port, authenticated with the token in komorebi.token
+    #[clap(long)]
+    tcp_port: Option<u16>,
+    /// Accept commands and push notifications on this localhost WebSocket port, authenticated with the token in komorebi.token
+    #[clap(long)]
+    ws_port: Option<u16>,
+    /// Append every event and command, with periodic state checkpoints, to this file
+    #[clap(long)]
+    #[clap(value_parser = replace_env_in_path)]
+    record: Option<PathBuf>,
 }
 
 fn main() -> eyre::Result<()> {
@@ -251,14 +268,14 @@ fn main() -> eyre::Result<()> {
 
         Arc::new(Mutex::new(StaticConfig::preload(
             config,
-            window_manager_event_listener::event_rx(),
+            platform::backend().events(),
             None,
             &run_loop,
         )?))
     } else {
         Arc::new(Mutex::new(WindowManager::new(
             &run_loop,
-            window_manager_event_listener::event_rx(),
+            platform::backend().events(),
             None,
         )?))
     };
@@ -269,15 +286,35 @@ fn main() -> eyre::Result<()> {
         StaticConfig::postload(config, &wm)?;
     }
 
+    if let Some(path) = &opts.record {
+        recorder::start(path)?;
+    }
+
     wm.lock().retile_all(false)?;
 
     border_manager::listen_for_notifications(wm.clone(), CoreFoundationRunLoop(run_loop));
+    stackbar_manager::listen_for_notifications(wm.clone());
+    transparency_manager::listen_for_notifications(wm.clone(), Arc::new(SkyLightBackend));
+    input_event_listener::listen_for_notifications(wm.clone());
     theme_manager::listen_for_notifications();
     monitor_reconciliator::listen_for_notifications(wm.clone())?;
     reaper::listen_for_notifications(wm.clone());
     workspace_reconciliator::listen_for_notifications(wm.clone());
 
     listen_for_commands(wm.clone());
+
+    if opts.tcp_port.is_some() || opts.ws_port.is_some() {
+        let token = RemoteToken::load_or_create()?;
+
+        if let Some(port) = opts.tcp_port {
+            remote_listener::listen_for_tcp_commands(wm.clone(), port, token.clone())?;
+        }
+
+        if let Some(port) = opts.ws_port {
+            remote_listener::listen_for_websocket_commands(wm.clone(), port, token)?;
+        }
+    }
+
     listen_for_events(wm.clone());
 
     let quit_ctrlc = Arc::new(AtomicBool::new(false));
@@ -336,6 +373,9 @@ fn main() -> eyre::Result<()> {
         }
     }
 
+    // dropping the write end of each pipe signals the end of the stream to its reader
+    komorebi::SUBSCRIPTION_PIPES.lock().clear();
+
     let socket = DATA_DIR.join("komorebi.sock");
     let _ = std::fs::remove_file(socket);
 
@@ -0,0 +1,344 @@
+//! Counters and timings for the work done by the window manager
+//!
+//! Every [`SocketMessage`] handled by `process_command` and every [`WindowManagerEvent`] handled by
+//! `process_event` is counted and timed per variant, along with the time spent waiting to acquire
+//! the window manager lock and the time spent pushing notifications to subscribers. A snapshot can
+//! be requested with [`SocketMessage::Metrics`] as either JSON or Prometheus text.
+
+use crate::animation::ANIMATION_MANAGER;
+use crate::core::SocketMessage;
+use crate::window_manager::WindowManager;
+use crate::window_manager_event::WindowManagerEvent;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use parking_lot::MutexGuard;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+use std::time::Instant;
+
+lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(Registry::new());
+}
+
+struct Registry {
+    started: Instant,
+    commands: BTreeMap<String, Timing>,
+    events: BTreeMap<String, Timing>,
+    lock_wait: Timing,
+    subscriber_fanout: Timing,
+}
+
+impl Registry {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            commands: BTreeMap::new(),
+            events: BTreeMap::new(),
+            lock_wait: Timing::default(),
+            subscriber_fanout: Timing::default(),
+        }
+    }
+}
+
+/// The number of times something happened and how long it took in total
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timing {
+    pub count: u64,
+    pub total_micros: u64,
+    pub max_micros: u64,
+}
+
+impl Timing {
+    fn record(&mut self, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+
+        self.count += 1;
+        self.total_micros = self.total_micros.saturating_add(micros);
+        self.max_micros = self.max_micros.max(micros);
+    }
+
+    pub fn mean_micros(&self) -> u64 {
+        self.total_micros
+            .checked_div(self.count)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metrics {
+    pub uptime_secs: u64,
+    /// Socket messages handled, keyed by variant
+    pub commands: BTreeMap<String, Timing>,
+    /// Window manager events handled, keyed by variant
+    pub events: BTreeMap<String, Timing>,
+    /// Time spent waiting to acquire the window manager lock
+    pub lock_wait: Timing,
+    /// Time spent pushing notifications to subscribers
+    pub subscriber_fanout: Timing,
+    pub animations_in_flight: usize,
+}
+
+enum TimerKind {
+    Command(String),
+    Event(String),
+    SubscriberFanout,
+}
+
+/// Records the time between its creation and being dropped
+#[must_use]
+pub struct Timer {
+    started: Instant,
+    kind: TimerKind,
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        let mut registry = REGISTRY.lock();
+
+        match &mut self.kind {
+            TimerKind::Command(variant) => registry
+                .commands
+                .entry(std::mem::take(variant))
+                .or_default()
+                .record(elapsed),
+            TimerKind::Event(variant) => registry
+                .events
+                .entry(std::mem::take(variant))
+                .or_default()
+                .record(elapsed),
+            TimerKind::SubscriberFanout => registry.subscriber_fanout.record(elapsed),
+        }
+    }
+}
+
+fn timer(kind: TimerKind) -> Timer {
+    Timer {
+        started: Instant::now(),
+        kind,
+    }
+}
+
+pub fn time_command(message: &SocketMessage) -> Timer {
+    timer(TimerKind::Command(message.to_string()))
+}
+
+pub fn time_event(event: &WindowManagerEvent) -> Timer {
+    timer(TimerKind::Event(event.to_string()))
+}
+
+pub fn time_subscriber_fanout() -> Timer {
+    timer(TimerKind::SubscriberFanout)
+}
+
+/// Locks the window manager, recording how long it took to acquire the lock
+pub fn lock(wm: &Mutex<WindowManager>) -> MutexGuard<'_, WindowManager> {
+    let started = Instant::now();
+    let guard = wm.lock();
+    REGISTRY.lock().lock_wait.record(started.elapsed());
+
+    guard
+}
+
+/// Tries to lock the window manager within `timeout`, recording how long was spent waiting
+pub fn try_lock_for(
+    wm: &Mutex<WindowManager>,
+    timeout: Duration,
+) -> Option<MutexGuard<'_, WindowManager>> {
+    let started = Instant::now();
+    let guard = wm.try_lock_for(timeout);
+    REGISTRY.lock().lock_wait.record(started.elapsed());
+
+    guard
+}
+
+pub fn snapshot() -> Metrics {
+    let registry = REGISTRY.lock();
+
+    Metrics {
+        uptime_secs: registry.started.elapsed().as_secs(),
+        commands: registry.commands.clone(),
+        events: registry.events.clone(),
+        lock_wait: registry.lock_wait,
+        subscriber_fanout: registry.subscriber_fanout,
+        animations_in_flight: ANIMATION_MANAGER.lock().count(),
+    }
+}
+
+impl Metrics {
+    /// Renders the metrics in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut output = String::new();
+
+        write_gauge(
+            &mut output,
+            "komorebi_uptime_seconds",
+            "Seconds since the window manager started",
+            self.uptime_secs,
+        );
+
+        write_summary(
+            &mut output,
+            "komorebi_command_duration_seconds",
+            "Time spent processing socket messages",
+            "command",
+            &self.commands,
+        );
+
+        write_summary(
+            &mut output,
+            "komorebi_event_duration_seconds",
+            "Time spent processing window manager events",
+            "event",
+            &self.events,
+        );
+
+        write_summary(
+            &mut output,
+            "komorebi_lock_wait_seconds",
+            "Time spent waiting to acquire the window manager lock",
+            "",
+            &BTreeMap::from([(String::new(), self.lock_wait)]),
+        );
+
+        write_summary(
+            &mut output,
+            "komorebi_subscriber_fanout_seconds",
+            "Time spent pushing notifications to subscribers",
+            "",
+            &BTreeMap::from([(String::new(), self.subscriber_fanout)]),
+        );
+
+        write_gauge(
+            &mut output,
+            "komorebi_animations_in_flight",
+            "Animations which are currently running",
+            self.animations_in_flight,
+        );
+
+        output
+    }
+}
+
+fn write_gauge(output: &mut String, name: &str, help: &str, value: impl std::fmt::Display) {
+    let _ = writeln!(output, "# HELP {name} {help}");
+    let _ = writeln!(output, "# TYPE {name} gauge");
+    let _ = writeln!(output, "{name} {value}");
+}
+
+fn write_summary(
+    output: &mut String,
+    name: &str,
+    help: &str,
+    label: &str,
+    timings: &BTreeMap<String, Timing>,
+) {
+    let labels = |value: &str| {
+        if label.is_empty() {
+            String::new()
+        } else {
+            format!("{{{label}=\"{value}\"}}")
+        }
+    };
+
+    let _ = writeln!(output, "# HELP {name} {help}");
+    let _ = writeln!(output, "# TYPE {name} summary");
+    for (value, timing) in timings {
+        let labels = labels(value);
+        let _ = writeln!(
+            output,
+            "{name}_sum{labels} {}",
+            seconds(timing.total_micros)
+        );
+        let _ = writeln!(output, "{name}_count{labels} {}", timing.count);
+    }
+
+    let _ = writeln!(
+        output,
+        "# HELP {name}_max The longest observation of {name}"
+    );
+    let _ = writeln!(output, "# TYPE {name}_max gauge");
+    for (value, timing) in timings {
+        let _ = writeln!(
+            output,
+            "{name}_max{} {}",
+            labels(value),
+            seconds(timing.max_micros)
+        );
+    }
+}
+
+fn seconds(micros: u64) -> f64 {
+    micros as f64 / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timing_record() {
+        let mut timing = Timing::default();
+        assert_eq!(timing.mean_micros(), 0);
+
+        timing.record(Duration::from_micros(100));
+        timing.record(Duration::from_micros(300));
+
+        assert_eq!(
+            timing,
+            Timing {
+                count: 2,
+                total_micros: 400,
+                max_micros: 300,
+            }
+        );
+        assert_eq!(timing.mean_micros(), 200);
+    }
+
+    #[test]
+    fn test_to_prometheus() {
+        let metrics = Metrics {
+            uptime_secs: 42,
+            commands: BTreeMap::from([(
+                String::from("FocusWindow"),
+                Timing {
+                    count: 3,
+                    total_micros: 1_500,
+                    max_micros: 1_000,
+                },
+            )]),
+            events: BTreeMap::new(),
+            lock_wait: Timing {
+                count: 1,
+                total_micros: 250,
+                max_micros: 250,
+            },
+            subscriber_fanout: Timing::default(),
+            animations_in_flight: 2,
+        };
+
+        let output = metrics.to_prometheus();
+
+        assert!(output.contains("komorebi_uptime_seconds 42\n"));
+        assert!(
+            output.contains(
+                "komorebi_command_duration_seconds_sum{command=\"FocusWindow\"} 0.0015\n"
+            )
+        );
+        assert!(
+            output.contains("komorebi_command_duration_seconds_count{command=\"FocusWindow\"} 3\n")
+        );
+        assert!(
+            output
+                .contains("komorebi_command_duration_seconds_max{command=\"FocusWindow\"} 0.001\n")
+        );
+        assert!(output.contains("# TYPE komorebi_event_duration_seconds summary\n"));
+        assert!(!output.contains("komorebi_event_duration_seconds_count"));
+        assert!(output.contains("komorebi_lock_wait_seconds_count 1\n"));
+        assert!(output.contains("komorebi_subscriber_fanout_seconds_count 0\n"));
+        assert!(output.contains("komorebi_animations_in_flight 2\n"));
+    }
+}
@@ -7,7 +7,7 @@ use crate::core::Layout;
 use crate::core::OperationDirection;
 use crate::core::Rect;
 use crate::core::WindowHidingPosition;
-use crate::macos_api::MacosApi;
+use crate::platform;
 use crate::ring::Ring;
 use crate::static_config::Wallpaper;
 use crate::workspace::Workspace;
@@ -208,16 +208,30 @@ impl Monitor {
     pub fn focus_workspace(&mut self, idx: usize) -> eyre::Result<()> {
         tracing::info!("focusing workspace");
 
+        let previous_idx = self.workspaces.focused_idx();
+
         {
             let workspaces = self.workspaces_mut();
 
             if workspaces.get(idx).is_none() {
                 workspaces.resize(idx + 1, Workspace::default());
             }
-            self.last_focused_workspace = Some(self.workspaces.focused_idx());
+            self.last_focused_workspace = Some(previous_idx);
             self.workspaces.focus(idx);
         }
 
+        // sticky windows move along with focus so that they are never hidden
+        if previous_idx != idx
+            && let Some(previous) = self.workspaces_mut().get_mut(previous_idx)
+        {
+            let sticky_windows = previous.remove_sticky_windows();
+            if !sticky_windows.is_empty()
+                && let Some(workspace) = self.workspaces_mut().get_mut(idx)
+            {
+                workspace.add_sticky_windows(sticky_windows);
+            }
+        }
+
         Ok(())
     }
 
@@ -262,11 +276,9 @@ impl Monitor {
         //     eyre::bail!("cannot move native maximized window to another monitor or workspace");
         // }
 
-        let foreground_hwnd = if !cfg!(test) {
-            MacosApi::foreground_window_id().ok_or_eyre("no foreground window")?
-        } else {
-            0
-        };
+        let foreground_hwnd = platform::backend()
+            .foreground_window_id()
+            .ok_or_eyre("no foreground window")?;
 
         let floating_window_index = workspace
             .floating_windows()
@@ -552,6 +564,20 @@ impl Monitor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::platform::FakeBackend;
+    use crate::platform::ScopedBackendGuard;
+    use crate::window::Window;
+    use proptest::prelude::*;
+    use proptest::sample::Index;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    /// Window id 0 is reserved for the foreground window, which no test window uses
+    fn fake_backend() -> ScopedBackendGuard {
+        let backend = FakeBackend::default();
+        backend.set_foreground_window(Some(0));
+        platform::scoped_backend(Arc::new(backend))
+    }
 
     #[test]
     fn test_add_container() {
@@ -675,6 +701,46 @@ mod tests {
         assert_eq!(m.focused_workspace_idx(), 1);
     }
 
+    #[test]
+    fn test_focus_workspace_moves_sticky_windows() {
+        let mut m = Monitor::new(
+            0,
+            Rect::default(),
+            Rect::default(),
+            "TestDevice",
+            "TestDeviceId",
+        );
+
+        {
+            let mut sticky = Window::from(7001);
+            sticky.sticky = true;
+
+            let workspace = m.focused_workspace_mut().unwrap();
+            workspace.floating_windows_mut().push_back(sticky);
+            workspace
+                .floating_windows_mut()
+                .push_back(Window::from(7002));
+            workspace.layer = WorkspaceLayer::Floating;
+        }
+
+        m.focus_workspace(1).unwrap();
+
+        // The sticky window follows focus while the other floating window stays behind
+        let workspace = m.workspaces().get(0).unwrap();
+        assert!(!workspace.contains_window(7001));
+        assert!(workspace.contains_window(7002));
+        assert_eq!(workspace.layer, WorkspaceLayer::Floating);
+
+        assert!(m.focused_workspace().unwrap().contains_window(7001));
+
+        m.focus_workspace(0).unwrap();
+
+        // The sticky window comes back without being duplicated
+        let workspace = m.focused_workspace().unwrap();
+        assert_eq!(workspace.floating_windows().len(), 2);
+        assert!(!m.workspaces().get(1).unwrap().contains_window(7001));
+    }
+
     #[test]
     fn test_new_workspace_idx() {
         let m = Monitor::new(
@@ -693,6 +759,7 @@ mod tests {
 
     #[test]
     fn test_move_container_to_workspace() {
+        let _backend = fake_backend();
         let mut m = Monitor::new(
             0,
             Rect::default(),
@@ -755,6 +822,7 @@ mod tests {
 
     #[test]
     fn test_move_container_to_nonexistent_workspace() {
+        let _backend = fake_backend();
         let mut m = Monitor::new(
             0,
             Rect::default(),
@@ -835,4 +903,162 @@ mod tests {
         m.ensure_workspace_count(3);
         assert_eq!(m.workspaces().len(), 5, "Monitor should have 5 workspaces");
     }
+
+    #[derive(Debug, Clone)]
+    enum MonitorOperation {
+        NewWindow,
+        FocusWorkspace(usize),
+        MoveContainerToWorkspace(usize, bool),
+        ToggleLock(Index),
+        FocusContainer(Index),
+    }
+
+    fn arb_monitor_operation() -> impl Strategy<Value = MonitorOperation> {
+        prop_oneof![
+            3 => Just(MonitorOperation::NewWindow),
+            1 => (0usize..4).prop_map(MonitorOperation::FocusWorkspace),
+            3 => (0usize..4, any::<bool>())
+                .prop_map(|(idx, follow)| MonitorOperation::MoveContainerToWorkspace(idx, follow)),
+            1 => any::<Index>().prop_map(MonitorOperation::ToggleLock),
+            1 => any::<Index>().prop_map(MonitorOperation::FocusContainer),
+        ]
+    }
+
+    fn locked_container_positions(workspace: &Workspace) -> Vec<(usize, String)> {
+        workspace
+            .containers()
+            .iter()
+            .enumerate()
+            .filter(|(_, container)| container.locked)
+            .map(|(idx, container)| (idx, container.id.clone()))
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn test_monitor_operation_invariants(
+            operations in proptest::collection::vec(arb_monitor_operation(), 1..50)
+        ) {
+            let mut m = Monitor::new(
+                0,
+                Rect::default(),
+                Rect::default(),
+                "TestDevice",
+                "TestDeviceId",
+            );
+
+            let _backend = fake_backend();
+
+            // window id 0 is reserved for the foreground window
+            let mut next_window_id = 1;
+            let mut live_window_ids = HashSet::new();
+
+            for operation in operations {
+                let origin_workspace_idx = m.focused_workspace_idx();
+                let locked = m
+                    .workspaces()
+                    .iter()
+                    .map(locked_container_positions)
+                    .collect::<Vec<_>>();
+
+                let workspace = m.focused_workspace_mut().unwrap();
+                let len = workspace.containers().len();
+
+                match &operation {
+                    MonitorOperation::NewWindow => {
+                        workspace
+                            .new_container_for_window(&Window::from(next_window_id))
+                            .unwrap();
+                        live_window_ids.insert(next_window_id);
+                        next_window_id += 1;
+                    }
+                    MonitorOperation::FocusWorkspace(idx) => {
+                        m.focus_workspace(*idx).unwrap();
+                    }
+                    MonitorOperation::MoveContainerToWorkspace(idx, follow) => {
+                        m.move_container_to_workspace(*idx, *follow, None).ok();
+                    }
+                    MonitorOperation::ToggleLock(idx) => {
+                        if len == 0 {
+                            continue;
+                        }
+
+                        let container = &mut workspace.containers_mut()[idx.index(len)];
+                        container.locked = !container.locked;
+                        continue;
+                    }
+                    MonitorOperation::FocusContainer(idx) => {
+                        if len == 0 {
+                            continue;
+                        }
+
+                        workspace.focus_container(idx.index(len));
+                    }
+                }
+
+                prop_assert!(
+                    m.focused_workspace_idx() < m.workspaces().len(),
+                    "focused workspace out of bounds after {:?}",
+                    operation
+                );
+
+                let mut window_ids = vec![];
+                for (workspace_idx, workspace) in m.workspaces().iter().enumerate() {
+                    let len = workspace.containers().len();
+                    prop_assert!(
+                        len == 0 || workspace.focused_container_idx() < len,
+                        "focused container out of bounds on workspace {} after {:?}",
+                        workspace_idx,
+                        operation
+                    );
+
+                    window_ids.extend(workspace.containers().iter().flat_map(|container| {
+                        container.windows().iter().map(|window| window.id)
+                    }));
+
+                    // a container moved to another workspace is always added to the back, so
+                    // only the origin workspace can have its locked containers displaced
+                    let workspace_locked = locked.get(workspace_idx).cloned().unwrap_or_default();
+                    let is_origin = workspace_idx == origin_workspace_idx;
+                    let moved_to_self = matches!(
+                        operation,
+                        MonitorOperation::MoveContainerToWorkspace(target_idx, _)
+                            if target_idx == origin_workspace_idx
+                    );
+
+                    if (!is_origin || !moved_to_self)
+                        && workspace_locked.iter().all(|(idx, _)| *idx < len)
+                    {
+                        for (idx, id) in &workspace_locked {
+                            if let Some(current_idx) =
+                                workspace.containers().iter().position(|c| &c.id == id)
+                            {
+                                prop_assert_eq!(
+                                    current_idx,
+                                    *idx,
+                                    "locked container moved on workspace {} after {:?}",
+                                    workspace_idx,
+                                    operation
+                                );
+                            }
+                        }
+                    }
+                }
+
+                let unique_window_ids = window_ids.iter().copied().collect::<HashSet<_>>();
+                prop_assert_eq!(
+                    window_ids.len(),
+                    unique_window_ids.len(),
+                    "duplicate window ids after {:?}",
+                    operation
+                );
+                prop_assert_eq!(
+                    &unique_window_ids,
+                    &live_window_ids,
+                    "windows lost after {:?}",
+                    operation
+                );
+            }
+        }
+    }
 }
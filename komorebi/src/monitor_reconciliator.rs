@@ -7,7 +7,10 @@ use crate::core::config_generation::WorkspaceMatchingRule;
 use crate::macos_api::MacosApi;
 use crate::monitor::Monitor;
 use crate::notify_subscribers;
+use crate::platform;
+use crate::stackbar_manager;
 use crate::state::State;
+use crate::transparency_manager;
 use crate::window_manager::WindowManager;
 use crossbeam_channel::Receiver;
 use crossbeam_channel::Sender;
@@ -115,7 +118,7 @@ pub fn handle_notifications(wm: Arc<Mutex<WindowManager>>) -> color_eyre::Result
 
                 let mut attached_devices = vec![];
                 DispatchQueue::main().exec_sync(|| {
-                    if let Ok(latest) = MacosApi::latest_monitor_information() {
+                    if let Ok(latest) = platform::backend().monitors() {
                         attached_devices = latest.clone();
                     }
                 });
@@ -570,6 +573,8 @@ pub fn handle_notifications(wm: Arc<Mutex<WindowManager>>) -> color_eyre::Result
         }
 
         border_manager::send_notification(None, None, false);
+        stackbar_manager::send_notification();
+        transparency_manager::send_notification();
     }
 
     Ok(())
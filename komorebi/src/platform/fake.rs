@@ -0,0 +1,391 @@
+use crate::LibraryError;
+use crate::accessibility::error::AccessibilityApiError;
+use crate::accessibility::error::AccessibilityError;
+use crate::application::Application;
+use crate::core::Rect;
+use crate::core::WindowHidingPosition;
+use crate::monitor::Monitor;
+use crate::platform::PlatformBackend;
+use crate::window::Window;
+use crate::window_manager_event::WindowManagerEvent;
+use color_eyre::eyre;
+use crossbeam_channel::Receiver;
+use crossbeam_channel::Sender;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlatformCall {
+    SetPosition(u32, Rect),
+    Focus(u32),
+    Hide(u32),
+    Restore(u32),
+    Raise(u32),
+    Minimize(u32),
+    Close(u32),
+}
+
+impl PlatformCall {
+    pub const fn window_id(&self) -> u32 {
+        match self {
+            Self::SetPosition(window_id, _)
+            | Self::Focus(window_id)
+            | Self::Hide(window_id)
+            | Self::Restore(window_id)
+            | Self::Raise(window_id)
+            | Self::Minimize(window_id)
+            | Self::Close(window_id) => *window_id,
+        }
+    }
+}
+
+/// A window which only exists in a [`FakeBackend`], described by the same details that rules are
+/// matched against
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StubWindow {
+    pub id: u32,
+    pub process_id: i32,
+    pub title: String,
+    pub exe: String,
+    pub role: String,
+    pub subrole: String,
+    pub path: PathBuf,
+}
+
+impl StubWindow {
+    /// A standard application window, which is managed unless a rule says otherwise
+    pub fn new(id: u32, process_id: i32, exe: &str, title: &str) -> Self {
+        Self {
+            id,
+            process_id,
+            title: title.to_string(),
+            exe: exe.to_string(),
+            role: String::from("AXWindow"),
+            subrole: String::from("AXStandardWindow"),
+            path: PathBuf::from(format!("/Applications/{exe}.app/Contents/MacOS/{exe}")),
+        }
+    }
+}
+
+impl From<&Window> for StubWindow {
+    fn from(window: &Window) -> Self {
+        Self {
+            id: window.id,
+            process_id: window.application.process_id,
+            title: window.title().unwrap_or_default(),
+            exe: window.exe().unwrap_or_default(),
+            role: window.role().unwrap_or_default(),
+            subrole: window.subrole().unwrap_or_default(),
+            path: window.path().unwrap_or_default(),
+        }
+    }
+}
+
+/// An in-memory backend which records every call made to it
+///
+/// Window positions are remembered so that they can be read back, and events are only delivered
+/// when they are sent with [`FakeBackend::send_event`]. Windows added with
+/// [`FakeBackend::add_window`] can be looked up by their application, the most recently added or
+/// focused window of an application is its main window, and windows stay valid until they are
+/// passed to [`FakeBackend::destroy_window`], after which moving, hiding or restoring them fails
+/// as it would for a closed window; windows which were never added have no details but are
+/// otherwise treated as open.
+pub struct FakeBackend {
+    calls: Mutex<Vec<PlatformCall>>,
+    rects: Mutex<HashMap<u32, Rect>>,
+    windows: Mutex<HashMap<u32, StubWindow>>,
+    main_windows: Mutex<HashMap<i32, u32>>,
+    destroyed: Mutex<HashSet<u32>>,
+    foreground_window_id: Mutex<Option<u32>>,
+    cursor_pos: Mutex<(i32, i32)>,
+    monitors: Vec<Monitor>,
+    events: (Sender<WindowManagerEvent>, Receiver<WindowManagerEvent>),
+}
+
+impl Default for FakeBackend {
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+impl FakeBackend {
+    pub fn new(monitors: Vec<Monitor>) -> Self {
+        Self {
+            calls: Mutex::new(vec![]),
+            rects: Mutex::new(HashMap::new()),
+            windows: Mutex::new(HashMap::new()),
+            main_windows: Mutex::new(HashMap::new()),
+            destroyed: Mutex::new(HashSet::new()),
+            foreground_window_id: Mutex::new(None),
+            cursor_pos: Mutex::new((0, 0)),
+            monitors,
+            events: crossbeam_channel::unbounded(),
+        }
+    }
+
+    pub fn calls(&self) -> Vec<PlatformCall> {
+        self.calls.lock().clone()
+    }
+
+    pub fn calls_for(&self, window_id: u32) -> Vec<PlatformCall> {
+        self.calls
+            .lock()
+            .iter()
+            .filter(|call| call.window_id() == window_id)
+            .copied()
+            .collect()
+    }
+
+    pub fn clear_calls(&self) {
+        self.calls.lock().clear();
+    }
+
+    pub fn send_event(&self, event: WindowManagerEvent) {
+        // the receiving end is owned by the backend, so this can't be disconnected
+        let _ = self.events.0.send(event);
+    }
+
+    /// Opens a window, which becomes the main window of its application
+    pub fn add_window(&self, window: StubWindow) {
+        self.destroyed.lock().remove(&window.id);
+        self.main_windows
+            .lock()
+            .insert(window.process_id, window.id);
+        self.windows.lock().insert(window.id, window);
+    }
+
+    /// Closes a window, handing the main window of its application to one of its other windows
+    pub fn destroy_window(&self, window_id: u32) {
+        self.destroyed.lock().insert(window_id);
+
+        let mut foreground_window_id = self.foreground_window_id.lock();
+        if *foreground_window_id == Some(window_id) {
+            *foreground_window_id = None;
+        }
+
+        let Some(process_id) = self.windows.lock().get(&window_id).map(|w| w.process_id) else {
+            return;
+        };
+
+        let mut main_windows = self.main_windows.lock();
+        if main_windows.get(&process_id) == Some(&window_id) {
+            match self.open_window_ids(process_id).last() {
+                None => main_windows.remove(&process_id),
+                Some(id) => main_windows.insert(process_id, *id),
+            };
+        }
+    }
+
+    pub fn set_foreground_window(&self, window_id: Option<u32>) {
+        *self.foreground_window_id.lock() = window_id;
+    }
+
+    pub fn set_cursor_pos(&self, point: (i32, i32)) {
+        *self.cursor_pos.lock() = point;
+    }
+
+    fn record(&self, call: PlatformCall) {
+        self.calls.lock().push(call);
+    }
+
+    fn check_open(&self, window: &Window) -> Result<(), AccessibilityError> {
+        if self.destroyed.lock().contains(&window.id) {
+            return Err(AccessibilityApiError::InvalidUIElement.into());
+        }
+
+        Ok(())
+    }
+
+    fn stub(&self, window_id: u32) -> Option<StubWindow> {
+        if self.destroyed.lock().contains(&window_id) {
+            return None;
+        }
+
+        self.windows.lock().get(&window_id).cloned()
+    }
+
+    fn open_window_ids(&self, process_id: i32) -> Vec<u32> {
+        let destroyed = self.destroyed.lock();
+        let mut window_ids = self
+            .windows
+            .lock()
+            .values()
+            .filter(|w| w.process_id == process_id && !destroyed.contains(&w.id))
+            .map(|w| w.id)
+            .collect::<Vec<_>>();
+
+        window_ids.sort_unstable();
+        window_ids
+    }
+}
+
+impl PlatformBackend for FakeBackend {
+    fn window_rect(&self, window: &Window) -> Result<Rect, AccessibilityError> {
+        Ok(self
+            .rects
+            .lock()
+            .get(&window.id)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    fn set_window_position(&self, window: &Window, rect: &Rect) -> Result<(), AccessibilityError> {
+        self.record(PlatformCall::SetPosition(window.id, *rect));
+        self.check_open(window)?;
+        self.rects.lock().insert(window.id, *rect);
+        Ok(())
+    }
+
+    fn focus_window(&self, window: &Window, mouse_follows_focus: bool) -> Result<(), LibraryError> {
+        self.record(PlatformCall::Focus(window.id));
+        self.set_foreground_window(Some(window.id));
+
+        if let Some(stub) = self.stub(window.id) {
+            self.main_windows.lock().insert(stub.process_id, stub.id);
+        }
+
+        if mouse_follows_focus {
+            self.center_cursor_in_rect(&self.window_rect(window)?)?;
+        }
+
+        Ok(())
+    }
+
+    fn hide_window(
+        &self,
+        window: &Window,
+        _hiding_position: WindowHidingPosition,
+    ) -> Result<(), AccessibilityError> {
+        self.record(PlatformCall::Hide(window.id));
+        self.check_open(window)
+    }
+
+    fn restore_window(&self, window: &Window) -> Result<(), AccessibilityError> {
+        self.record(PlatformCall::Restore(window.id));
+        self.check_open(window)
+    }
+
+    fn raise_window(&self, window: &Window) -> Result<(), AccessibilityError> {
+        self.record(PlatformCall::Raise(window.id));
+        self.check_open(window)
+    }
+
+    fn minimize_window(&self, window: &Window) -> Result<(), AccessibilityError> {
+        self.record(PlatformCall::Minimize(window.id));
+        self.check_open(window)
+    }
+
+    fn close_window(&self, window: &Window) -> Result<(), AccessibilityError> {
+        self.record(PlatformCall::Close(window.id));
+        self.check_open(window)
+    }
+
+    fn window_is_valid(&self, window: &Window) -> bool {
+        !self.destroyed.lock().contains(&window.id)
+    }
+
+    fn window_id(&self, window: &Window) -> Result<u32, AccessibilityError> {
+        Ok(window.id)
+    }
+
+    fn window_title(&self, window: &Window) -> Option<String> {
+        self.stub(window.id).map(|stub| stub.title)
+    }
+
+    fn window_role(&self, window: &Window) -> Option<String> {
+        self.stub(window.id).map(|stub| stub.role)
+    }
+
+    fn window_subrole(&self, window: &Window) -> Option<String> {
+        self.stub(window.id).map(|stub| stub.subrole)
+    }
+
+    fn window_path(&self, window: &Window) -> Option<PathBuf> {
+        self.stub(window.id).map(|stub| stub.path)
+    }
+
+    fn window(&self, window_id: u32) -> Option<Window> {
+        let stub = self.stub(window_id)?;
+        Some(Window::stub(stub.id, Application::stub(stub.process_id)))
+    }
+
+    fn foreground_window(&self) -> Option<Window> {
+        self.window(self.foreground_window_id()?)
+    }
+
+    fn foreground_window_id(&self) -> Option<u32> {
+        *self.foreground_window_id.lock()
+    }
+
+    fn focus_desktop(&self) {
+        self.set_foreground_window(None);
+    }
+
+    fn application(&self, process_id: i32) -> Result<Application, AccessibilityError> {
+        Ok(Application::stub(process_id))
+    }
+
+    fn application_name(&self, application: &Application) -> Option<String> {
+        self.windows
+            .lock()
+            .values()
+            .find(|stub| stub.process_id == application.process_id)
+            .map(|stub| stub.exe.clone())
+    }
+
+    fn main_window(&self, application: &Application) -> Option<Window> {
+        let window_id = *self.main_windows.lock().get(&application.process_id)?;
+        self.stub(window_id)?;
+
+        Some(Window::stub(window_id, application.clone()))
+    }
+
+    fn window_ids(&self, application: &Application) -> Vec<u32> {
+        self.open_window_ids(application.process_id)
+    }
+
+    fn cursor_pos(&self) -> (i32, i32) {
+        *self.cursor_pos.lock()
+    }
+
+    fn center_cursor_in_rect(&self, rect: &Rect) -> Result<(), LibraryError> {
+        self.set_cursor_pos((rect.left + (rect.right / 2), rect.top + (rect.bottom / 2)));
+        Ok(())
+    }
+
+    fn left_mouse_button_is_pressed(&self) -> bool {
+        false
+    }
+
+    fn monitor_from_point(&self, point: (i32, i32)) -> Option<u32> {
+        self.monitors
+            .iter()
+            .find(|monitor| monitor.size.contains_point(point))
+            .map(|monitor| monitor.id)
+    }
+
+    fn space_id(&self) -> Option<u64> {
+        None
+    }
+
+    fn set_wallpaper(&self, _path: &Path, _monitor_id: u32) -> eyre::Result<()> {
+        Ok(())
+    }
+
+    fn disable_screen_updates(&self) {}
+
+    fn reenable_screen_updates(&self) {}
+
+    fn monitors(&self) -> Result<Vec<Monitor>, LibraryError> {
+        Ok(self.monitors.clone())
+    }
+
+    fn events(&self) -> Receiver<WindowManagerEvent> {
+        self.events.1.clone()
+    }
+}
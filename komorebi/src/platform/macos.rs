@@ -0,0 +1,187 @@
+use crate::LibraryError;
+use crate::accessibility::AccessibilityApi;
+use crate::accessibility::error::AccessibilityError;
+use crate::application::Application;
+use crate::cf_array_as;
+use crate::core::Rect;
+use crate::core::WindowHidingPosition;
+use crate::core_graphics::CoreGraphicsApi;
+use crate::current_space_id;
+use crate::macos_api::MacosApi;
+use crate::monitor::Monitor;
+use crate::platform::PlatformBackend;
+use crate::skylight::CGSMainConnectionID;
+use crate::skylight::SLSDisableUpdate;
+use crate::skylight::SLSReenableUpdate;
+use crate::window::AdhocWindow;
+use crate::window::Window;
+use crate::window::WindowInfo;
+use crate::window_manager_event::WindowManagerEvent;
+use crate::window_manager_event_listener;
+use color_eyre::eyre;
+use crossbeam_channel::Receiver;
+use objc2_core_foundation::CFDictionary;
+use objc2_core_foundation::CGPoint;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Manages windows through the Accessibility API, AppKit and CoreGraphics
+pub struct MacosBackend;
+
+impl PlatformBackend for MacosBackend {
+    fn window_rect(&self, window: &Window) -> Result<Rect, AccessibilityError> {
+        Ok(Rect::from(MacosApi::window_rect(&window.element)?))
+    }
+
+    fn set_window_position(&self, window: &Window, rect: &Rect) -> Result<(), AccessibilityError> {
+        window.set_position_native(rect)
+    }
+
+    fn focus_window(&self, window: &Window, mouse_follows_focus: bool) -> Result<(), LibraryError> {
+        window.focus_native(mouse_follows_focus)
+    }
+
+    fn hide_window(
+        &self,
+        window: &Window,
+        hiding_position: WindowHidingPosition,
+    ) -> Result<(), AccessibilityError> {
+        window.hide_native(hiding_position)
+    }
+
+    fn restore_window(&self, window: &Window) -> Result<(), AccessibilityError> {
+        window.restore_native()
+    }
+
+    fn raise_window(&self, window: &Window) -> Result<(), AccessibilityError> {
+        window.raise_native()
+    }
+
+    fn minimize_window(&self, window: &Window) -> Result<(), AccessibilityError> {
+        window.minimize_native()
+    }
+
+    fn close_window(&self, window: &Window) -> Result<(), AccessibilityError> {
+        window.close_native()
+    }
+
+    fn window_is_valid(&self, window: &Window) -> bool {
+        window.is_valid_native()
+    }
+
+    fn window_id(&self, window: &Window) -> Result<u32, AccessibilityError> {
+        AccessibilityApi::window_id(&window.element)
+    }
+
+    fn window_title(&self, window: &Window) -> Option<String> {
+        window.title_native()
+    }
+
+    fn window_role(&self, window: &Window) -> Option<String> {
+        window.role_native()
+    }
+
+    fn window_subrole(&self, window: &Window) -> Option<String> {
+        window.subrole_native()
+    }
+
+    fn window_path(&self, window: &Window) -> Option<PathBuf> {
+        window.path_native()
+    }
+
+    fn window(&self, window_id: u32) -> Option<Window> {
+        let window_list_info = CoreGraphicsApi::window_list_info()?;
+
+        for raw_window_info in cf_array_as::<CFDictionary>(&window_list_info) {
+            let raw_info = WindowInfo::new(raw_window_info);
+            let Ok(application) = Application::new(raw_info.owner_pid) else {
+                continue;
+            };
+
+            for element in application.window_elements().into_iter().flatten() {
+                if AccessibilityApi::window_id(&element).is_ok_and(|id| id == window_id) {
+                    return Window::new(element, application.clone()).ok();
+                }
+            }
+        }
+
+        None
+    }
+
+    fn foreground_window(&self) -> Option<Window> {
+        let element = MacosApi::foreground_window()?;
+        let process_id = AdhocWindow::process_id(&element)?;
+        let application = Application::new(process_id).ok()?;
+        Window::new(element, application).ok()
+    }
+
+    fn foreground_window_id(&self) -> Option<u32> {
+        MacosApi::foreground_window_id()
+    }
+
+    fn focus_desktop(&self) {
+        MacosApi::activate_finder();
+    }
+
+    fn application(&self, process_id: i32) -> Result<Application, AccessibilityError> {
+        Application::new(process_id)
+    }
+
+    fn application_name(&self, application: &Application) -> Option<String> {
+        application.name_native()
+    }
+
+    fn main_window(&self, application: &Application) -> Option<Window> {
+        Window::new(application.main_window()?, application.clone()).ok()
+    }
+
+    fn window_ids(&self, application: &Application) -> Vec<u32> {
+        application
+            .window_elements()
+            .into_iter()
+            .flatten()
+            .filter_map(|element| AccessibilityApi::window_id(&element).ok())
+            .collect()
+    }
+
+    fn cursor_pos(&self) -> (i32, i32) {
+        let point = MacosApi::cursor_pos();
+        (point.x as i32, point.y as i32)
+    }
+
+    fn center_cursor_in_rect(&self, rect: &Rect) -> Result<(), LibraryError> {
+        MacosApi::center_cursor_in_rect(rect)
+    }
+
+    fn left_mouse_button_is_pressed(&self) -> bool {
+        MacosApi::left_mouse_button_is_pressed()
+    }
+
+    fn monitor_from_point(&self, point: (i32, i32)) -> Option<u32> {
+        MacosApi::monitor_from_point(CGPoint::new(point.0 as f64, point.1 as f64))
+    }
+
+    fn space_id(&self) -> Option<u64> {
+        current_space_id()
+    }
+
+    fn set_wallpaper(&self, path: &Path, monitor_id: u32) -> eyre::Result<()> {
+        MacosApi::set_wallpaper(path, monitor_id)
+    }
+
+    fn disable_screen_updates(&self) {
+        unsafe { SLSDisableUpdate(CGSMainConnectionID()) };
+    }
+
+    fn reenable_screen_updates(&self) {
+        unsafe { SLSReenableUpdate(CGSMainConnectionID()) };
+    }
+
+    fn monitors(&self) -> Result<Vec<Monitor>, LibraryError> {
+        MacosApi::latest_monitor_information()
+    }
+
+    fn events(&self) -> Receiver<WindowManagerEvent> {
+        window_manager_event_listener::event_rx()
+    }
+}
@@ -0,0 +1,198 @@
+//! The operations the window manager performs against the OS
+//!
+//! Window geometry and details, focus, hiding and restoring, application and window lookup, the
+//! cursor, monitor enumeration and event delivery all go through the installed
+//! [`PlatformBackend`] when commands and events are processed. [`MacosBackend`] is installed by
+//! default, and [`FakeBackend`] keeps everything in memory so that full command and event flows can
+//! be exercised against stub windows.
+//!
+//! A backend installed with [`scoped_backend`] only applies to the current thread until the
+//! returned guard is dropped, so tests running in parallel can each use their own [`FakeBackend`].
+//!
+//! The crate still only builds for macOS: [`Window`] and [`Application`] hold Accessibility API
+//! handles, while the border, stackbar, transparency and input listener managers, monitor
+//! reconciliation and the run loop passed to `WindowManager::new` use AppKit, CoreGraphics and
+//! CoreFoundation directly. Tests which install a [`FakeBackend`] don't touch the running desktop
+//! or need accessibility permissions, but they have to run on macOS.
+
+pub mod fake;
+pub mod macos;
+
+pub use fake::FakeBackend;
+pub use fake::PlatformCall;
+pub use fake::StubWindow;
+pub use macos::MacosBackend;
+
+use crate::LibraryError;
+use crate::accessibility::error::AccessibilityError;
+use crate::application::Application;
+use crate::core::Rect;
+use crate::core::WindowHidingPosition;
+use crate::monitor::Monitor;
+use crate::window::Window;
+use crate::window_manager_event::WindowManagerEvent;
+use color_eyre::eyre;
+use crossbeam_channel::Receiver;
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+lazy_static! {
+    static ref BACKEND: RwLock<Arc<dyn PlatformBackend>> = RwLock::new(Arc::new(MacosBackend));
+}
+
+thread_local! {
+    static SCOPED_BACKEND: RefCell<Option<Arc<dyn PlatformBackend>>> = const { RefCell::new(None) };
+}
+
+pub trait PlatformBackend: Send + Sync {
+    fn window_rect(&self, window: &Window) -> Result<Rect, AccessibilityError>;
+
+    fn set_window_position(&self, window: &Window, rect: &Rect) -> Result<(), AccessibilityError>;
+
+    fn focus_window(&self, window: &Window, mouse_follows_focus: bool) -> Result<(), LibraryError>;
+
+    fn hide_window(
+        &self,
+        window: &Window,
+        hiding_position: WindowHidingPosition,
+    ) -> Result<(), AccessibilityError>;
+
+    fn restore_window(&self, window: &Window) -> Result<(), AccessibilityError>;
+
+    /// Brings the window in front of the other windows of its application
+    fn raise_window(&self, window: &Window) -> Result<(), AccessibilityError>;
+
+    fn minimize_window(&self, window: &Window) -> Result<(), AccessibilityError>;
+
+    fn close_window(&self, window: &Window) -> Result<(), AccessibilityError>;
+
+    /// Whether the window is still open
+    fn window_is_valid(&self, window: &Window) -> bool;
+
+    /// The id currently reported for the window's element, which no longer matches [`Window::id`]
+    /// once another tab of a natively tabbed application has taken its place
+    fn window_id(&self, window: &Window) -> Result<u32, AccessibilityError>;
+
+    fn window_title(&self, window: &Window) -> Option<String>;
+
+    fn window_role(&self, window: &Window) -> Option<String>;
+
+    fn window_subrole(&self, window: &Window) -> Option<String>;
+
+    /// The path of the executable which owns the window
+    fn window_path(&self, window: &Window) -> Option<PathBuf>;
+
+    /// Looks up an open window by its id
+    fn window(&self, window_id: u32) -> Option<Window>;
+
+    /// The window which currently has keyboard focus
+    fn foreground_window(&self) -> Option<Window>;
+
+    /// The id of the window which currently has keyboard focus, without looking up the window
+    fn foreground_window_id(&self) -> Option<u32>;
+
+    /// Moves focus away from every window, leaving the desktop focused
+    fn focus_desktop(&self);
+
+    fn application(&self, process_id: i32) -> Result<Application, AccessibilityError>;
+
+    fn application_name(&self, application: &Application) -> Option<String>;
+
+    fn main_window(&self, application: &Application) -> Option<Window>;
+
+    /// The ids of every window the application has open
+    fn window_ids(&self, application: &Application) -> Vec<u32>;
+
+    fn cursor_pos(&self) -> (i32, i32);
+
+    fn center_cursor_in_rect(&self, rect: &Rect) -> Result<(), LibraryError>;
+
+    fn left_mouse_button_is_pressed(&self) -> bool;
+
+    /// The id of the monitor containing the point
+    fn monitor_from_point(&self, point: (i32, i32)) -> Option<u32>;
+
+    /// The id of the active space, if the platform has spaces
+    fn space_id(&self) -> Option<u64>;
+
+    fn set_wallpaper(&self, path: &Path, monitor_id: u32) -> eyre::Result<()>;
+
+    /// Holds back redrawing the screen so that a whole layout is applied at once
+    fn disable_screen_updates(&self);
+
+    fn reenable_screen_updates(&self);
+
+    /// Every connected monitor, with a single default workspace each
+    fn monitors(&self) -> Result<Vec<Monitor>, LibraryError>;
+
+    /// The events which the window manager should process
+    fn events(&self) -> Receiver<WindowManagerEvent>;
+}
+
+/// The backend scoped to the current thread, or the process-wide backend if there isn't one
+pub fn backend() -> Arc<dyn PlatformBackend> {
+    SCOPED_BACKEND
+        .with_borrow(Clone::clone)
+        .unwrap_or_else(|| BACKEND.read().clone())
+}
+
+/// Replaces the process-wide backend
+pub fn set_backend(backend: Arc<dyn PlatformBackend>) {
+    *BACKEND.write() = backend;
+}
+
+/// Uses `backend` on the current thread until the returned guard is dropped
+pub fn scoped_backend(backend: Arc<dyn PlatformBackend>) -> ScopedBackendGuard {
+    ScopedBackendGuard {
+        previous: SCOPED_BACKEND.replace(Some(backend)),
+        _thread: PhantomData,
+    }
+}
+
+/// Restores the backend which was scoped to the current thread before [`scoped_backend`] was
+/// called
+#[must_use]
+pub struct ScopedBackendGuard {
+    previous: Option<Arc<dyn PlatformBackend>>,
+    // the guard has to be dropped on the thread it was created on
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for ScopedBackendGuard {
+    fn drop(&mut self) {
+        SCOPED_BACKEND.set(self.previous.take());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoped_backends_are_restored() {
+        let outer: Arc<dyn PlatformBackend> = Arc::new(FakeBackend::default());
+        let inner: Arc<dyn PlatformBackend> = Arc::new(FakeBackend::default());
+
+        let outer_guard = scoped_backend(outer.clone());
+        assert!(Arc::ptr_eq(&backend(), &outer));
+
+        {
+            let _inner_guard = scoped_backend(inner.clone());
+            assert!(Arc::ptr_eq(&backend(), &inner));
+
+            // other threads keep using the process-wide backend
+            let elsewhere = std::thread::spawn(backend).join().unwrap();
+            assert!(!Arc::ptr_eq(&elsewhere, &inner));
+        }
+
+        assert!(Arc::ptr_eq(&backend(), &outer));
+
+        drop(outer_guard);
+        assert!(!Arc::ptr_eq(&backend(), &outer));
+    }
+}
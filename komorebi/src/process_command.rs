@@ -6,10 +6,10 @@ use crate::MANAGE_IDENTIFIERS;
 use crate::Notification;
 use crate::NotificationEvent;
 use crate::SESSION_FLOATING_APPLICATIONS;
+use crate::SUBSCRIPTION_PIPES;
 use crate::SUBSCRIPTION_SOCKET_OPTIONS;
 use crate::SUBSCRIPTION_SOCKETS;
 use crate::WORKSPACE_MATCHING_RULES;
-use crate::accessibility::AccessibilityApi;
 use crate::animation::ANIMATION_DURATION_GLOBAL;
 use crate::animation::ANIMATION_DURATION_PER_ANIMATION;
 use crate::animation::ANIMATION_ENABLED_GLOBAL;
@@ -17,17 +17,15 @@ use crate::animation::ANIMATION_ENABLED_PER_ANIMATION;
 use crate::animation::ANIMATION_FPS;
 use crate::animation::ANIMATION_STYLE_GLOBAL;
 use crate::animation::ANIMATION_STYLE_PER_ANIMATION;
-use crate::application::Application;
 use crate::border_manager;
-use crate::cf_array_as;
 use crate::core::ApplicationIdentifier;
 use crate::core::Axis;
 use crate::core::Layout;
 use crate::core::LayoutOptions;
+use crate::core::MetricsFormat;
 use crate::core::MonocleFocusBehaviour;
 use crate::core::MoveBehaviour;
 use crate::core::OperationDirection;
-use crate::core::Rect;
 use crate::core::ScrollingLayoutOptions;
 use crate::core::SocketMessage;
 use crate::core::StateQuery;
@@ -37,35 +35,46 @@ use crate::core::config_generation::IdWithIdentifier;
 use crate::core::config_generation::MatchingRule;
 use crate::core::config_generation::MatchingStrategy;
 use crate::core::config_generation::WorkspaceMatchingRule;
-
-use crate::core_graphics::CoreGraphicsApi;
-use crate::current_space_id;
-use crate::macos_api::MacosApi;
+use crate::core::ipc;
+use crate::core::ipc::Response;
+use crate::core::ipc::ResponseError;
+use crate::core::ipc::ResponseErrorKind;
+use crate::core::state_path::StatePath;
+
+use crate::history;
+use crate::history::LayoutSnapshot;
+use crate::metrics;
 use crate::monitor::MonitorInformation;
 use crate::notify_subscribers;
+use crate::platform;
+use crate::recorder;
+use crate::session::Session;
+use crate::stackbar_manager;
+use crate::stackbar_manager::ExplicitTabColours;
 use crate::state::GlobalState;
 use crate::state::State;
 use crate::static_config::StaticConfig;
+use crate::subscriber_pipe;
+use crate::subscriber_pipe::SubscriberPipe;
 use crate::theme_manager;
-use crate::window::AdhocWindow;
+use crate::transaction;
+use crate::transaction::TransactionGuard;
+use crate::transparency_manager;
 use crate::window::RuleDebug;
 use crate::window::Window;
-use crate::window::WindowInfo;
 use crate::window_manager::WindowManager;
-use crate::window_manager_event_listener;
 use crate::workspace::WorkspaceLayer;
-use crate::workspace::WorkspaceWindowLocation;
 use color_eyre::eyre;
 use color_eyre::eyre::Context;
 use color_eyre::eyre::OptionExt;
 use komorebi_themes::colour::Rgb;
-use objc2_core_foundation::CFDictionary;
 use parking_lot::Mutex;
 use std::collections::HashMap;
-use std::fs::File;
 use std::fs::OpenOptions;
 use std::io::BufRead;
 use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
 use std::num::NonZeroUsize;
 use std::os::unix::net::UnixStream;
 use std::str::FromStr;
@@ -116,14 +125,98 @@ pub fn listen_for_commands(wm: Arc<Mutex<WindowManager>>) {
 }
 
 impl WindowManager {
+    /// The focused window of the focused workspace's active layer, falling back to the focused
+    /// floating window when no tiled window is focused
+    fn queried_window(&self) -> eyre::Result<&Window> {
+        let workspace = self.focused_workspace()?;
+
+        if matches!(workspace.layer, WorkspaceLayer::Floating)
+            && let Some(window) = workspace.focused_floating_window()
+        {
+            return Ok(window);
+        }
+
+        self.focused_window()
+            .or_else(|error| workspace.focused_floating_window().ok_or(error))
+    }
+
+    pub fn query_state(&self, query: StateQuery) -> eyre::Result<serde_json::Value> {
+        Ok(match query {
+            StateQuery::FocusedMonitorIndex => self.focused_monitor_idx().into(),
+            StateQuery::FocusedWorkspaceIndex => self
+                .focused_monitor()
+                .ok_or_eyre("there is no monitor")?
+                .focused_workspace_idx()
+                .into(),
+            StateQuery::FocusedContainerIndex => {
+                self.focused_workspace()?.focused_container_idx().into()
+            }
+            StateQuery::FocusedWindowIndex => self.focused_container()?.focused_window_idx().into(),
+            StateQuery::FocusedWorkspaceName => {
+                let focused_monitor = self.focused_monitor().ok_or_eyre("there is no monitor")?;
+
+                focused_monitor
+                    .focused_workspace_name()
+                    .unwrap_or_else(|| focused_monitor.focused_workspace_idx().to_string())
+                    .into()
+            }
+            StateQuery::Version => build::VERSION.into(),
+            StateQuery::FocusedWorkspaceLayout => {
+                let focused_monitor = self.focused_monitor().ok_or_eyre("there is no monitor")?;
+
+                let is_custom = focused_monitor
+                    .focused_workspace()
+                    .is_some_and(|workspace| workspace.custom_layout.is_some());
+
+                focused_monitor
+                    .focused_workspace_layout()
+                    .map_or_else(
+                        || "None".to_string(),
+                        |layout| match layout {
+                            _ if is_custom => "Custom".to_string(),
+                            Layout::Default(default_layout) => default_layout.to_string(),
+                        },
+                    )
+                    .into()
+            }
+            StateQuery::FocusedContainerKind => match self.focused_workspace()?.focused_container()
+            {
+                None => "None".into(),
+                Some(container) => {
+                    if container.windows().len() > 1 {
+                        "Stack".into()
+                    } else {
+                        "Single".into()
+                    }
+                }
+            },
+            StateQuery::FocusedWindowTitle => {
+                self.queried_window()?.title().unwrap_or_default().into()
+            }
+            StateQuery::FocusedWindowExe => self.queried_window()?.exe().unwrap_or_default().into(),
+            StateQuery::FocusedWindowId => self.queried_window()?.id.into(),
+            StateQuery::WorkspaceCountPerMonitor => self
+                .monitors()
+                .iter()
+                .map(|monitor| monitor.workspaces().len())
+                .collect::<Vec<_>>()
+                .into(),
+            StateQuery::FloatingWindowCount => {
+                self.focused_workspace()?.floating_windows().len().into()
+            }
+        })
+    }
+
     #[tracing::instrument(skip(self, reply, message))]
     pub fn process_command(
         &mut self,
         message: SocketMessage,
         mut reply: impl std::io::Write,
     ) -> eyre::Result<()> {
+        let _timer = metrics::time_command(&message);
+
         if let Some(space_id) = &self.space_id
-            && let Some(current_space_id) = current_space_id()
+            && let Some(current_space_id) = platform::backend().space_id()
             && *space_id != current_space_id
         {
             tracing::trace!("ignoring events and commands while not on space {space_id}");
@@ -136,10 +229,19 @@ impl WindowManager {
             tracing::info!("processing command: {message}");
         }
 
+        // the steps of a transaction are recorded as part of the transaction
+        if !transaction::in_progress() {
+            recorder::record_command(self, &message);
+        }
+
         #[allow(clippy::useless_asref)]
         // We don't have From implemented for &mut WindowManager
         let initial_state = State::from(self.as_ref());
 
+        // the steps of a transaction are undone together with the transaction
+        let layout_snapshot = (history::is_undoable(&message) && !transaction::in_progress())
+            .then(|| LayoutSnapshot::from(&*self));
+
         self.handle_unmanaged_window_behaviour()?;
 
         match message {
@@ -151,80 +253,24 @@ impl WindowManager {
                 self.promote_container_to_front()?
             }
             SocketMessage::EagerFocus(ref exe) => {
-                let focused_monitor_idx = self.focused_monitor_idx();
-                let mouse_follows_focus = self.mouse_follows_focus;
-
-                let mut window_location = None;
-                let mut monitor_to_focus = None;
-                let mut needs_workspace_loading = false;
+                let mut target = None;
 
-                'search: for (monitor_idx, monitor) in self.monitors_mut().iter_mut().enumerate() {
+                'search: for (monitor_idx, monitor) in self.monitors().iter().enumerate() {
                     for (workspace_idx, workspace) in monitor.workspaces().iter().enumerate() {
                         if let Some(location) = workspace.location_from_exe(exe) {
-                            window_location = Some(location);
-
-                            if monitor_idx != focused_monitor_idx {
-                                monitor_to_focus = Some(monitor_idx);
-                            }
-
-                            // Focus workspace if it is not already the focused one, without
-                            // loading it so that we don't give focus to the wrong window, we will
-                            // load it later after focusing the wanted window
-                            let focused_ws_idx = monitor.focused_workspace_idx();
-                            if focused_ws_idx != workspace_idx {
-                                monitor.last_focused_workspace = Option::from(focused_ws_idx);
-                                monitor.focus_workspace(workspace_idx)?;
-                                needs_workspace_loading = true;
-                            }
-
+                            target = Some((monitor_idx, workspace_idx, location));
                             break 'search;
                         }
                     }
                 }
 
-                if let Some(monitor_idx) = monitor_to_focus {
-                    self.focus_monitor(monitor_idx)?;
-                }
-
-                if let Some(location) = window_location {
-                    match location {
-                        WorkspaceWindowLocation::Monocle(window_idx) => {
-                            self.focus_container_window(window_idx)?;
-                        }
-                        WorkspaceWindowLocation::Maximized => {
-                            if let Some(window) =
-                                &mut self.focused_workspace_mut()?.maximized_window
-                            {
-                                window.focus(mouse_follows_focus)?;
-                            }
-                        }
-                        WorkspaceWindowLocation::Container(container_idx, window_idx) => {
-                            let focused_container_idx = self.focused_container_idx()?;
-                            if container_idx != focused_container_idx {
-                                self.focused_workspace_mut()?.focus_container(container_idx);
-                            }
-
-                            self.focus_container_window(window_idx)?;
-                        }
-                        WorkspaceWindowLocation::Floating(window_idx) => {
-                            if let Some(window) = self
-                                .focused_workspace_mut()?
-                                .floating_windows_mut()
-                                .get_mut(window_idx)
-                            {
-                                window.focus(mouse_follows_focus)?;
-                            }
-                        }
-                    }
-
-                    if needs_workspace_loading {
-                        let mouse_follows_focus = self.mouse_follows_focus;
-                        if let Some(monitor) = self.focused_monitor_mut() {
-                            monitor.load_focused_workspace(mouse_follows_focus)?;
-                        }
-                    }
+                if let Some((monitor_idx, workspace_idx, location)) = target {
+                    self.focus_window_location(monitor_idx, workspace_idx, location)?;
                 }
             }
+            SocketMessage::Mark(ref mark) => self.mark_focused_window(mark)?,
+            SocketMessage::FocusMark(ref mark) => self.focus_mark(mark)?,
+            SocketMessage::SwapWithMark(ref mark) => self.swap_with_mark(mark)?,
             SocketMessage::FocusWindow(direction) => {
                 let focused_workspace = self.focused_workspace()?;
                 match focused_workspace.layer {
@@ -329,14 +375,16 @@ impl WindowManager {
                 ANIMATION_ENABLED_GLOBAL.store(restore, Ordering::SeqCst);
             }
             SocketMessage::Minimize => {
-                let foreground_window =
-                    MacosApi::foreground_window().ok_or_eyre("there is no foreground window")?;
-                AdhocWindow::minimize(&foreground_window)?;
+                platform::backend()
+                    .foreground_window()
+                    .ok_or_eyre("there is no foreground window")?
+                    .minimize()?;
             }
             SocketMessage::Close => {
-                let foreground_window =
-                    MacosApi::foreground_window().ok_or_eyre("there is no foreground window")?;
-                AdhocWindow::close(&foreground_window)?;
+                platform::backend()
+                    .foreground_window()
+                    .ok_or_eyre("there is no foreground window")?
+                    .close()?;
             }
             SocketMessage::ManageFocusedWindow => {
                 self.manage_focused_window()?;
@@ -384,6 +432,9 @@ impl WindowManager {
             }
             SocketMessage::FlipLayout(layout_flip) => self.flip_layout(layout_flip)?,
             SocketMessage::ChangeLayout(layout) => self.change_workspace_layout_default(layout)?,
+            SocketMessage::ChangeLayoutCustom(ref path) => {
+                self.change_workspace_custom_layout(path)?;
+            }
             SocketMessage::CycleLayout(direction) => self.cycle_layout(direction)?,
             SocketMessage::TogglePause => {
                 if self.is_paused {
@@ -571,6 +622,12 @@ impl WindowManager {
                     self.focus_workspace(workspace_idx)?;
                 }
             }
+            SocketMessage::FocusWorkspaceSet(ref name) => {
+                self.focus_workspace_set(name)?;
+            }
+            SocketMessage::CycleFocusWorkspaceSet(direction) => {
+                self.cycle_workspace_set(direction)?;
+            }
             SocketMessage::CloseWorkspace => {
                 // TODO: figure out if we need to do this on macOS
                 // // This is to ensure that even on an empty workspace on a secondary monitor, the
@@ -623,7 +680,9 @@ impl WindowManager {
                 self.move_container_to_workspace(workspace_idx, false, None)?;
             }
             SocketMessage::ToggleMonocle => self.toggle_monocle()?,
+            SocketMessage::ToggleScratchpad(ref name) => self.toggle_scratchpad(name)?,
             SocketMessage::ToggleFloat => self.toggle_float(false)?,
+            SocketMessage::ToggleSticky => self.toggle_sticky()?,
             SocketMessage::ToggleWorkspaceLayer => {
                 let mouse_follows_focus = self.mouse_follows_focus;
                 let hiding_position = self
@@ -648,8 +707,7 @@ impl WindowManager {
 
                         // Sort by window area
                         window_idx_pairs.sort_by_key(|(_, w)| {
-                            let rect =
-                                Rect::from(MacosApi::window_rect(&w.element).unwrap_or_default());
+                            let rect = w.rect().unwrap_or_default();
                             rect.right * rect.bottom
                         });
                         window_idx_pairs.reverse();
@@ -718,9 +776,7 @@ impl WindowManager {
 
                             // Sort by window area
                             window_idx_pairs.sort_by_key(|w| {
-                                let rect = Rect::from(
-                                    MacosApi::window_rect(&w.element).unwrap_or_default(),
-                                );
+                                let rect = w.rect().unwrap_or_default();
                                 rect.right * rect.bottom
                             });
 
@@ -802,6 +858,9 @@ impl WindowManager {
             }
             SocketMessage::Retile => self.retile_all(false)?,
             SocketMessage::RetileWithResizeDimensions => self.retile_all(true)?,
+            SocketMessage::Undo => self.undo()?,
+            SocketMessage::Redo => self.redo()?,
+            SocketMessage::Transaction(ref steps) => self.process_transaction(steps, &mut reply)?,
             SocketMessage::ToggleWorkspaceWindowContainerBehaviour => {
                 let current_global_behaviour = self.window_management_behaviour.current_behaviour;
                 if let Some(behaviour) =
@@ -1043,6 +1102,15 @@ impl WindowManager {
             SocketMessage::ReloadStaticConfiguration(ref pathbuf) => {
                 self.reload_static_configuration(pathbuf)?;
             }
+            SocketMessage::ReloadConfiguration => {
+                self.reload_configuration()?;
+            }
+            SocketMessage::ActivateProfile(ref name) => {
+                self.activate_profile(name.as_deref())?;
+            }
+            SocketMessage::WatchConfiguration(enable) => {
+                self.watch_configuration(enable)?;
+            }
             SocketMessage::State => {
                 let state = match serde_json::to_string_pretty(&State::from(&*self)) {
                     Ok(state) => state,
@@ -1093,64 +1161,44 @@ impl WindowManager {
 
                 reply.write_all(monitors_state.as_bytes())?;
             }
-            SocketMessage::Query(query) => {
-                let response = match query {
-                    StateQuery::FocusedMonitorIndex => self.focused_monitor_idx().to_string(),
-                    StateQuery::FocusedWorkspaceIndex => self
-                        .focused_monitor()
-                        .ok_or_eyre("there is no monitor")?
-                        .focused_workspace_idx()
-                        .to_string(),
-                    StateQuery::FocusedContainerIndex => self
-                        .focused_workspace()?
-                        .focused_container_idx()
-                        .to_string(),
-                    StateQuery::FocusedWindowIndex => {
-                        self.focused_container()?.focused_window_idx().to_string()
-                    }
-                    StateQuery::FocusedWorkspaceName => {
-                        let focused_monitor =
-                            self.focused_monitor().ok_or_eyre("there is no monitor")?;
+            SocketMessage::Metrics(format) => {
+                let metrics = metrics::snapshot();
+                let response = match format {
+                    MetricsFormat::Json => serde_json::to_string_pretty(&metrics)?,
+                    MetricsFormat::Prometheus => metrics.to_prometheus(),
+                };
 
-                        focused_monitor
-                            .focused_workspace_name()
-                            .unwrap_or_else(|| focused_monitor.focused_workspace_idx().to_string())
-                    }
-                    StateQuery::Version => build::VERSION.to_string(),
-                    StateQuery::FocusedWorkspaceLayout => {
-                        let focused_monitor =
-                            self.focused_monitor().ok_or_eyre("there is no monitor")?;
-
-                        focused_monitor.focused_workspace_layout().map_or_else(
-                            || "None".to_string(),
-                            |layout| match layout {
-                                Layout::Default(default_layout) => default_layout.to_string(),
-                            },
-                        )
-                    }
-                    StateQuery::FocusedContainerKind => {
-                        match self.focused_workspace()?.focused_container() {
-                            None => "None".to_string(),
-                            Some(container) => {
-                                if container.windows().len() > 1 {
-                                    "Stack".to_string()
-                                } else {
-                                    "Single".to_string()
-                                }
-                            }
-                        }
-                    }
+                reply.write_all(response.as_bytes())?;
+            }
+            SocketMessage::Query(query) => {
+                let response = match self.query_state(query)? {
+                    serde_json::Value::String(value) => value,
+                    value => value.to_string(),
                 };
 
                 reply.write_all(response.as_bytes())?;
             }
+            SocketMessage::QueryJson(query) => {
+                let response = serde_json::to_string(&self.query_state(query)?)?;
+                reply.write_all(response.as_bytes())?;
+            }
+            SocketMessage::QueryPath(ref path) => {
+                let path = StatePath::from_str(path)?;
+                let state = serde_json::to_value(State::from(&*self))?;
+                let Some(value) = path.select(&state) else {
+                    eyre::bail!("there is no value at {path}");
+                };
+
+                reply.write_all(serde_json::to_string(&value)?.as_bytes())?;
+            }
             SocketMessage::SessionFloatRule => {
-                let foreground_window =
-                    MacosApi::foreground_window().ok_or_eyre("there is no foreground window")?;
+                let foreground_window = platform::backend()
+                    .foreground_window()
+                    .ok_or_eyre("there is no foreground window")?;
                 let (exe, title, role) = (
-                    AdhocWindow::exe(&foreground_window),
-                    AdhocWindow::title(&foreground_window),
-                    AdhocWindow::role(&foreground_window),
+                    foreground_window.exe(),
+                    foreground_window.title(),
+                    foreground_window.role(),
                 );
 
                 if let (Some(exe), Some(title), Some(role)) = (exe, title, role) {
@@ -1438,6 +1486,22 @@ impl WindowManager {
             SocketMessage::WorkspaceLayout(monitor_idx, workspace_idx, layout) => {
                 self.set_workspace_layout_default(monitor_idx, workspace_idx, layout)?;
             }
+            SocketMessage::WorkspaceLayoutCustom(monitor_idx, workspace_idx, ref path) => {
+                self.set_workspace_custom_layout(monitor_idx, workspace_idx, path)?;
+            }
+            SocketMessage::WorkspaceLayoutCustomRule(
+                monitor_idx,
+                workspace_idx,
+                at_container_count,
+                ref path,
+            ) => {
+                self.add_workspace_custom_layout_rule(
+                    monitor_idx,
+                    workspace_idx,
+                    at_container_count,
+                    path,
+                )?;
+            }
             SocketMessage::WorkspaceLayoutRule(
                 monitor_idx,
                 workspace_idx,
@@ -1468,6 +1532,13 @@ impl WindowManager {
                     self.set_workspace_layout_default(monitor_idx, workspace_idx, layout)?;
                 }
             }
+            SocketMessage::NamedWorkspaceLayoutCustom(ref workspace, ref path) => {
+                if let Some((monitor_idx, workspace_idx)) =
+                    self.monitor_workspace_index_by_name(workspace)
+                {
+                    self.set_workspace_custom_layout(monitor_idx, workspace_idx, path)?;
+                }
+            }
             SocketMessage::ClearWorkspaceLayoutRules(monitor_idx, workspace_idx) => {
                 self.clear_workspace_layout_rules(monitor_idx, workspace_idx)?;
             }
@@ -1558,6 +1629,22 @@ impl WindowManager {
                     )?;
                 }
             }
+            SocketMessage::NamedWorkspaceLayoutCustomRule(
+                ref workspace,
+                at_container_count,
+                ref path,
+            ) => {
+                if let Some((monitor_idx, workspace_idx)) =
+                    self.monitor_workspace_index_by_name(workspace)
+                {
+                    self.add_workspace_custom_layout_rule(
+                        monitor_idx,
+                        workspace_idx,
+                        at_container_count,
+                        path,
+                    )?;
+                }
+            }
             SocketMessage::ClearNamedWorkspaceLayoutRules(ref workspace) => {
                 if let Some((monitor_idx, workspace_idx)) =
                     self.monitor_workspace_index_by_name(workspace)
@@ -1577,6 +1664,17 @@ impl WindowManager {
             SocketMessage::ToggleMouseFollowsFocus => {
                 self.mouse_follows_focus = !self.mouse_follows_focus;
             }
+            SocketMessage::FocusFollowsMouse(implementation, enable) => {
+                self.set_focus_follows_mouse(enable.then_some(implementation));
+            }
+            SocketMessage::ToggleFocusFollowsMouse(implementation) => {
+                let implementation = match self.focus_follows_mouse {
+                    Some(_) => None,
+                    None => Some(implementation),
+                };
+
+                self.set_focus_follows_mouse(implementation);
+            }
             SocketMessage::CrossMonitorMoveBehaviour(behaviour) => {
                 self.cross_monitor_move_behaviour = behaviour;
             }
@@ -1647,6 +1745,18 @@ impl WindowManager {
                 let mut sockets = SUBSCRIPTION_SOCKETS.lock();
                 sockets.remove(socket);
             }
+            SocketMessage::AddSubscriberPipe(ref pipe) => {
+                let subscriber =
+                    SubscriberPipe::create(subscriber_pipe::pipe_path(&DATA_DIR, pipe)?)?;
+                let mut pipes = SUBSCRIPTION_PIPES.lock();
+                pipes.insert(pipe.clone(), subscriber);
+            }
+            SocketMessage::RemoveSubscriberPipe(ref pipe) => {
+                let mut pipes = SUBSCRIPTION_PIPES.lock();
+                if let Some(subscriber) = pipes.remove(pipe) {
+                    subscriber.close()?;
+                }
+            }
             SocketMessage::Stop => {
                 self.stop(false)?;
             }
@@ -1659,9 +1769,6 @@ impl WindowManager {
                 reply.write_all(config.as_bytes())?;
             }
             SocketMessage::QuickSave => {
-                let workspace = self.focused_workspace()?;
-                let resize = &workspace.resize_dimensions;
-
                 let quicksave_json = std::env::temp_dir().join("komorebi.quicksave.json");
 
                 let file = OpenOptions::new()
@@ -1670,66 +1777,36 @@ impl WindowManager {
                     .create(true)
                     .open(quicksave_json)?;
 
-                serde_json::to_writer_pretty(&file, &resize)?;
+                serde_json::to_writer_pretty(&file, &Session::from(&*self))?;
             }
             SocketMessage::QuickLoad => {
-                let workspace = self.focused_workspace_mut()?;
-
                 let quicksave_json = std::env::temp_dir().join("komorebi.quicksave.json");
 
-                let file = File::open(&quicksave_json).wrap_err(format!(
-                    "no quicksave found at {}",
-                    quicksave_json.display()
-                ))?;
-
-                let resize: Vec<Option<Rect>> = serde_json::from_reader(file)?;
+                if !quicksave_json.is_file() {
+                    eyre::bail!("no quicksave found at {}", quicksave_json.display());
+                }
 
-                workspace.resize_dimensions = resize;
-                self.update_focused_workspace(false, false)?;
+                self.load_save_file(&quicksave_json)?;
             }
             SocketMessage::Save(ref path) => {
-                let workspace = self.focused_workspace_mut()?;
-                let resize = &workspace.resize_dimensions;
-
                 let file = OpenOptions::new()
                     .write(true)
                     .truncate(true)
                     .create(true)
                     .open(path)?;
 
-                serde_json::to_writer_pretty(&file, &resize)?;
+                serde_json::to_writer_pretty(&file, &Session::from(&*self))?;
             }
             SocketMessage::Load(ref path) => {
-                let workspace = self.focused_workspace_mut()?;
-
-                let file =
-                    File::open(path).wrap_err(format!("no file found at {}", path.display()))?;
-
-                let resize: Vec<Option<Rect>> = serde_json::from_reader(file)?;
-
-                workspace.resize_dimensions = resize;
-                self.update_focused_workspace(false, false)?;
+                self.load_save_file(path)?;
             }
             SocketMessage::DebugWindow(window_id) => {
-                if let Some(window_list_info) = CoreGraphicsApi::window_list_info() {
-                    for raw_window_info in cf_array_as::<CFDictionary>(&window_list_info) {
-                        let raw_info = WindowInfo::new(raw_window_info);
-                        let application = Application::new(raw_info.owner_pid)?;
-                        if let Some(elements) = application.window_elements() {
-                            for element in elements {
-                                if let Ok(wid) = AccessibilityApi::window_id(&element)
-                                    && wid == window_id
-                                    && let Ok(window) = Window::new(element, application.clone())
-                                {
-                                    let mut rule_debug = RuleDebug::default();
-                                    let _ = window.should_manage(None, &mut rule_debug);
-                                    let schema = serde_json::to_string_pretty(&rule_debug)?;
-
-                                    reply.write_all(schema.as_bytes())?;
-                                }
-                            }
-                        }
-                    }
+                if let Some(window) = platform::backend().window(window_id) {
+                    let mut rule_debug = RuleDebug::default();
+                    let _ = window.should_manage(None, &mut rule_debug);
+                    let schema = serde_json::to_string_pretty(&rule_debug)?;
+
+                    reply.write_all(schema.as_bytes())?;
                 }
             }
             SocketMessage::DisplayIndexPreference(index_preference, ref display) => {
@@ -1737,34 +1814,40 @@ impl WindowManager {
                 display_index_preferences.insert(index_preference, display.clone());
             }
             SocketMessage::ReplaceConfiguration(ref config) => {
-                // Check that this is a valid static config file first
-                if StaticConfig::read(config).is_ok() {
-                    // Clear workspace rules; these will need to be replaced
-                    WORKSPACE_MATCHING_RULES.lock().clear();
-                    // Pause so that restored windows come to the foreground from all workspaces
-                    self.is_paused = true;
-                    // Bring all windows to the foreground
-                    self.restore_all_windows(false)?;
-
-                    // Create a new wm from the config path
-                    let mut wm = StaticConfig::preload(
-                        config,
-                        window_manager_event_listener::event_rx(),
-                        self.command_listener.try_clone().ok(),
-                        &self.run_loop.0,
-                    )?;
+                // Check that this is a valid static config file first, so that the caller hears
+                // about it instead of the current configuration being silently kept
+                StaticConfig::read(config)
+                    .and_then(|value| value.validate())
+                    .wrap_err(format!(
+                        "rejected invalid static configuration at {}",
+                        config.display()
+                    ))?;
+
+                // Clear workspace rules; these will need to be replaced
+                WORKSPACE_MATCHING_RULES.lock().clear();
+                // Pause so that restored windows come to the foreground from all workspaces
+                self.is_paused = true;
+                // Bring all windows to the foreground
+                self.restore_all_windows(false)?;
+
+                // Create a new wm from the config path
+                let mut wm = StaticConfig::preload(
+                    config,
+                    platform::backend().events(),
+                    self.command_listener.try_clone().ok(),
+                    &self.run_loop.0,
+                )?;
 
-                    // Initialize the new wm
-                    wm.init()?;
+                // Initialize the new wm
+                wm.init()?;
 
-                    wm.restore_all_windows(true)?;
+                wm.restore_all_windows(true)?;
 
-                    // This is equivalent to StaticConfig::postload for this use case
-                    StaticConfig::reload(config, &mut wm)?;
+                // This is equivalent to StaticConfig::postload for this use case
+                StaticConfig::reload(config, &mut wm)?;
 
-                    // Set self to the new wm instance
-                    *self = wm;
-                }
+                // Set self to the new wm instance
+                *self = wm;
             }
             SocketMessage::Border(enable) => {
                 border_manager::BORDER_ENABLED.store(enable, Ordering::SeqCst);
@@ -1801,6 +1884,60 @@ impl WindowManager {
                 border_manager::BORDER_OFFSET.store(offset, Ordering::SeqCst);
                 border_manager::destroy_all_borders()?;
             }
+            SocketMessage::Transparency(enable) => {
+                transparency_manager::TRANSPARENCY_ENABLED.store(enable, Ordering::SeqCst);
+            }
+            SocketMessage::ToggleTransparency => {
+                let current = transparency_manager::TRANSPARENCY_ENABLED.load(Ordering::SeqCst);
+                transparency_manager::TRANSPARENCY_ENABLED.store(!current, Ordering::SeqCst);
+            }
+            SocketMessage::TransparencyAlpha(alpha) => {
+                transparency_manager::TRANSPARENCY_ALPHA.store(alpha, Ordering::SeqCst);
+            }
+            SocketMessage::StackbarMode(mode) => {
+                stackbar_manager::STACKBAR_MODE.store(mode);
+                self.retile_all(true)?;
+            }
+            SocketMessage::StackbarLabel(label) => {
+                stackbar_manager::STACKBAR_LABEL.store(label);
+            }
+            SocketMessage::StackbarFocusedTextColour(r, g, b) => {
+                stackbar_manager::STACKBAR_FOCUSED_TEXT_COLOUR
+                    .store(Rgb::new(r, g, b).into(), Ordering::SeqCst);
+                stackbar_manager::STACKBAR_EXPLICIT_TAB_COLOURS.store(ExplicitTabColours {
+                    focused_text: true,
+                    ..stackbar_manager::STACKBAR_EXPLICIT_TAB_COLOURS.load()
+                });
+            }
+            SocketMessage::StackbarUnfocusedTextColour(r, g, b) => {
+                stackbar_manager::STACKBAR_UNFOCUSED_TEXT_COLOUR
+                    .store(Rgb::new(r, g, b).into(), Ordering::SeqCst);
+                stackbar_manager::STACKBAR_EXPLICIT_TAB_COLOURS.store(ExplicitTabColours {
+                    unfocused_text: true,
+                    ..stackbar_manager::STACKBAR_EXPLICIT_TAB_COLOURS.load()
+                });
+            }
+            SocketMessage::StackbarBackgroundColour(r, g, b) => {
+                stackbar_manager::STACKBAR_TAB_BACKGROUND_COLOUR
+                    .store(Rgb::new(r, g, b).into(), Ordering::SeqCst);
+                stackbar_manager::STACKBAR_EXPLICIT_TAB_COLOURS.store(ExplicitTabColours {
+                    background: true,
+                    ..stackbar_manager::STACKBAR_EXPLICIT_TAB_COLOURS.load()
+                });
+            }
+            SocketMessage::StackbarHeight(height) => {
+                stackbar_manager::STACKBAR_TAB_HEIGHT.store(height, Ordering::SeqCst);
+                self.retile_all(true)?;
+            }
+            SocketMessage::StackbarTabWidth(width) => {
+                stackbar_manager::STACKBAR_TAB_WIDTH.store(width, Ordering::SeqCst);
+            }
+            SocketMessage::StackbarFontSize(size) => {
+                stackbar_manager::STACKBAR_FONT_SIZE.store(size, Ordering::SeqCst);
+            }
+            SocketMessage::StackbarFontFamily(ref font_family) => {
+                *stackbar_manager::STACKBAR_FONT_FAMILY.lock() = font_family.clone();
+            }
             SocketMessage::Theme(ref theme) => {
                 theme_manager::send_notification(*theme.clone());
             }
@@ -1841,8 +1978,19 @@ impl WindowManager {
             },
         }
 
+        if let Some(snapshot) = layout_snapshot
+            && snapshot != LayoutSnapshot::from(&*self)
+        {
+            self.history.push(snapshot);
+        }
+
         self.update_known_window_ids();
 
+        // subscribers are notified once when the whole transaction has been processed
+        if transaction::in_progress() {
+            return Ok(());
+        }
+
         notify_subscribers(
             Notification {
                 event: NotificationEvent::Socket(message.clone()),
@@ -1852,6 +2000,8 @@ impl WindowManager {
         )?;
 
         border_manager::send_notification(None, None, false);
+        stackbar_manager::send_notification();
+        transparency_manager::send_notification();
 
         if matches!(message, SocketMessage::Theme(_)) {
             tracing::trace!("processed command: {message}");
@@ -1861,21 +2011,102 @@ impl WindowManager {
 
         Ok(())
     }
+
+    /// Processes every step of a transaction before applying the resulting layout in one go
+    ///
+    /// Every step has to be accepted by [`transaction::is_allowed`], which limits steps to
+    /// commands that only change the arrangement of the monitors. If any step fails, the monitors
+    /// are rolled back to the state they were in before the transaction started; since nothing
+    /// was moved on screen, there is nothing else to undo
+    #[tracing::instrument(skip_all)]
+    pub fn process_transaction(
+        &mut self,
+        steps: &[SocketMessage],
+        reply: &mut dyn Write,
+    ) -> eyre::Result<()> {
+        if let Some((idx, step)) = steps
+            .iter()
+            .enumerate()
+            .find(|(_, step)| !transaction::is_allowed(step))
+        {
+            eyre::bail!("step {idx} ({step}) can't be used in a transaction");
+        }
+
+        let guard = TransactionGuard::begin()?;
+        let snapshot = self.monitors.clone();
+
+        let mut result = Ok(());
+        for (idx, step) in steps.iter().enumerate() {
+            if let Err(error) = self.process_command(step.clone(), &mut *reply) {
+                result =
+                    Err(error.wrap_err(format!("step {idx} ({step}) of the transaction failed")));
+                break;
+            }
+        }
+
+        drop(guard);
+
+        if let Err(error) = result {
+            tracing::warn!("rolling back transaction");
+            self.monitors = snapshot;
+            self.update_known_window_ids();
+            return Err(error);
+        }
+
+        let mouse_follows_focus = self.mouse_follows_focus;
+        let focused_monitor_idx = self.focused_monitor_idx();
+
+        for (idx, monitor) in self.monitors_mut().iter_mut().enumerate() {
+            if idx != focused_monitor_idx {
+                monitor.load_focused_workspace(mouse_follows_focus)?;
+            }
+        }
+
+        // the focused monitor is loaded last so that its focused window ends up with focus
+        self.focused_monitor_mut()
+            .ok_or_eyre("there is no monitor")?
+            .load_focused_workspace(mouse_follows_focus)?;
+
+        self.retile_all(true)
+    }
+}
+
+/// Commands which are still processed while the window manager is paused
+fn is_allowed_while_paused(message: &SocketMessage) -> bool {
+    matches!(
+        message,
+        SocketMessage::TogglePause
+            | SocketMessage::State
+            | SocketMessage::GlobalState
+            | SocketMessage::Metrics(_)
+            | SocketMessage::Stop
+    )
 }
 
-pub fn read_commands_uds(
+pub fn read_commands_uds(wm: &Arc<Mutex<WindowManager>>, stream: UnixStream) -> eyre::Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    read_commands(wm, reader, stream)
+}
+
+/// Reads and processes commands from any transport, replying on `stream`
+pub fn read_commands(
     wm: &Arc<Mutex<WindowManager>>,
-    mut stream: UnixStream,
+    reader: BufReader<impl Read>,
+    mut stream: impl Write,
 ) -> eyre::Result<()> {
-    let reader = BufReader::new(stream.try_clone()?);
-    // TODO(raggi): while this processes more than one command, if there are
-    // replies there is no clearly defined protocol for framing yet - it's
-    // perhaps whole-json objects for now, but termination is signalled by
-    // socket shutdown.
+    let (framed, reader) = ipc::peek_framed(reader)?;
+    let reader = BufReader::new(reader);
+
+    if framed {
+        return read_framed_commands(wm, reader, stream);
+    }
+
+    // legacy clients write bare newline-delimited json and only receive a reply for queries,
+    // with termination signalled by socket shutdown
     for line in reader.lines() {
         let message = SocketMessage::from_str(&line?)?;
 
-        match wm.try_lock_for(Duration::from_secs(1)) {
+        match metrics::try_lock_for(wm, Duration::from_secs(1)) {
             None => {
                 tracing::warn!(
                     "could not acquire window manager lock, not processing message: {message}"
@@ -1883,15 +2114,11 @@ pub fn read_commands_uds(
             }
             Some(mut wm) => {
                 if wm.is_paused {
-                    return match message {
-                        SocketMessage::TogglePause
-                        | SocketMessage::State
-                        | SocketMessage::GlobalState
-                        | SocketMessage::Stop => Ok(wm.process_command(message, &mut stream)?),
-                        _ => {
-                            tracing::trace!("ignoring while paused");
-                            Ok(())
-                        }
+                    return if is_allowed_while_paused(&message) {
+                        Ok(wm.process_command(message, &mut stream)?)
+                    } else {
+                        tracing::trace!("ignoring while paused");
+                        Ok(())
                     };
                 }
 
@@ -1903,23 +2130,94 @@ pub fn read_commands_uds(
     Ok(())
 }
 
+fn read_framed_commands(
+    wm: &Arc<Mutex<WindowManager>>,
+    mut reader: BufReader<impl Read>,
+    mut stream: impl Write,
+) -> eyre::Result<()> {
+    while let Some((version, payload)) = ipc::read_frame(&mut reader)? {
+        let response = Response::from(process_request(wm, version, &payload));
+        ipc::write_frame(&mut stream, &serde_json::to_vec(&response)?)?;
+    }
+
+    Ok(())
+}
+
+pub fn process_request(
+    wm: &Arc<Mutex<WindowManager>>,
+    version: u8,
+    payload: &[u8],
+) -> Result<String, ResponseError> {
+    if version != ipc::PROTOCOL_VERSION {
+        return Err(ResponseError::new(
+            ResponseErrorKind::UnsupportedVersion,
+            format!(
+                "request was sent using protocol version {version}, but only version {} is supported",
+                ipc::PROTOCOL_VERSION
+            ),
+        ));
+    }
+
+    let message = serde_json::from_slice::<SocketMessage>(payload).map_err(|error| {
+        ResponseError::new(ResponseErrorKind::InvalidRequest, error.to_string())
+    })?;
+
+    let Some(mut wm) = metrics::try_lock_for(wm, Duration::from_secs(1)) else {
+        tracing::warn!("could not acquire window manager lock, not processing message: {message}");
+        return Err(ResponseError::new(
+            ResponseErrorKind::Busy,
+            "could not acquire window manager lock",
+        ));
+    };
+
+    if wm.is_paused && !is_allowed_while_paused(&message) {
+        tracing::trace!("ignoring while paused");
+        return Err(ResponseError::new(
+            ResponseErrorKind::Paused,
+            format!("not processing message while paused: {message}"),
+        ));
+    }
+
+    let mut reply = vec![];
+    if let Err(error) = wm.process_command(message, &mut reply) {
+        tracing::error!("{error}");
+        return Err(ResponseError::from(&error));
+    }
+
+    Ok(String::from_utf8_lossy(&reply).into_owned())
+}
+
 #[cfg(test)]
 mod tests {
+    use super::read_commands_uds;
     use crate::SocketMessage;
     use crate::WindowManagerEvent;
+    use crate::container::Container;
     use crate::core::Rect;
+    use crate::core::StateQuery;
+    use crate::core::ipc;
+    use crate::core::ipc::Response;
+    use crate::core::ipc::ResponseError;
+    use crate::core::ipc::ResponseErrorKind;
+    use crate::core::state_path::StatePath;
     use crate::monitor;
+    use crate::state::State;
+    use crate::window::Window;
     use crate::window_manager::WindowManager;
+    use crate::workspace::WorkspaceLayer;
     use crossbeam_channel::Receiver;
     use crossbeam_channel::Sender;
     use crossbeam_channel::bounded;
     use objc2_core_foundation::CFRunLoop;
+    use parking_lot::Mutex;
     use std::io::BufRead;
     use std::io::BufReader;
     use std::io::Write;
+    use std::net::Shutdown;
     use std::os::unix::net::UnixStream;
     use std::path::PathBuf;
     use std::str::FromStr;
+    use std::sync::Arc;
     use std::time::Duration;
     use uuid::Uuid;
 
@@ -1975,4 +2273,225 @@ mod tests {
 
         std::fs::remove_file(socket_path).unwrap();
     }
+
+    #[test]
+    fn test_framed_request_receives_response() {
+        let (_sender, receiver): (Sender<WindowManagerEvent>, Receiver<WindowManagerEvent>) =
+            bounded(1);
+        let socket_name = format!("komorebi-test-{}.sock", Uuid::new_v4());
+        let socket_path = PathBuf::from(&socket_name);
+        let mut wm = WindowManager::new(
+            &CFRunLoop::main().unwrap(),
+            receiver,
+            Some(socket_path.clone()),
+        )
+        .unwrap();
+        let m = monitor::new(
+            0,
+            Rect::default(),
+            Rect::default(),
+            "TestMonitor".to_string(),
+            "TestDeviceID".to_string(),
+        );
+
+        wm.monitors_mut().push_back(m);
+
+        let mut client = UnixStream::connect(&socket_path).unwrap();
+        let request = serde_json::to_vec(&SocketMessage::FocusWorkspaceNumber(5)).unwrap();
+        ipc::write_frame(&mut client, &request).unwrap();
+
+        // a frame sent using a protocol version from the future
+        let mut unsupported = ipc::FRAME_MAGIC.to_vec();
+        unsupported.push(ipc::PROTOCOL_VERSION + 1);
+        unsupported.extend_from_slice(&(request.len() as u32).to_be_bytes());
+        unsupported.extend_from_slice(&request);
+        client.write_all(&unsupported).unwrap();
+        client.shutdown(Shutdown::Write).unwrap();
+
+        let (stream, _) = wm.command_listener.accept().unwrap();
+        let wm = Arc::new(Mutex::new(wm));
+        read_commands_uds(&wm, stream).unwrap();
+
+        assert_eq!(wm.lock().focused_workspace_idx().unwrap(), 5);
+
+        let (_, payload) = ipc::read_frame(&mut client).unwrap().unwrap();
+        let response = serde_json::from_slice::<Response>(&payload).unwrap();
+        assert_eq!(response, Response::Ok(String::new()));
+
+        let (_, payload) = ipc::read_frame(&mut client).unwrap().unwrap();
+        let response = serde_json::from_slice::<Response>(&payload).unwrap();
+        assert!(matches!(
+            response,
+            Response::Err(ResponseError {
+                kind: ResponseErrorKind::UnsupportedVersion,
+                ..
+            })
+        ));
+
+        assert!(ipc::read_frame(&mut client).unwrap().is_none());
+
+        std::fs::remove_file(socket_path).unwrap();
+    }
+
+    #[test]
+    fn test_transaction() {
+        let (_sender, receiver): (Sender<WindowManagerEvent>, Receiver<WindowManagerEvent>) =
+            bounded(1);
+        let socket_name = format!("komorebi-test-{}.sock", Uuid::new_v4());
+        let socket_path = PathBuf::from(&socket_name);
+        let mut wm = WindowManager::new(
+            &CFRunLoop::main().unwrap(),
+            receiver,
+            Some(socket_path.clone()),
+        )
+        .unwrap();
+        let m = monitor::new(
+            0,
+            Rect::default(),
+            Rect::default(),
+            "TestMonitor".to_string(),
+            "TestDeviceID".to_string(),
+        );
+
+        wm.monitors_mut().push_back(m);
+
+        let transaction = SocketMessage::Transaction(vec![
+            SocketMessage::FocusWorkspaceNumber(3),
+            SocketMessage::FocusWorkspaceNumber(5),
+        ]);
+
+        wm.process_command(transaction, std::io::sink()).unwrap();
+        assert_eq!(wm.focused_workspace_idx().unwrap(), 5);
+
+        // the second step fails because there is only one monitor
+        let transaction = SocketMessage::Transaction(vec![
+            SocketMessage::FocusWorkspaceNumber(2),
+            SocketMessage::FocusMonitorNumber(1),
+        ]);
+
+        assert!(wm.process_command(transaction, std::io::sink()).is_err());
+        assert_eq!(wm.focused_workspace_idx().unwrap(), 5);
+
+        // transactions can't be nested
+        let transaction = SocketMessage::Transaction(vec![
+            SocketMessage::FocusWorkspaceNumber(2),
+            SocketMessage::Transaction(vec![SocketMessage::FocusWorkspaceNumber(1)]),
+        ]);
+
+        assert!(wm.process_command(transaction, std::io::sink()).is_err());
+        assert_eq!(wm.focused_workspace_idx().unwrap(), 5);
+
+        // steps which couldn't be rolled back are rejected before anything is processed
+        let transaction = SocketMessage::Transaction(vec![
+            SocketMessage::FocusWorkspaceNumber(2),
+            SocketMessage::ToggleMouseFollowsFocus,
+        ]);
+
+        let mouse_follows_focus = wm.mouse_follows_focus;
+        assert!(wm.process_command(transaction, std::io::sink()).is_err());
+        assert_eq!(wm.focused_workspace_idx().unwrap(), 5);
+        assert_eq!(wm.mouse_follows_focus, mouse_follows_focus);
+
+        std::fs::remove_file(socket_path).unwrap();
+    }
+
+    #[test]
+    fn test_query_path_round_trip() {
+        let (_sender, receiver): (Sender<WindowManagerEvent>, Receiver<WindowManagerEvent>) =
+            bounded(1);
+        let socket_name = format!("komorebi-test-{}.sock", Uuid::new_v4());
+        let socket_path = PathBuf::from(&socket_name);
+        let mut wm = WindowManager::new(
+            &CFRunLoop::main().unwrap(),
+            receiver,
+            Some(socket_path.clone()),
+        )
+        .unwrap();
+        let m = monitor::new(
+            0,
+            Rect::default(),
+            Rect::default(),
+            "TestMonitor".to_string(),
+            "TestDeviceID".to_string(),
+        );
+
+        wm.monitors_mut().push_back(m);
+
+        let path = "monitors[focused].workspaces[*].name";
+        let mut reply = vec![];
+        wm.process_command(SocketMessage::QueryPath(path.to_string()), &mut reply)
+            .unwrap();
+
+        let state = serde_json::to_value(State::from(&wm)).unwrap();
+        let expected = StatePath::from_str(path).unwrap().select(&state).unwrap();
+        let actual = serde_json::from_slice::<serde_json::Value>(&reply).unwrap();
+        assert_eq!(actual, expected);
+        assert!(actual.is_array());
+
+        // paths which don't select anything are reported as errors
+        let path = "monitors[1].workspaces";
+        assert!(StatePath::from_str(path).unwrap().select(&state).is_none());
+        assert!(
+            wm.process_command(SocketMessage::QueryPath(path.to_string()), std::io::sink())
+                .is_err()
+        );
+
+        std::fs::remove_file(socket_path).unwrap();
+    }
+
+    #[test]
+    fn test_focused_window_query_falls_back_to_floating_window() {
+        let (_sender, receiver): (Sender<WindowManagerEvent>, Receiver<WindowManagerEvent>) =
+            bounded(1);
+        let socket_name = format!("komorebi-test-{}.sock", Uuid::new_v4());
+        let socket_path = PathBuf::from(&socket_name);
+        let mut wm = WindowManager::new(
+            &CFRunLoop::main().unwrap(),
+            receiver,
+            Some(socket_path.clone()),
+        )
+        .unwrap();
+        let m = monitor::new(
+            0,
+            Rect::default(),
+            Rect::default(),
+            "TestMonitor".to_string(),
+            "TestDeviceID".to_string(),
+        );
+
+        wm.monitors_mut().push_back(m);
+
+        assert!(wm.query_state(StateQuery::FocusedWindowId).is_err());
+
+        wm.focused_workspace_mut()
+            .unwrap()
+            .floating_windows_mut()
+            .push_back(Window::from(7));
+
+        assert_eq!(
+            wm.query_state(StateQuery::FocusedWindowId).unwrap(),
+            serde_json::Value::from(7)
+        );
+
+        // a focused tiled window takes precedence on the tiling layer
+        let mut container = Container::default();
+        container.windows_mut().push_back(Window::from(3));
+        wm.focused_workspace_mut()
+            .unwrap()
+            .containers_mut()
+            .push_back(container);
+
+        assert_eq!(
+            wm.query_state(StateQuery::FocusedWindowId).unwrap(),
+            serde_json::Value::from(3)
+        );
+
+        wm.focused_workspace_mut().unwrap().layer = WorkspaceLayer::Floating;
+        assert_eq!(
+            wm.query_state(StateQuery::FocusedWindowId).unwrap(),
+            serde_json::Value::from(7)
+        );
+
+        std::fs::remove_file(socket_path).unwrap();
+    }
 }
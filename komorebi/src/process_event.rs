@@ -1,12 +1,11 @@
-use crate::AccessibilityUiElement;
 use crate::FLOATING_APPLICATIONS;
 use crate::Notification;
 use crate::NotificationEvent;
 use crate::REGEX_IDENTIFIERS;
+use crate::STICKY_APPLICATIONS;
 use crate::TABBED_APPLICATIONS;
 use crate::UNMANAGED_WINDOW_IDS;
 use crate::WORKSPACE_MATCHING_RULES;
-use crate::accessibility::AccessibilityApi;
 use crate::accessibility::error::AccessibilityApiError;
 use crate::accessibility::error::AccessibilityError;
 use crate::accessibility::notification_constants::AccessibilityNotification;
@@ -19,15 +18,16 @@ use crate::core::Sizing;
 use crate::core::WindowContainerBehaviour;
 use crate::core::WindowHidingPosition;
 use crate::core::config_generation::MatchingRule;
-use crate::current_space_id;
-use crate::macos_api::MacosApi;
+use crate::metrics;
 use crate::notify_subscribers;
+use crate::platform;
+use crate::recorder;
 use crate::splash;
 use crate::splash::mdm_enrollment;
+use crate::stackbar_manager;
 use crate::state::State;
-use crate::window::AdhocWindow;
+use crate::transparency_manager;
 use crate::window::RuleDebug;
-use crate::window::Window;
 use crate::window::should_act;
 use crate::window_manager::WindowManager;
 use crate::window_manager_event::ManualNotification;
@@ -69,7 +69,7 @@ pub fn listen_for_events(wm: Arc<Mutex<WindowManager>>) {
         tracing::info!("listening");
         loop {
             if let Ok(event) = receiver.recv() {
-                let mut guard = wm.lock();
+                let mut guard = metrics::lock(&wm);
                 match guard.process_event(event) {
                     Ok(()) => {}
                     Err(error) => {
@@ -88,6 +88,8 @@ pub fn listen_for_events(wm: Arc<Mutex<WindowManager>>) {
 impl WindowManager {
     #[instrument(skip_all)]
     pub fn process_event(&mut self, event: WindowManagerEvent) -> eyre::Result<()> {
+        let _timer = metrics::time_event(&event);
+
         if matches!(event, WindowManagerEvent::ScreenLock(_, _)) {
             let application = self.application(event.process_id())?;
             if application.name().unwrap_or_default() == "loginwindow" {
@@ -111,34 +113,38 @@ impl WindowManager {
 
         if matches!(event, WindowManagerEvent::SpaceChange(_, _))
             && let Some(space_id) = &self.space_id
-            && let Some(current_space_id) = current_space_id()
+            && let Some(current_space_id) = platform::backend().space_id()
         {
             if *space_id == current_space_id {
                 border_manager::send_notification(None, None, false);
+                stackbar_manager::send_notification();
+                transparency_manager::send_notification();
             } else {
                 border_manager::destroy_all_borders()?;
+                stackbar_manager::destroy_all_stackbars()?;
             }
 
             return Ok(());
         }
 
         if let Some(space_id) = &self.space_id
-            && let Some(current_space_id) = current_space_id()
+            && let Some(current_space_id) = platform::backend().space_id()
             && *space_id != current_space_id
         {
             tracing::trace!("ignoring events and commands while not on space {space_id}");
             border_manager::destroy_all_borders()?;
+            stackbar_manager::destroy_all_stackbars()?;
             return Ok(());
         }
 
+        recorder::record_event(self, event);
+
         let mut rule_debug = RuleDebug::default();
 
         let mut should_manage = true;
         {
             let application = self.application(event.process_id())?;
-            if let Some(window_element) = application.main_window()
-                && let Ok(window) = Window::new(window_element, application.clone())
-            {
+            if let Some(window) = platform::backend().main_window(application) {
                 let window_id = window.id;
                 let print_window = window.clone();
                 should_manage = window.should_manage(Some(event), &mut rule_debug)?;
@@ -164,11 +170,9 @@ impl WindowManager {
 
         {
             let application = self.application(event.process_id())?;
-            if let Some(element) = application.main_window()
-                && let Ok(wid) = AccessibilityApi::window_id(&element)
-            {
-                window_id = Some(wid);
-                window_element = Some(AccessibilityUiElement(element.clone()));
+            if let Some(window) = platform::backend().main_window(application) {
+                window_id = Some(window.id);
+                window_element = Some(window.element.clone());
             }
         }
 
@@ -205,9 +209,8 @@ impl WindowManager {
                 let mut tabbed_window = false;
                 let mut needs_reconciliation = false;
 
-                if let Some(window_id) = application.main_window_id()
-                    && let Some(element) = application.main_window()
-                {
+                if let Some(main_window) = platform::backend().main_window(application) {
+                    let window_id = main_window.id;
                     let workspace = self.focused_workspace_mut()?;
 
                     let tabbed_applications = TABBED_APPLICATIONS.lock();
@@ -221,8 +224,8 @@ impl WindowManager {
                                 && window.application.name().unwrap_or_default() == application_name
                                 && window.application.process_id == process_id
                             {
-                                let tab_rect = MacosApi::window_rect(&element)?;
-                                let main_rect = match MacosApi::window_rect(&window.element) {
+                                let tab_rect = main_window.rect()?;
+                                let main_rect = match window.rect() {
                                     Ok(rect) => rect,
                                     Err(AccessibilityError::Api(
                                         AccessibilityApiError::InvalidUIElement,
@@ -258,7 +261,7 @@ impl WindowManager {
                                     // Additional check: verify the stored element belongs to this container
                                     // This prevents treating separate windows as tabs when they have matching geometry
                                     if let Ok(stored_window_id) =
-                                        AccessibilityApi::window_id(&window.element.0)
+                                        platform::backend().window_id(window)
                                         && stored_window_id == window.id
                                     {
                                         // All checks pass: stored element matches container ID = true tab change
@@ -279,7 +282,7 @@ impl WindowManager {
                             && let Some(window) = container.focused_window_mut()
                         {
                             window.id = window_id;
-                            window.element = AccessibilityUiElement(element.clone());
+                            window.element = main_window.element.clone();
                         }
 
                         // check monocle_container for tabbed applications
@@ -289,8 +292,8 @@ impl WindowManager {
                             && window.application.name().unwrap_or_default() == application_name
                             && window.application.process_id == process_id
                         {
-                            let tab_rect = MacosApi::window_rect(&element)?;
-                            let main_rect = match MacosApi::window_rect(&window.element) {
+                            let tab_rect = main_window.rect()?;
+                            let main_rect = match window.rect() {
                                 Ok(rect) => rect,
                                 Err(AccessibilityError::Api(
                                     AccessibilityApiError::InvalidUIElement,
@@ -312,7 +315,7 @@ impl WindowManager {
                             && let Some(window) = monocle.focused_window_mut()
                         {
                             window.id = window_id;
-                            window.element = AccessibilityUiElement(element.clone());
+                            window.element = main_window.element.clone();
                         }
 
                         if first_tab_destroyed {
@@ -457,35 +460,32 @@ impl WindowManager {
                 let focused_workspace_idx =
                     self.focused_workspace_idx_for_monitor_idx(focused_monitor_idx)?;
 
-                let mut window_id = None;
-                let mut window_element = None;
+                let mut main_window = None;
                 let mut application_name = String::new();
                 let mut tabbed_window = false;
                 let mut create = true;
 
                 {
                     let application = self.application(process_id)?;
-                    if let Some(element) = application.main_window()
-                        && let Ok(wid) = AccessibilityApi::window_id(&element)
-                    {
-                        window_id = Some(wid);
-                        window_element = Some(element.clone());
+                    if let Some(window) = platform::backend().main_window(application) {
+                        main_window = Some(window);
                         application_name = application.name().unwrap_or_default().clone();
                     }
                 }
 
-                if let (Some(window_id), Some(element)) = (window_id, &window_element) {
+                if let Some(main_window) = &main_window {
+                    let window_id = main_window.id;
                     let workspace = self.focused_workspace()?;
 
                     let tabbed_applications = TABBED_APPLICATIONS.lock();
                     if tabbed_applications.contains(&application_name) {
                         // Get the window_id for the new window
-                        if let Ok(new_window_id) = AccessibilityApi::window_id(element) {
+                        if let Ok(new_window_id) = platform::backend().window_id(main_window) {
                             for window in workspace.visible_windows().iter().flatten() {
                                 if window.application.name().unwrap_or_default() == application_name
                                 {
-                                    let tab_rect = MacosApi::window_rect(element)?;
-                                    let main_rect = MacosApi::window_rect(&window.element)?;
+                                    let tab_rect = main_window.rect()?;
+                                    let main_rect = window.rect()?;
                                     // BOTH conditions must be true:
                                     // 1. Geometry matches (original check - preserves existing behavior)
                                     // 2. Window IDs match (new check - prevents false positives)
@@ -522,17 +522,40 @@ impl WindowManager {
                             "ignoring show event for window already associated with another workspace"
                         );
 
-                        // TODO: probably shouldn't default here
-                        AdhocWindow::hide(window_id, element, WindowHidingPosition::default())?;
+                        // sticky windows are expected to be visible over other workspaces
+                        if !self.is_sticky_window(window_id) {
+                            // TODO: probably shouldn't default here
+                            main_window.clone().hide(WindowHidingPosition::default())?;
+                        }
+
                         create = false;
                     }
                 }
 
+                // hidden scratchpad windows are only ever shown by toggling their scratchpad, and
+                // new windows matching a scratchpad are captured by it instead of being managed
+                if create
+                    && !tabbed_window
+                    && !self.scratchpads.is_empty()
+                    && let Some(main_window) = &main_window
+                {
+                    if self.is_hidden_scratchpad_window(main_window.id) {
+                        create = false;
+                    } else {
+                        let mut window = main_window.clone();
+                        if window.should_manage(Some(event), &mut rule_debug)?
+                            && let Some(name) = self.scratchpad_for_window(&window)
+                        {
+                            window.observe(&self.run_loop, None)?;
+                            self.capture_scratchpad_window(&name, window)?;
+                            create = false;
+                        }
+                    }
+                }
+
                 // this happens sometimes because of the mouse event from input listener which emits a show
                 // before a window has updated things like its subrole, so we need to check again
-                let application = self.application(process_id)?;
-                if let Some(element) = &window_element
-                    && let Ok(window) = Window::new(element.clone(), application.clone())
+                if let Some(window) = &main_window
                     && !window.should_manage(Some(event), &mut rule_debug)?
                 {
                     create = false;
@@ -540,8 +563,7 @@ impl WindowManager {
 
                 if create
                     && !tabbed_window
-                    && let Some(element) = window_element
-                    && let Ok(mut window) = Window::new(element, application.clone())
+                    && let Some(mut window) = main_window
                 {
                     window.observe(&self.run_loop, None)?;
                     let behaviour = self
@@ -551,9 +573,11 @@ impl WindowManager {
                     let monocle_container = workspace.monocle_container.clone();
 
                     let floating_applications = FLOATING_APPLICATIONS.lock();
+                    let sticky_applications = STICKY_APPLICATIONS.lock();
                     let mut should_float = false;
+                    let mut should_stick = false;
 
-                    if !floating_applications.is_empty() {
+                    if !floating_applications.is_empty() || !sticky_applications.is_empty() {
                         let regex_identifiers = REGEX_IDENTIFIERS.lock();
 
                         if let (
@@ -578,6 +602,19 @@ impl WindowManager {
                                 &regex_identifiers,
                             )
                             .is_some();
+
+                            // sticky windows are always floating windows
+                            should_stick = should_act(
+                                &title,
+                                &exe_name,
+                                &[&role, &subrole],
+                                &path.to_string_lossy(),
+                                &sticky_applications,
+                                &regex_identifiers,
+                            )
+                            .is_some();
+
+                            should_float |= should_stick;
                         }
                     }
 
@@ -598,8 +635,10 @@ impl WindowManager {
                         // Center floating windows according to the proper placement if not
                         // on a floating workspace
                         let center_spawned_floats = placement.should_center() && workspace.tile;
+                        window.sticky = should_stick;
                         workspace.floating_windows_mut().push_back(window.clone());
                         workspace.layer = WorkspaceLayer::Floating;
+
                         if center_spawned_floats {
                             let mut floating_window = window.clone();
                             floating_window
@@ -649,7 +688,7 @@ impl WindowManager {
                             && let Some(monocle_window) = monocle.focused_window()
                         {
                             // we should have the window_id at this point
-                            if monocle_window.id == window_id.unwrap_or_default() {
+                            if monocle_window.id == window.id {
                                 monocle_window_event = true;
                             }
                         }
@@ -679,10 +718,7 @@ impl WindowManager {
                     )
                 ) {
                     let application = self.application(process_id)?;
-                    let window_count = application
-                        .window_elements()
-                        .map(|elements| elements.len())
-                        .unwrap_or(0);
+                    let window_count = platform::backend().window_ids(application).len();
 
                     // force reap if the app has exactly 1 window (i.e. the one being hidden)
                     if window_count == 1 {
@@ -710,7 +746,7 @@ impl WindowManager {
                     tracing::debug!(
                         "workspace is now empty, activating Finder to prevent unwanted workspace switch"
                     );
-                    MacosApi::activate_finder();
+                    platform::backend().focus_desktop();
                 }
 
                 self.update_focused_workspace(false, false)?;
@@ -806,7 +842,7 @@ impl WindowManager {
                     {
                         // TODO: not sure about this clone
                         let window = window.clone();
-                        let new_position = Rect::from(MacosApi::window_rect(&window.element)?);
+                        let new_position = window.rect()?;
                         let old_position = *workspace
                             .latest_layout
                             .get(focused_container_idx)
@@ -982,7 +1018,7 @@ impl WindowManager {
                     && let Some(window) = container.focused_window()
                 {
                     let window = window.clone();
-                    let new_position = Rect::from(MacosApi::window_rect(&window.element)?);
+                    let new_position = window.rect()?;
 
                     let pending_resize_op = Arc::make_mut(&mut self.pending_resize_op);
                     *pending_resize_op = Option::from((window_id, Some(new_position)));
@@ -1085,6 +1121,8 @@ impl WindowManager {
         )?;
 
         border_manager::send_notification(window_element, window_id, false);
+        stackbar_manager::send_notification();
+        transparency_manager::send_notification();
 
         Ok(())
     }
@@ -0,0 +1,238 @@
+//! Named overrides layered on top of the static configuration
+//!
+//! A [`ProfileConfig`] overrides a subset of the keys of a [`StaticConfig`]. Activating a profile
+//! merges its overrides into the base configuration and applies only the settings which profiles
+//! are able to change, so rules are not reloaded and windows are not re-enumerated. Keys which
+//! are set by neither the profile nor the base configuration fall back to their defaults, which
+//! means that a profile without any overrides restores the base configuration. Deactivating the
+//! active profile applies the base configuration on its own.
+
+use crate::core::DefaultLayout;
+use crate::core::LayoutDefaultEntry;
+use crate::core::LayoutOptions;
+use crate::core::Rect;
+use crate::static_config::AnimationsConfig;
+use crate::static_config::StaticConfig;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+/// Overrides which are applied on top of the base configuration when the profile is activated
+pub struct ProfileConfig {
+    /// Global default workspace padding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_workspace_padding: Option<i32>,
+    /// Global default container padding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_container_padding: Option<i32>,
+    /// Per-layout default options and rules, keyed by layout name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout_defaults: Option<HashMap<DefaultLayout, LayoutDefaultEntry>>,
+    /// Display window borders
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border: Option<bool>,
+    /// Width of window borders
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border_width: Option<i32>,
+    /// Offset of window borders
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border_offset: Option<i32>,
+    /// Radius of window borders
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border_radius: Option<i32>,
+    /// Animations configuration options
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub animation: Option<AnimationsConfig>,
+    /// Add transparency to unfocused windows
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transparency: Option<bool>,
+    /// Alpha value for unfocused window transparency [[0-255]]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transparency_alpha: Option<u8>,
+    /// Global work area (space used for tiling) offset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub global_work_area_offset: Option<Rect>,
+    /// Monitor overrides, keyed by the index of the monitor in the base configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitors: Option<HashMap<usize, ProfileMonitorConfig>>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+/// Overrides for a monitor of the base configuration
+pub struct ProfileMonitorConfig {
+    /// Container padding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_padding: Option<i32>,
+    /// Workspace padding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_padding: Option<i32>,
+    /// Monitor-specific work area offset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub work_area_offset: Option<Rect>,
+    /// Workspace overrides, matched to the workspaces of the monitor by name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspaces: Option<Vec<ProfileWorkspaceConfig>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+/// Overrides for a workspace of the base configuration
+pub struct ProfileWorkspaceConfig {
+    /// Name of the workspace to override
+    pub name: String,
+    /// Layout
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<DefaultLayout>,
+    /// Layout-specific options
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout_options: Option<LayoutOptions>,
+    /// Container padding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_padding: Option<i32>,
+    /// Workspace padding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_padding: Option<i32>,
+    /// Workspace specific work area offset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub work_area_offset: Option<Rect>,
+    /// Enable or disable tiling for the workspace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile: Option<bool>,
+}
+
+impl ProfileConfig {
+    /// Returns a copy of `base` with the overrides of this profile merged in
+    pub fn merge(&self, base: &StaticConfig) -> StaticConfig {
+        let mut merged = base.clone();
+
+        merged.default_workspace_padding = self
+            .default_workspace_padding
+            .or(base.default_workspace_padding);
+        merged.default_container_padding = self
+            .default_container_padding
+            .or(base.default_container_padding);
+        merged.layout_defaults = self
+            .layout_defaults
+            .clone()
+            .or_else(|| base.layout_defaults.clone());
+        merged.border = self.border.or(base.border);
+        merged.border_width = self.border_width.or(base.border_width);
+        merged.border_offset = self.border_offset.or(base.border_offset);
+        merged.border_radius = self.border_radius.or(base.border_radius);
+        merged.animation = self.animation.clone().or_else(|| base.animation.clone());
+        merged.transparency = self.transparency.or(base.transparency);
+        merged.transparency_alpha = self.transparency_alpha.or(base.transparency_alpha);
+        merged.global_work_area_offset = self
+            .global_work_area_offset
+            .or(base.global_work_area_offset);
+
+        for (idx, overrides) in self.monitors.iter().flatten() {
+            let Some(monitor) = merged
+                .monitors
+                .as_mut()
+                .and_then(|monitors| monitors.get_mut(*idx))
+            else {
+                continue;
+            };
+
+            monitor.container_padding = overrides.container_padding.or(monitor.container_padding);
+            monitor.workspace_padding = overrides.workspace_padding.or(monitor.workspace_padding);
+            monitor.work_area_offset = overrides.work_area_offset.or(monitor.work_area_offset);
+
+            for workspace_overrides in overrides.workspaces.iter().flatten() {
+                let Some(workspace) = monitor
+                    .workspaces
+                    .iter_mut()
+                    .find(|workspace| workspace.name == workspace_overrides.name)
+                else {
+                    continue;
+                };
+
+                workspace.layout = workspace_overrides.layout.or(workspace.layout);
+                workspace.layout_options = workspace_overrides
+                    .layout_options
+                    .or(workspace.layout_options);
+                workspace.container_padding = workspace_overrides
+                    .container_padding
+                    .or(workspace.container_padding);
+                workspace.workspace_padding = workspace_overrides
+                    .workspace_padding
+                    .or(workspace.workspace_padding);
+                workspace.work_area_offset = workspace_overrides
+                    .work_area_offset
+                    .or(workspace.work_area_offset);
+                workspace.tile = workspace_overrides.tile.or(workspace.tile);
+            }
+        }
+
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let base = StaticConfig::read_raw(
+            r#"{
+                "default_workspace_padding": 10,
+                "border": true,
+                "border_width": 4,
+                "monitors": [
+                    {
+                        "workspaces": [
+                            { "name": "code", "layout": "BSP", "container_padding": 8 },
+                            { "name": "web", "layout": "Columns" }
+                        ]
+                    }
+                ],
+                "profiles": {
+                    "presenting": {
+                        "default_workspace_padding": 40,
+                        "border": false,
+                        "monitors": {
+                            "0": {
+                                "workspace_padding": 60,
+                                "workspaces": [{ "name": "code", "layout": "Rows" }]
+                            }
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let profile = &base.profiles.as_ref().unwrap()["presenting"];
+        let merged = profile.merge(&base);
+
+        assert_eq!(merged.default_workspace_padding, Some(40));
+        assert_eq!(merged.border, Some(false));
+        // keys which the profile doesn't override are kept from the base configuration
+        assert_eq!(merged.border_width, Some(4));
+
+        let monitor = &merged.monitors.as_ref().unwrap()[0];
+        assert_eq!(monitor.workspace_padding, Some(60));
+        assert_eq!(monitor.workspaces[0].layout, Some(DefaultLayout::Rows));
+        assert_eq!(monitor.workspaces[0].container_padding, Some(8));
+        assert_eq!(
+            monitor.workspaces[1],
+            base.monitors.as_ref().unwrap()[0].workspaces[1]
+        );
+
+        // an empty profile restores the base configuration
+        assert_eq!(ProfileConfig::default().merge(&base), base);
+    }
+
+    #[test]
+    fn test_profile_rejects_unknown_keys() {
+        assert!(serde_json::from_str::<ProfileConfig>(r#"{ "ignore_rules": [] }"#).is_err());
+    }
+}
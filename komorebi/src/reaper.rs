@@ -2,6 +2,8 @@ use crate::TABBED_APPLICATIONS;
 use crate::accessibility::AccessibilityApi;
 use crate::accessibility::error::AccessibilityError;
 use crate::border_manager;
+use crate::stackbar_manager;
+use crate::transparency_manager;
 use crate::window::Window;
 use crate::window_manager::WindowManager;
 use crossbeam_channel::Receiver;
@@ -107,11 +109,25 @@ fn handle_notifications(wm: Arc<Mutex<WindowManager>>) -> color_eyre::Result<()>
                     }
                 }
 
+                if wm.marks.remove(&window_id).is_some() {
+                    tracing::info!("removing mark from window: {window_id}");
+                }
+
+                for scratchpad in wm.scratchpads.values_mut() {
+                    if scratchpad.window_id == Some(window_id) {
+                        tracing::info!("releasing window from scratchpad: {window_id}");
+                        scratchpad.window_id = None;
+                        scratchpad.hidden_window = None;
+                    }
+                }
+
                 // If an invalid window was cleaned up, we update the workspace
                 if should_update {
                     // If an invalid window was cleaned up, we update the workspace
                     wm.update_focused_workspace(false, false)?;
                     border_manager::send_notification(None, Some(window_id), true);
+                    stackbar_manager::send_notification();
+                    transparency_manager::send_notification();
                 }
             }
             ReaperNotification::MouseUpKeyUp => {
@@ -0,0 +1,710 @@
+//! Recording of every event and command handled by the window manager, and deterministic replay
+//! of those recordings against stub windows
+//!
+//! A recording is a newline-delimited JSON log of [`RecordEntry`] values. A [`State`] checkpoint
+//! is written before the first entry and then after every [`CHECKPOINT_INTERVAL`] entries, so that
+//! the state reached by a replay can be compared against the state that was actually recorded.
+//!
+//! Events carry no more than a process id and a window id, so the main window of the application
+//! which sent an event is recorded alongside it, together with the ids of the application's other
+//! windows and of the managed windows it had closed. When a recording is replayed, those windows
+//! are opened and closed as stub windows on a [`FakeBackend`] before the event is processed. Events
+//! which were recorded without a main window can't be resolved to a window and are skipped, with
+//! the exception of destroy events; every checkpoint reports how many events were replayed, failed
+//! and skipped since the previous one.
+//!
+//! Commands which load configuration, layouts or saved state from disk are not replayed, because
+//! they would read the files of the machine doing the replay rather than the ones which were
+//! recorded.
+
+use crate::application::Application;
+use crate::core::SocketMessage;
+use crate::history::History;
+use crate::platform;
+use crate::platform::FakeBackend;
+use crate::platform::PlatformBackend;
+use crate::platform::StubWindow;
+use crate::state::State;
+use crate::window::Window;
+use crate::window_manager::WindowManager;
+use crate::window_manager_event::WindowManagerEvent;
+use color_eyre::eyre;
+use color_eyre::eyre::OptionExt;
+use objc2_core_foundation::CFRunLoop;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// The number of events and commands recorded between [`State`] checkpoints
+pub const CHECKPOINT_INTERVAL: usize = 25;
+
+/// Keys holding information which is read from the OS when a window is serialized, and which is
+/// not available for stub windows
+const OS_DERIVED_KEYS: [&str; 2] = ["rect", "details"];
+
+static RECORDER: OnceLock<Mutex<Recorder>> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "content")]
+pub enum RecordEntry {
+    Checkpoint {
+        elapsed_ms: u64,
+        state: Box<State>,
+    },
+    Event {
+        elapsed_ms: u64,
+        event: WindowManagerEvent,
+        /// The main window of the application which sent the event
+        #[serde(default)]
+        window: Option<StubWindow>,
+        /// Every window the application had open
+        #[serde(default)]
+        window_ids: Vec<u32>,
+        /// Managed windows of the application which were no longer open
+        #[serde(default)]
+        destroyed: Vec<u32>,
+        #[serde(default)]
+        cursor_pos: Option<(i32, i32)>,
+    },
+    Command {
+        elapsed_ms: u64,
+        message: SocketMessage,
+    },
+}
+
+struct Recorder {
+    file: File,
+    started: Instant,
+    since_checkpoint: usize,
+}
+
+impl Recorder {
+    fn create(path: &Path) -> eyre::Result<Self> {
+        Ok(Self {
+            file: OpenOptions::new().create(true).append(true).open(path)?,
+            started: Instant::now(),
+            since_checkpoint: CHECKPOINT_INTERVAL,
+        })
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    fn record(
+        &mut self,
+        wm: &WindowManager,
+        entry: impl FnOnce(u64) -> RecordEntry,
+    ) -> eyre::Result<()> {
+        if self.since_checkpoint >= CHECKPOINT_INTERVAL {
+            self.write(&RecordEntry::Checkpoint {
+                elapsed_ms: self.elapsed_ms(),
+                state: Box::new(State::from(wm)),
+            })?;
+
+            self.since_checkpoint = 0;
+        }
+
+        self.write(&entry(self.elapsed_ms()))?;
+        self.since_checkpoint += 1;
+
+        Ok(())
+    }
+
+    /// Every entry is written in a single call so that the log stays readable if komorebi crashes
+    fn write(&mut self, entry: &RecordEntry) -> eyre::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+
+        Ok(())
+    }
+}
+
+/// Starts appending every event and command handled by the window manager to `path`
+pub fn start(path: &Path) -> eyre::Result<()> {
+    if RECORDER.set(Mutex::new(Recorder::create(path)?)).is_err() {
+        eyre::bail!("a recording is already in progress");
+    }
+
+    tracing::info!("recording events and commands to {}", path.display());
+
+    Ok(())
+}
+
+fn record(wm: &WindowManager, entry: impl FnOnce(u64) -> RecordEntry) {
+    let Some(recorder) = RECORDER.get() else {
+        return;
+    };
+
+    if let Err(error) = recorder.lock().record(wm, entry) {
+        tracing::error!("failed to write to recording: {error}");
+    }
+}
+
+pub fn record_event(wm: &WindowManager, event: WindowManagerEvent) {
+    record(wm, |elapsed_ms| {
+        let backend = platform::backend();
+        let process_id = event.process_id();
+        let application = backend.application(process_id).ok();
+
+        let window_ids = application
+            .as_ref()
+            .map(|application| backend.window_ids(application))
+            .unwrap_or_default();
+
+        let window = application
+            .as_ref()
+            .and_then(|application| backend.main_window(application))
+            .map(|window| StubWindow::from(&window));
+
+        let destroyed = managed_windows(wm)
+            .filter(|window| {
+                window.application.process_id == process_id && !window_ids.contains(&window.id)
+            })
+            .map(|window| window.id)
+            .collect();
+
+        RecordEntry::Event {
+            elapsed_ms,
+            event,
+            window,
+            window_ids,
+            destroyed,
+            cursor_pos: Some(backend.cursor_pos()),
+        }
+    });
+}
+
+pub fn record_command(wm: &WindowManager, message: &SocketMessage) {
+    record(wm, |elapsed_ms| RecordEntry::Command {
+        elapsed_ms,
+        message: message.clone(),
+    });
+}
+
+pub fn read(path: &Path) -> eyre::Result<Vec<RecordEntry>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut entries = vec![];
+
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(error) => eyre::bail!("line {} of {} is invalid: {error}", idx + 1, path.display()),
+        }
+    }
+
+    Ok(entries)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Difference {
+    /// A JSON pointer to the value which differs
+    pub path: String,
+    pub recorded: Value,
+    pub replayed: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckpointReport {
+    /// The position of the checkpoint in the recording
+    pub entry: usize,
+    pub elapsed_ms: u64,
+    pub commands_replayed: usize,
+    pub commands_failed: usize,
+    pub events_replayed: usize,
+    pub events_failed: usize,
+    pub events_skipped: usize,
+    pub differences: Vec<Difference>,
+}
+
+/// Commands which would affect more than the window manager state if they were replayed, or
+/// which read files from the machine doing the replay and so wouldn't replay the same way twice
+fn is_replayable(message: &SocketMessage) -> bool {
+    !matches!(
+        message,
+        SocketMessage::Stop
+            | SocketMessage::StopIgnoreRestore
+            | SocketMessage::QuickSave
+            | SocketMessage::QuickLoad
+            | SocketMessage::Save(_)
+            | SocketMessage::Load(_)
+            | SocketMessage::ChangeLayoutCustom(_)
+            | SocketMessage::WorkspaceLayoutCustom(_, _, _)
+            | SocketMessage::NamedWorkspaceLayoutCustom(_, _)
+            | SocketMessage::WorkspaceLayoutCustomRule(_, _, _, _)
+            | SocketMessage::NamedWorkspaceLayoutCustomRule(_, _, _)
+            | SocketMessage::ReloadConfiguration
+            | SocketMessage::ReplaceConfiguration(_)
+            | SocketMessage::ReloadStaticConfiguration(_)
+            | SocketMessage::ActivateProfile(_)
+            | SocketMessage::WatchConfiguration(_)
+            | SocketMessage::AddSubscriberSocket(_)
+            | SocketMessage::AddSubscriberSocketWithOptions(_, _)
+            | SocketMessage::RemoveSubscriberSocket(_)
+            | SocketMessage::AddSubscriberPipe(_)
+            | SocketMessage::RemoveSubscriberPipe(_)
+    )
+}
+
+fn managed_windows(wm: &WindowManager) -> impl Iterator<Item = &Window> {
+    wm.monitors()
+        .iter()
+        .flat_map(|monitor| monitor.workspaces())
+        .flat_map(|workspace| {
+            workspace
+                .containers()
+                .iter()
+                .chain(workspace.monocle_container.iter())
+                .flat_map(|container| container.windows())
+                .chain(workspace.floating_windows())
+                .chain(workspace.maximized_window.iter())
+        })
+}
+
+/// Opens and closes stub windows so that the backend describes the application which sent an
+/// event as it was when the event was recorded
+///
+/// The main window is opened last so that it stays the main window of its application, and
+/// managed windows are handed the application's process id, which is not part of the recorded
+/// state
+fn stub_windows(
+    wm: &mut WindowManager,
+    backend: &FakeBackend,
+    process_id: i32,
+    window: Option<StubWindow>,
+    window_ids: &[u32],
+    destroyed: &[u32],
+) {
+    let exe = window
+        .as_ref()
+        .map(|window| window.exe.clone())
+        .or_else(|| backend.application_name(&Application::stub(process_id)))
+        .unwrap_or_default();
+
+    let mut adopted = window_ids
+        .iter()
+        .chain(destroyed)
+        .copied()
+        .collect::<Vec<_>>();
+
+    for window_id in window_ids {
+        if backend.window(*window_id).is_none() {
+            backend.add_window(StubWindow::new(*window_id, process_id, &exe, ""));
+        }
+    }
+
+    if let Some(window) = window {
+        adopted.push(window.id);
+        backend.add_window(window);
+    }
+
+    for window_id in destroyed {
+        backend.destroy_window(*window_id);
+    }
+
+    for workspace in wm
+        .monitors_mut()
+        .iter_mut()
+        .flat_map(|monitor| monitor.workspaces_mut().iter_mut())
+    {
+        let windows = workspace
+            .containers
+            .elements_mut()
+            .iter_mut()
+            .chain(workspace.monocle_container.iter_mut())
+            .flat_map(|container| container.windows.elements_mut().iter_mut())
+            .chain(workspace.floating_windows.elements_mut().iter_mut())
+            .chain(workspace.maximized_window.iter_mut());
+
+        for window in windows {
+            if adopted.contains(&window.id) {
+                window.application = Application::stub(process_id);
+            }
+        }
+    }
+}
+
+fn load_checkpoint(wm: &mut WindowManager, state: State) {
+    wm.monitors = state.monitors;
+    wm.monitor_usr_idx_map = state.monitor_usr_idx_map;
+    wm.is_paused = state.is_paused;
+    wm.resize_delta = state.resize_delta;
+    wm.window_management_behaviour.current_behaviour = state.new_window_behaviour;
+    wm.window_management_behaviour.float_override = state.float_override;
+    wm.cross_monitor_move_behaviour = state.cross_monitor_move_behaviour;
+    wm.monocle_focus_behaviour = state.monocle_focus_behaviour;
+    wm.unmanaged_window_operation_behaviour = state.unmanaged_window_operation_behaviour;
+    wm.work_area_offset = state.work_area_offset;
+    wm.focus_follows_mouse = state.focus_follows_mouse;
+    wm.mouse_follows_focus = state.mouse_follows_focus;
+    // snapshots from before the checkpoint don't describe the windows which were just loaded
+    wm.history = History::default();
+    wm.update_known_window_ids();
+}
+
+/// Replays a recording, comparing the state reached after the commands leading up to each
+/// checkpoint against the recorded state
+///
+/// The window manager is loaded from the recorded state at every checkpoint, so a difference is
+/// only ever reported against the checkpoint where it first appeared
+pub fn replay(path: &Path) -> eyre::Result<Vec<CheckpointReport>> {
+    let mut entries = read(path)?.into_iter().enumerate();
+
+    let Some((_, RecordEntry::Checkpoint { state, .. })) = entries.next() else {
+        eyre::bail!("{} does not start with a checkpoint", path.display());
+    };
+
+    // windows from the recording are only ever updated in memory
+    let backend = Arc::new(FakeBackend::default());
+    let _backend = platform::scoped_backend(backend.clone());
+
+    let socket = std::env::temp_dir().join(format!("komorebi-replay-{}.sock", std::process::id()));
+    let run_loop = CFRunLoop::main().ok_or_eyre("couldn't get CFRunLoop::main")?;
+    let (_sender, receiver) = crossbeam_channel::bounded(1);
+    let mut wm = WindowManager::new(&run_loop, receiver, Some(socket.clone()))?;
+    load_checkpoint(&mut wm, *state);
+
+    let reports = replay_entries(&mut wm, &backend, entries);
+
+    std::fs::remove_file(socket)?;
+
+    reports
+}
+
+fn replay_entries(
+    wm: &mut WindowManager,
+    backend: &FakeBackend,
+    entries: impl Iterator<Item = (usize, RecordEntry)>,
+) -> eyre::Result<Vec<CheckpointReport>> {
+    let mut reports = vec![];
+    let mut commands_replayed = 0;
+    let mut commands_failed = 0;
+    let mut events_replayed = 0;
+    let mut events_failed = 0;
+    let mut events_skipped = 0;
+
+    for (idx, entry) in entries {
+        match entry {
+            RecordEntry::Command { message, .. } if is_replayable(&message) => {
+                commands_replayed += 1;
+                if let Err(error) = wm.process_command(message, std::io::sink()) {
+                    tracing::warn!("replayed command failed: {error}");
+                    commands_failed += 1;
+                }
+            }
+            RecordEntry::Command { .. } => {}
+            RecordEntry::Event {
+                event,
+                window,
+                window_ids,
+                destroyed,
+                cursor_pos,
+                ..
+            } => {
+                // the window of an event is looked up through the main window of its application,
+                // but a destroyed window is looked up through the windows which are still open
+                if window.is_none() && !matches!(event, WindowManagerEvent::Destroy(_, _)) {
+                    events_skipped += 1;
+                    continue;
+                }
+
+                stub_windows(
+                    wm,
+                    backend,
+                    event.process_id(),
+                    window,
+                    &window_ids,
+                    &destroyed,
+                );
+
+                if let Some(cursor_pos) = cursor_pos {
+                    backend.set_cursor_pos(cursor_pos);
+                }
+
+                events_replayed += 1;
+                if let Err(error) = wm.process_event(event) {
+                    tracing::warn!("replayed event failed: {error}");
+                    events_failed += 1;
+                }
+            }
+            RecordEntry::Checkpoint { elapsed_ms, state } => {
+                reports.push(CheckpointReport {
+                    entry: idx,
+                    elapsed_ms,
+                    commands_replayed,
+                    commands_failed,
+                    events_replayed,
+                    events_failed,
+                    events_skipped,
+                    differences: differences(&state, &State::from(&*wm))?,
+                });
+
+                load_checkpoint(wm, *state);
+                commands_replayed = 0;
+                commands_failed = 0;
+                events_replayed = 0;
+                events_failed = 0;
+                events_skipped = 0;
+            }
+        }
+    }
+
+    Ok(reports)
+}
+
+pub fn differences(recorded: &State, replayed: &State) -> eyre::Result<Vec<Difference>> {
+    let mut differences = vec![];
+    diff_values(
+        String::new(),
+        &serde_json::to_value(recorded)?,
+        &serde_json::to_value(replayed)?,
+        &mut differences,
+    );
+
+    Ok(differences)
+}
+
+fn diff_values(
+    path: String,
+    recorded: &Value,
+    replayed: &Value,
+    differences: &mut Vec<Difference>,
+) {
+    match (recorded, replayed) {
+        (Value::Object(recorded), Value::Object(replayed)) => {
+            let keys = recorded
+                .keys()
+                .chain(replayed.keys().filter(|key| !recorded.contains_key(*key)));
+
+            for key in keys {
+                if OS_DERIVED_KEYS.contains(&key.as_str()) {
+                    continue;
+                }
+
+                diff_values(
+                    format!("{path}/{key}"),
+                    recorded.get(key).unwrap_or(&Value::Null),
+                    replayed.get(key).unwrap_or(&Value::Null),
+                    differences,
+                );
+            }
+        }
+        (Value::Array(recorded), Value::Array(replayed)) if recorded.len() == replayed.len() => {
+            for (idx, (recorded, replayed)) in recorded.iter().zip(replayed).enumerate() {
+                diff_values(format!("{path}/{idx}"), recorded, replayed, differences);
+            }
+        }
+        (recorded, replayed) if recorded != replayed => differences.push(Difference {
+            path,
+            recorded: recorded.clone(),
+            replayed: replayed.clone(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accessibility::notification_constants::AccessibilityNotification;
+    use crate::core::Rect;
+    use crate::monitor;
+    use crate::window_manager_event::SystemNotification;
+    use serde_json::json;
+
+    fn event(
+        event: WindowManagerEvent,
+        window: Option<StubWindow>,
+        destroyed: &[u32],
+    ) -> RecordEntry {
+        RecordEntry::Event {
+            elapsed_ms: 0,
+            event,
+            window_ids: window.iter().map(|window| window.id).collect(),
+            window,
+            destroyed: destroyed.to_vec(),
+            cursor_pos: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_values() {
+        let recorded = json!({
+            "monitors": {
+                "elements": [{ "id": 1, "rect": { "left": 0 }, "focused": 2 }],
+            },
+            "is_paused": false,
+        });
+
+        let replayed = json!({
+            "monitors": {
+                "elements": [{ "id": 1, "rect": { "left": 100 }, "focused": 3 }],
+            },
+            "is_paused": false,
+        });
+
+        let mut differences = vec![];
+        diff_values(String::new(), &recorded, &replayed, &mut differences);
+
+        assert_eq!(
+            differences,
+            vec![Difference {
+                path: String::from("/monitors/elements/0/focused"),
+                recorded: json!(2),
+                replayed: json!(3),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_is_replayable() {
+        assert!(is_replayable(&SocketMessage::FocusWorkspaceNumber(1)));
+        assert!(is_replayable(&SocketMessage::Retile));
+        assert!(!is_replayable(&SocketMessage::QuickLoad));
+        assert!(!is_replayable(&SocketMessage::ReloadConfiguration));
+        assert!(!is_replayable(&SocketMessage::WatchConfiguration(true)));
+        assert!(!is_replayable(&SocketMessage::ActivateProfile(None)));
+    }
+
+    #[test]
+    fn test_read_recording() {
+        let path =
+            std::env::temp_dir().join(format!("komorebi-test-{}.ndjson", std::process::id()));
+
+        let entries = [
+            RecordEntry::Command {
+                elapsed_ms: 10,
+                message: SocketMessage::FocusWorkspaceNumber(2),
+            },
+            RecordEntry::Command {
+                elapsed_ms: 20,
+                message: SocketMessage::Retile,
+            },
+        ];
+
+        let log = entries
+            .iter()
+            .map(|entry| serde_json::to_string(entry).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        std::fs::write(&path, log).unwrap();
+
+        let read = read(&path).unwrap();
+        assert!(matches!(
+            read.as_slice(),
+            [
+                RecordEntry::Command {
+                    elapsed_ms: 10,
+                    message: SocketMessage::FocusWorkspaceNumber(2)
+                },
+                RecordEntry::Command {
+                    elapsed_ms: 20,
+                    message: SocketMessage::Retile
+                }
+            ]
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_events_against_stub_windows() {
+        let backend = Arc::new(FakeBackend::default());
+        let _backend = platform::scoped_backend(backend.clone());
+
+        let socket =
+            std::env::temp_dir().join(format!("komorebi-test-replay-{}.sock", std::process::id()));
+        let (_sender, receiver) = crossbeam_channel::bounded(1);
+        let mut wm =
+            WindowManager::new(&CFRunLoop::main().unwrap(), receiver, Some(socket.clone()))
+                .unwrap();
+
+        let size = Rect {
+            left: 0,
+            top: 0,
+            right: 1920,
+            bottom: 1080,
+        };
+
+        wm.monitors_mut().push_back(monitor::new(
+            0,
+            size,
+            size,
+            "TestMonitor".to_string(),
+            "TestDeviceID".to_string(),
+        ));
+
+        let created = SystemNotification::Accessibility(AccessibilityNotification::AXWindowCreated);
+        let destroyed =
+            SystemNotification::Accessibility(AccessibilityNotification::AXUIElementDestroyed);
+        let activated =
+            SystemNotification::Accessibility(AccessibilityNotification::AXApplicationActivated);
+
+        let safari = StubWindow::new(9501, 100, "Safari", "Start Page");
+        let terminal = StubWindow::new(9502, 200, "Terminal", "zsh");
+
+        let entries = vec![
+            event(
+                WindowManagerEvent::Show(created, 100),
+                Some(safari.clone()),
+                &[],
+            ),
+            event(WindowManagerEvent::Show(created, 200), Some(terminal), &[]),
+            event(WindowManagerEvent::Destroy(destroyed, 200), None, &[9502]),
+        ];
+
+        // there is no checkpoint to report against, but the windows are managed and reaped
+        let reports = replay_entries(&mut wm, &backend, entries.into_iter().enumerate()).unwrap();
+        assert!(reports.is_empty());
+
+        let workspace = wm.focused_workspace().unwrap();
+        assert!(workspace.contains_window(9501));
+        assert!(!workspace.contains_window(9502));
+        assert_eq!(
+            workspace.containers()[0].windows()[0].exe(),
+            Some(String::from("Safari"))
+        );
+
+        // a repeated show event doesn't change anything, and an event recorded without a main
+        // window is skipped
+        let entries = vec![
+            event(WindowManagerEvent::Show(created, 100), Some(safari), &[]),
+            event(
+                WindowManagerEvent::FocusChange(activated, 300, None),
+                None,
+                &[],
+            ),
+            RecordEntry::Checkpoint {
+                elapsed_ms: 0,
+                state: Box::new(State::from(&wm)),
+            },
+        ];
+
+        let reports = replay_entries(&mut wm, &backend, entries.into_iter().enumerate()).unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].events_replayed, 1);
+        assert_eq!(reports[0].events_failed, 0);
+        assert_eq!(reports[0].events_skipped, 1);
+        assert!(reports[0].differences.is_empty());
+
+        std::fs::remove_file(socket).unwrap();
+    }
+}
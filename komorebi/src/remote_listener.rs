@@ -0,0 +1,313 @@
+//! Opt-in command listeners for clients which cannot open `komorebi.sock`
+//!
+//! Both listeners only bind to localhost, and every connection has to present the token stored in
+//! [`TOKEN_FILE`] in the data directory before any command is processed:
+//!
+//! - TCP connections send the token on the first line, followed by commands using the same framed
+//!   or newline-delimited JSON protocol spoken on `komorebi.sock`
+//! - WebSocket connections send the token in a `token` query parameter or as an
+//!   `Authorization: Bearer` header during the handshake, then send one JSON `SocketMessage` per
+//!   message; each one is answered with a JSON [`Response`], and every [`Notification`] sent to
+//!   subscribers is pushed to the connection as well
+//!
+//! [`Notification`]: crate::Notification
+
+use crate::DATA_DIR;
+use crate::core::ipc;
+use crate::core::ipc::Response;
+use crate::core::ipc::ResponseError;
+use crate::core::ipc::ResponseErrorKind;
+use crate::process_command::process_request;
+use crate::process_command::read_commands;
+use crate::window_manager::WindowManager;
+use color_eyre::eyre;
+use crossbeam_channel::Receiver;
+use crossbeam_channel::Sender;
+use crossbeam_channel::TrySendError;
+use lazy_static::lazy_static;
+use nanoid::nanoid;
+use parking_lot::Mutex;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::ErrorKind;
+use std::io::Write;
+use std::net::Ipv4Addr;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::os::unix::fs::OpenOptionsExt;
+use std::sync::Arc;
+use std::time::Duration;
+use tungstenite::Message;
+use tungstenite::handshake::server::ErrorResponse;
+use tungstenite::handshake::server::Request;
+use tungstenite::http::StatusCode;
+
+/// The file in the data directory holding the token remote clients authenticate with
+pub const TOKEN_FILE: &str = "komorebi.token";
+
+/// How long a new connection has to authenticate before it is dropped
+const AUTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a WebSocket connection checks for notifications while waiting for messages
+const WEBSOCKET_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+lazy_static! {
+    static ref WEBSOCKET_CLIENTS: Mutex<Vec<Sender<String>>> = Mutex::new(vec![]);
+}
+
+#[derive(Clone)]
+pub struct RemoteToken(Arc<str>);
+
+impl RemoteToken {
+    /// Reads the token from [`TOKEN_FILE`], generating a new one the first time a remote listener
+    /// is enabled
+    pub fn load_or_create() -> eyre::Result<Self> {
+        let path = DATA_DIR.join(TOKEN_FILE);
+
+        match std::fs::read_to_string(&path) {
+            Ok(token) if !token.trim().is_empty() => return Ok(Self(token.trim().into())),
+            Ok(_) => {}
+            Err(error) if error.kind() == ErrorKind::NotFound => {}
+            Err(error) => return Err(error.into()),
+        }
+
+        let token = nanoid!(32);
+
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&path)?
+            .write_all(token.as_bytes())?;
+
+        tracing::info!("generated a new remote access token at {}", path.display());
+
+        Ok(Self(token.into()))
+    }
+
+    /// Compares in constant time so that the token can't be guessed one character at a time
+    pub fn verify(&self, candidate: &str) -> bool {
+        let expected = self.0.as_bytes();
+        let candidate = candidate.as_bytes();
+
+        expected.len() == candidate.len()
+            && expected
+                .iter()
+                .zip(candidate)
+                .fold(0, |acc, (a, b)| acc | (a ^ b))
+                == 0
+    }
+}
+
+impl From<&str> for RemoteToken {
+    fn from(value: &str) -> Self {
+        Self(value.into())
+    }
+}
+
+fn unauthorized() -> Response {
+    Response::Err(ResponseError::new(
+        ResponseErrorKind::Unauthorized,
+        "a valid token is required",
+    ))
+}
+
+fn bind(port: u16) -> eyre::Result<TcpListener> {
+    Ok(TcpListener::bind((Ipv4Addr::LOCALHOST, port))?)
+}
+
+pub fn listen_for_tcp_commands(
+    wm: Arc<Mutex<WindowManager>>,
+    port: u16,
+    token: RemoteToken,
+) -> eyre::Result<()> {
+    let listener = bind(port)?;
+    tracing::info!("listening for tcp commands on localhost:{port}");
+
+    std::thread::spawn(move || {
+        for client in listener.incoming() {
+            match client {
+                Ok(stream) => {
+                    let wm = wm.clone();
+                    let token = token.clone();
+                    std::thread::spawn(move || {
+                        if let Err(error) = read_commands_tcp(&wm, &token, stream) {
+                            tracing::error!("{error}");
+                        }
+                    });
+                }
+                Err(error) => {
+                    tracing::error!("failed to get tcp stream {}", error);
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn read_commands_tcp(
+    wm: &Arc<Mutex<WindowManager>>,
+    token: &RemoteToken,
+    mut stream: TcpStream,
+) -> eyre::Result<()> {
+    stream.set_read_timeout(Some(AUTH_TIMEOUT))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut candidate = String::new();
+    reader.read_line(&mut candidate)?;
+
+    if !token.verify(candidate.trim_end()) {
+        writeln!(stream, "{}", serde_json::to_string(&unauthorized())?)?;
+        eyre::bail!(
+            "rejected tcp connection from {} with an invalid token",
+            stream.peer_addr()?
+        );
+    }
+
+    stream.set_read_timeout(None)?;
+    read_commands(wm, reader, stream)
+}
+
+/// Pushes a serialized notification to every connected WebSocket client
+pub fn push_notification(notification: &str) {
+    WEBSOCKET_CLIENTS
+        .lock()
+        .retain(|client| match client.try_send(notification.to_string()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                tracing::warn!("websocket client is not keeping up; dropping notification");
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+}
+
+pub fn listen_for_websocket_commands(
+    wm: Arc<Mutex<WindowManager>>,
+    port: u16,
+    token: RemoteToken,
+) -> eyre::Result<()> {
+    let listener = bind(port)?;
+    tracing::info!("listening for websocket commands on localhost:{port}");
+
+    std::thread::spawn(move || {
+        for client in listener.incoming() {
+            match client {
+                Ok(stream) => {
+                    let wm = wm.clone();
+                    let token = token.clone();
+                    std::thread::spawn(move || {
+                        if let Err(error) = read_commands_websocket(&wm, &token, stream) {
+                            tracing::error!("{error}");
+                        }
+                    });
+                }
+                Err(error) => {
+                    tracing::error!("failed to get websocket stream {}", error);
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Finds the token in a `token` query parameter or an `Authorization: Bearer` header
+fn request_token<'a>(query: Option<&'a str>, authorization: Option<&'a str>) -> Option<&'a str> {
+    query
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .find_map(|pair| pair.strip_prefix("token="))
+        .or_else(|| authorization?.strip_prefix("Bearer "))
+}
+
+fn read_commands_websocket(
+    wm: &Arc<Mutex<WindowManager>>,
+    token: &RemoteToken,
+    stream: TcpStream,
+) -> eyre::Result<()> {
+    stream.set_read_timeout(Some(AUTH_TIMEOUT))?;
+
+    let authenticate = |request: &Request, response: tungstenite::handshake::server::Response| {
+        let authorization = request
+            .headers()
+            .get("Authorization")
+            .and_then(|value| value.to_str().ok());
+
+        match request_token(request.uri().query(), authorization) {
+            Some(candidate) if token.verify(candidate) => Ok(response),
+            _ => {
+                let mut error = ErrorResponse::new(serde_json::to_string(&unauthorized()).ok());
+                *error.status_mut() = StatusCode::UNAUTHORIZED;
+                Err(error)
+            }
+        }
+    };
+
+    let mut websocket = match tungstenite::accept_hdr(stream, authenticate) {
+        Ok(websocket) => websocket,
+        Err(error) => eyre::bail!("rejected websocket connection: {error}"),
+    };
+
+    websocket
+        .get_ref()
+        .set_read_timeout(Some(WEBSOCKET_POLL_INTERVAL))?;
+
+    let (sender, receiver): (Sender<String>, Receiver<String>) = crossbeam_channel::bounded(50);
+    WEBSOCKET_CLIENTS.lock().push(sender);
+
+    loop {
+        for notification in receiver.try_iter() {
+            websocket.send(Message::text(notification))?;
+        }
+
+        let payload = match websocket.read() {
+            Ok(Message::Text(text)) => text.as_bytes().to_vec(),
+            Ok(Message::Binary(bytes)) => bytes.to_vec(),
+            Ok(Message::Close(_))
+            | Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                return Ok(());
+            }
+            // pings are answered by tungstenite
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(error))
+                if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+            {
+                continue;
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        let response = Response::from(process_request(wm, ipc::PROTOCOL_VERSION, &payload));
+        websocket.send(Message::text(serde_json::to_string(&response)?))?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_token() {
+        let token = RemoteToken::from("V1StGXR8_Z5jdHi6B-myT");
+
+        assert!(token.verify("V1StGXR8_Z5jdHi6B-myT"));
+        assert!(!token.verify("V1StGXR8_Z5jdHi6B-myt"));
+        assert!(!token.verify("V1StGXR8_Z5jdHi6B-my"));
+        assert!(!token.verify(""));
+    }
+
+    #[test]
+    fn test_request_token() {
+        assert_eq!(request_token(Some("token=abc"), None), Some("abc"));
+        assert_eq!(
+            request_token(Some("client=deck&token=abc"), Some("Bearer xyz")),
+            Some("abc")
+        );
+        assert_eq!(request_token(None, Some("Bearer xyz")), Some("xyz"));
+        assert_eq!(request_token(Some("client=deck"), Some("Basic xyz")), None);
+        assert_eq!(request_token(None, None), None);
+    }
+}
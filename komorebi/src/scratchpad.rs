@@ -0,0 +1,202 @@
+//! Named floating windows which are toggled over the focused workspace
+//!
+//! The first managed window matching the rules of a [`ScratchpadConfig`] is captured by that
+//! scratchpad and shown as a floating window on the focused workspace. Toggling the scratchpad
+//! hides the window again, and toggling it once more brings it back over whichever workspace is
+//! focused at the time, placed according to the scratchpad's [`Placement`] and [`AspectRatio`].
+
+use crate::FLOATING_WINDOW_TOGGLE_ASPECT_RATIO;
+use crate::REGEX_IDENTIFIERS;
+use crate::accessibility::error::AccessibilityError;
+use crate::core::Placement;
+use crate::core::Rect;
+use crate::core::config_generation::MatchingRule;
+use crate::window::AspectRatio;
+use crate::window::Window;
+use crate::window::should_act;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// A named floating window which can be toggled over the focused workspace
+pub struct ScratchpadConfig {
+    /// Name used to toggle the scratchpad
+    pub name: String,
+    /// Rules identifying the window which should be captured by the scratchpad
+    pub match_rules: Vec<MatchingRule>,
+    /// Placement of the window whenever it is shown (default: CenterAndResize)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placement: Option<Placement>,
+    /// Aspect ratio to resize the window to when it is shown (default: floating_window_aspect_ratio)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aspect_ratio: Option<AspectRatio>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Scratchpad {
+    pub config: ScratchpadConfig,
+    /// The id of the captured window, whether it is shown or hidden
+    pub window_id: Option<u32>,
+    /// The captured window while it is hidden; a shown window is one of the floating windows of
+    /// the workspace it is shown on
+    pub hidden_window: Option<Window>,
+}
+
+impl From<ScratchpadConfig> for Scratchpad {
+    fn from(config: ScratchpadConfig) -> Self {
+        Self {
+            config,
+            window_id: None,
+            hidden_window: None,
+        }
+    }
+}
+
+impl Scratchpad {
+    pub fn is_open(&self) -> bool {
+        self.window_id.is_some() && self.hidden_window.is_none()
+    }
+
+    /// Whether a newly managed window can be captured, which is only the case if no window has
+    /// been captured yet or if the captured window is no longer managed
+    pub fn can_capture(&self, known_window_ids: &HashMap<u32, (usize, usize)>) -> bool {
+        match self.window_id {
+            None => true,
+            Some(window_id) => {
+                self.hidden_window.is_none() && !known_window_ids.contains_key(&window_id)
+            }
+        }
+    }
+
+    pub fn matches(&self, window: &Window) -> bool {
+        let (Some(title), Some(exe_name), Some(role), Some(subrole), Some(path)) = (
+            window.title(),
+            window.exe(),
+            window.role(),
+            window.subrole(),
+            window.path(),
+        ) else {
+            return false;
+        };
+
+        should_act(
+            &title,
+            &exe_name,
+            &[&role, &subrole],
+            &path.to_string_lossy(),
+            &self.config.match_rules,
+            &REGEX_IDENTIFIERS.lock(),
+        )
+        .is_some()
+    }
+
+    /// Moves the captured window into position as it is being shown over `work_area`
+    pub fn place(&self, window: &mut Window, work_area: &Rect) -> Result<(), AccessibilityError> {
+        let placement = self.config.placement.unwrap_or(Placement::CenterAndResize);
+        if !placement.should_center() {
+            return Ok(());
+        }
+
+        let aspect_ratio = placement.should_resize().then(|| {
+            self.config
+                .aspect_ratio
+                .unwrap_or_else(|| *FLOATING_WINDOW_TOGGLE_ASPECT_RATIO.lock())
+        });
+
+        window.center_with_aspect_ratio(work_area, aspect_ratio)
+    }
+}
+
+/// The state of a scratchpad as reported to subscribers and the bar
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ScratchpadState {
+    pub name: String,
+    /// The id of the captured window, if a window has been captured
+    pub window_id: Option<u32>,
+    /// Whether the captured window is shown on a workspace rather than hidden
+    pub open: bool,
+}
+
+impl From<&Scratchpad> for ScratchpadState {
+    fn from(value: &Scratchpad) -> Self {
+        Self {
+            name: value.config.name.clone(),
+            window_id: value.window_id,
+            open: value.is_open(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratchpad() -> Scratchpad {
+        Scratchpad::from(ScratchpadConfig {
+            name: String::from("terminal"),
+            match_rules: vec![],
+            placement: None,
+            aspect_ratio: None,
+        })
+    }
+
+    #[test]
+    fn test_scratchpad_config_deserialization() {
+        let config: ScratchpadConfig = serde_json::from_str(
+            r#"{
+                "name": "notes",
+                "match_rules": [{ "kind": "Exe", "id": "Notes" }],
+                "placement": "Center",
+                "aspect_ratio": [3, 2]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.name, "notes");
+        assert_eq!(config.match_rules.len(), 1);
+        assert_eq!(config.placement, Some(Placement::Center));
+        assert_eq!(config.aspect_ratio, Some(AspectRatio::Custom(3, 2)));
+    }
+
+    #[test]
+    fn test_can_capture() {
+        let mut scratchpad = scratchpad();
+        let mut known_window_ids = HashMap::new();
+        assert!(scratchpad.can_capture(&known_window_ids));
+
+        // a captured window which is shown on a workspace
+        scratchpad.window_id = Some(1);
+        known_window_ids.insert(1, (0, 0));
+        assert!(!scratchpad.can_capture(&known_window_ids));
+
+        // a captured window which was closed while it was shown
+        known_window_ids.clear();
+        assert!(scratchpad.can_capture(&known_window_ids));
+
+        // a captured window which is hidden isn't known to any workspace
+        scratchpad.hidden_window = Some(Window::from(1));
+        assert!(!scratchpad.can_capture(&known_window_ids));
+    }
+
+    #[test]
+    fn test_scratchpad_state() {
+        let mut scratchpad = scratchpad();
+        assert_eq!(
+            ScratchpadState::from(&scratchpad),
+            ScratchpadState {
+                name: String::from("terminal"),
+                window_id: None,
+                open: false,
+            }
+        );
+
+        scratchpad.window_id = Some(1);
+        assert!(ScratchpadState::from(&scratchpad).open);
+
+        scratchpad.hidden_window = Some(Window::from(1));
+        assert!(!ScratchpadState::from(&scratchpad).open);
+    }
+}
@@ -0,0 +1,421 @@
+use crate::container::Container;
+use crate::core::Axis;
+use crate::core::Layout;
+use crate::core::Rect;
+use crate::core::custom_layout::CustomLayout;
+use crate::monitor::Monitor;
+use crate::window::Window;
+use crate::window_manager::WindowManager;
+use crate::workspace::Workspace;
+use crate::workspace::WorkspaceLayer;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// A snapshot of the arrangement of every monitor and workspace, which can be used to put live
+/// windows back into the same positions later on
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Session {
+    pub monitors: Vec<MonitorSession>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MonitorSession {
+    /// Used to find the same physical monitor again, falling back to the monitor index
+    pub serial_number_id: String,
+    pub focused_workspace_idx: usize,
+    pub workspaces: Vec<WorkspaceSession>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceSession {
+    pub name: Option<String>,
+    pub layout: Layout,
+    pub custom_layout: Option<CustomLayout>,
+    pub layout_flip: Option<Axis>,
+    pub tile: bool,
+    pub layer: WorkspaceLayer,
+    pub focused_container_idx: usize,
+    pub containers: Vec<ContainerSession>,
+    pub floating_windows: Vec<WindowSession>,
+    pub monocle_container: Option<ContainerSession>,
+    pub monocle_container_restore_idx: Option<usize>,
+    pub resize_dimensions: Vec<Option<Rect>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ContainerSession {
+    pub focused_window_idx: usize,
+    pub windows: Vec<WindowSession>,
+}
+
+/// The application identifiers used to find a live window matching a saved one
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WindowSession {
+    pub exe: Option<String>,
+    pub title: Option<String>,
+    pub bundle_identifier: Option<String>,
+    /// Only used by floating windows, which are put back as sticky windows
+    #[serde(default)]
+    pub sticky: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchQuality {
+    /// Same application, different title
+    Application,
+    /// Same application and title
+    Exact,
+}
+
+impl WindowSession {
+    /// How well a live window matches this saved window, if it belongs to the same application
+    pub fn match_quality(&self, live: &WindowSession) -> Option<MatchQuality> {
+        let same_application = match (&self.bundle_identifier, &live.bundle_identifier) {
+            (Some(saved), Some(live)) => saved == live,
+            _ => matches!((&self.exe, &live.exe), (Some(saved), Some(live)) if saved == live),
+        };
+
+        if !same_application {
+            return None;
+        }
+
+        if self.title.is_some() && self.title == live.title {
+            Some(MatchQuality::Exact)
+        } else {
+            Some(MatchQuality::Application)
+        }
+    }
+}
+
+/// Assigns each saved window to at most one live window, returning the index of the live window
+/// for every saved window
+///
+/// Exact matches are assigned first so that, for example, two terminal windows with different
+/// titles end up back in their own positions, and only then are the remaining live windows handed
+/// out to saved windows of the same application in the order they were saved
+pub fn assign_windows(saved: &[&WindowSession], live: &[WindowSession]) -> Vec<Option<usize>> {
+    let mut assignments = vec![None; saved.len()];
+    let mut taken = vec![false; live.len()];
+
+    for quality in [MatchQuality::Exact, MatchQuality::Application] {
+        for (saved_idx, window) in saved.iter().enumerate() {
+            if assignments[saved_idx].is_some() {
+                continue;
+            }
+
+            let candidate = live.iter().enumerate().position(|(live_idx, live)| {
+                !taken[live_idx] && window.match_quality(live) >= Some(quality)
+            });
+
+            if let Some(live_idx) = candidate {
+                taken[live_idx] = true;
+                assignments[saved_idx] = Some(live_idx);
+            }
+        }
+    }
+
+    assignments
+}
+
+/// Drops the assignments of live windows which belong to a container that can't be moved as a
+/// whole, so that containers which only partially match the snapshot stay together where they are
+///
+/// `containers` holds a key identifying the live container of each live window, or `None` for
+/// windows which don't belong to a container, such as floating windows
+pub fn release_partial_containers(assignments: &mut [Option<usize>], containers: &[Option<usize>]) {
+    let mut taken = vec![false; containers.len()];
+    for live_idx in assignments.iter().flatten() {
+        taken[*live_idx] = true;
+    }
+
+    let partial = containers
+        .iter()
+        .zip(&taken)
+        .filter_map(|(container, taken)| if *taken { None } else { *container })
+        .collect::<Vec<_>>();
+
+    for assignment in assignments.iter_mut() {
+        if assignment.is_some_and(|live_idx| {
+            containers[live_idx].is_some_and(|container| partial.contains(&container))
+        }) {
+            *assignment = None;
+        }
+    }
+}
+
+/// The contents of a file written by `save` or `quick-save`
+#[derive(Debug, Clone, PartialEq)]
+pub enum SaveFile {
+    Session(Session),
+    /// Files written before full sessions were saved only hold the resize dimensions of the
+    /// workspace which was focused at the time
+    ResizeDimensions(Vec<Option<Rect>>),
+}
+
+impl SaveFile {
+    /// Parses a session, falling back to the older resize dimensions format
+    ///
+    /// The error from parsing a session is returned if neither format matches
+    pub fn parse(contents: &str) -> serde_json::Result<Self> {
+        match serde_json::from_str(contents) {
+            Ok(session) => Ok(Self::Session(session)),
+            Err(error) => serde_json::from_str(contents)
+                .map(Self::ResizeDimensions)
+                .map_err(|_| error),
+        }
+    }
+}
+
+impl Session {
+    /// All saved windows in a stable order, matching the order in which they are placed on load
+    pub fn windows(&self) -> Vec<&WindowSession> {
+        self.monitors
+            .iter()
+            .flat_map(MonitorSession::windows)
+            .collect()
+    }
+}
+
+impl MonitorSession {
+    pub fn windows(&self) -> Vec<&WindowSession> {
+        self.workspaces
+            .iter()
+            .flat_map(WorkspaceSession::windows)
+            .collect()
+    }
+}
+
+impl WorkspaceSession {
+    /// Saved windows of tiled containers, the monocle container and then floating windows
+    pub fn windows(&self) -> Vec<&WindowSession> {
+        let mut windows = vec![];
+
+        for container in &self.containers {
+            windows.extend(&container.windows);
+        }
+
+        if let Some(container) = &self.monocle_container {
+            windows.extend(&container.windows);
+        }
+
+        windows.extend(&self.floating_windows);
+
+        windows
+    }
+}
+
+impl From<&Window> for WindowSession {
+    fn from(value: &Window) -> Self {
+        Self {
+            exe: value.exe(),
+            title: value.title(),
+            bundle_identifier: value.bundle_identifier(),
+            sticky: value.sticky,
+        }
+    }
+}
+
+impl From<&Container> for ContainerSession {
+    fn from(value: &Container) -> Self {
+        Self {
+            focused_window_idx: value.focused_window_idx(),
+            windows: value.windows().iter().map(WindowSession::from).collect(),
+        }
+    }
+}
+
+impl From<&Workspace> for WorkspaceSession {
+    fn from(value: &Workspace) -> Self {
+        Self {
+            name: value.name.clone(),
+            layout: value.layout.clone(),
+            custom_layout: value.custom_layout.clone(),
+            layout_flip: value.layout_flip,
+            tile: value.tile,
+            layer: value.layer,
+            focused_container_idx: value.focused_container_idx(),
+            containers: value
+                .containers()
+                .iter()
+                .map(ContainerSession::from)
+                .collect(),
+            floating_windows: value
+                .floating_windows()
+                .iter()
+                .map(WindowSession::from)
+                .collect(),
+            monocle_container: value.monocle_container.as_ref().map(ContainerSession::from),
+            monocle_container_restore_idx: value.monocle_container_restore_idx,
+            resize_dimensions: value.resize_dimensions.clone(),
+        }
+    }
+}
+
+impl From<&Monitor> for MonitorSession {
+    fn from(value: &Monitor) -> Self {
+        Self {
+            serial_number_id: value.serial_number_id.clone(),
+            focused_workspace_idx: value.focused_workspace_idx(),
+            workspaces: value
+                .workspaces()
+                .iter()
+                .map(WorkspaceSession::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<&WindowManager> for Session {
+    fn from(value: &WindowManager) -> Self {
+        Self {
+            monitors: value.monitors().iter().map(MonitorSession::from).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(bundle_identifier: &str, title: &str) -> WindowSession {
+        WindowSession {
+            exe: None,
+            title: Some(title.to_string()),
+            bundle_identifier: Some(bundle_identifier.to_string()),
+            sticky: false,
+        }
+    }
+
+    #[test]
+    fn test_match_quality() {
+        let saved = window("com.apple.Terminal", "~");
+
+        assert_eq!(
+            saved.match_quality(&window("com.apple.Terminal", "~")),
+            Some(MatchQuality::Exact)
+        );
+        assert_eq!(
+            saved.match_quality(&window("com.apple.Terminal", "~/src")),
+            Some(MatchQuality::Application)
+        );
+        assert_eq!(saved.match_quality(&window("com.apple.Safari", "~")), None);
+    }
+
+    #[test]
+    fn test_match_quality_falls_back_to_exe() {
+        let saved = WindowSession {
+            exe: Some("Zed".to_string()),
+            title: None,
+            bundle_identifier: None,
+            sticky: false,
+        };
+
+        let live = WindowSession {
+            exe: Some("Zed".to_string()),
+            title: Some("README.md".to_string()),
+            bundle_identifier: Some("dev.zed.Zed".to_string()),
+            sticky: false,
+        };
+
+        assert_eq!(saved.match_quality(&live), Some(MatchQuality::Application));
+        assert_eq!(saved.match_quality(&WindowSession::default()), None);
+    }
+
+    #[test]
+    fn test_assign_windows_prefers_exact_matches() {
+        let first = window("com.apple.Terminal", "~/src");
+        let second = window("com.apple.Terminal", "~");
+        let saved = vec![&first, &second];
+
+        let live = vec![
+            window("com.apple.Terminal", "~"),
+            window("com.apple.Terminal", "~/src"),
+        ];
+
+        assert_eq!(assign_windows(&saved, &live), vec![Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn test_assign_windows_assigns_each_live_window_once() {
+        let first = window("com.apple.Safari", "Start Page");
+        let second = window("com.apple.Safari", "Start Page");
+        let third = window("com.apple.Mail", "Inbox");
+        let saved = vec![&first, &second, &third];
+
+        let live = vec![
+            window("com.apple.Safari", "GitHub"),
+            window("com.apple.Finder", "Downloads"),
+        ];
+
+        assert_eq!(assign_windows(&saved, &live), vec![Some(0), None, None]);
+    }
+
+    #[test]
+    fn test_release_partial_containers() {
+        // live windows 0 and 1 are stacked, 2 is stacked alone and 3 is floating
+        let containers = vec![Some(0), Some(0), Some(1), None];
+
+        let mut assignments = vec![Some(0), Some(2), Some(3)];
+        release_partial_containers(&mut assignments, &containers);
+        assert_eq!(assignments, vec![None, Some(2), Some(3)]);
+
+        let mut assignments = vec![Some(1), Some(0)];
+        release_partial_containers(&mut assignments, &containers);
+        assert_eq!(assignments, vec![Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn test_save_file_parses_both_formats() {
+        let session = Session {
+            monitors: vec![MonitorSession::default()],
+        };
+
+        assert_eq!(
+            SaveFile::parse(&serde_json::to_string(&session).unwrap()).unwrap(),
+            SaveFile::Session(session)
+        );
+
+        let resize = vec![
+            None,
+            Some(Rect {
+                left: 0,
+                top: 0,
+                right: 100,
+                bottom: 0,
+            }),
+        ];
+
+        assert_eq!(
+            SaveFile::parse(&serde_json::to_string(&resize).unwrap()).unwrap(),
+            SaveFile::ResizeDimensions(resize)
+        );
+
+        let error = SaveFile::parse(r#"{"monitors": 1}"#).unwrap_err();
+        assert!(error.to_string().contains("invalid type"));
+    }
+
+    #[test]
+    fn test_session_windows_order() {
+        let session = Session {
+            monitors: vec![MonitorSession {
+                workspaces: vec![WorkspaceSession {
+                    containers: vec![ContainerSession {
+                        focused_window_idx: 0,
+                        windows: vec![window("a", "a"), window("b", "b")],
+                    }],
+                    floating_windows: vec![window("c", "c")],
+                    ..WorkspaceSession::from(&Workspace::default())
+                }],
+                ..Default::default()
+            }],
+        };
+
+        let titles = session
+            .windows()
+            .iter()
+            .filter_map(|w| w.title.clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(titles, vec!["a", "b", "c"]);
+    }
+}
@@ -1,7 +1,7 @@
 // Private SkyLight/CoreGraphics APIs
 //
 // Read operations (CGS*) are used for querying window server state.
-// Write operations (SLS*) are used for animation screen update batching.
+// Write operations (SLS*) are used for animation screen update batching and window alpha.
 unsafe extern "C" {
     // Read operations - these APIs are undocumented but stable - their usage should not disrupt the window manager.
     pub fn CGSMainConnectionID() -> i32;
@@ -12,4 +12,8 @@ unsafe extern "C" {
     pub fn SLSDisableUpdate(cid: i32) -> i32;
     // SLSReenableUpdate resumes compositing - all batched changes appear at once
     pub fn SLSReenableUpdate(cid: i32) -> i32;
+
+    // Write operations for transparency - the window server may refuse these for windows owned by
+    // other processes, so every call has to be treated as fallible
+    pub fn SLSSetWindowAlpha(cid: i32, wid: u32, alpha: f32) -> i32;
 }
@@ -0,0 +1,261 @@
+use crate::core::Rect;
+use crate::core::StackbarLabel;
+use crate::core::StackbarMode;
+
+/// Font size used when no explicit stackbar font size has been configured
+pub const DEFAULT_FONT_SIZE: i32 = 12;
+
+/// Horizontal space reserved on each side of a tab label
+pub const TAB_LABEL_PADDING: i32 = 8;
+
+// A rough average glyph width relative to the font size; this doesn't need to be exact, it just
+// needs to stop labels from spilling over into neighbouring tabs
+const GLYPH_WIDTH_RATIO: f32 = 0.6;
+
+const ELLIPSIS: char = '…';
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tab {
+    /// Position of the tab in screen coordinates
+    pub rect: Rect,
+    /// Label to render on the tab, already truncated to fit
+    pub label: String,
+    /// Whether this tab represents the focused window of the container
+    pub focused: bool,
+}
+
+/// Whether a container with `window_count` windows should have a stackbar drawn above it
+pub fn is_visible(mode: StackbarMode, window_count: usize) -> bool {
+    match mode {
+        StackbarMode::Always => window_count > 0,
+        StackbarMode::Never => false,
+        StackbarMode::OnStack => window_count > 1,
+    }
+}
+
+/// Splits a container rect into the stackbar rect along its top edge and the remaining rect
+/// which the focused window of the container should be positioned in
+pub fn split_container(container: &Rect, height: i32) -> (Rect, Rect) {
+    let height = height.clamp(0, container.bottom.max(0));
+
+    let stackbar = Rect {
+        left: container.left,
+        top: container.top,
+        right: container.right,
+        bottom: height,
+    };
+
+    let window = Rect {
+        left: container.left,
+        top: container.top + height,
+        right: container.right,
+        bottom: container.bottom - height,
+    };
+
+    (stackbar, window)
+}
+
+/// Lays out `count` tabs from left to right within the stackbar, shrinking them evenly if
+/// `tab_width` would make them overflow
+pub fn tab_rects(stackbar: &Rect, count: usize, tab_width: i32) -> Vec<Rect> {
+    if count == 0 {
+        return vec![];
+    }
+
+    let available = stackbar.right / i32::try_from(count).unwrap_or(i32::MAX);
+    let width = tab_width.min(available).max(1);
+
+    (0..count)
+        .map(|i| Rect {
+            left: stackbar.left + width * i as i32,
+            top: stackbar.top,
+            right: width,
+            bottom: stackbar.bottom,
+        })
+        .collect()
+}
+
+/// Returns the index of the tab containing `point`, if any
+pub fn tab_at_point(tabs: &[Rect], point: (i32, i32)) -> Option<usize> {
+    tabs.iter().position(|rect| rect.contains_point(point))
+}
+
+/// Picks the text for a tab, falling back to whichever of the title or process name is
+/// available if the preferred one is missing or empty
+pub fn label(kind: StackbarLabel, title: Option<&str>, exe: Option<&str>) -> String {
+    let title = title.filter(|t| !t.trim().is_empty());
+    let exe = exe.filter(|e| !e.trim().is_empty());
+
+    let label = match kind {
+        StackbarLabel::Process => exe.or(title),
+        StackbarLabel::Title => title.or(exe),
+    };
+
+    label.unwrap_or_default().trim().to_string()
+}
+
+/// Truncates a label with an ellipsis so that it fits within a tab of the given width
+pub fn truncate_label(label: &str, tab_width: i32, font_size: i32) -> String {
+    let font_size = if font_size > 0 {
+        font_size
+    } else {
+        DEFAULT_FONT_SIZE
+    };
+
+    let available = (tab_width - TAB_LABEL_PADDING * 2).max(0) as f32;
+    let max_chars = (available / (font_size as f32 * GLYPH_WIDTH_RATIO)).floor() as usize;
+
+    if label.chars().count() <= max_chars {
+        return label.to_string();
+    }
+
+    if max_chars == 0 {
+        return String::new();
+    }
+
+    let mut truncated = label.chars().take(max_chars - 1).collect::<String>();
+    truncated.push(ELLIPSIS);
+    truncated
+}
+
+/// Calculates the tabs to render for a container's stackbar
+pub fn tabs(
+    stackbar: &Rect,
+    labels: &[String],
+    focused_idx: usize,
+    tab_width: i32,
+    font_size: i32,
+) -> Vec<Tab> {
+    tab_rects(stackbar, labels.len(), tab_width)
+        .into_iter()
+        .zip(labels)
+        .enumerate()
+        .map(|(i, (rect, label))| Tab {
+            rect,
+            label: truncate_label(label, rect.right, font_size),
+            focused: i == focused_idx,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> Rect {
+        Rect {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    #[test]
+    fn test_visibility_by_mode() {
+        assert!(!is_visible(StackbarMode::Never, 3));
+        assert!(!is_visible(StackbarMode::OnStack, 1));
+        assert!(is_visible(StackbarMode::OnStack, 2));
+        assert!(is_visible(StackbarMode::Always, 1));
+        assert!(!is_visible(StackbarMode::Always, 0));
+    }
+
+    #[test]
+    fn test_split_container() {
+        let (stackbar, window) = split_container(&rect(100, 50, 800, 600), 40);
+
+        assert_eq!(stackbar, rect(100, 50, 800, 40));
+        assert_eq!(window, rect(100, 90, 800, 560));
+    }
+
+    #[test]
+    fn test_split_container_clamps_height() {
+        let (stackbar, window) = split_container(&rect(0, 0, 800, 30), 40);
+        assert_eq!(stackbar, rect(0, 0, 800, 30));
+        assert_eq!(window, rect(0, 30, 800, 0));
+
+        let (stackbar, window) = split_container(&rect(0, 0, 800, 600), -10);
+        assert_eq!(stackbar, rect(0, 0, 800, 0));
+        assert_eq!(window, rect(0, 0, 800, 600));
+    }
+
+    #[test]
+    fn test_tab_rects_use_configured_width() {
+        let tabs = tab_rects(&rect(100, 50, 1000, 40), 3, 200);
+
+        assert_eq!(
+            tabs,
+            vec![
+                rect(100, 50, 200, 40),
+                rect(300, 50, 200, 40),
+                rect(500, 50, 200, 40),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tab_rects_shrink_to_fit() {
+        let tabs = tab_rects(&rect(0, 0, 500, 40), 4, 200);
+
+        assert_eq!(tabs.len(), 4);
+        assert!(tabs.iter().all(|t| t.right == 125));
+        assert_eq!(tabs[3].left + tabs[3].right, 500);
+    }
+
+    #[test]
+    fn test_tab_rects_empty() {
+        assert!(tab_rects(&rect(0, 0, 500, 40), 0, 200).is_empty());
+    }
+
+    #[test]
+    fn test_tab_at_point() {
+        let tabs = tab_rects(&rect(100, 50, 1000, 40), 3, 200);
+
+        assert_eq!(tab_at_point(&tabs, (150, 60)), Some(0));
+        assert_eq!(tab_at_point(&tabs, (450, 89)), Some(1));
+        assert_eq!(tab_at_point(&tabs, (750, 60)), None);
+        assert_eq!(tab_at_point(&tabs, (150, 10)), None);
+    }
+
+    #[test]
+    fn test_label_fallbacks() {
+        assert_eq!(
+            label(StackbarLabel::Process, Some("README.md"), Some("Zed")),
+            "Zed"
+        );
+        assert_eq!(
+            label(StackbarLabel::Title, Some("README.md"), Some("Zed")),
+            "README.md"
+        );
+        assert_eq!(label(StackbarLabel::Title, Some("  "), Some("Zed")), "Zed");
+        assert_eq!(
+            label(StackbarLabel::Process, Some("README.md"), None),
+            "README.md"
+        );
+        assert_eq!(label(StackbarLabel::Process, None, None), "");
+    }
+
+    #[test]
+    fn test_truncate_label() {
+        // (200 - 16) / (10 * 0.6) = 30 characters
+        let long = "a".repeat(40);
+        let truncated = truncate_label(&long, 200, 10);
+        assert_eq!(truncated.chars().count(), 30);
+        assert!(truncated.ends_with(ELLIPSIS));
+
+        assert_eq!(truncate_label("Safari", 200, 10), "Safari");
+        assert_eq!(truncate_label("Safari", 10, 10), "");
+    }
+
+    #[test]
+    fn test_tabs_mark_focused_window() {
+        let labels = vec!["Safari".to_string(), "Mail".to_string()];
+        let tabs = tabs(&rect(0, 0, 1000, 40), &labels, 1, 200, 0);
+
+        assert_eq!(tabs.len(), 2);
+        assert!(!tabs[0].focused);
+        assert!(tabs[1].focused);
+        assert_eq!(tabs[1].label, "Mail");
+        assert_eq!(tabs[1].rect, rect(200, 0, 200, 40));
+    }
+}
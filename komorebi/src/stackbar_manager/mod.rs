@@ -0,0 +1,255 @@
+pub mod geometry;
+mod stackbar;
+
+use crate::border_manager;
+use crate::core::StackbarLabel;
+use crate::core::StackbarMode;
+use crate::stackbar_manager::stackbar::Stackbar;
+use crate::window_manager::WindowManager;
+use color_eyre::eyre;
+use color_eyre::eyre::OptionExt;
+use crossbeam_channel::Receiver;
+use crossbeam_channel::Sender;
+use crossbeam_utils::atomic::AtomicCell;
+use komorebi_themes::colour::Colour;
+use komorebi_themes::colour::Rgb;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::sync::atomic::AtomicI32;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+
+pub static STACKBAR_TAB_HEIGHT: AtomicI32 = AtomicI32::new(40);
+pub static STACKBAR_TAB_WIDTH: AtomicI32 = AtomicI32::new(200);
+pub static STACKBAR_FONT_SIZE: AtomicI32 = AtomicI32::new(0); // 0 will produce the system default
+pub static STACKBAR_MODE: AtomicCell<StackbarMode> = AtomicCell::new(StackbarMode::Never);
+pub static STACKBAR_LABEL: AtomicCell<StackbarLabel> = AtomicCell::new(StackbarLabel::Process);
+pub static STACKBAR_EXPLICIT_TAB_COLOURS: AtomicCell<ExplicitTabColours> =
+    AtomicCell::new(ExplicitTabColours {
+        focused_text: false,
+        unfocused_text: false,
+        background: false,
+    });
+
+/// Tab colours which have been set by the user and are not replaced by themes
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ExplicitTabColours {
+    pub focused_text: bool,
+    pub unfocused_text: bool,
+    pub background: bool,
+}
+
+lazy_static! {
+    pub static ref STACKBAR_FOCUSED_TEXT_COLOUR: AtomicU32 =
+        AtomicU32::new(u32::from(Colour::Rgb(Rgb::new(0, 165, 66))));
+    pub static ref STACKBAR_UNFOCUSED_TEXT_COLOUR: AtomicU32 =
+        AtomicU32::new(u32::from(Colour::Rgb(Rgb::new(128, 128, 128))));
+    pub static ref STACKBAR_TAB_BACKGROUND_COLOUR: AtomicU32 =
+        AtomicU32::new(u32::from(Colour::Rgb(Rgb::new(51, 51, 51))));
+    pub static ref STACKBAR_FONT_FAMILY: Mutex<Option<String>> = Mutex::new(None);
+}
+
+lazy_static! {
+    static ref STACKBAR_STATE: Mutex<HashMap<String, Stackbar>> = Mutex::new(HashMap::new());
+}
+
+pub enum Notification {
+    Update,
+    // NOTE: the point is in screen coordinates, as reported by the input event listener
+    Click(i32, i32),
+}
+
+static CHANNEL: OnceLock<(Sender<Notification>, Receiver<Notification>)> = OnceLock::new();
+
+pub fn channel() -> &'static (Sender<Notification>, Receiver<Notification>) {
+    CHANNEL.get_or_init(|| crossbeam_channel::bounded(50))
+}
+
+fn event_tx() -> Sender<Notification> {
+    channel().0.clone()
+}
+
+fn event_rx() -> Receiver<Notification> {
+    channel().1.clone()
+}
+
+pub fn send_notification() {
+    if event_tx().try_send(Notification::Update).is_err() {
+        tracing::warn!("channel is full; dropping notification")
+    }
+}
+
+pub fn send_click(x: i32, y: i32) {
+    if event_tx().try_send(Notification::Click(x, y)).is_err() {
+        tracing::warn!("channel is full; dropping notification")
+    }
+}
+
+/// Whether a container with `window_count` windows should currently have a stackbar
+pub fn should_have_stackbar(window_count: usize) -> bool {
+    geometry::is_visible(STACKBAR_MODE.load(), window_count)
+}
+
+pub fn destroy_all_stackbars() -> eyre::Result<()> {
+    let mut stackbars = STACKBAR_STATE.lock();
+    tracing::info!(
+        "purging known stackbars: {:?}",
+        stackbars.keys().collect::<Vec<_>>()
+    );
+
+    for (_, stackbar) in stackbars.drain() {
+        stackbar.destroy();
+    }
+
+    Ok(())
+}
+
+pub fn listen_for_notifications(wm: Arc<Mutex<WindowManager>>) {
+    std::thread::spawn(move || {
+        loop {
+            match handle_notifications(wm.clone()) {
+                Ok(()) => {
+                    tracing::warn!("restarting finished thread");
+                }
+                Err(error) => {
+                    tracing::warn!("restarting failed thread: {}", error);
+                }
+            }
+        }
+    });
+}
+
+fn handle_notifications(wm: Arc<Mutex<WindowManager>>) -> eyre::Result<()> {
+    tracing::info!("listening");
+
+    let receiver = event_rx();
+    event_tx().send(Notification::Update)?;
+
+    for notification in receiver {
+        if let Notification::Click(x, y) = notification {
+            if let Err(error) = handle_click(&wm, (x, y)) {
+                tracing::warn!("could not focus stackbar tab: {error}");
+            }
+
+            continue;
+        }
+
+        let state = wm.lock();
+        let is_paused = state.is_paused;
+        let monitors = state.monitors.clone();
+        drop(state);
+
+        let mode = STACKBAR_MODE.load();
+        let mut stackbars = STACKBAR_STATE.lock();
+
+        if matches!(mode, StackbarMode::Never) || is_paused {
+            for (_, stackbar) in stackbars.drain() {
+                stackbar.destroy();
+            }
+
+            continue;
+        }
+
+        let height = STACKBAR_TAB_HEIGHT.load(Ordering::SeqCst);
+        let tab_width = STACKBAR_TAB_WIDTH.load(Ordering::SeqCst);
+        let font_size = STACKBAR_FONT_SIZE.load(Ordering::SeqCst);
+        let label = STACKBAR_LABEL.load();
+
+        let mut visible = vec![];
+
+        for (monitor_idx, m) in monitors.elements().iter().enumerate() {
+            let Some(ws) = m.focused_workspace() else {
+                continue;
+            };
+
+            // monocle containers take up the whole work area, so there is nowhere to put a stackbar
+            if !ws.tile || ws.monocle_container.is_some() {
+                continue;
+            }
+
+            for (idx, c) in ws.containers().iter().enumerate() {
+                if !geometry::is_visible(mode, c.windows().len()) {
+                    continue;
+                }
+
+                let Some(layout) = ws.latest_layout.get(idx) else {
+                    continue;
+                };
+
+                let (rect, _) = geometry::split_container(layout, height);
+                let labels = c
+                    .windows()
+                    .iter()
+                    .map(|w| geometry::label(label, w.title().as_deref(), w.exe().as_deref()))
+                    .collect::<Vec<_>>();
+                let tabs =
+                    geometry::tabs(&rect, &labels, c.focused_window_idx(), tab_width, font_size);
+
+                let stackbar = match stackbars.entry(c.id.clone()) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => match Stackbar::create(&c.id, monitor_idx, &rect) {
+                        Ok(stackbar) => entry.insert(stackbar),
+                        Err(error) => {
+                            tracing::warn!("could not create stackbar: {error}");
+                            continue;
+                        }
+                    },
+                };
+
+                stackbar.monitor_idx = monitor_idx;
+                stackbar.update(&rect, &tabs);
+
+                visible.push(c.id.clone());
+            }
+        }
+
+        let stale = stackbars
+            .keys()
+            .filter(|id| !visible.contains(id))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for id in stale {
+            if let Some(stackbar) = stackbars.remove(&id) {
+                stackbar.destroy();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_click(wm: &Arc<Mutex<WindowManager>>, point: (i32, i32)) -> eyre::Result<()> {
+    let target = STACKBAR_STATE.lock().values().find_map(|stackbar| {
+        geometry::tab_at_point(&stackbar.tabs, point)
+            .map(|idx| (stackbar.id.clone(), stackbar.monitor_idx, idx))
+    });
+
+    let Some((container_id, monitor_idx, window_idx)) = target else {
+        return Ok(());
+    };
+
+    let mut wm = wm.lock();
+
+    let container_idx = wm
+        .monitors()
+        .get(monitor_idx)
+        .and_then(|m| m.focused_workspace())
+        .and_then(|ws| ws.containers().iter().position(|c| c.id == container_id))
+        .ok_or_eyre("there is no container for this stackbar")?;
+
+    wm.focus_monitor(monitor_idx)?;
+    wm.focused_workspace_mut()?.focus_container(container_idx);
+    wm.focus_container_window(window_idx)?;
+
+    drop(wm);
+
+    border_manager::send_notification(None, None, false);
+    send_notification();
+
+    Ok(())
+}
@@ -0,0 +1,238 @@
+use crate::core::Rect;
+use crate::core_graphics::CoreGraphicsApi;
+use crate::stackbar_manager::STACKBAR_FOCUSED_TEXT_COLOUR;
+use crate::stackbar_manager::STACKBAR_FONT_FAMILY;
+use crate::stackbar_manager::STACKBAR_FONT_SIZE;
+use crate::stackbar_manager::STACKBAR_TAB_BACKGROUND_COLOUR;
+use crate::stackbar_manager::STACKBAR_UNFOCUSED_TEXT_COLOUR;
+use crate::stackbar_manager::geometry::DEFAULT_FONT_SIZE;
+use crate::stackbar_manager::geometry::TAB_LABEL_PADDING;
+use crate::stackbar_manager::geometry::Tab;
+use color_eyre::eyre;
+use color_eyre::eyre::OptionExt;
+use dispatch2::DispatchQueue;
+use komorebi_themes::colour::Rgb;
+use objc2::MainThreadMarker;
+use objc2::MainThreadOnly;
+use objc2::msg_send;
+use objc2::rc::Retained;
+use objc2::rc::autoreleasepool;
+use objc2_app_kit::NSBackingStoreType;
+use objc2_app_kit::NSColor;
+use objc2_app_kit::NSNormalWindowLevel;
+use objc2_app_kit::NSView;
+use objc2_app_kit::NSWindow;
+use objc2_app_kit::NSWindowAnimationBehavior;
+use objc2_app_kit::NSWindowCollectionBehavior;
+use objc2_app_kit::NSWindowStyleMask;
+use objc2_core_foundation::CFRetained;
+use objc2_core_foundation::CGFloat;
+use objc2_core_graphics::CGColor;
+use objc2_core_graphics::CGMainDisplayID;
+use objc2_foundation::NSPoint;
+use objc2_foundation::NSRect;
+use objc2_foundation::NSSize;
+use objc2_foundation::NSString;
+use objc2_quartz_core::CALayer;
+use objc2_quartz_core::CATextLayer;
+use std::ffi::c_void;
+use std::ops::Deref;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+
+#[derive(Debug)]
+pub struct Stackbar {
+    /// The id of the container this stackbar is drawn for
+    pub id: String,
+    pub monitor_idx: usize,
+    /// Tab positions in screen coordinates, used to hit-test clicks
+    pub tabs: Vec<Rect>,
+    pub window: Retained<NSWindow>,
+}
+
+unsafe impl Send for Stackbar {}
+
+fn ns_rect(rect: &Rect) -> NSRect {
+    let frame = Rect::from(CoreGraphicsApi::display_bounds(CGMainDisplayID()));
+
+    NSRect::new(
+        NSPoint::new(
+            rect.left as CGFloat,
+            (frame.bottom - rect.top - rect.bottom) as CGFloat,
+        ),
+        NSSize::new(rect.right as CGFloat, rect.bottom as CGFloat),
+    )
+}
+
+fn cg_colour(rgb: Rgb) -> CFRetained<CGColor> {
+    CGColor::new_generic_rgb(
+        rgb.r as f64 / 255.0,
+        rgb.g as f64 / 255.0,
+        rgb.b as f64 / 255.0,
+        1.0,
+    )
+}
+
+impl Stackbar {
+    pub fn create(id: &str, monitor_idx: usize, rect: &Rect) -> eyre::Result<Self> {
+        let ns_rect = ns_rect(rect);
+        let (tx, rx) = mpsc::channel();
+
+        DispatchQueue::main().exec_async(move || {
+            autoreleasepool(|_| {
+                let mtm = unsafe { MainThreadMarker::new_unchecked() };
+
+                let window = unsafe {
+                    let window = NSWindow::alloc(mtm);
+                    NSWindow::initWithContentRect_styleMask_backing_defer(
+                        window,
+                        ns_rect,
+                        NSWindowStyleMask::Borderless,
+                        NSBackingStoreType::Buffered,
+                        false,
+                    )
+                };
+
+                window.setBackgroundColor(Some(&NSColor::clearColor()));
+                window.setAnimationBehavior(NSWindowAnimationBehavior::None);
+                window.disableSnapshotRestoration();
+                window.setRestorable(false);
+                window.setHasShadow(false);
+                window.setOpaque(false);
+                window.setLevel(NSNormalWindowLevel);
+                // clicks are picked up by the input event listener, we just want to make sure
+                // they don't fall through to whatever happens to be behind the stackbar
+                window.setIgnoresMouseEvents(false);
+
+                window.setCollectionBehavior(
+                    NSWindowCollectionBehavior::CanJoinAllSpaces
+                        | NSWindowCollectionBehavior::Stationary
+                        | NSWindowCollectionBehavior::IgnoresCycle
+                        | NSWindowCollectionBehavior::Transient,
+                );
+
+                let content_view = {
+                    let view = NSView::alloc(mtm);
+                    NSView::initWithFrame(view, ns_rect)
+                };
+
+                content_view.setWantsLayer(true);
+                content_view.setAutoresizesSubviews(false);
+
+                window.setContentView(Some(&content_view));
+                window.setMovableByWindowBackground(false);
+                window.orderFront(None);
+
+                if let Err(error) = tx.send(window) {
+                    tracing::error!("could not send NSWindow created for stackbar: {error}")
+                }
+            })
+        });
+
+        let window = rx
+            .recv()
+            .ok()
+            .ok_or_eyre("could not create a stackbar NSWindow")?;
+
+        Ok(Self {
+            id: id.to_string(),
+            monitor_idx,
+            tabs: vec![],
+            window,
+        })
+    }
+
+    pub fn update(&mut self, rect: &Rect, tabs: &[Tab]) {
+        self.tabs = tabs.iter().map(|tab| tab.rect).collect();
+
+        let frame = ns_rect(rect);
+        let origin = rect.left;
+        let tabs = tabs.to_vec();
+
+        let font_size = match STACKBAR_FONT_SIZE.load(Ordering::Relaxed) {
+            size if size > 0 => size,
+            _ => DEFAULT_FONT_SIZE,
+        } as CGFloat;
+        let font_family = STACKBAR_FONT_FAMILY.lock().clone();
+        let focused = Rgb::from(STACKBAR_FOCUSED_TEXT_COLOUR.load(Ordering::Relaxed));
+        let unfocused = Rgb::from(STACKBAR_UNFOCUSED_TEXT_COLOUR.load(Ordering::Relaxed));
+        let background = Rgb::from(STACKBAR_TAB_BACKGROUND_COLOUR.load(Ordering::Relaxed));
+
+        let window_ptr = Retained::as_ptr(&self.window) as usize;
+
+        DispatchQueue::main().exec_sync(move || {
+            autoreleasepool(|_| unsafe {
+                let window = &*(window_ptr as *const NSWindow);
+                window.setFrame_display(frame, true);
+
+                let scale = window.backingScaleFactor();
+                let line_height = (font_size * 1.25).min(frame.size.height);
+                let background = cg_colour(background);
+                let background_ptr = background.deref() as *const CGColor;
+                let font = font_family.as_deref().map(NSString::from_str);
+                let alignment = NSString::from_str("center");
+                let truncation = NSString::from_str("end");
+
+                let root = CALayer::new();
+                root.setFrame(NSRect::new(NSPoint::new(0.0, 0.0), frame.size));
+
+                for tab in &tabs {
+                    let tab_frame = NSRect::new(
+                        NSPoint::new((tab.rect.left - origin) as CGFloat, 0.0),
+                        NSSize::new(tab.rect.right as CGFloat, frame.size.height),
+                    );
+
+                    let tab_layer = CALayer::new();
+                    tab_layer.setFrame(tab_frame);
+                    tab_layer.setCornerRadius(4.0);
+                    let _: () = msg_send![&tab_layer, setBackgroundColor: background_ptr];
+
+                    let text_colour = cg_colour(if tab.focused { focused } else { unfocused });
+                    let text_colour_ptr = text_colour.deref() as *const CGColor;
+                    let label = NSString::from_str(&tab.label);
+
+                    let text_layer = CATextLayer::new();
+                    text_layer.setFrame(NSRect::new(
+                        NSPoint::new(
+                            TAB_LABEL_PADDING as CGFloat,
+                            (frame.size.height - line_height) / 2.0,
+                        ),
+                        NSSize::new(
+                            (tab_frame.size.width - (TAB_LABEL_PADDING * 2) as CGFloat).max(0.0),
+                            line_height,
+                        ),
+                    ));
+                    text_layer.setContentsScale(scale);
+                    let _: () = msg_send![&text_layer, setString: &*label];
+                    let _: () = msg_send![&text_layer, setFontSize: font_size];
+                    let _: () = msg_send![&text_layer, setForegroundColor: text_colour_ptr];
+                    let _: () = msg_send![&text_layer, setAlignmentMode: &*alignment];
+                    let _: () = msg_send![&text_layer, setTruncationMode: &*truncation];
+
+                    if let Some(font) = &font {
+                        let font_ptr = Retained::as_ptr(font).cast::<c_void>();
+                        let _: () = msg_send![&text_layer, setFont: font_ptr];
+                    }
+
+                    tab_layer.addSublayer(&text_layer);
+                    root.addSublayer(&tab_layer);
+                }
+
+                if let Some(view) = window.contentView() {
+                    view.setLayer(Some(&root));
+                }
+            })
+        });
+    }
+
+    pub fn destroy(&self) {
+        let window_ptr = Retained::as_ptr(&self.window) as usize;
+
+        DispatchQueue::main().exec_sync(|| {
+            autoreleasepool(|_| unsafe {
+                let window = window_ptr as *const NSWindow;
+                (*window).close();
+            });
+        });
+    }
+}
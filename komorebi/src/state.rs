@@ -3,23 +3,39 @@ use crate::DISPLAY_INDEX_PREFERENCES;
 use crate::HOME_DIR;
 use crate::IGNORE_IDENTIFIERS;
 use crate::MANAGE_IDENTIFIERS;
+use crate::TRANSPARENCY_BLACKLIST;
 use crate::WORKSPACE_MATCHING_RULES;
+use crate::core::FocusFollowsMouseImplementation;
 use crate::core::MonocleFocusBehaviour;
 use crate::core::MoveBehaviour;
 use crate::core::OperationBehaviour;
 use crate::core::Rect;
+use crate::core::StackbarLabel;
+use crate::core::StackbarMode;
 use crate::core::WindowContainerBehaviour;
 use crate::core::config_generation::MatchingRule;
 use crate::core::config_generation::WorkspaceMatchingRule;
 use crate::monitor::Monitor;
 use crate::ring::Ring;
+use crate::scratchpad::ScratchpadState;
+use crate::stackbar_manager::STACKBAR_FOCUSED_TEXT_COLOUR;
+use crate::stackbar_manager::STACKBAR_LABEL;
+use crate::stackbar_manager::STACKBAR_MODE;
+use crate::stackbar_manager::STACKBAR_TAB_BACKGROUND_COLOUR;
+use crate::stackbar_manager::STACKBAR_TAB_HEIGHT;
+use crate::stackbar_manager::STACKBAR_TAB_WIDTH;
+use crate::stackbar_manager::STACKBAR_UNFOCUSED_TEXT_COLOUR;
+use crate::transparency_manager::TRANSPARENCY_ALPHA;
+use crate::transparency_manager::TRANSPARENCY_ENABLED;
 use crate::window_manager::WindowManager;
 use crate::workspace::Workspace;
+use komorebi_themes::colour::Colour;
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::path::PathBuf;
+use std::sync::atomic::Ordering;
 
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Serialize, Deserialize)]
@@ -29,16 +45,16 @@ pub struct GlobalState {
     // pub border_style: BorderStyle,
     // pub border_offset: i32,
     // pub border_width: i32,
-    // pub stackbar_mode: StackbarMode,
-    // pub stackbar_label: StackbarLabel,
-    // pub stackbar_focused_text_colour: Colour,
-    // pub stackbar_unfocused_text_colour: Colour,
-    // pub stackbar_tab_background_colour: Colour,
-    // pub stackbar_tab_width: i32,
-    // pub stackbar_height: i32,
-    // pub transparency_enabled: bool,
-    // pub transparency_alpha: u8,
-    // pub transparency_blacklist: Vec<MatchingRule>,
+    pub stackbar_mode: StackbarMode,
+    pub stackbar_label: StackbarLabel,
+    pub stackbar_focused_text_colour: Colour,
+    pub stackbar_unfocused_text_colour: Colour,
+    pub stackbar_tab_background_colour: Colour,
+    pub stackbar_tab_width: i32,
+    pub stackbar_height: i32,
+    pub transparency_enabled: bool,
+    pub transparency_alpha: u8,
+    pub transparency_blacklist: Vec<MatchingRule>,
     // pub remove_titlebars: bool,
     // #[serde(alias = "float_identifiers")]
     pub ignore_identifiers: Vec<MatchingRule>,
@@ -59,6 +75,22 @@ pub struct GlobalState {
 impl Default for GlobalState {
     fn default() -> Self {
         Self {
+            stackbar_mode: STACKBAR_MODE.load(),
+            stackbar_label: STACKBAR_LABEL.load(),
+            stackbar_focused_text_colour: Colour::from(
+                STACKBAR_FOCUSED_TEXT_COLOUR.load(Ordering::SeqCst),
+            ),
+            stackbar_unfocused_text_colour: Colour::from(
+                STACKBAR_UNFOCUSED_TEXT_COLOUR.load(Ordering::SeqCst),
+            ),
+            stackbar_tab_background_colour: Colour::from(
+                STACKBAR_TAB_BACKGROUND_COLOUR.load(Ordering::SeqCst),
+            ),
+            stackbar_tab_width: STACKBAR_TAB_WIDTH.load(Ordering::SeqCst),
+            stackbar_height: STACKBAR_TAB_HEIGHT.load(Ordering::SeqCst),
+            transparency_enabled: TRANSPARENCY_ENABLED.load(Ordering::SeqCst),
+            transparency_alpha: TRANSPARENCY_ALPHA.load(Ordering::SeqCst),
+            transparency_blacklist: TRANSPARENCY_BLACKLIST.lock().clone(),
             ignore_identifiers: IGNORE_IDENTIFIERS.lock().clone(),
             manage_identifiers: MANAGE_IDENTIFIERS.lock().clone(),
             display_index_preferences: DISPLAY_INDEX_PREFERENCES.read().clone(),
@@ -83,8 +115,23 @@ pub struct State {
     pub monocle_focus_behaviour: MonocleFocusBehaviour,
     pub unmanaged_window_operation_behaviour: OperationBehaviour,
     pub work_area_offset: Option<Rect>,
-    // pub focus_follows_mouse: Option<FocusFollowsMouseImplementation>,
+    pub focus_follows_mouse: Option<FocusFollowsMouseImplementation>,
     pub mouse_follows_focus: bool,
+    /// Scratchpads from the static configuration, in order of their names
+    #[serde(default)]
+    pub scratchpads: Vec<ScratchpadState>,
+    /// Labels attached to windows, keyed by window id
+    #[serde(default)]
+    pub marks: HashMap<u32, String>,
+    /// Names of the workspace sets from the static configuration, in the order they were declared
+    #[serde(default)]
+    pub workspace_sets: Vec<String>,
+    /// The workspace set whose workspaces are all focused, if any
+    #[serde(default)]
+    pub active_workspace_set: Option<String>,
+    /// The profile from the static configuration whose overrides are applied, if any
+    #[serde(default)]
+    pub active_profile: Option<String>,
     // pub has_pending_raise_op: bool,
 }
 
@@ -121,8 +168,10 @@ impl From<&WindowManager> for State {
                             maximized_window_restore_idx: workspace.maximized_window_restore_idx,
                             floating_windows: workspace.floating_windows.clone(),
                             layout: workspace.layout.clone(),
+                            custom_layout: workspace.custom_layout.clone(),
                             layout_options: workspace.layout_options,
                             layout_rules: workspace.layout_rules.clone(),
+                            custom_layout_rules: workspace.custom_layout_rules.clone(),
                             layout_options_rules: workspace.layout_options_rules.clone(),
                             layout_defaults_cache: workspace.layout_defaults_cache.clone(),
                             layout_flip: workspace.layout_flip,
@@ -173,8 +222,17 @@ impl From<&WindowManager> for State {
             float_override: wm.window_management_behaviour.float_override,
             cross_monitor_move_behaviour: wm.cross_monitor_move_behaviour,
             monocle_focus_behaviour: wm.monocle_focus_behaviour,
-            // focus_follows_mouse: wm.focus_follows_mouse,
+            focus_follows_mouse: wm.focus_follows_mouse,
             mouse_follows_focus: wm.mouse_follows_focus,
+            scratchpads: wm.scratchpads.values().map(ScratchpadState::from).collect(),
+            marks: wm.marks.clone(),
+            workspace_sets: wm
+                .workspace_sets
+                .iter()
+                .map(|set| set.name.clone())
+                .collect(),
+            active_workspace_set: wm.active_workspace_set(),
+            active_profile: wm.active_profile.clone(),
             // has_pending_raise_op: wm.has_pending_raise_op,
             unmanaged_window_operation_behaviour: wm.unmanaged_window_operation_behaviour,
             // has_pending_raise_op: false,
@@ -218,14 +276,34 @@ impl State {
             return true;
         }
 
-        // if self.focus_follows_mouse != new.focus_follows_mouse {
-        //     return true;
-        // }
+        if self.focus_follows_mouse != new.focus_follows_mouse {
+            return true;
+        }
 
         if self.mouse_follows_focus != new.mouse_follows_focus {
             return true;
         }
 
+        if self.scratchpads != new.scratchpads {
+            return true;
+        }
+
+        if self.marks != new.marks {
+            return true;
+        }
+
+        if self.workspace_sets != new.workspace_sets {
+            return true;
+        }
+
+        if self.active_workspace_set != new.active_workspace_set {
+            return true;
+        }
+
+        if self.active_profile != new.active_profile {
+            return true;
+        }
+
         // if self.has_pending_raise_op != new.has_pending_raise_op {
         //     return true;
         // }
@@ -2,6 +2,7 @@ use crate::CoreFoundationRunLoop;
 use crate::DATA_DIR;
 use crate::DEFAULT_CONTAINER_PADDING;
 use crate::DEFAULT_MOUSE_FOLLOWS_FOCUS;
+use crate::DEFAULT_PADDING;
 use crate::DEFAULT_RESIZE_DELTA;
 use crate::DEFAULT_WORKSPACE_PADDING;
 use crate::DISPLAY_INDEX_PREFERENCES;
@@ -11,8 +12,10 @@ use crate::IGNORE_IDENTIFIERS;
 use crate::LAYOUT_DEFAULTS;
 use crate::MANAGE_IDENTIFIERS;
 use crate::REGEX_IDENTIFIERS;
+use crate::STICKY_APPLICATIONS;
 use crate::TABBED_APPLICATIONS;
 use crate::TITLELESS_APPLICATIONS;
+use crate::TRANSPARENCY_BLACKLIST;
 use crate::WORKSPACE_MATCHING_RULES;
 use crate::animation::ANIMATION_DURATION_GLOBAL;
 use crate::animation::ANIMATION_DURATION_PER_ANIMATION;
@@ -21,7 +24,10 @@ use crate::animation::ANIMATION_ENABLED_PER_ANIMATION;
 use crate::animation::ANIMATION_FPS;
 use crate::animation::ANIMATION_STYLE_GLOBAL;
 use crate::animation::ANIMATION_STYLE_PER_ANIMATION;
+use crate::animation::DEFAULT_ANIMATION_DURATION;
+use crate::animation::DEFAULT_ANIMATION_ENABLED;
 use crate::animation::DEFAULT_ANIMATION_FPS;
+use crate::animation::DEFAULT_ANIMATION_STYLE;
 use crate::animation::PerAnimationPrefixConfig;
 use crate::border_manager;
 use crate::core::ApplicationIdentifier;
@@ -29,6 +35,7 @@ use crate::core::Axis;
 use crate::core::CrossBoundaryBehaviour;
 use crate::core::DefaultLayout;
 use crate::core::FloatingLayerBehaviour;
+use crate::core::FocusFollowsMouseImplementation;
 use crate::core::Layout;
 use crate::core::LayoutDefaultEntry;
 use crate::core::LayoutOptions;
@@ -37,7 +44,8 @@ use crate::core::MoveBehaviour;
 use crate::core::OperationBehaviour;
 use crate::core::Placement;
 use crate::core::Rect;
-use crate::core::SocketMessage;
+use crate::core::StackbarLabel;
+use crate::core::StackbarMode;
 use crate::core::WindowContainerBehaviour;
 use crate::core::WindowHidingPosition;
 use crate::core::WindowManagementBehaviour;
@@ -47,21 +55,40 @@ use crate::core::asc::AscApplicationRulesOrSchema;
 use crate::core::config_generation::MatchingRule;
 use crate::core::config_generation::MatchingStrategy;
 use crate::core::config_generation::WorkspaceMatchingRule;
+use crate::core::custom_layout::CustomLayout;
+use crate::core::layered_config::LayeredConfig;
 use crate::core::pathext::ResolvedPathBuf;
-use crate::current_space_id;
+use crate::core::pathext::resolve_option_hashmap_usize_path;
+use crate::history::History;
 use crate::monitor;
 use crate::monitor::Monitor;
 use crate::monitor_reconciliator;
+use crate::platform;
+use crate::profile::ProfileConfig;
 use crate::ring::Ring;
+use crate::scratchpad::ScratchpadConfig;
+use crate::stackbar_manager::ExplicitTabColours;
+use crate::stackbar_manager::STACKBAR_EXPLICIT_TAB_COLOURS;
+use crate::stackbar_manager::STACKBAR_FOCUSED_TEXT_COLOUR;
+use crate::stackbar_manager::STACKBAR_FONT_FAMILY;
+use crate::stackbar_manager::STACKBAR_FONT_SIZE;
+use crate::stackbar_manager::STACKBAR_LABEL;
+use crate::stackbar_manager::STACKBAR_MODE;
+use crate::stackbar_manager::STACKBAR_TAB_BACKGROUND_COLOUR;
+use crate::stackbar_manager::STACKBAR_TAB_HEIGHT;
+use crate::stackbar_manager::STACKBAR_TAB_WIDTH;
+use crate::stackbar_manager::STACKBAR_UNFOCUSED_TEXT_COLOUR;
 use crate::theme_manager;
+use crate::transparency_manager;
 use crate::window::AspectRatio;
 use crate::window::PredefinedAspectRatio;
 use crate::window_manager::WindowManager;
 use crate::window_manager_event::WindowManagerEvent;
 use crate::workspace::Workspace;
+use crate::workspace_set::WorkspaceSetConfig;
 use color_eyre::eyre;
+use color_eyre::eyre::Context;
 use crossbeam_channel::Receiver;
-use hotwatch::EventKind;
 use hotwatch::Hotwatch;
 use komorebi_themes::KomorebiTheme;
 use komorebi_themes::colour::Colour;
@@ -71,11 +98,11 @@ use parking_lot::Mutex;
 use regex::Regex;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::io::ErrorKind;
-use std::io::Write;
 use std::os::unix::net::UnixListener;
-use std::os::unix::net::UnixStream;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
@@ -138,15 +165,18 @@ pub struct ThemeOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[cfg_attr(feature = "schemars", schemars(extend("default" = komorebi_themes::Base16Value::Base08)))]
     pub unfocused_locked_border: Option<komorebi_themes::Base16Value>,
-    // /// Stackbar focused tab text colour (default: Base0B)
-    // #[serde(skip_serializing_if = "Option::is_none")]
-    // pub stackbar_focused_text: Option<komorebi_themes::Base16Value>,
-    // /// Stackbar unfocused tab text colour (default: Base05)
-    // #[serde(skip_serializing_if = "Option::is_none")]
-    // pub stackbar_unfocused_text: Option<komorebi_themes::Base16Value>,
-    // /// Stackbar tab background colour (default: Base01)
-    // #[serde(skip_serializing_if = "Option::is_none")]
-    // pub stackbar_background: Option<komorebi_themes::Base16Value>,
+    /// Stackbar focused tab text colour
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schemars", schemars(extend("default" = komorebi_themes::Base16Value::Base0B)))]
+    pub stackbar_focused_text: Option<komorebi_themes::Base16Value>,
+    /// Stackbar unfocused tab text colour
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schemars", schemars(extend("default" = komorebi_themes::Base16Value::Base05)))]
+    pub stackbar_unfocused_text: Option<komorebi_themes::Base16Value>,
+    /// Stackbar tab background colour
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schemars", schemars(extend("default" = komorebi_themes::Base16Value::Base01)))]
+    pub stackbar_background: Option<komorebi_themes::Base16Value>,
     /// Komorebi status bar accent
     #[serde(skip_serializing_if = "Option::is_none")]
     #[cfg_attr(feature = "schemars", schemars(extend("default" = komorebi_themes::Base16Value::Base0D)))]
@@ -191,13 +221,17 @@ pub struct WorkspaceConfig {
     /// This follows the same threshold logic as `layout_rules`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub layout_options_rules: Option<HashMap<usize, LayoutOptions>>,
-    // /// END OF LIFE FEATURE: Custom Layout (default: None)
-    // #[serde(skip_serializing_if = "Option::is_none")]
-    // #[serde_as(as = "Option<ResolvedPathBuf>")]
-    // pub custom_layout: Option<PathBuf>,
+    /// Path to a JSON or YAML custom layout file, which takes precedence over `layout` (default: None)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde_as(as = "Option<ResolvedPathBuf>")]
+    pub custom_layout: Option<PathBuf>,
     /// Layout rules in the format of threshold => layout
     #[serde(skip_serializing_if = "Option::is_none")]
     pub layout_rules: Option<HashMap<usize, DefaultLayout>>,
+    /// Custom layout rules in the format of threshold => path to a custom layout file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "resolve_option_hashmap_usize_path", default)]
+    pub custom_layout_rules: Option<HashMap<usize, PathBuf>>,
     /// Container padding (default: global)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub container_padding: Option<i32>,
@@ -315,11 +349,15 @@ impl From<&Workspace> for WorkspaceConfig {
             } else {
                 Some(value.layout_options_rules.iter().copied().collect())
             },
-            // custom_layout: value
-            //     .workspace_config
-            //     .as_ref()
-            //     .and_then(|c| c.custom_layout.clone()),
+            custom_layout: value
+                .workspace_config
+                .as_ref()
+                .and_then(|c| c.custom_layout.clone()),
             layout_rules,
+            custom_layout_rules: value
+                .workspace_config
+                .as_ref()
+                .and_then(|c| c.custom_layout_rules.clone()),
             container_padding,
             workspace_padding,
             initial_workspace_rules: value
@@ -495,6 +533,9 @@ pub struct StaticConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[cfg_attr(feature = "schemars", schemars(extend("default" = OperationBehaviour::Op)))]
     pub unmanaged_window_operation_behaviour: Option<OperationBehaviour>,
+    /// Focus the tiled or floating window under the cursor when the cursor comes to rest (default: None)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus_follows_mouse: Option<FocusFollowsMouseImplementation>,
     /// Enable or disable mouse follows focus
     #[serde(skip_serializing_if = "Option::is_none")]
     #[cfg_attr(feature = "schemars", schemars(extend("default" = DEFAULT_MOUSE_FOLLOWS_FOCUS)))]
@@ -532,15 +573,15 @@ pub struct StaticConfig {
     // /// Active window border implementation (default: Komorebi)
     // #[serde(skip_serializing_if = "Option::is_none")]
     // pub border_implementation: Option<BorderImplementation>,
-    // /// Add transparency to unfocused windows (default: false)
-    // #[serde(skip_serializing_if = "Option::is_none")]
-    // pub transparency: Option<bool>,
-    // /// Alpha value for unfocused window transparency [[0-255]] (default: 200)
-    // #[serde(skip_serializing_if = "Option::is_none")]
-    // pub transparency_alpha: Option<u8>,
-    // /// Individual window transparency ignore rules
-    // #[serde(skip_serializing_if = "Option::is_none")]
-    // pub transparency_ignore_rules: Option<Vec<MatchingRule>>,
+    /// Add transparency to unfocused windows (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transparency: Option<bool>,
+    /// Alpha value for unfocused window transparency [[0-255]] (default: 200)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transparency_alpha: Option<u8>,
+    /// Individual window transparency ignore rules
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transparency_ignore_rules: Option<Vec<MatchingRule>>,
     /// Global default workspace padding
     #[serde(skip_serializing_if = "Option::is_none")]
     #[cfg_attr(feature = "schemars", schemars(extend("default" = DEFAULT_WORKSPACE_PADDING)))]
@@ -573,6 +614,13 @@ pub struct StaticConfig {
     /// Identify applications which should be managed as floating windows
     #[serde(skip_serializing_if = "Option::is_none")]
     pub floating_applications: Option<Vec<MatchingRule>>,
+    /// Identify applications which should be managed as floating windows that stay visible when
+    /// switching workspaces
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sticky_applications: Option<Vec<MatchingRule>>,
+    /// Move sticky windows to the monitor which receives focus (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sticky_windows_follow_monitor_focus: Option<bool>,
     /// Identify applications which should be managed despite not reporting titles to the system
     #[serde(skip_serializing_if = "Option::is_none")]
     pub titleless_applications: Option<Vec<String>>,
@@ -600,9 +648,9 @@ pub struct StaticConfig {
     /// Set display index preferences
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_index_preferences: Option<HashMap<usize, String>>,
-    // /// Stackbar configuration options
-    // #[serde(skip_serializing_if = "Option::is_none")]
-    // pub stackbar: Option<StackbarConfig>,
+    /// Stackbar configuration options
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stackbar: Option<StackbarConfig>,
     /// Animations configuration options
     #[serde(skip_serializing_if = "Option::is_none")]
     pub animation: Option<AnimationsConfig>,
@@ -628,6 +676,15 @@ pub struct StaticConfig {
     /// Aspect ratio to resize with when toggling floating mode for a window
     #[serde(skip_serializing_if = "Option::is_none")]
     pub floating_window_aspect_ratio: Option<AspectRatio>,
+    /// Named floating windows which can be toggled over the focused workspace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scratchpads: Option<Vec<ScratchpadConfig>>,
+    /// Named groups of workspaces across monitors which are focused together
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_sets: Option<Vec<WorkspaceSetConfig>>,
+    /// Named overrides which can be activated on top of this configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<BTreeMap<String, ProfileConfig>>,
     // /// Which Windows API behaviour to use when manipulating windows (default: Sync)
     // #[serde(skip_serializing_if = "Option::is_none")]
     // pub window_handling_behaviour: Option<WindowHandlingBehaviour>,
@@ -654,45 +711,47 @@ pub struct AnimationsConfig {
     pub fps: Option<u64>,
 }
 
-// #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
-// #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
-// pub struct TabsConfig {
-//     /// Width of a stackbar tab
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub width: Option<i32>,
-//     /// Focused tab text colour
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub focused_text: Option<Colour>,
-//     /// Unfocused tab text colour
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub unfocused_text: Option<Colour>,
-//     /// Tab background colour
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub background: Option<Colour>,
-//     /// Font family
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub font_family: Option<String>,
-//     /// Font size
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub font_size: Option<i32>,
-// }
-
-// #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
-// #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
-// pub struct StackbarConfig {
-//     /// Stackbar height
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub height: Option<i32>,
-//     /// Stackbar label
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub label: Option<StackbarLabel>,
-//     /// Stackbar mode (default: Never)
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub mode: Option<StackbarMode>,
-//     /// Stackbar tab configuration options
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub tabs: Option<TabsConfig>,
-// }
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// Stackbar tab configuration
+pub struct TabsConfig {
+    /// Width of a stackbar tab
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<i32>,
+    /// Focused tab text colour
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focused_text: Option<Colour>,
+    /// Unfocused tab text colour
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unfocused_text: Option<Colour>,
+    /// Tab background colour
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<Colour>,
+    /// Font family
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    /// Font size
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<i32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// Stackbar configuration
+pub struct StackbarConfig {
+    /// Stackbar height
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<i32>,
+    /// Stackbar label
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<StackbarLabel>,
+    /// Stackbar mode (default: Never)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<StackbarMode>,
+    /// Stackbar tab configuration options
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tabs: Option<TabsConfig>,
+}
 
 impl From<&WindowManager> for StaticConfig {
     #[allow(clippy::too_many_lines)]
@@ -751,7 +810,7 @@ impl From<&WindowManager> for StaticConfig {
             ),
             // minimum_window_height: Some(window::MINIMUM_HEIGHT.load(Ordering::SeqCst)),
             // minimum_window_width: Some(window::MINIMUM_WIDTH.load(Ordering::SeqCst)),
-            // focus_follows_mouse: value.focus_follows_mouse,
+            focus_follows_mouse: value.focus_follows_mouse,
             mouse_follows_focus: Option::from(value.mouse_follows_focus),
             app_specific_configuration_path: None,
             border_width: Option::from(border_manager::BORDER_WIDTH.load(Ordering::SeqCst)),
@@ -759,13 +818,13 @@ impl From<&WindowManager> for StaticConfig {
             border_radius: Option::from(border_manager::BORDER_RADIUS.load(Ordering::SeqCst)),
             border: Option::from(border_manager::BORDER_ENABLED.load(Ordering::SeqCst)),
             border_colours,
-            // transparency: Option::from(
-            //     transparency_manager::TRANSPARENCY_ENABLED.load(Ordering::SeqCst),
-            // ),
-            // transparency_alpha: Option::from(
-            //     transparency_manager::TRANSPARENCY_ALPHA.load(Ordering::SeqCst),
-            // ),
-            // transparency_ignore_rules: None,
+            transparency: Option::from(
+                transparency_manager::TRANSPARENCY_ENABLED.load(Ordering::SeqCst),
+            ),
+            transparency_alpha: Option::from(
+                transparency_manager::TRANSPARENCY_ALPHA.load(Ordering::SeqCst),
+            ),
+            transparency_ignore_rules: None,
             // border_style: Option::from(STYLE.load()),
             // border_z_order: None,
             // border_implementation: Option::from(IMPLEMENTATION.load()),
@@ -788,6 +847,10 @@ impl From<&WindowManager> for StaticConfig {
             global_work_area_offset: value.work_area_offset,
             ignore_rules: None,
             floating_applications: None,
+            sticky_applications: None,
+            sticky_windows_follow_monitor_focus: Option::from(
+                value.sticky_windows_follow_monitor_focus,
+            ),
             titleless_applications: None,
             manage_rules: None,
             // border_overflow_applications: None,
@@ -797,7 +860,25 @@ impl From<&WindowManager> for StaticConfig {
             // object_name_change_title_ignore_list: None,
             // monitor_index_preferences: Option::from(MONITOR_INDEX_PREFERENCES.lock().clone()),
             display_index_preferences: Option::from(DISPLAY_INDEX_PREFERENCES.read().clone()),
-            // stackbar: None,
+            stackbar: Option::from(StackbarConfig {
+                height: Option::from(STACKBAR_TAB_HEIGHT.load(Ordering::SeqCst)),
+                label: Option::from(STACKBAR_LABEL.load()),
+                mode: Option::from(STACKBAR_MODE.load()),
+                tabs: Option::from(TabsConfig {
+                    width: Option::from(STACKBAR_TAB_WIDTH.load(Ordering::SeqCst)),
+                    focused_text: Option::from(Colour::from(
+                        STACKBAR_FOCUSED_TEXT_COLOUR.load(Ordering::SeqCst),
+                    )),
+                    unfocused_text: Option::from(Colour::from(
+                        STACKBAR_UNFOCUSED_TEXT_COLOUR.load(Ordering::SeqCst),
+                    )),
+                    background: Option::from(Colour::from(
+                        STACKBAR_TAB_BACKGROUND_COLOUR.load(Ordering::SeqCst),
+                    )),
+                    font_family: STACKBAR_FONT_FAMILY.lock().clone(),
+                    font_size: Option::from(STACKBAR_FONT_SIZE.load(Ordering::SeqCst)),
+                }),
+            }),
             animation: None,
             theme: None,
             // slow_application_compensation_time: Option::from(
@@ -807,6 +888,23 @@ impl From<&WindowManager> for StaticConfig {
             // bar_configurations: None,
             // remove_titlebar_applications: Option::from(NO_TITLEBAR.lock().clone()),
             floating_window_aspect_ratio: Option::from(*FLOATING_WINDOW_TOGGLE_ASPECT_RATIO.lock()),
+            scratchpads: if value.scratchpads.is_empty() {
+                None
+            } else {
+                Some(
+                    value
+                        .scratchpads
+                        .values()
+                        .map(|scratchpad| scratchpad.config.clone())
+                        .collect(),
+                )
+            },
+            workspace_sets: if value.workspace_sets.is_empty() {
+                None
+            } else {
+                Some(value.workspace_sets.clone())
+            },
+            profiles: None,
             // window_handling_behaviour: Option::from(WINDOW_HANDLING_BEHAVIOUR.load()),
             tabbed_applications: None,
         }
@@ -845,43 +943,7 @@ impl StaticConfig {
         // }
 
         if let Some(animations) = &self.animation {
-            match &animations.enabled {
-                PerAnimationPrefixConfig::Prefix(enabled) => {
-                    ANIMATION_ENABLED_PER_ANIMATION.lock().clone_from(enabled);
-                }
-                PerAnimationPrefixConfig::Global(enabled) => {
-                    ANIMATION_ENABLED_GLOBAL.store(*enabled, Ordering::SeqCst);
-                    ANIMATION_ENABLED_PER_ANIMATION.lock().clear();
-                }
-            }
-
-            match &animations.style {
-                Some(PerAnimationPrefixConfig::Prefix(style)) => {
-                    ANIMATION_STYLE_PER_ANIMATION.lock().clone_from(style);
-                }
-                Some(PerAnimationPrefixConfig::Global(style)) => {
-                    let mut animation_style = ANIMATION_STYLE_GLOBAL.lock();
-                    *animation_style = *style;
-                    ANIMATION_STYLE_PER_ANIMATION.lock().clear();
-                }
-                None => {}
-            }
-
-            match &animations.duration {
-                Some(PerAnimationPrefixConfig::Prefix(duration)) => {
-                    ANIMATION_DURATION_PER_ANIMATION.lock().clone_from(duration);
-                }
-                Some(PerAnimationPrefixConfig::Global(duration)) => {
-                    ANIMATION_DURATION_GLOBAL.store(*duration, Ordering::SeqCst);
-                    ANIMATION_DURATION_PER_ANIMATION.lock().clear();
-                }
-                None => {}
-            }
-
-            ANIMATION_FPS.store(
-                animations.fps.unwrap_or(DEFAULT_ANIMATION_FPS),
-                Ordering::SeqCst,
-            );
+            apply_animations(animations);
         }
 
         if let Some(container) = self.default_container_padding {
@@ -965,11 +1027,11 @@ impl StaticConfig {
         //
         //     border_manager::send_notification(None);
         // }
-        //
-        // transparency_manager::TRANSPARENCY_ENABLED
-        //     .store(self.transparency.unwrap_or(false), Ordering::SeqCst);
-        // transparency_manager::TRANSPARENCY_ALPHA
-        //     .store(self.transparency_alpha.unwrap_or(200), Ordering::SeqCst);
+
+        transparency_manager::TRANSPARENCY_ENABLED
+            .store(self.transparency.unwrap_or(false), Ordering::SeqCst);
+        transparency_manager::TRANSPARENCY_ALPHA
+            .store(self.transparency_alpha.unwrap_or(200), Ordering::SeqCst);
 
         let mut ignore_identifiers = IGNORE_IDENTIFIERS.lock();
         let mut regex_identifiers = REGEX_IDENTIFIERS.lock();
@@ -978,9 +1040,10 @@ impl StaticConfig {
         // let mut object_name_change_identifiers = OBJECT_NAME_CHANGE_ON_LAUNCH.lock();
         // let mut object_name_change_title_ignore_list = OBJECT_NAME_CHANGE_TITLE_IGNORE_LIST.lock();
         // let mut layered_identifiers = LAYERED_WHITELIST.lock();
-        // let mut transparency_blacklist = TRANSPARENCY_BLACKLIST.lock();
+        let mut transparency_blacklist = TRANSPARENCY_BLACKLIST.lock();
         // let mut slow_application_identifiers = SLOW_APPLICATION_IDENTIFIERS.lock();
         let mut floating_applications = FLOATING_APPLICATIONS.lock();
+        let mut sticky_applications = STICKY_APPLICATIONS.lock();
         // let mut no_titlebar_applications = NO_TITLEBAR.lock();
         let mut titleless_applications = TITLELESS_APPLICATIONS.lock();
         let mut tabbed_applications = TABBED_APPLICATIONS.lock();
@@ -993,10 +1056,27 @@ impl StaticConfig {
             populate_rules(rules, &mut floating_applications, &mut regex_identifiers)?;
         }
 
+        if let Some(rules) = &mut self.sticky_applications {
+            populate_rules(rules, &mut sticky_applications, &mut regex_identifiers)?;
+        }
+
         if let Some(rules) = &mut self.manage_rules {
             populate_rules(rules, &mut manage_identifiers, &mut regex_identifiers)?;
         }
 
+        if let Some(rules) = &mut self.transparency_ignore_rules {
+            populate_rules(rules, &mut transparency_blacklist, &mut regex_identifiers)?;
+        }
+
+        // scratchpad rules are matched by each scratchpad rather than through a global list
+        for scratchpad in self.scratchpads.iter_mut().flatten() {
+            populate_rules(
+                &mut scratchpad.match_rules,
+                &mut vec![],
+                &mut regex_identifiers,
+            )?;
+        }
+
         if let Some(rules) = &mut self.tabbed_applications {
             for r in rules {
                 if !tabbed_applications.contains(r) {
@@ -1044,10 +1124,6 @@ impl StaticConfig {
         //     )?;
         // }
         //
-        // if let Some(rules) = &mut self.transparency_ignore_rules {
-        //     populate_rules(rules, &mut transparency_blacklist, &mut regex_identifiers)?;
-        // }
-        //
         // if let Some(rules) = &mut self.slow_application_identifiers {
         //     populate_rules(
         //         rules,
@@ -1059,43 +1135,54 @@ impl StaticConfig {
         // if let Some(rules) = &mut self.remove_titlebar_applications {
         //     populate_rules(rules, &mut no_titlebar_applications, &mut regex_identifiers)?;
         // }
-        //
-        // if let Some(stackbar) = &self.stackbar {
-        //     if let Some(height) = &stackbar.height {
-        //         STACKBAR_TAB_HEIGHT.store(*height, Ordering::SeqCst);
-        //     }
-        //
-        //     if let Some(label) = &stackbar.label {
-        //         STACKBAR_LABEL.store(*label);
-        //     }
-        //
-        //     if let Some(mode) = &stackbar.mode {
-        //         STACKBAR_MODE.store(*mode);
-        //     }
-        //
-        //     #[allow(clippy::assigning_clones)]
-        //     if let Some(tabs) = &stackbar.tabs {
-        //         if let Some(background) = &tabs.background {
-        //             STACKBAR_TAB_BACKGROUND_COLOUR.store((*background).into(), Ordering::SeqCst);
-        //         }
-        //
-        //         if let Some(colour) = &tabs.focused_text {
-        //             STACKBAR_FOCUSED_TEXT_COLOUR.store((*colour).into(), Ordering::SeqCst);
-        //         }
-        //
-        //         if let Some(colour) = &tabs.unfocused_text {
-        //             STACKBAR_UNFOCUSED_TEXT_COLOUR.store((*colour).into(), Ordering::SeqCst);
-        //         }
-        //
-        //         if let Some(width) = &tabs.width {
-        //             STACKBAR_TAB_WIDTH.store(*width, Ordering::SeqCst);
-        //         }
-        //
-        //         STACKBAR_FONT_SIZE.store(tabs.font_size.unwrap_or(0), Ordering::SeqCst);
-        //         *STACKBAR_FONT_FAMILY.lock() = tabs.font_family.clone();
-        //     }
-        // }
-        //
+
+        let tabs = self
+            .stackbar
+            .as_ref()
+            .and_then(|stackbar| stackbar.tabs.as_ref());
+
+        STACKBAR_EXPLICIT_TAB_COLOURS.store(ExplicitTabColours {
+            focused_text: tabs.is_some_and(|tabs| tabs.focused_text.is_some()),
+            unfocused_text: tabs.is_some_and(|tabs| tabs.unfocused_text.is_some()),
+            background: tabs.is_some_and(|tabs| tabs.background.is_some()),
+        });
+
+        if let Some(stackbar) = &self.stackbar {
+            if let Some(height) = &stackbar.height {
+                STACKBAR_TAB_HEIGHT.store(*height, Ordering::SeqCst);
+            }
+
+            if let Some(label) = &stackbar.label {
+                STACKBAR_LABEL.store(*label);
+            }
+
+            if let Some(mode) = &stackbar.mode {
+                STACKBAR_MODE.store(*mode);
+            }
+
+            #[allow(clippy::assigning_clones)]
+            if let Some(tabs) = &stackbar.tabs {
+                if let Some(background) = &tabs.background {
+                    STACKBAR_TAB_BACKGROUND_COLOUR.store((*background).into(), Ordering::SeqCst);
+                }
+
+                if let Some(colour) = &tabs.focused_text {
+                    STACKBAR_FOCUSED_TEXT_COLOUR.store((*colour).into(), Ordering::SeqCst);
+                }
+
+                if let Some(colour) = &tabs.unfocused_text {
+                    STACKBAR_UNFOCUSED_TEXT_COLOUR.store((*colour).into(), Ordering::SeqCst);
+                }
+
+                if let Some(width) = &tabs.width {
+                    STACKBAR_TAB_WIDTH.store(*width, Ordering::SeqCst);
+                }
+
+                STACKBAR_FONT_SIZE.store(tabs.font_size.unwrap_or(0), Ordering::SeqCst);
+                *STACKBAR_FONT_FAMILY.lock() = tabs.font_family.clone();
+            }
+        }
+
         if let Some(theme) = &self.theme {
             theme_manager::send_notification(theme.clone());
         }
@@ -1138,9 +1225,146 @@ impl StaticConfig {
         Ok(serde_json::from_str(raw)?)
     }
 
+    /// Reads the configuration file at `path`, merging in the files it includes and resolving
+    /// its variables
     pub fn read(path: &PathBuf) -> eyre::Result<Self> {
-        let content = std::fs::read_to_string(path)?;
-        serde_json::from_str(&content).map_err(Into::into)
+        LayeredConfig::read(path)?.deserialize()
+    }
+
+    /// Paths of the application-specific configuration files referenced by this configuration
+    pub fn app_specific_configuration_paths(&self) -> Vec<PathBuf> {
+        match &self.app_specific_configuration_path {
+            None => vec![],
+            Some(AppSpecificConfigurationPath::Single(path)) => vec![path.clone()],
+            Some(AppSpecificConfigurationPath::Multiple(paths)) => paths.clone(),
+        }
+    }
+
+    /// Checks everything that can fail while applying this configuration, so that an invalid
+    /// configuration can be rejected before any global or window manager state is touched
+    pub fn validate(&self) -> eyre::Result<()> {
+        for rules in [
+            &self.ignore_rules,
+            &self.manage_rules,
+            &self.floating_applications,
+            &self.sticky_applications,
+            &self.transparency_ignore_rules,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            validate_rules(rules)?;
+        }
+
+        let mut scratchpad_names = HashSet::new();
+        for scratchpad in self.scratchpads.iter().flatten() {
+            if !scratchpad_names.insert(&scratchpad.name) {
+                eyre::bail!(
+                    "there is more than one scratchpad named {}",
+                    scratchpad.name
+                );
+            }
+
+            validate_rules(&scratchpad.match_rules)?;
+        }
+
+        let mut workspace_set_names = HashSet::new();
+        for set in self.workspace_sets.iter().flatten() {
+            if !workspace_set_names.insert(&set.name) {
+                eyre::bail!("there is more than one workspace set named {}", set.name);
+            }
+
+            let Some(monitors) = &self.monitors else {
+                continue;
+            };
+
+            for member in &set.workspaces {
+                let Some(monitor) = monitors.get(member.monitor) else {
+                    eyre::bail!(
+                        "workspace set {} refers to monitor {} which is not configured",
+                        set.name,
+                        member.monitor
+                    );
+                };
+
+                if !monitor
+                    .workspaces
+                    .iter()
+                    .any(|workspace| workspace.name == member.workspace)
+                {
+                    eyre::bail!(
+                        "workspace set {} refers to workspace {} which is not configured on monitor {}",
+                        set.name,
+                        member.workspace,
+                        member.monitor
+                    );
+                }
+            }
+        }
+
+        for (name, profile) in self.profiles.iter().flatten() {
+            for (idx, overrides) in profile.monitors.iter().flatten() {
+                let Some(monitor) = self
+                    .monitors
+                    .as_ref()
+                    .and_then(|monitors| monitors.get(*idx))
+                else {
+                    eyre::bail!("profile {name} overrides monitor {idx} which is not configured");
+                };
+
+                for workspace in overrides.workspaces.iter().flatten() {
+                    if !monitor
+                        .workspaces
+                        .iter()
+                        .any(|configured| configured.name == workspace.name)
+                    {
+                        eyre::bail!(
+                            "profile {name} overrides workspace {} which is not configured on monitor {idx}",
+                            workspace.name
+                        );
+                    }
+                }
+            }
+        }
+
+        for path in self.app_specific_configuration_paths() {
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let asc = ApplicationSpecificConfiguration::load(&path)
+                    .wrap_err(format!("invalid applications.json at {}", path.display()))?;
+
+                for entry in asc.values() {
+                    if let AscApplicationRulesOrSchema::AscApplicationRules(entry) = entry {
+                        for rules in [&entry.ignore, &entry.manage, &entry.floating]
+                            .into_iter()
+                            .flatten()
+                        {
+                            validate_rules(rules)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        for monitor in self.monitors.iter().flatten() {
+            for workspace in &monitor.workspaces {
+                let custom_layout_paths = workspace.custom_layout.iter().chain(
+                    workspace
+                        .custom_layout_rules
+                        .iter()
+                        .flat_map(|rules| rules.values()),
+                );
+
+                for path in custom_layout_paths {
+                    CustomLayout::from_path(path).wrap_err(format!(
+                        "invalid custom layout for workspace {} at {}",
+                        workspace.name,
+                        path.display()
+                    ))?;
+                }
+            }
+        }
+
+        Ok(())
     }
 
     #[allow(clippy::too_many_lines)]
@@ -1179,7 +1403,7 @@ impl StaticConfig {
             applications: Default::default(),
             incoming_events: incoming,
             command_listener: listener,
-            space_id: current_space_id(),
+            space_id: platform::backend().space_id(),
             is_paused: false,
             // virtual_desktop_id: current_virtual_desktop(),
             work_area_offset: value.global_work_area_offset,
@@ -1213,30 +1437,32 @@ impl StaticConfig {
             mouse_follows_focus: value
                 .mouse_follows_focus
                 .unwrap_or(DEFAULT_MOUSE_FOLLOWS_FOCUS),
+            sticky_windows_follow_monitor_focus: value
+                .sticky_windows_follow_monitor_focus
+                .unwrap_or_default(),
+            focus_follows_mouse: None,
             hotwatch: Hotwatch::new()?,
+            static_config_path: Some(path.clone()),
+            watched_configuration_paths: vec![],
             run_loop: CoreFoundationRunLoop(run_loop.clone()),
             minimized_windows: HashMap::new(),
             pending_move_op: Arc::new(None),
             pending_resize_op: Arc::new(None),
             already_moved_window_handles: Default::default(),
             known_window_ids: HashMap::new(),
+            history: History::default(),
+            marks: HashMap::new(),
+            scratchpads: BTreeMap::new(),
+            workspace_sets: value.workspace_sets.clone().unwrap_or_default(),
+            last_focused_workspace_set: None,
+            active_profile: None,
         };
 
-        let bytes = SocketMessage::ReloadStaticConfiguration(path.clone()).as_bytes()?;
+        wm.set_scratchpads(value.scratchpads.clone().unwrap_or_default());
 
-        wm.hotwatch.watch(path, move |event| match event.kind {
-            // Editing in Notepad sends a NoticeWrite while editing in (Neo)Vim sends
-            // a NoticeRemove, presumably because of the use of swap files?
-            EventKind::Modify(_) | EventKind::Remove(_) => {
-                let socket = DATA_DIR.join("komorebi.sock");
-                let mut stream =
-                    UnixStream::connect(socket).expect("could not connect to komorebi.sock");
-                stream
-                    .write_all(&bytes)
-                    .expect("could not write to komorebi.sock");
-            }
-            _ => {}
-        })?;
+        wm.set_focus_follows_mouse(value.focus_follows_mouse);
+
+        wm.watch_configuration(true)?;
 
         Ok(wm)
     }
@@ -1404,7 +1630,13 @@ impl StaticConfig {
     }
 
     pub fn reload(path: &PathBuf, wm: &mut WindowManager) -> eyre::Result<()> {
-        let mut value = Self::read(path)?;
+        // nothing has been applied yet if either of these fail, so the current state is kept as-is
+        let mut value = Self::read(path)
+            .and_then(|value| value.validate().map(|()| value))
+            .wrap_err(format!(
+                "rejected invalid static configuration at {}",
+                path.display()
+            ))?;
 
         value.apply_globals()?;
 
@@ -1589,16 +1821,28 @@ impl StaticConfig {
         wm.mouse_follows_focus = value
             .mouse_follows_focus
             .unwrap_or(DEFAULT_MOUSE_FOLLOWS_FOCUS);
+        wm.sticky_windows_follow_monitor_focus = value
+            .sticky_windows_follow_monitor_focus
+            .unwrap_or_default();
         wm.work_area_offset = value.global_work_area_offset;
-        // wm.focus_follows_mouse = value.focus_follows_mouse;
-
-        // match wm.focus_follows_mouse {
-        //     None => WindowsApi::disable_focus_follows_mouse()?,
-        //     Some(FocusFollowsMouseImplementation::Windows) => {
-        //         WindowsApi::enable_focus_follows_mouse()?;
-        //     }
-        //     Some(FocusFollowsMouseImplementation::Komorebi) => {}
-        // };
+        wm.set_focus_follows_mouse(value.focus_follows_mouse);
+        wm.set_scratchpads(value.scratchpads.clone().unwrap_or_default());
+        wm.workspace_sets = value.workspace_sets.clone().unwrap_or_default();
+
+        // the active profile is layered back on top of the freshly loaded base configuration
+        if let Some(name) = wm.active_profile.clone() {
+            match value
+                .profiles
+                .as_ref()
+                .and_then(|profiles| profiles.get(&name))
+            {
+                Some(profile) => profile.merge(&value).apply_profile_settings(wm)?,
+                None => {
+                    tracing::info!("profile {name} is no longer configured, deactivating it");
+                    wm.active_profile = None;
+                }
+            }
+        }
 
         let monitor_count = wm.monitors().len();
 
@@ -1613,9 +1857,151 @@ impl StaticConfig {
 
         Ok(())
     }
+
+    /// Applies the settings which a profile is able to override, leaving rules and managed
+    /// windows untouched; settings which this configuration doesn't set are restored to their
+    /// defaults so that switching between profiles doesn't leave stale overrides behind
+    pub fn apply_profile_settings(&self, wm: &mut WindowManager) -> eyre::Result<()> {
+        DEFAULT_WORKSPACE_PADDING.store(
+            self.default_workspace_padding.unwrap_or(DEFAULT_PADDING),
+            Ordering::SeqCst,
+        );
+        DEFAULT_CONTAINER_PADDING.store(
+            self.default_container_padding.unwrap_or(DEFAULT_PADDING),
+            Ordering::SeqCst,
+        );
+
+        let layout_defaults = self.layout_defaults.clone().unwrap_or_default();
+        let layout_defaults_changed = {
+            let mut current = LAYOUT_DEFAULTS.lock();
+            let changed = *current != layout_defaults;
+            *current = layout_defaults;
+            changed
+        };
+
+        border_manager::BORDER_ENABLED.store(
+            self.border
+                .unwrap_or(border_manager::DEFAULT_BORDER_ENABLED),
+            Ordering::SeqCst,
+        );
+        border_manager::BORDER_WIDTH.store(
+            self.border_width
+                .unwrap_or(border_manager::DEFAULT_BORDER_WIDTH),
+            Ordering::SeqCst,
+        );
+        border_manager::BORDER_OFFSET.store(
+            self.border_offset
+                .unwrap_or(border_manager::DEFAULT_BORDER_OFFSET),
+            Ordering::SeqCst,
+        );
+        border_manager::BORDER_RADIUS.store(
+            self.border_radius
+                .unwrap_or(border_manager::DEFAULT_BORDER_RADIUS),
+            Ordering::SeqCst,
+        );
+
+        match &self.animation {
+            Some(animations) => apply_animations(animations),
+            None => reset_animations(),
+        }
+
+        transparency_manager::TRANSPARENCY_ENABLED
+            .store(self.transparency.unwrap_or(false), Ordering::SeqCst);
+        transparency_manager::TRANSPARENCY_ALPHA
+            .store(self.transparency_alpha.unwrap_or(200), Ordering::SeqCst);
+
+        wm.work_area_offset = self.global_work_area_offset;
+
+        let offset = wm.work_area_offset;
+        let monitor_usr_idx_map = wm.monitor_usr_idx_map.clone();
+
+        for (config_idx, monitor_config) in self.monitors.iter().flatten().enumerate() {
+            let monitor_idx = monitor_usr_idx_map
+                .get(&config_idx)
+                .copied()
+                .unwrap_or(config_idx);
+
+            let Some(monitor) = wm.monitors_mut().get_mut(monitor_idx) else {
+                continue;
+            };
+
+            monitor.container_padding = monitor_config.container_padding;
+            monitor.workspace_padding = monitor_config.workspace_padding;
+            monitor.work_area_offset = monitor_config.work_area_offset;
+            monitor.update_workspaces_globals(offset);
+
+            // only workspaces whose configuration differs are reloaded, so that any runtime
+            // changes to the other workspaces are kept
+            for (workspace, workspace_config) in monitor
+                .workspaces_mut()
+                .iter_mut()
+                .zip(&monitor_config.workspaces)
+            {
+                if layout_defaults_changed
+                    || workspace.workspace_config.as_ref() != Some(workspace_config)
+                {
+                    workspace
+                        .load_static_config(workspace_config, self.layout_defaults.as_ref())?;
+                }
+            }
+        }
+
+        wm.retile_all(true)
+    }
 }
 
-fn populate_rules(
+fn apply_animations(animations: &AnimationsConfig) {
+    match &animations.enabled {
+        PerAnimationPrefixConfig::Prefix(enabled) => {
+            ANIMATION_ENABLED_PER_ANIMATION.lock().clone_from(enabled);
+        }
+        PerAnimationPrefixConfig::Global(enabled) => {
+            ANIMATION_ENABLED_GLOBAL.store(*enabled, Ordering::SeqCst);
+            ANIMATION_ENABLED_PER_ANIMATION.lock().clear();
+        }
+    }
+
+    match &animations.style {
+        Some(PerAnimationPrefixConfig::Prefix(style)) => {
+            ANIMATION_STYLE_PER_ANIMATION.lock().clone_from(style);
+        }
+        Some(PerAnimationPrefixConfig::Global(style)) => {
+            let mut animation_style = ANIMATION_STYLE_GLOBAL.lock();
+            *animation_style = *style;
+            ANIMATION_STYLE_PER_ANIMATION.lock().clear();
+        }
+        None => {}
+    }
+
+    match &animations.duration {
+        Some(PerAnimationPrefixConfig::Prefix(duration)) => {
+            ANIMATION_DURATION_PER_ANIMATION.lock().clone_from(duration);
+        }
+        Some(PerAnimationPrefixConfig::Global(duration)) => {
+            ANIMATION_DURATION_GLOBAL.store(*duration, Ordering::SeqCst);
+            ANIMATION_DURATION_PER_ANIMATION.lock().clear();
+        }
+        None => {}
+    }
+
+    ANIMATION_FPS.store(
+        animations.fps.unwrap_or(DEFAULT_ANIMATION_FPS),
+        Ordering::SeqCst,
+    );
+}
+
+/// Restores every animation setting to its default value
+fn reset_animations() {
+    ANIMATION_ENABLED_GLOBAL.store(DEFAULT_ANIMATION_ENABLED, Ordering::SeqCst);
+    ANIMATION_ENABLED_PER_ANIMATION.lock().clear();
+    *ANIMATION_STYLE_GLOBAL.lock() = DEFAULT_ANIMATION_STYLE;
+    ANIMATION_STYLE_PER_ANIMATION.lock().clear();
+    ANIMATION_DURATION_GLOBAL.store(DEFAULT_ANIMATION_DURATION, Ordering::SeqCst);
+    ANIMATION_DURATION_PER_ANIMATION.lock().clear();
+    ANIMATION_FPS.store(DEFAULT_ANIMATION_FPS, Ordering::SeqCst);
+}
+
+pub(crate) fn populate_rules(
     matching_rules: &mut Vec<MatchingRule>,
     identifiers: &mut Vec<MatchingRule>,
     regex_identifiers: &mut HashMap<String, Regex>,
@@ -1653,8 +2039,26 @@ fn populate_rules(
     Ok(())
 }
 
+fn validate_rules(rules: &[MatchingRule]) -> eyre::Result<()> {
+    for rule in rules {
+        let simple_rules = match rule {
+            MatchingRule::Simple(simple) => std::slice::from_ref(simple),
+            MatchingRule::Composite(composite) => composite.as_slice(),
+        };
+
+        for simple in simple_rules {
+            if matches!(simple.matching_strategy, Some(MatchingStrategy::Regex)) {
+                Regex::new(&simple.id)
+                    .wrap_err(format!("invalid regular expression in rule: {}", simple.id))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
-fn handle_asc_file(
+pub(crate) fn handle_asc_file(
     path: &PathBuf,
     ignore_identifiers: &mut Vec<MatchingRule>,
     manage_identifiers: &mut Vec<MatchingRule>,
@@ -1716,3 +2120,251 @@ fn handle_asc_file(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::FakeBackend;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_validate_accepts_valid_config() {
+        let config = StaticConfig::read_raw(
+            r#"{
+                "ignore_rules": [
+                    { "kind": "Exe", "id": "^Finder$", "matching_strategy": "Regex" }
+                ],
+                "monitors": [{ "workspaces": [{ "name": "I", "layout": "BSP" }] }]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_regex() {
+        let config = StaticConfig::read_raw(
+            r#"{
+                "floating_applications": [
+                    [
+                        { "kind": "Exe", "id": "Finder" },
+                        { "kind": "Title", "id": "(unclosed", "matching_strategy": "Regex" }
+                    ]
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_duplicate_scratchpads() {
+        let config = StaticConfig::read_raw(
+            r#"{
+                "scratchpads": [
+                    { "name": "terminal", "match_rules": [{ "kind": "Exe", "id": "Ghostty" }] },
+                    { "name": "terminal", "match_rules": [{ "kind": "Exe", "id": "kitty" }] }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_workspace_sets() {
+        let config = |sets: &str| {
+            StaticConfig::read_raw(&format!(
+                r#"{{
+                    "monitors": [
+                        {{ "workspaces": [{{ "name": "code" }}] }},
+                        {{ "workspaces": [{{ "name": "docs" }}] }}
+                    ],
+                    "workspace_sets": {sets}
+                }}"#
+            ))
+            .unwrap()
+        };
+
+        assert!(
+            config(
+                r#"[{ "name": "coding", "workspaces": [
+                    { "monitor": 0, "workspace": "code" },
+                    { "monitor": 1, "workspace": "docs" }
+                ] }]"#
+            )
+            .validate()
+            .is_ok()
+        );
+
+        // duplicate names
+        assert!(
+            config(
+                r#"[
+                    { "name": "coding", "workspaces": [{ "monitor": 0, "workspace": "code" }] },
+                    { "name": "coding", "workspaces": [{ "monitor": 1, "workspace": "docs" }] }
+                ]"#
+            )
+            .validate()
+            .is_err()
+        );
+
+        // unknown workspace on a configured monitor
+        assert!(
+            config(
+                r#"[{ "name": "coding", "workspaces": [{ "monitor": 0, "workspace": "docs" }] }]"#
+            )
+            .validate()
+            .is_err()
+        );
+
+        // unknown monitor
+        assert!(
+            config(
+                r#"[{ "name": "coding", "workspaces": [{ "monitor": 2, "workspace": "code" }] }]"#
+            )
+            .validate()
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_profiles() {
+        let config = |profile: &str| {
+            StaticConfig::read_raw(&format!(
+                r#"{{
+                    "monitors": [{{ "workspaces": [{{ "name": "code" }}] }}],
+                    "profiles": {{ "presenting": {profile} }}
+                }}"#
+            ))
+            .unwrap()
+        };
+
+        assert!(config(r#"{ "border": false }"#).validate().is_ok());
+
+        assert!(
+            config(
+                r#"{ "monitors": { "0": { "workspaces": [{ "name": "code", "tile": false }] } } }"#
+            )
+            .validate()
+            .is_ok()
+        );
+
+        // unknown workspace on a configured monitor
+        assert!(
+            config(r#"{ "monitors": { "0": { "workspaces": [{ "name": "docs" }] } } }"#)
+                .validate()
+                .is_err()
+        );
+
+        // unknown monitor
+        assert!(
+            config(r#"{ "monitors": { "1": { "workspace_padding": 0 } } }"#)
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_apply_profile_settings() {
+        let backend = Arc::new(FakeBackend::default());
+        let _backend = platform::scoped_backend(backend);
+
+        let socket = std::env::temp_dir().join(format!("komorebi-test-{}.sock", Uuid::new_v4()));
+        let (_sender, receiver) = crossbeam_channel::bounded(1);
+        let mut wm =
+            WindowManager::new(&CFRunLoop::main().unwrap(), receiver, Some(socket.clone()))
+                .unwrap();
+
+        wm.monitors_mut().push_back(monitor::new(
+            0,
+            Rect::default(),
+            Rect::default(),
+            "TestMonitor".to_string(),
+            "TestDeviceID".to_string(),
+        ));
+
+        let base = StaticConfig::read_raw(
+            r#"{
+                "border_width": 4,
+                "monitors": [{ "workspaces": [{ "name": "code", "layout": "BSP" }] }],
+                "profiles": {
+                    "presenting": {
+                        "border_width": 2,
+                        "global_work_area_offset": { "left": 0, "top": 40, "right": 0, "bottom": 40 },
+                        "monitors": {
+                            "0": {
+                                "workspace_padding": 0,
+                                "workspaces": [{ "name": "code", "tile": false }]
+                            }
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let profile = &base.profiles.as_ref().unwrap()["presenting"];
+
+        base.apply_profile_settings(&mut wm).unwrap();
+        assert_eq!(border_manager::BORDER_WIDTH.load(Ordering::SeqCst), 4);
+        assert!(wm.focused_workspace().unwrap().tile);
+
+        // activating the profile applies its overrides on top of the base configuration
+        profile
+            .merge(&base)
+            .apply_profile_settings(&mut wm)
+            .unwrap();
+        assert_eq!(border_manager::BORDER_WIDTH.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            wm.work_area_offset,
+            Some(Rect {
+                left: 0,
+                top: 40,
+                right: 0,
+                bottom: 40
+            })
+        );
+        assert_eq!(wm.focused_monitor().unwrap().workspace_padding, Some(0));
+        assert!(!wm.focused_workspace().unwrap().tile);
+
+        // switching back to the base configuration doesn't leave any of the overrides behind
+        base.apply_profile_settings(&mut wm).unwrap();
+        assert_eq!(border_manager::BORDER_WIDTH.load(Ordering::SeqCst), 4);
+        assert_eq!(wm.work_area_offset, None);
+        assert_eq!(wm.focused_monitor().unwrap().workspace_padding, None);
+        assert!(wm.focused_workspace().unwrap().tile);
+
+        std::fs::remove_file(socket).unwrap();
+    }
+
+    #[test]
+    fn test_validate_rejects_missing_files() {
+        let missing = std::env::temp_dir().join(format!("{}.json", uuid::Uuid::new_v4()));
+
+        let config = StaticConfig {
+            app_specific_configuration_path: Some(AppSpecificConfigurationPath::Single(
+                missing.clone(),
+            )),
+            ..Default::default()
+        };
+
+        assert!(config.validate().is_err());
+
+        let config = StaticConfig {
+            monitors: Some(vec![MonitorConfig {
+                workspaces: vec![WorkspaceConfig {
+                    custom_layout_rules: Some(HashMap::from([(2, missing)])),
+                    ..serde_json::from_str(r#"{ "name": "I" }"#).unwrap()
+                }],
+                ..serde_json::from_str(r#"{ "workspaces": [] }"#).unwrap()
+            }]),
+            ..Default::default()
+        };
+
+        assert!(config.validate().is_err());
+    }
+}
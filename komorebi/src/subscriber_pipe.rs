@@ -0,0 +1,291 @@
+use std::ffi::CString;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+use std::io::Write;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// A FIFO subscriber which is sent every notification as a line of JSON
+///
+/// The write end of the FIFO is only opened once a reader has opened the other end, so a
+/// subscriber can be registered before anything is reading from it. Notifications pushed while
+/// there is no reader are skipped.
+///
+/// Writes never block the window manager. Whatever part of a notification doesn't fit in the FIFO
+/// is held back and written before the next notification, so a reader never sees a partial line,
+/// and a reader which falls more than [`MAX_PENDING_BYTES`] behind is treated in the same way as a
+/// reader which has gone away.
+#[derive(Debug)]
+pub struct SubscriberPipe {
+    path: PathBuf,
+    writer: Option<File>,
+    pending: Vec<u8>,
+    created: bool,
+}
+
+/// How much unread notification data is held back for a reader before it is considered to have
+/// stopped reading
+pub const MAX_PENDING_BYTES: usize = 4 * 1024 * 1024;
+
+/// Resolves a client-supplied pipe name to a path inside `dir`
+///
+/// Names with path separators or which refer to `.` or `..` are rejected, so that clients can't
+/// create or remove files anywhere else
+pub fn pipe_path(dir: &Path, name: &str) -> std::io::Result<PathBuf> {
+    let mut components = Path::new(name).components();
+
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) if !name.contains(std::path::MAIN_SEPARATOR) => {
+            Ok(dir.join(name))
+        }
+        _ => Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("{name} is not a valid subscriber pipe name"),
+        )),
+    }
+}
+
+impl SubscriberPipe {
+    /// Creates the FIFO at `path` if there isn't one there already
+    pub fn create(path: PathBuf) -> std::io::Result<Self> {
+        let created = match std::fs::metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_fifo() => false,
+            Ok(_) => {
+                return Err(std::io::Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a fifo", path.display()),
+                ));
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                let c_path = CString::new(path.as_os_str().as_bytes())?;
+                if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+
+                true
+            }
+            Err(error) => return Err(error),
+        };
+
+        Ok(Self {
+            path,
+            writer: None,
+            pending: vec![],
+            created,
+        })
+    }
+
+    /// Closes the write end and removes the FIFO if it was created by [`SubscriberPipe::create`]
+    pub fn close(self) -> std::io::Result<()> {
+        if !self.created {
+            return Ok(());
+        }
+
+        match std::fs::remove_file(&self.path) {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes a notification to the pipe, followed by a newline
+    ///
+    /// An error means the subscriber has gone away, either because the FIFO was removed or
+    /// because the reader closed its end after the write end was opened, or that it has stopped
+    /// reading, in which case the error is [`ErrorKind::WouldBlock`]
+    pub fn push(&mut self, notification: &str) -> std::io::Result<()> {
+        if self.writer.is_none() {
+            match open_writer(&self.path)? {
+                Some(writer) => self.writer = Some(writer),
+                None => return Ok(()),
+            }
+        }
+
+        if self.pending.len() + notification.len() + 1 > MAX_PENDING_BYTES {
+            return Err(std::io::Error::new(
+                ErrorKind::WouldBlock,
+                "the reader has fallen too far behind",
+            ));
+        }
+
+        self.pending.extend_from_slice(notification.as_bytes());
+        self.pending.push(b'\n');
+
+        self.write_pending()
+    }
+
+    /// Writes as much of the held back notification data as the FIFO will take without blocking
+    fn write_pending(&mut self) -> std::io::Result<()> {
+        let Some(writer) = &mut self.writer else {
+            return Ok(());
+        };
+
+        while !self.pending.is_empty() {
+            match writer.write(&self.pending) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.pending.drain(..written);
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Opens the non-blocking write end of a FIFO, returning `None` if there is no reader yet
+fn open_writer(path: &Path) -> std::io::Result<Option<File>> {
+    match OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)
+    {
+        Ok(file) => Ok(Some(file)),
+        Err(error) if error.raw_os_error() == Some(libc::ENXIO) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::io::Read;
+
+    #[test]
+    fn test_push_to_fifo() {
+        let path = std::env::temp_dir().join(format!("komorebi-test-{}.fifo", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut pipe = SubscriberPipe::create(path.clone()).unwrap();
+        assert!(std::fs::metadata(&path).unwrap().file_type().is_fifo());
+
+        // there is no reader yet, so this notification is skipped
+        pipe.push(r#"{"skipped":true}"#).unwrap();
+
+        let reader = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)
+            .unwrap();
+
+        pipe.push(r#"{"first":true}"#).unwrap();
+        pipe.push(r#"{"second":true}"#).unwrap();
+
+        let mut lines = BufReader::new(&reader).lines();
+        assert_eq!(lines.next().unwrap().unwrap(), r#"{"first":true}"#);
+        assert_eq!(lines.next().unwrap().unwrap(), r#"{"second":true}"#);
+
+        // the subscriber has gone away once the reader is closed
+        drop(lines);
+        drop(reader);
+        assert!(pipe.push(r#"{"third":true}"#).is_err());
+
+        pipe.close().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_notifications_larger_than_the_fifo_are_never_split() {
+        let path =
+            std::env::temp_dir().join(format!("komorebi-test-{}-large.fifo", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut pipe = SubscriberPipe::create(path.clone()).unwrap();
+        let mut reader = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)
+            .unwrap();
+
+        // much larger than the fifo buffer, so only part of it can be written straight away
+        let large = "x".repeat(512 * 1024);
+        pipe.push(&large).unwrap();
+        pipe.push("small").unwrap();
+
+        let mut received = vec![];
+        let mut buf = [0; 8192];
+        while received.iter().filter(|byte| **byte == b'\n').count() < 2 {
+            match reader.read(&mut buf) {
+                Ok(read) => received.extend_from_slice(&buf[..read]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                    pipe.write_pending().unwrap();
+                }
+                Err(error) => panic!("{error}"),
+            }
+        }
+
+        assert_eq!(received, format!("{large}\nsmall\n").into_bytes());
+
+        pipe.close().unwrap();
+    }
+
+    #[test]
+    fn test_reader_which_stops_reading_is_dropped() {
+        let path =
+            std::env::temp_dir().join(format!("komorebi-test-{}-full.fifo", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut pipe = SubscriberPipe::create(path.clone()).unwrap();
+        let _reader = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)
+            .unwrap();
+
+        // nothing is read, so notifications are held back until too many of them pile up
+        let notification = "x".repeat(64 * 1024);
+        let error = (0..MAX_PENDING_BYTES / notification.len() + 16)
+            .find_map(|_| pipe.push(&notification).err())
+            .unwrap();
+        assert_eq!(error.kind(), ErrorKind::WouldBlock);
+
+        pipe.close().unwrap();
+    }
+
+    #[test]
+    fn test_pipe_path_rejects_other_directories() {
+        let dir = Path::new("/data/komorebi");
+
+        assert_eq!(
+            pipe_path(dir, "bar.fifo").unwrap(),
+            PathBuf::from("/data/komorebi/bar.fifo")
+        );
+
+        for name in [
+            "",
+            ".",
+            "..",
+            "../bar.fifo",
+            "nested/bar.fifo",
+            "/tmp/bar.fifo",
+        ] {
+            assert!(pipe_path(dir, name).is_err(), "{name} was accepted");
+        }
+    }
+
+    #[test]
+    fn test_close_leaves_existing_fifos_alone() {
+        let path = std::env::temp_dir().join(format!(
+            "komorebi-test-{}-existing.fifo",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        SubscriberPipe::create(path.clone()).unwrap();
+        let pipe = SubscriberPipe::create(path.clone()).unwrap();
+
+        pipe.close().unwrap();
+        assert!(path.exists());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
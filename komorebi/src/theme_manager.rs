@@ -1,9 +1,11 @@
 #![deny(clippy::unwrap_used, clippy::expect_used)]
 
 use crate::border_manager;
+use crate::stackbar_manager;
 use crossbeam_channel::Receiver;
 use crossbeam_channel::Sender;
 use crossbeam_utils::atomic::AtomicCell;
+use komorebi_themes::Base16Value;
 use komorebi_themes::Base16Wrapper;
 use komorebi_themes::KomorebiTheme;
 use komorebi_themes::KomorebiThemeBase16;
@@ -18,6 +20,21 @@ pub struct Notification(KomorebiTheme);
 
 pub static CURRENT_THEME: AtomicCell<Option<KomorebiTheme>> = AtomicCell::new(None);
 
+/// Stackbar colour overrides for custom themes, these are set from wallpaper `ThemeOptions`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct StackbarThemeOptions {
+    pub focused_text: Option<Base16Value>,
+    pub unfocused_text: Option<Base16Value>,
+    pub background: Option<Base16Value>,
+}
+
+pub static STACKBAR_THEME_OPTIONS: AtomicCell<StackbarThemeOptions> =
+    AtomicCell::new(StackbarThemeOptions {
+        focused_text: None,
+        unfocused_text: None,
+        background: None,
+    });
+
 impl Deref for Notification {
     type Target = KomorebiTheme;
 
@@ -211,6 +228,37 @@ pub fn handle_notifications() -> color_eyre::Result<()> {
             }
         };
 
+        let (stackbar_focused_text, stackbar_unfocused_text, stackbar_background) = match theme {
+            KomorebiTheme::Catppuccin(KomorebiThemeCatppuccin { name, .. }) => (
+                komorebi_themes::CatppuccinValue::Green.color32(name.as_theme()),
+                komorebi_themes::CatppuccinValue::Text.color32(name.as_theme()),
+                komorebi_themes::CatppuccinValue::Base.color32(name.as_theme()),
+            ),
+            KomorebiTheme::Base16(KomorebiThemeBase16 { name, .. }) => (
+                Base16Value::Base0B.color32(Base16Wrapper::Base16(*name)),
+                Base16Value::Base05.color32(Base16Wrapper::Base16(*name)),
+                Base16Value::Base01.color32(Base16Wrapper::Base16(*name)),
+            ),
+            KomorebiTheme::Custom(KomorebiThemeCustom { colours, .. }) => {
+                let options = STACKBAR_THEME_OPTIONS.load();
+
+                (
+                    options
+                        .focused_text
+                        .unwrap_or(Base16Value::Base0B)
+                        .color32(Base16Wrapper::Custom(colours.clone())),
+                    options
+                        .unfocused_text
+                        .unwrap_or(Base16Value::Base05)
+                        .color32(Base16Wrapper::Custom(colours.clone())),
+                    options
+                        .background
+                        .unwrap_or(Base16Value::Base01)
+                        .color32(Base16Wrapper::Custom(colours.clone())),
+                )
+            }
+        };
+
         border_manager::FOCUSED.store(u32::from(Colour::from(single_border)), Ordering::SeqCst);
         border_manager::MONOCLE.store(u32::from(Colour::from(monocle_border)), Ordering::SeqCst);
         border_manager::STACK.store(u32::from(Colour::from(stack_border)), Ordering::SeqCst);
@@ -222,9 +270,33 @@ pub fn handle_notifications() -> color_eyre::Result<()> {
             Ordering::SeqCst,
         );
 
+        let explicit_tab_colours = stackbar_manager::STACKBAR_EXPLICIT_TAB_COLOURS.load();
+
+        if !explicit_tab_colours.focused_text {
+            stackbar_manager::STACKBAR_FOCUSED_TEXT_COLOUR.store(
+                u32::from(Colour::from(stackbar_focused_text)),
+                Ordering::SeqCst,
+            );
+        }
+
+        if !explicit_tab_colours.unfocused_text {
+            stackbar_manager::STACKBAR_UNFOCUSED_TEXT_COLOUR.store(
+                u32::from(Colour::from(stackbar_unfocused_text)),
+                Ordering::SeqCst,
+            );
+        }
+
+        if !explicit_tab_colours.background {
+            stackbar_manager::STACKBAR_TAB_BACKGROUND_COLOUR.store(
+                u32::from(Colour::from(stackbar_background)),
+                Ordering::SeqCst,
+            );
+        }
+
         CURRENT_THEME.store(Some(notification.0));
 
         border_manager::destroy_all_borders()?;
+        stackbar_manager::send_notification();
     }
 
     Ok(())
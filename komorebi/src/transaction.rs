@@ -0,0 +1,153 @@
+//! Support for processing a batch of commands as a single [`SocketMessage::Transaction`]
+//!
+//! While a transaction is in progress on a thread, windows are not moved, hidden, restored or
+//! focused from that thread, and subscribers are not notified after each step. The final layout is
+//! applied once every step has been processed successfully, and nothing has to be undone on screen
+//! if a step fails.
+//!
+//! Only the commands accepted by [`is_allowed`] can be used as steps. They only change the
+//! arrangement of monitors, workspaces, containers and windows, which is exactly the state that is
+//! rolled back when a step fails.
+
+use crate::core::SocketMessage;
+use color_eyre::eyre;
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+thread_local! {
+    static IN_PROGRESS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether a transaction is being processed on the current thread
+pub fn in_progress() -> bool {
+    IN_PROGRESS.get()
+}
+
+/// Whether a command can be used as a step of a transaction
+///
+/// Commands which change settings, rules, subscribers or configuration, act on windows in ways
+/// that can't be deferred, or read and write files other than layouts are rejected, as they
+/// couldn't be rolled back if a later step failed
+pub fn is_allowed(message: &SocketMessage) -> bool {
+    matches!(
+        message,
+        SocketMessage::FocusWindow(_)
+            | SocketMessage::MoveWindow(_)
+            | SocketMessage::CycleFocusWindow(_)
+            | SocketMessage::CycleMoveWindow(_)
+            | SocketMessage::StackWindow(_)
+            | SocketMessage::UnstackWindow
+            | SocketMessage::CycleStack(_)
+            | SocketMessage::CycleStackIndex(_)
+            | SocketMessage::FocusStackWindow(_)
+            | SocketMessage::StackAll
+            | SocketMessage::UnstackAll
+            | SocketMessage::ResizeWindowEdge(_, _)
+            | SocketMessage::ResizeWindowAxis(_, _)
+            | SocketMessage::MoveContainerToLastWorkspace
+            | SocketMessage::SendContainerToLastWorkspace
+            | SocketMessage::MoveContainerToMonitorNumber(_)
+            | SocketMessage::CycleMoveContainerToMonitor(_)
+            | SocketMessage::MoveContainerToWorkspaceNumber(_)
+            | SocketMessage::MoveContainerToNamedWorkspace(_)
+            | SocketMessage::CycleMoveContainerToWorkspace(_)
+            | SocketMessage::SendContainerToMonitorNumber(_)
+            | SocketMessage::CycleSendContainerToMonitor(_)
+            | SocketMessage::SendContainerToWorkspaceNumber(_)
+            | SocketMessage::CycleSendContainerToWorkspace(_)
+            | SocketMessage::SendContainerToMonitorWorkspaceNumber(_, _)
+            | SocketMessage::MoveContainerToMonitorWorkspaceNumber(_, _)
+            | SocketMessage::SendContainerToNamedWorkspace(_)
+            | SocketMessage::CycleMoveWorkspaceToMonitor(_)
+            | SocketMessage::MoveWorkspaceToMonitorNumber(_)
+            | SocketMessage::SwapWorkspacesToMonitorNumber(_)
+            | SocketMessage::Promote
+            | SocketMessage::PromoteSwap
+            | SocketMessage::PromoteFocus
+            | SocketMessage::PromoteWindow(_)
+            | SocketMessage::LockMonitorWorkspaceContainer(_, _, _)
+            | SocketMessage::UnlockMonitorWorkspaceContainer(_, _, _)
+            | SocketMessage::ToggleLock
+            | SocketMessage::ToggleFloat
+            | SocketMessage::ToggleMonocle
+            | SocketMessage::ToggleWorkspaceWindowContainerBehaviour
+            | SocketMessage::ToggleWorkspaceFloatOverride
+            | SocketMessage::ToggleWorkspaceLayer
+            | SocketMessage::AdjustContainerPadding(_, _)
+            | SocketMessage::AdjustWorkspacePadding(_, _)
+            | SocketMessage::ChangeLayout(_)
+            | SocketMessage::CycleLayout(_)
+            | SocketMessage::ScrollingLayoutColumns(_)
+            | SocketMessage::LayoutRatios(_, _)
+            | SocketMessage::ChangeLayoutCustom(_)
+            | SocketMessage::FlipLayout(_)
+            | SocketMessage::ToggleTiling
+            | SocketMessage::Retile
+            | SocketMessage::RetileWithResizeDimensions
+            | SocketMessage::CycleFocusMonitor(_)
+            | SocketMessage::CycleFocusWorkspace(_)
+            | SocketMessage::CycleFocusEmptyWorkspace(_)
+            | SocketMessage::FocusMonitorNumber(_)
+            | SocketMessage::FocusLastWorkspace
+            | SocketMessage::FocusWorkspaceNumber(_)
+            | SocketMessage::FocusWorkspaceNumbers(_)
+            | SocketMessage::FocusMonitorWorkspaceNumber(_, _)
+            | SocketMessage::FocusNamedWorkspace(_)
+            | SocketMessage::ContainerPadding(_, _, _)
+            | SocketMessage::NamedWorkspaceContainerPadding(_, _)
+            | SocketMessage::FocusedWorkspaceContainerPadding(_)
+            | SocketMessage::WorkspacePadding(_, _, _)
+            | SocketMessage::NamedWorkspacePadding(_, _)
+            | SocketMessage::FocusedWorkspacePadding(_)
+            | SocketMessage::WorkspaceTiling(_, _, _)
+            | SocketMessage::NamedWorkspaceTiling(_, _)
+            | SocketMessage::WorkspaceName(_, _, _)
+            | SocketMessage::WorkspaceLayout(_, _, _)
+            | SocketMessage::NamedWorkspaceLayout(_, _)
+            | SocketMessage::WorkspaceLayoutCustom(_, _, _)
+            | SocketMessage::NamedWorkspaceLayoutCustom(_, _)
+    )
+}
+
+/// Marks a transaction as being in progress on the current thread until it is dropped
+#[must_use]
+pub struct TransactionGuard {
+    // the guard has to be dropped on the thread it was created on
+    _thread: PhantomData<*const ()>,
+}
+
+impl TransactionGuard {
+    pub fn begin() -> eyre::Result<Self> {
+        if IN_PROGRESS.replace(true) {
+            eyre::bail!("transactions cannot be nested");
+        }
+
+        Ok(Self {
+            _thread: PhantomData,
+        })
+    }
+}
+
+impl Drop for TransactionGuard {
+    fn drop(&mut self) {
+        IN_PROGRESS.set(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transactions_are_scoped_to_the_current_thread() {
+        let guard = TransactionGuard::begin().unwrap();
+        assert!(in_progress());
+        assert!(TransactionGuard::begin().is_err());
+
+        // windows can still be moved from other threads, such as animations or the reaper
+        assert!(!std::thread::spawn(in_progress).join().unwrap());
+
+        drop(guard);
+        assert!(!in_progress());
+    }
+}
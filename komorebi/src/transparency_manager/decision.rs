@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+/// The alpha of a fully opaque window
+pub const OPAQUE: u8 = 255;
+
+/// A window which is currently visible on the focused workspace of a monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    pub window_id: u32,
+    pub focused: bool,
+    /// Whether the window matches one of the transparency ignore rules
+    pub ignored: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlphaChange {
+    pub window_id: u32,
+    pub from: u8,
+    pub to: u8,
+}
+
+pub fn target_alpha(candidate: &Candidate, unfocused_alpha: u8) -> u8 {
+    if candidate.focused || candidate.ignored {
+        OPAQUE
+    } else {
+        unfocused_alpha
+    }
+}
+
+/// Works out which windows need a new alpha, given the alpha that was last applied to each window
+///
+/// Windows which are no longer candidates, because they were closed, are on a workspace which is
+/// no longer visible or because transparency was disabled, are made opaque again
+pub fn changes(
+    applied: &HashMap<u32, u8>,
+    candidates: &[Candidate],
+    unfocused_alpha: u8,
+) -> Vec<AlphaChange> {
+    let mut changes = vec![];
+
+    for candidate in candidates {
+        let from = applied.get(&candidate.window_id).copied().unwrap_or(OPAQUE);
+        let to = target_alpha(candidate, unfocused_alpha);
+
+        if from != to {
+            changes.push(AlphaChange {
+                window_id: candidate.window_id,
+                from,
+                to,
+            });
+        }
+    }
+
+    let mut restored = applied
+        .iter()
+        .filter(|(window_id, alpha)| {
+            **alpha != OPAQUE && !candidates.iter().any(|c| c.window_id == **window_id)
+        })
+        .map(|(window_id, alpha)| AlphaChange {
+            window_id: *window_id,
+            from: *alpha,
+            to: OPAQUE,
+        })
+        .collect::<Vec<_>>();
+
+    restored.sort_by_key(|change| change.window_id);
+    changes.extend(restored);
+
+    changes
+}
+
+/// Records changes once the backend has applied them; opaque windows are not tracked
+pub fn record(applied: &mut HashMap<u32, u8>, changes: &[AlphaChange]) {
+    for change in changes {
+        if change.to == OPAQUE {
+            applied.remove(&change.window_id);
+        } else {
+            applied.insert(change.window_id, change.to);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(window_id: u32, focused: bool) -> Candidate {
+        Candidate {
+            window_id,
+            focused,
+            ignored: false,
+        }
+    }
+
+    #[test]
+    fn test_unfocused_windows_become_transparent() {
+        let candidates = [candidate(1, true), candidate(2, false), candidate(3, false)];
+
+        assert_eq!(
+            changes(&HashMap::new(), &candidates, 200),
+            vec![
+                AlphaChange {
+                    window_id: 2,
+                    from: OPAQUE,
+                    to: 200
+                },
+                AlphaChange {
+                    window_id: 3,
+                    from: OPAQUE,
+                    to: 200
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ignored_windows_stay_opaque() {
+        let candidates = [
+            candidate(1, true),
+            Candidate {
+                window_id: 2,
+                focused: false,
+                ignored: true,
+            },
+        ];
+
+        assert!(changes(&HashMap::new(), &candidates, 200).is_empty());
+    }
+
+    #[test]
+    fn test_focus_change_swaps_alpha() {
+        let mut applied = HashMap::new();
+        let candidates = [candidate(1, true), candidate(2, false)];
+        record(&mut applied, &changes(&applied, &candidates, 200));
+
+        assert_eq!(applied, HashMap::from([(2, 200)]));
+
+        let candidates = [candidate(1, false), candidate(2, true)];
+        let focus_changes = changes(&applied, &candidates, 200);
+
+        assert_eq!(
+            focus_changes,
+            vec![
+                AlphaChange {
+                    window_id: 1,
+                    from: OPAQUE,
+                    to: 200
+                },
+                AlphaChange {
+                    window_id: 2,
+                    from: 200,
+                    to: OPAQUE
+                },
+            ]
+        );
+
+        record(&mut applied, &focus_changes);
+        assert_eq!(applied, HashMap::from([(1, 200)]));
+
+        // nothing to do when nothing has changed
+        assert!(changes(&applied, &candidates, 200).is_empty());
+    }
+
+    #[test]
+    fn test_windows_which_are_no_longer_candidates_are_restored() {
+        let applied = HashMap::from([(2, 200), (3, 150)]);
+
+        assert_eq!(
+            changes(&applied, &[], 200),
+            vec![
+                AlphaChange {
+                    window_id: 2,
+                    from: 200,
+                    to: OPAQUE
+                },
+                AlphaChange {
+                    window_id: 3,
+                    from: 150,
+                    to: OPAQUE
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alpha_change_is_applied_to_transparent_windows() {
+        let applied = HashMap::from([(2, 200)]);
+        let candidates = [candidate(1, true), candidate(2, false)];
+
+        assert_eq!(
+            changes(&applied, &candidates, 100),
+            vec![AlphaChange {
+                window_id: 2,
+                from: 200,
+                to: 100
+            }]
+        );
+    }
+}
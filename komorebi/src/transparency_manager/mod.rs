@@ -0,0 +1,312 @@
+pub mod decision;
+
+use crate::REGEX_IDENTIFIERS;
+use crate::TRANSPARENCY_BLACKLIST;
+use crate::animation::ANIMATION_DURATION_GLOBAL;
+use crate::animation::ANIMATION_DURATION_PER_ANIMATION;
+use crate::animation::ANIMATION_ENABLED_GLOBAL;
+use crate::animation::ANIMATION_ENABLED_PER_ANIMATION;
+use crate::animation::ANIMATION_STYLE_GLOBAL;
+use crate::animation::ANIMATION_STYLE_PER_ANIMATION;
+use crate::animation::AnimationEngine;
+use crate::animation::AnimationPrefix;
+use crate::animation::RenderDispatcher;
+use crate::animation::lerp::Lerp;
+use crate::animation::prefix::new_animation_key;
+use crate::core::animation::AnimationStyle;
+use crate::macos_api::MacosApi;
+use crate::skylight::CGSMainConnectionID;
+use crate::skylight::SLSSetWindowAlpha;
+use crate::transparency_manager::decision::AlphaChange;
+use crate::transparency_manager::decision::Candidate;
+use crate::transparency_manager::decision::OPAQUE;
+use crate::window::Window;
+use crate::window::should_act;
+use crate::window_manager::WindowManager;
+use color_eyre::eyre;
+use crossbeam_channel::Receiver;
+use crossbeam_channel::Sender;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+pub static TRANSPARENCY_ENABLED: AtomicBool = AtomicBool::new(false);
+pub static TRANSPARENCY_ALPHA: AtomicU8 = AtomicU8::new(200);
+
+lazy_static! {
+    /// The last alpha sent to the backend for every window which is not opaque
+    static ref APPLIED_ALPHAS: Mutex<HashMap<u32, u8>> = Mutex::new(HashMap::new());
+}
+
+static BACKEND: OnceLock<Arc<dyn TransparencyBackend>> = OnceLock::new();
+
+/// Applies alpha values to windows on behalf of the transparency manager
+pub trait TransparencyBackend: Send + Sync {
+    fn set_alpha(&self, window_id: u32, alpha: u8) -> eyre::Result<()>;
+}
+
+/// Sets window alpha using the private SkyLight API
+///
+/// The window server can refuse to change the alpha of windows owned by other applications
+/// depending on the version of macOS, in which case every attempt is logged and skipped
+pub struct SkyLightBackend;
+
+impl TransparencyBackend for SkyLightBackend {
+    fn set_alpha(&self, window_id: u32, alpha: u8) -> eyre::Result<()> {
+        let error = unsafe {
+            SLSSetWindowAlpha(CGSMainConnectionID(), window_id, f32::from(alpha) / 255.0)
+        };
+
+        if error != 0 {
+            eyre::bail!("could not set alpha {alpha} on window {window_id}: error {error}");
+        }
+
+        Ok(())
+    }
+}
+
+pub struct Notification;
+
+static CHANNEL: OnceLock<(Sender<Notification>, Receiver<Notification>)> = OnceLock::new();
+
+pub fn channel() -> &'static (Sender<Notification>, Receiver<Notification>) {
+    CHANNEL.get_or_init(|| crossbeam_channel::bounded(50))
+}
+
+fn event_tx() -> Sender<Notification> {
+    channel().0.clone()
+}
+
+fn event_rx() -> Receiver<Notification> {
+    channel().1.clone()
+}
+
+pub fn send_notification() {
+    if event_tx().try_send(Notification).is_err() {
+        tracing::warn!("channel is full; dropping notification")
+    }
+}
+
+/// Render dispatcher for fading window alpha changes
+pub struct TransparencyRenderDispatcher {
+    backend: Arc<dyn TransparencyBackend>,
+    change: AlphaChange,
+    style: AnimationStyle,
+}
+
+impl TransparencyRenderDispatcher {
+    pub const PREFIX: AnimationPrefix = AnimationPrefix::Transparency;
+
+    pub fn new(
+        backend: Arc<dyn TransparencyBackend>,
+        change: AlphaChange,
+        style: AnimationStyle,
+    ) -> Self {
+        Self {
+            backend,
+            change,
+            style,
+        }
+    }
+}
+
+impl RenderDispatcher for TransparencyRenderDispatcher {
+    fn get_animation_key(&self) -> String {
+        new_animation_key(
+            TransparencyRenderDispatcher::PREFIX,
+            self.change.window_id.to_string(),
+        )
+    }
+
+    fn pre_render(&self) -> eyre::Result<()> {
+        Ok(())
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn render(&self, progress: f64) -> eyre::Result<()> {
+        let alpha = f64::from(self.change.from)
+            .lerp(f64::from(self.change.to), progress, self.style)
+            .round()
+            .clamp(0.0, 255.0) as u8;
+
+        self.backend.set_alpha(self.change.window_id, alpha)
+    }
+
+    fn post_render(&self) -> eyre::Result<()> {
+        self.backend
+            .set_alpha(self.change.window_id, self.change.to)?;
+
+        // cancelled animations never get here, so only alphas which were reached are recorded
+        decision::record(&mut APPLIED_ALPHAS.lock(), &[self.change]);
+
+        Ok(())
+    }
+}
+
+/// The style and duration to fade with, if transparency animations are enabled
+fn animation() -> Option<(AnimationStyle, Duration)> {
+    let prefix = TransparencyRenderDispatcher::PREFIX;
+
+    let enabled = ANIMATION_ENABLED_PER_ANIMATION
+        .lock()
+        .get(&prefix)
+        .copied()
+        .unwrap_or_else(|| ANIMATION_ENABLED_GLOBAL.load(Ordering::SeqCst));
+
+    if !enabled {
+        return None;
+    }
+
+    let style = ANIMATION_STYLE_PER_ANIMATION
+        .lock()
+        .get(&prefix)
+        .copied()
+        .unwrap_or_else(|| *ANIMATION_STYLE_GLOBAL.lock());
+
+    let duration = ANIMATION_DURATION_PER_ANIMATION
+        .lock()
+        .get(&prefix)
+        .copied()
+        .unwrap_or_else(|| ANIMATION_DURATION_GLOBAL.load(Ordering::SeqCst));
+
+    Some((style, Duration::from_millis(duration)))
+}
+
+fn apply(backend: &Arc<dyn TransparencyBackend>, changes: &[AlphaChange], animate: bool) {
+    let animation = animation().filter(|_| animate);
+
+    for change in changes {
+        // animated changes are recorded by the dispatcher once the animation has finished
+        let result = match animation {
+            Some((style, duration)) => AnimationEngine::animate(
+                TransparencyRenderDispatcher::new(backend.clone(), *change, style),
+                duration,
+            ),
+            None => backend
+                .set_alpha(change.window_id, change.to)
+                .map(|()| decision::record(&mut APPLIED_ALPHAS.lock(), &[*change])),
+        };
+
+        if let Err(error) = result {
+            tracing::warn!("{error}");
+        }
+    }
+}
+
+/// Makes every window which has been made transparent opaque again
+pub fn restore_opacity() {
+    let Some(backend) = BACKEND.get() else {
+        return;
+    };
+
+    let changes = decision::changes(&APPLIED_ALPHAS.lock(), &[], OPAQUE);
+    apply(backend, &changes, false);
+}
+
+fn is_ignored(window: &Window) -> bool {
+    let rules = TRANSPARENCY_BLACKLIST.lock();
+    if rules.is_empty() {
+        return false;
+    }
+
+    let (Some(title), Some(exe_name), Some(role), Some(subrole), Some(path)) = (
+        window.title(),
+        window.exe(),
+        window.role(),
+        window.subrole(),
+        window.path(),
+    ) else {
+        return false;
+    };
+
+    should_act(
+        &title,
+        &exe_name,
+        &[&role, &subrole],
+        &path.to_string_lossy(),
+        &rules,
+        &REGEX_IDENTIFIERS.lock(),
+    )
+    .is_some()
+}
+
+pub fn listen_for_notifications(
+    wm: Arc<Mutex<WindowManager>>,
+    backend: Arc<dyn TransparencyBackend>,
+) {
+    let backend = BACKEND.get_or_init(|| backend).clone();
+
+    std::thread::spawn(move || {
+        loop {
+            match handle_notifications(wm.clone(), backend.clone()) {
+                Ok(()) => {
+                    tracing::warn!("restarting finished thread");
+                }
+                Err(error) => {
+                    tracing::warn!("restarting failed thread: {}", error);
+                }
+            }
+        }
+    });
+}
+
+fn handle_notifications(
+    wm: Arc<Mutex<WindowManager>>,
+    backend: Arc<dyn TransparencyBackend>,
+) -> eyre::Result<()> {
+    tracing::info!("listening");
+
+    let receiver = event_rx();
+    event_tx().send(Notification)?;
+
+    for _ in receiver {
+        let state = wm.lock();
+        let is_paused = state.is_paused;
+        let monitors = state.monitors.clone();
+        drop(state);
+
+        let mut candidates = vec![];
+
+        if TRANSPARENCY_ENABLED.load(Ordering::SeqCst) && !is_paused {
+            let foreground_window = MacosApi::foreground_window_id().unwrap_or_default();
+
+            for monitor in monitors.elements() {
+                let Some(workspace) = monitor.focused_workspace() else {
+                    continue;
+                };
+
+                // only the focused window of each container is visible
+                let visible = workspace
+                    .monocle_container
+                    .iter()
+                    .chain(workspace.containers())
+                    .filter_map(|container| container.focused_window())
+                    .chain(workspace.floating_windows());
+
+                for window in visible {
+                    candidates.push(Candidate {
+                        window_id: window.id,
+                        focused: window.id == foreground_window,
+                        ignored: is_ignored(window),
+                    });
+                }
+            }
+        }
+
+        let changes = decision::changes(
+            &APPLIED_ALPHAS.lock(),
+            &candidates,
+            TRANSPARENCY_ALPHA.load(Ordering::SeqCst),
+        );
+
+        apply(&backend, &changes, true);
+    }
+
+    Ok(())
+}
@@ -55,9 +55,12 @@ use crate::core::config_generation::MatchingStrategy;
 use crate::core_graphics::CoreGraphicsApi;
 use crate::hidden_frame_bottom_left;
 use crate::hidden_frame_bottom_right;
+use crate::input_event_listener;
 use crate::macos_api::MacosApi;
+use crate::platform;
 use crate::reaper;
 use crate::reaper::ReaperNotification;
+use crate::transaction;
 use crate::window_manager_event::SystemNotification;
 use crate::window_manager_event::WindowManagerEvent;
 use crate::window_manager_event_listener;
@@ -332,18 +335,21 @@ pub struct Window {
     #[serde(skip_deserializing)]
     observer: AccessibilityObserver,
     pub details: Option<WindowDetails>,
+    /// Whether this floating window follows focus to other workspaces instead of being hidden
+    #[serde(default)]
+    pub sticky: bool,
+}
+
+/// Changes to windows are only made in the window manager state while a transaction is being
+/// processed on the current thread
+fn is_deferred() -> bool {
+    transaction::in_progress()
 }
 
 #[cfg(test)]
 impl From<u32> for Window {
     fn from(id: u32) -> Self {
-        Self {
-            id,
-            element: Default::default(),
-            application: Default::default(),
-            observer: Default::default(),
-            details: None,
-        }
+        Self::stub(id, Application::default())
     }
 }
 
@@ -385,7 +391,9 @@ impl Drop for Window {
             reaper::send_notification(ReaperNotification::InvalidWindow(self.id));
 
             // make sure the observer gets removed from any run loops
-            AccessibilityApi::invalidate_observer(&self.observer);
+            if let Some(observer) = &self.observer.0 {
+                AccessibilityApi::invalidate_observer(observer);
+            }
         }
     }
 }
@@ -426,11 +434,9 @@ impl Serialize for Window {
     {
         let mut state = serializer.serialize_struct("Window", 6)?;
         state.serialize_field("id", &self.id)?;
-        state.serialize_field(
-            "rect",
-            &Rect::from(MacosApi::window_rect(&self.element).unwrap_or_default()),
-        )?;
+        state.serialize_field("rect", &self.rect().unwrap_or_default())?;
         state.serialize_field("details", &WindowDetails::from(self))?;
+        state.serialize_field("sticky", &self.sticky)?;
         state.end()
     }
 }
@@ -445,6 +451,7 @@ struct SerializedWindow {
     role: String,
     subrole: String,
     rect: Rect,
+    sticky: bool,
 }
 
 #[cfg(feature = "schemars")]
@@ -474,15 +481,36 @@ impl Window {
             application,
             observer: AccessibilityObserver(Some(observer)),
             details: None,
+            sticky: false,
         })
     }
 
+    /// A window without an accessibility element or observer, which only a
+    /// [`FakeBackend`](crate::platform::FakeBackend) knows about
+    pub(crate) fn stub(id: u32, application: Application) -> Self {
+        Self {
+            id,
+            element: Default::default(),
+            application,
+            observer: Default::default(),
+            details: None,
+            sticky: false,
+        }
+    }
+
     pub fn is_valid(&self) -> bool {
+        platform::backend().window_is_valid(self)
+    }
+
+    pub(crate) fn is_valid_native(&self) -> bool {
         AccessibilityApi::copy_attribute_names(&self.element).is_some()
     }
 
     pub fn is_focused(&self) -> bool {
-        MacosApi::foreground_window_id().unwrap_or_default() == self.id
+        platform::backend()
+            .foreground_window_id()
+            .unwrap_or_default()
+            == self.id
     }
 
     #[tracing::instrument(skip_all)]
@@ -491,6 +519,11 @@ impl Window {
         run_loop: &CFRunLoop,
         refcon: Option<*mut c_void>,
     ) -> Result<(), AccessibilityError> {
+        // stub windows have nothing to observe
+        if self.observer.0.is_none() {
+            return Ok(());
+        }
+
         tracing::info!("registering observer for {self}");
 
         AccessibilityApi::add_observer_to_run_loop(
@@ -502,10 +535,21 @@ impl Window {
         )
     }
 
-    #[tracing::instrument(skip_all)]
     pub fn hide(
         &mut self,
         hiding_position: WindowHidingPosition,
+    ) -> Result<(), AccessibilityError> {
+        if is_deferred() {
+            return Ok(());
+        }
+
+        platform::backend().hide_window(self, hiding_position)
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub(crate) fn hide_native(
+        &self,
+        hiding_position: WindowHidingPosition,
     ) -> Result<(), AccessibilityError> {
         let rect = MacosApi::window_rect(&self.element)?;
 
@@ -541,7 +585,11 @@ impl Window {
         Ok(())
     }
 
-    pub fn minimize(&mut self) -> Result<(), AccessibilityError> {
+    pub fn minimize(&self) -> Result<(), AccessibilityError> {
+        platform::backend().minimize_window(self)
+    }
+
+    pub(crate) fn minimize_native(&self) -> Result<(), AccessibilityError> {
         let cf_boolean = CFBoolean::new(true);
         let value = &**cf_boolean;
         AccessibilityApi::set_attribute_cf_value(&self.element, kAXMinimizedAttribute, value)
@@ -553,8 +601,24 @@ impl Window {
         AccessibilityApi::set_attribute_cf_value(&self.element, kAXMinimizedAttribute, value)
     }
 
-    #[tracing::instrument(skip_all)]
+    pub fn close(&self) -> Result<(), AccessibilityError> {
+        platform::backend().close_window(self)
+    }
+
+    pub(crate) fn close_native(&self) -> Result<(), AccessibilityError> {
+        AdhocWindow::close(&self.element)
+    }
+
     pub fn restore(&mut self) -> Result<(), AccessibilityError> {
+        if is_deferred() {
+            return Ok(());
+        }
+
+        platform::backend().restore_window(self)
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub(crate) fn restore_native(&self) -> Result<(), AccessibilityError> {
         let mut should_remove_restore_position = false;
         let mut window_restore_positions = WINDOW_RESTORE_POSITIONS.lock();
         if let Some(cg_rect) = window_restore_positions.get(&self.id) {
@@ -576,7 +640,16 @@ impl Window {
         Ok(())
     }
 
+    /// The current position and size of the window, as reported by the platform backend
+    pub fn rect(&self) -> Result<Rect, AccessibilityError> {
+        platform::backend().window_rect(self)
+    }
+
     pub fn title(&self) -> Option<String> {
+        platform::backend().window_title(self)
+    }
+
+    pub(crate) fn title_native(&self) -> Option<String> {
         AccessibilityApi::copy_attribute_value::<CFString>(&self.element, kAXTitleAttribute)
             .map(|s| s.to_string())
     }
@@ -606,6 +679,10 @@ impl Window {
     }
 
     pub fn path(&self) -> Option<PathBuf> {
+        platform::backend().window_path(self)
+    }
+
+    pub(crate) fn path_native(&self) -> Option<PathBuf> {
         if let Ok(Some(path)) = self
             .running_application()
             .map(|app| app.executableURL())
@@ -642,11 +719,19 @@ impl Window {
     }
 
     pub fn role(&self) -> Option<String> {
+        platform::backend().window_role(self)
+    }
+
+    pub(crate) fn role_native(&self) -> Option<String> {
         AccessibilityApi::copy_attribute_value::<CFString>(&self.element, kAXRoleAttribute)
             .map(|s| s.to_string())
     }
 
     pub fn subrole(&self) -> Option<String> {
+        platform::backend().window_subrole(self)
+    }
+
+    pub(crate) fn subrole_native(&self) -> Option<String> {
         AccessibilityApi::copy_attribute_value::<CFString>(&self.element, kAXSubroleAttribute)
             .map(|s| s.to_string())
     }
@@ -659,6 +744,14 @@ impl Window {
     }
 
     pub fn set_position(&self, rect: &Rect) -> Result<(), AccessibilityError> {
+        if is_deferred() {
+            return Ok(());
+        }
+
+        platform::backend().set_window_position(self, rect)
+    }
+
+    pub(crate) fn set_position_native(&self, rect: &Rect) -> Result<(), AccessibilityError> {
         // Check if animation is enabled (per-animation or global)
         let animation_enabled = {
             let per_animation = ANIMATION_ENABLED_PER_ANIMATION.lock();
@@ -735,6 +828,16 @@ impl Window {
     }
 
     pub fn focus(&self, mouse_follows_focus: bool) -> Result<(), LibraryError> {
+        if is_deferred() {
+            return Ok(());
+        }
+
+        platform::backend().focus_window(self, mouse_follows_focus)
+    }
+
+    pub(crate) fn focus_native(&self, mouse_follows_focus: bool) -> Result<(), LibraryError> {
+        input_event_listener::record_programmatic_focus();
+
         match self.running_application() {
             Ok(running_application) => {
                 running_application.activateWithOptions(NSApplicationActivationOptions::empty());
@@ -785,6 +888,10 @@ impl Window {
     }
 
     pub fn raise(&self) -> Result<(), AccessibilityError> {
+        platform::backend().raise_window(self)
+    }
+
+    pub(crate) fn raise_native(&self) -> Result<(), AccessibilityError> {
         let cf_boolean = CFBoolean::new(true);
         let value = &**cf_boolean;
         AccessibilityApi::set_attribute_cf_value(&self.element, kAXMainAttribute, value)?;
@@ -822,15 +929,23 @@ impl Window {
     }
 
     pub fn center(&mut self, work_area: &Rect, resize: bool) -> Result<(), AccessibilityError> {
-        let (target_width, target_height) = if resize {
-            let (aspect_ratio_width, aspect_ratio_height) = FLOATING_WINDOW_TOGGLE_ASPECT_RATIO
-                .lock()
-                .width_and_height();
+        let aspect_ratio = resize.then(|| *FLOATING_WINDOW_TOGGLE_ASPECT_RATIO.lock());
+        self.center_with_aspect_ratio(work_area, aspect_ratio)
+    }
+
+    /// Centers the window in the work area, resizing it to `aspect_ratio` if one is given
+    pub fn center_with_aspect_ratio(
+        &mut self,
+        work_area: &Rect,
+        aspect_ratio: Option<AspectRatio>,
+    ) -> Result<(), AccessibilityError> {
+        let (target_width, target_height) = if let Some(aspect_ratio) = aspect_ratio {
+            let (aspect_ratio_width, aspect_ratio_height) = aspect_ratio.width_and_height();
             let target_height = work_area.bottom / 2;
             let target_width = (target_height * aspect_ratio_width) / aspect_ratio_height;
             (target_width, target_height)
         } else {
-            let current_rect = Rect::from(MacosApi::window_rect(&self.element)?);
+            let current_rect = self.rect()?;
             (current_rect.right, current_rect.bottom)
         };
 
@@ -850,7 +965,7 @@ impl Window {
         current_area: &Rect,
         target_area: &Rect,
     ) -> Result<(), AccessibilityError> {
-        let current_rect = Rect::from(MacosApi::window_rect(&self.element)?);
+        let current_rect = self.rect()?;
         let x_diff = target_area.left - current_area.left;
         let y_diff = target_area.top - current_area.top;
         let x_ratio = f32::abs((target_area.right as f32) / (current_area.right as f32));
@@ -943,33 +1058,16 @@ impl Window {
         //
         // debug.has_minimum_height = true;
 
-        match self.title() {
-            None => {
-                if TITLELESS_APPLICATIONS
-                    .lock()
-                    .contains(&self.exe().unwrap_or_default())
-                {
-                    debug.matches_titleless_applications = self.exe();
-                } else {
-                    return Ok(false);
-                }
-            }
-            Some(title) => {
-                // Raycast is dumb and reports an empty string as a title
-                if title.is_empty() {
-                    if TITLELESS_APPLICATIONS
-                        .lock()
-                        .contains(&self.exe().unwrap_or_default())
-                    {
-                        debug.matches_titleless_applications = self.exe();
-                    } else {
-                        return Ok(false);
-                    }
-                } else {
-                    debug.has_title = true;
-                }
-            }
-        }
+        let title = resolve_title(
+            self.title(),
+            self.exe(),
+            &TITLELESS_APPLICATIONS.lock(),
+            debug,
+        );
+
+        let Some(title) = title else {
+            return Ok(false);
+        };
 
         // let is_cloaked = self.is_cloaked().unwrap_or_default();
         //
@@ -993,14 +1091,8 @@ impl Window {
         //     (true, _) |
         //     // If not allowing cloaked windows, we need to ensure the window is not cloaked
         //     (false, false) => {
-        let title = if debug.matches_titleless_applications.is_some() {
-            self.exe()
-        } else {
-            self.title()
-        };
-
-        if let (Some(title), Some(exe_name), Some(role), Some(subrole), Some(path)) =
-            (title, self.exe(), self.role(), self.subrole(), self.path())
+        if let (Some(exe_name), Some(role), Some(subrole), Some(path)) =
+            (self.exe(), self.role(), self.subrole(), self.path())
         {
             debug.title = Some(title.clone());
             debug.exe_name = Some(exe_name.clone());
@@ -1305,10 +1397,47 @@ pub struct RuleDebug {
     // pub matches_layered_whitelist: Option<MatchingRule>,
     pub matches_floating_applications: Option<MatchingRule>,
     pub matches_titleless_applications: Option<String>,
+    pub matches_tabbed_applications: Option<String>,
     // pub matches_wsl2_gui: Option<String>,
     // pub matches_no_titlebar: Option<MatchingRule>,
 }
 
+/// Resolves the title which rules are matched against, which is the executable name for
+/// applications whose windows have no title
+pub fn resolve_title(
+    title: Option<String>,
+    exe_name: Option<String>,
+    titleless_applications: &[String],
+    debug: &mut RuleDebug,
+) -> Option<String> {
+    match title {
+        // Raycast is dumb and reports an empty string as a title
+        Some(title) if !title.is_empty() => {
+            debug.has_title = true;
+            Some(title)
+        }
+        _ => {
+            let exe_name = exe_name?;
+            if titleless_applications.contains(&exe_name) {
+                debug.matches_titleless_applications = Some(exe_name.clone());
+                Some(exe_name)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// The rules which decide whether a window is eligible to be managed
+pub struct EligibilityRules<'a> {
+    pub permaignore_classes: &'a [String],
+    pub ignore_identifiers: &'a [MatchingRule],
+    pub manage_identifiers: &'a [MatchingRule],
+    pub floating_applications: &'a [MatchingRule],
+    pub tabbed_applications: &'a [String],
+    pub regex_identifiers: &'a HashMap<String, Regex>,
+}
+
 #[allow(clippy::too_many_arguments)]
 fn window_is_eligible(
     _window_id: u32,
@@ -1321,26 +1450,57 @@ fn window_is_eligible(
     _event: Option<WindowManagerEvent>,
     debug: &mut RuleDebug,
 ) -> bool {
-    {
-        let permaignore_classes = PERMAIGNORE_CLASSES.lock();
-        for class in classes {
-            if permaignore_classes.contains(&class.to_string()) {
-                debug.matches_permaignore_class = Some(class.to_string());
-                return false;
-            }
+    let permaignore_classes = PERMAIGNORE_CLASSES.lock();
+    let regex_identifiers = REGEX_IDENTIFIERS.lock();
+    let ignore_identifiers = IGNORE_IDENTIFIERS.lock();
+    let manage_identifiers = MANAGE_IDENTIFIERS.lock();
+    let floating_identifiers = FLOATING_APPLICATIONS.lock();
+    let tabbed_applications = TABBED_APPLICATIONS.lock();
+
+    let rules = EligibilityRules {
+        permaignore_classes: &permaignore_classes,
+        ignore_identifiers: &ignore_identifiers,
+        manage_identifiers: &manage_identifiers,
+        floating_applications: &floating_identifiers,
+        tabbed_applications: &tabbed_applications,
+        regex_identifiers: &regex_identifiers,
+    };
+
+    window_is_eligible_for_rules(title, exe_name, classes, path, &rules, debug)
+}
+
+/// Whether a window with these identifiers is eligible to be managed according to `rules`,
+/// recording every rule which matched in `debug`
+pub fn window_is_eligible_for_rules(
+    title: &str,
+    exe_name: &str,
+    classes: &[&str],
+    path: &str,
+    rules: &EligibilityRules,
+    debug: &mut RuleDebug,
+) -> bool {
+    for class in classes {
+        if rules.permaignore_classes.contains(&class.to_string()) {
+            debug.matches_permaignore_class = Some(class.to_string());
+            return false;
         }
     }
 
-    let regex_identifiers = REGEX_IDENTIFIERS.lock();
+    if rules
+        .tabbed_applications
+        .iter()
+        .any(|tabbed| tabbed == exe_name)
+    {
+        debug.matches_tabbed_applications = Some(exe_name.to_string());
+    }
 
-    let ignore_identifiers = IGNORE_IDENTIFIERS.lock();
     let should_ignore = if let Some(rule) = should_act(
         title,
         exe_name,
         classes,
         path,
-        &ignore_identifiers,
-        &regex_identifiers,
+        rules.ignore_identifiers,
+        rules.regex_identifiers,
     ) {
         debug.matches_ignore_identifier = Some(rule);
         true
@@ -1348,14 +1508,13 @@ fn window_is_eligible(
         false
     };
 
-    let manage_identifiers = MANAGE_IDENTIFIERS.lock();
     let managed_override = if let Some(rule) = should_act(
         title,
         exe_name,
         classes,
         path,
-        &manage_identifiers,
-        &regex_identifiers,
+        rules.manage_identifiers,
+        rules.regex_identifiers,
     ) {
         debug.matches_managed_override = Some(rule);
         true
@@ -1363,14 +1522,13 @@ fn window_is_eligible(
         false
     };
 
-    let floating_identifiers = FLOATING_APPLICATIONS.lock();
     if let Some(rule) = should_act(
         title,
         exe_name,
         classes,
         path,
-        &floating_identifiers,
-        &regex_identifiers,
+        rules.floating_applications,
+        rules.regex_identifiers,
     ) {
         debug.matches_floating_applications = Some(rule);
     }
@@ -2,10 +2,10 @@ use crate::CoreFoundationRunLoop;
 use crate::DATA_DIR;
 use crate::LibraryError;
 use crate::REGEX_IDENTIFIERS;
+use crate::SUBSCRIPTION_PIPES;
 use crate::SUBSCRIPTION_SOCKETS;
 use crate::UNMANAGED_WINDOW_IDS;
 use crate::WORKSPACE_MATCHING_RULES;
-use crate::accessibility::AccessibilityApi;
 use crate::application::Application;
 use crate::border_manager;
 use crate::container::Container;
@@ -14,6 +14,7 @@ use crate::core::Axis;
 use crate::core::CrossBoundaryBehaviour;
 use crate::core::CycleDirection;
 use crate::core::DefaultLayout;
+use crate::core::FocusFollowsMouseImplementation;
 use crate::core::Layout;
 use crate::core::MonocleFocusBehaviour;
 use crate::core::MoveBehaviour;
@@ -22,17 +23,32 @@ use crate::core::OperationDirection;
 use crate::core::Placement;
 use crate::core::Rect;
 use crate::core::Sizing;
+use crate::core::SocketMessage;
 use crate::core::WindowContainerBehaviour;
 use crate::core::WindowHidingPosition;
 use crate::core::WindowManagementBehaviour;
 use crate::core::config_generation::MatchingRule;
-use crate::current_space_id;
+use crate::core::custom_layout::CustomLayout;
+use crate::core::layered_config::LayeredConfig;
+use crate::history::History;
+use crate::history::LayoutSnapshot;
+use crate::input_event_listener;
+use crate::input_event_listener::FocusFollowsMouseAction;
 use crate::lockable_sequence::Lockable;
 use crate::macos_api::MacosApi;
 use crate::monitor::Monitor;
+use crate::platform;
 use crate::ring::Ring;
+use crate::scratchpad::Scratchpad;
+use crate::scratchpad::ScratchpadConfig;
+use crate::session;
+use crate::session::ContainerSession;
+use crate::session::MonitorSession;
+use crate::session::SaveFile;
+use crate::session::Session;
+use crate::session::WindowSession;
 use crate::static_config::StaticConfig;
-use crate::window::AdhocWindow;
+use crate::transparency_manager;
 use crate::window::Window;
 use crate::window::should_act_individual;
 use crate::window_manager_event::ManualNotification;
@@ -41,25 +57,33 @@ use crate::window_manager_event::WindowManagerEvent;
 use crate::window_manager_event_listener;
 use crate::workspace::Workspace;
 use crate::workspace::WorkspaceLayer;
+use crate::workspace::WorkspaceWindowLocation;
+use crate::workspace_set::WorkspaceSetConfig;
 use color_eyre::eyre;
+use color_eyre::eyre::Context;
 use color_eyre::eyre::OptionExt;
 use color_eyre::eyre::bail;
 use crossbeam_channel::Receiver;
+use hotwatch::EventKind;
 use hotwatch::Hotwatch;
 use objc2_core_foundation::CFRetained;
 use objc2_core_foundation::CFRunLoop;
 use parking_lot::Mutex;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
 use std::collections::hash_map::Entry;
 use std::io::ErrorKind;
+use std::io::Write;
 use std::net::Shutdown;
 use std::num::NonZeroUsize;
 use std::os::unix::net::UnixListener;
 use std::os::unix::net::UnixStream;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 
 #[derive(Debug)]
 pub struct WindowManager {
@@ -72,12 +96,17 @@ pub struct WindowManager {
     pub is_paused: bool,
     pub resize_delta: i32,
     pub hotwatch: Hotwatch,
+    /// The static configuration file this instance was started with, if any
+    pub static_config_path: Option<PathBuf>,
+    /// Configuration files currently being watched for changes by `hotwatch`
+    pub watched_configuration_paths: Vec<PathBuf>,
     pub unmanaged_window_operation_behaviour: OperationBehaviour,
     pub window_management_behaviour: WindowManagementBehaviour,
     pub cross_monitor_move_behaviour: MoveBehaviour,
     pub cross_boundary_behaviour: CrossBoundaryBehaviour,
     pub monocle_focus_behaviour: MonocleFocusBehaviour,
     pub mouse_follows_focus: bool,
+    pub focus_follows_mouse: Option<FocusFollowsMouseImplementation>,
     pub work_area_offset: Option<Rect>,
     pub incoming_events: Receiver<WindowManagerEvent>,
     pub minimized_windows: HashMap<u32, Window>,
@@ -86,6 +115,20 @@ pub struct WindowManager {
     pub already_moved_window_handles: Arc<Mutex<HashSet<u32>>>,
     /// Maps each known window id to the (monitor, workspace) index pair managing it
     pub known_window_ids: HashMap<u32, (usize, usize)>,
+    /// Arrangements from before layout-altering commands, walked by undo and redo
+    pub history: History,
+    /// Move sticky windows to the focused workspace of whichever monitor receives focus
+    pub sticky_windows_follow_monitor_focus: bool,
+    /// Labels attached to windows for jumping back to them, keyed by window id
+    pub marks: HashMap<u32, String>,
+    /// Scratchpads from the static configuration, keyed by name
+    pub scratchpads: BTreeMap<String, Scratchpad>,
+    /// Workspace sets from the static configuration, in the order they were declared
+    pub workspace_sets: Vec<WorkspaceSetConfig>,
+    /// The name of the workspace set which was focused most recently
+    pub last_focused_workspace_set: Option<String>,
+    /// The profile from the static configuration whose overrides are currently applied
+    pub active_profile: Option<String>,
 }
 
 impl_ring_elements!(WindowManager, Monitor);
@@ -152,16 +195,19 @@ impl WindowManager {
             applications: Default::default(),
             run_loop: CoreFoundationRunLoop(run_loop.clone()),
             command_listener: listener,
-            space_id: current_space_id(),
+            space_id: platform::backend().space_id(),
             is_paused: false,
             resize_delta: 50,
             hotwatch: Hotwatch::new()?,
+            static_config_path: None,
+            watched_configuration_paths: vec![],
             unmanaged_window_operation_behaviour: Default::default(),
             window_management_behaviour: behaviour,
             cross_monitor_move_behaviour: Default::default(),
             cross_boundary_behaviour: Default::default(),
             monocle_focus_behaviour: MonocleFocusBehaviour::default(),
             mouse_follows_focus: true,
+            focus_follows_mouse: None,
             work_area_offset: None,
             incoming_events: incoming,
             minimized_windows: HashMap::new(),
@@ -169,6 +215,13 @@ impl WindowManager {
             pending_resize_op: Arc::new(None),
             already_moved_window_handles: Default::default(),
             known_window_ids: Default::default(),
+            history: History::default(),
+            sticky_windows_follow_monitor_focus: false,
+            marks: HashMap::new(),
+            scratchpads: BTreeMap::new(),
+            workspace_sets: vec![],
+            last_focused_workspace_set: None,
+            active_profile: None,
         })
     }
 
@@ -183,7 +236,7 @@ impl WindowManager {
         match self.applications.entry(process_id) {
             Entry::Occupied(entry) => Ok(entry.into_mut()),
             Entry::Vacant(vacant) => {
-                let mut application = Application::new(process_id)?;
+                let mut application = platform::backend().application(process_id)?;
                 application.observe(&self.run_loop, None);
                 Ok(vacant.insert(application))
             }
@@ -202,13 +255,11 @@ impl WindowManager {
         let len = focused_workspace.floating_windows().len();
 
         if len > 1 {
-            let focused_window_id =
-                MacosApi::foreground_window_id().ok_or_eyre("no foreground window")?;
-            let focused_rect = Rect::from(MacosApi::window_rect(
-                MacosApi::foreground_window()
-                    .ok_or_eyre("no foreground window")?
-                    .as_ref(),
-            )?);
+            let foreground_window = platform::backend()
+                .foreground_window()
+                .ok_or_eyre("no foreground window")?;
+            let focused_window_id = foreground_window.id;
+            let focused_rect = foreground_window.rect()?;
 
             match direction {
                 OperationDirection::Left => {
@@ -217,11 +268,7 @@ impl WindowManager {
                         .iter()
                         .enumerate()
                         .flat_map(|(idx, w)| {
-                            (w.id != focused_window_id).then_some(
-                                MacosApi::window_rect(&w.element)
-                                    .ok()
-                                    .map(|r| (idx, Rect::from(r))),
-                            )
+                            (w.id != focused_window_id).then_some(w.rect().ok().map(|r| (idx, r)))
                         })
                         .flatten()
                         .flat_map(|(idx, r)| {
@@ -243,11 +290,7 @@ impl WindowManager {
                         .iter()
                         .enumerate()
                         .flat_map(|(idx, w)| {
-                            (w.id != focused_window_id).then_some(
-                                MacosApi::window_rect(&w.element)
-                                    .ok()
-                                    .map(|r| (idx, Rect::from(r))),
-                            )
+                            (w.id != focused_window_id).then_some(w.rect().ok().map(|r| (idx, r)))
                         })
                         .flatten()
                         .flat_map(|(idx, r)| {
@@ -269,11 +312,7 @@ impl WindowManager {
                         .iter()
                         .enumerate()
                         .flat_map(|(idx, w)| {
-                            (w.id != focused_window_id).then_some(
-                                MacosApi::window_rect(&w.element)
-                                    .ok()
-                                    .map(|r| (idx, Rect::from(r))),
-                            )
+                            (w.id != focused_window_id).then_some(w.rect().ok().map(|r| (idx, r)))
                         })
                         .flatten()
                         .flat_map(|(idx, r)| {
@@ -295,11 +334,7 @@ impl WindowManager {
                         .iter()
                         .enumerate()
                         .flat_map(|(idx, w)| {
-                            (w.id != focused_window_id).then_some(
-                                MacosApi::window_rect(&w.element)
-                                    .ok()
-                                    .map(|r| (idx, Rect::from(r))),
-                            )
+                            (w.id != focused_window_id).then_some(w.rect().ok().map(|r| (idx, r)))
                         })
                         .flatten()
                         .flat_map(|(idx, r)| {
@@ -682,11 +717,12 @@ impl WindowManager {
         let focused_workspace = self.focused_workspace()?;
         let delta = self.resize_delta;
 
-        let focused_window_id =
-            MacosApi::foreground_window_id().ok_or_eyre("no foreground window")?;
+        let focused_window_id = platform::backend()
+            .foreground_window_id()
+            .ok_or_eyre("no foreground window")?;
         for window in focused_workspace.floating_windows().iter() {
             if window.id == focused_window_id {
-                let mut rect = Rect::from(MacosApi::window_rect(&window.element)?);
+                let mut rect = window.rect()?;
                 match direction {
                     OperationDirection::Left => {
                         if rect.left - delta < focused_monitor_work_area.left {
@@ -729,7 +765,7 @@ impl WindowManager {
                 window.set_position(&rect)?;
 
                 if mouse_follows_focus {
-                    MacosApi::center_cursor_in_rect(&rect)?;
+                    platform::backend().center_cursor_in_rect(&rect)?;
                 }
 
                 break;
@@ -959,6 +995,21 @@ impl WindowManager {
         let workspace = self.focused_workspace_mut()?;
 
         workspace.layout = Layout::Default(layout);
+        workspace.custom_layout = None;
+        self.update_focused_workspace(self.mouse_follows_focus, false)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn change_workspace_custom_layout<P>(&mut self, path: P) -> eyre::Result<()>
+    where
+        P: AsRef<Path> + std::fmt::Debug,
+    {
+        tracing::info!("changing layout");
+
+        let layout = CustomLayout::from_path(path)?;
+        let workspace = self.focused_workspace_mut()?;
+
+        workspace.custom_layout = Option::from(layout);
         self.update_focused_workspace(self.mouse_follows_focus, false)
     }
 
@@ -986,6 +1037,45 @@ impl WindowManager {
             .ok_or_eyre("there is no monitor")?;
 
         workspace.layout = Layout::Default(layout);
+        workspace.custom_layout = None;
+
+        // If this is the focused workspace on a non-focused screen, let's update it
+        if focused_monitor_idx != monitor_idx && focused_workspace_idx == workspace_idx {
+            workspace.update()?;
+            Ok(())
+        } else {
+            Ok(self.update_focused_workspace(false, false)?)
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn set_workspace_custom_layout<P>(
+        &mut self,
+        monitor_idx: usize,
+        workspace_idx: usize,
+        path: P,
+    ) -> eyre::Result<()>
+    where
+        P: AsRef<Path> + std::fmt::Debug,
+    {
+        tracing::info!("setting workspace layout");
+
+        let layout = CustomLayout::from_path(path)?;
+        let focused_monitor_idx = self.focused_monitor_idx();
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_eyre("there is no monitor")?;
+
+        let focused_workspace_idx = monitor.focused_workspace_idx();
+
+        let workspace = monitor
+            .workspaces_mut()
+            .get_mut(workspace_idx)
+            .ok_or_eyre("there is no workspace")?;
+
+        workspace.custom_layout = Option::from(layout);
 
         // If this is the focused workspace on a non-focused screen, let's update it
         if focused_monitor_idx != monitor_idx && focused_workspace_idx == workspace_idx {
@@ -1111,14 +1201,7 @@ impl WindowManager {
 
     pub fn reap_invalid_windows_for_application(&mut self, process_id: i32) -> eyre::Result<()> {
         let application = self.application(process_id)?;
-        let mut valid_window_ids = vec![];
-        if let Some(elements) = application.window_elements() {
-            for element in elements {
-                if let Ok(window_id) = AccessibilityApi::window_id(&element) {
-                    valid_window_ids.push(window_id);
-                }
-            }
-        }
+        let valid_window_ids = platform::backend().window_ids(application);
 
         let mut reaped_count = 0;
 
@@ -1139,19 +1222,12 @@ impl WindowManager {
         let mouse_follows_focus = self.mouse_follows_focus;
 
         let workspace = self.focused_workspace_mut()?;
-        let len = NonZeroUsize::new(workspace.containers_mut().len())
-            .ok_or_eyre("there must be at least one container")?;
-        let current_container_idx = workspace.focused_container_idx();
+        if workspace.containers().is_empty() {
+            eyre::bail!("there must be at least one container");
+        }
 
-        let is_valid = direction
-            .destination(
-                workspace.layout.as_boxed_direction().as_ref(),
-                workspace.layout_flip,
-                workspace.focused_container_idx(),
-                len,
-                workspace.layout_options,
-            )
-            .is_some();
+        let current_container_idx = workspace.focused_container_idx();
+        let is_valid = workspace.new_idx_for_direction(direction).is_some();
 
         if is_valid {
             let new_idx = workspace
@@ -1241,29 +1317,749 @@ impl WindowManager {
             }
         }
 
+        // dropping the write end of each pipe signals the end of the stream to its reader
+        SUBSCRIPTION_PIPES.lock().clear();
+
         let socket = DATA_DIR.join("komorebi.sock");
         let _ = std::fs::remove_file(socket);
 
-        std::process::exit(0)
+        std::process::exit(0)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn restore_all_windows(&mut self, ignore_restore: bool) -> eyre::Result<()> {
+        tracing::info!("restoring all hidden windows");
+
+        for monitor in self.monitors_mut() {
+            for workspace in monitor.workspaces_mut() {
+                for containers in workspace.containers_mut() {
+                    for window in containers.windows_mut() {
+                        if !ignore_restore && let Err(error) = window.restore() {
+                            tracing::error!("failed to restore window: {}", error);
+                        }
+                    }
+                }
+            }
+        }
+
+        for scratchpad in self.scratchpads.values_mut() {
+            if let Some(window) = &mut scratchpad.hidden_window
+                && let Err(error) = window.restore()
+            {
+                tracing::error!("failed to restore scratchpad window: {}", error);
+            }
+        }
+
+        transparency_manager::restore_opacity();
+
+        Ok(())
+    }
+
+    /// Loads a file written by `save` or `quick-save`
+    ///
+    /// Files written before full sessions were saved only have their resize dimensions applied
+    /// to the focused workspace, as they were when they were written
+    pub fn load_save_file(&mut self, path: &Path) -> eyre::Result<()> {
+        let contents = std::fs::read_to_string(path)
+            .wrap_err(format!("no file found at {}", path.display()))?;
+
+        let save_file = SaveFile::parse(&contents)
+            .wrap_err(format!("{} is not a valid save file", path.display()))?;
+
+        match save_file {
+            SaveFile::Session(session) => self.load_session(&session),
+            SaveFile::ResizeDimensions(resize) => {
+                tracing::warn!(
+                    "{} only holds resize dimensions, save it again to store the full arrangement",
+                    path.display()
+                );
+
+                self.focused_workspace_mut()?.resize_dimensions = resize;
+                self.update_focused_workspace(false, false)
+            }
+        }
+    }
+
+    /// Re-homes live windows into the positions recorded in a session snapshot
+    ///
+    /// Only monitors which are part of the snapshot are rearranged, and saved workspaces are found
+    /// by name before falling back to their index. Saved windows which can't be matched to a live
+    /// window are skipped, and live containers which can't be matched as a whole are left exactly
+    /// where they are
+    #[tracing::instrument(skip_all)]
+    pub fn load_session(&mut self, session: &Session) -> eyre::Result<()> {
+        tracing::info!("loading session");
+
+        let mouse_follows_focus = self.mouse_follows_focus;
+
+        // saved monitors are matched by serial number first and by index as a fallback
+        let mut targets: Vec<(usize, &MonitorSession)> = vec![];
+        for (saved_idx, saved) in session.monitors.iter().enumerate() {
+            let monitor_idx = self
+                .monitors()
+                .iter()
+                .position(|m| {
+                    !saved.serial_number_id.is_empty()
+                        && m.serial_number_id == saved.serial_number_id
+                })
+                .unwrap_or(saved_idx);
+
+            if monitor_idx < self.monitors().len()
+                && !targets.iter().any(|(idx, _)| *idx == monitor_idx)
+            {
+                targets.push((monitor_idx, saved));
+            }
+        }
+
+        let mut workspace_targets = vec![];
+        for (monitor_idx, saved_monitor) in &targets {
+            let monitor = self
+                .monitors_mut()
+                .get_mut(*monitor_idx)
+                .ok_or_eyre("there is no monitor at this index")?;
+
+            workspace_targets.push(resolve_session_workspaces(monitor, saved_monitor));
+        }
+
+        // every managed window on the target monitors, alongside a key for the container it
+        // belongs to, in the same order in which they are taken out below
+        let mut live = vec![];
+        let mut containers = vec![];
+        for (monitor_idx, _) in &targets {
+            let monitor = self
+                .monitors()
+                .get(*monitor_idx)
+                .ok_or_eyre("there is no monitor at this index")?;
+
+            for workspace in monitor.workspaces() {
+                for container in workspace
+                    .containers()
+                    .iter()
+                    .chain(workspace.monocle_container.as_ref())
+                {
+                    let key = Some(containers.len());
+                    for window in container.windows() {
+                        live.push(WindowSession::from(window));
+                        containers.push(key);
+                    }
+                }
+
+                for window in workspace
+                    .maximized_window
+                    .iter()
+                    .chain(workspace.floating_windows())
+                {
+                    live.push(WindowSession::from(window));
+                    containers.push(None);
+                }
+            }
+        }
+
+        let saved = targets
+            .iter()
+            .flat_map(|(_, monitor)| monitor.windows())
+            .collect::<Vec<_>>();
+
+        let mut assignments = session::assign_windows(&saved, &live);
+        session::release_partial_containers(&mut assignments, &containers);
+
+        let mut taken = vec![false; live.len()];
+        for live_idx in assignments.iter().flatten() {
+            taken[*live_idx] = true;
+        }
+
+        // take the matched windows out of their workspaces, leaving everything else in place
+        let mut moved = HashMap::new();
+        let mut live_idx = 0;
+        for (monitor_idx, _) in &targets {
+            let monitor = self
+                .monitors_mut()
+                .get_mut(*monitor_idx)
+                .ok_or_eyre("there is no monitor at this index")?;
+
+            for workspace in monitor.workspaces_mut() {
+                let mut take_container = |container: &Container| {
+                    let first = live_idx;
+                    live_idx += container.windows().len();
+
+                    if container.windows().is_empty() || !taken[first] {
+                        return false;
+                    }
+
+                    for (offset, window) in container.windows().iter().enumerate() {
+                        moved.insert(first + offset, window.clone());
+                    }
+
+                    true
+                };
+
+                let resize_dimensions = std::mem::take(&mut workspace.resize_dimensions);
+                let has_resize_dimensions = resize_dimensions.len() == workspace.containers().len();
+                let mut kept = VecDeque::new();
+
+                let drained = workspace.containers_mut().drain(..).collect::<Vec<_>>();
+                for (idx, container) in drained.into_iter().enumerate() {
+                    if !take_container(&container) {
+                        if has_resize_dimensions {
+                            workspace.resize_dimensions.push(resize_dimensions[idx]);
+                        }
+
+                        kept.push_back(container);
+                    }
+                }
+
+                *workspace.containers_mut() = kept;
+
+                if workspace
+                    .monocle_container
+                    .as_ref()
+                    .is_some_and(&mut take_container)
+                {
+                    workspace.monocle_container = None;
+                    workspace.monocle_container_restore_idx = None;
+                }
+
+                if workspace.maximized_window.is_some() {
+                    if taken[live_idx]
+                        && let Some(window) = workspace.maximized_window.take()
+                    {
+                        moved.insert(live_idx, window);
+                        workspace.maximized_window_restore_idx = None;
+                    }
+
+                    live_idx += 1;
+                }
+
+                let mut floating = VecDeque::new();
+                for window in workspace.floating_windows_mut().drain(..) {
+                    if taken[live_idx] {
+                        moved.insert(live_idx, window);
+                    } else {
+                        floating.push_back(window);
+                    }
+
+                    live_idx += 1;
+                }
+
+                *workspace.floating_windows_mut() = floating;
+
+                let container_count = workspace.containers().len();
+                workspace.focus_container(
+                    workspace
+                        .focused_container_idx()
+                        .min(container_count.saturating_sub(1)),
+                );
+            }
+        }
+
+        let mut assignments = assignments.into_iter();
+        let mut next_window = || {
+            assignments
+                .next()
+                .flatten()
+                .and_then(|live_idx| moved.remove(&live_idx))
+        };
+
+        let container_from =
+            |saved: &ContainerSession, next: &mut dyn FnMut() -> Option<Window>| {
+                let windows = saved
+                    .windows
+                    .iter()
+                    .filter_map(|_| next())
+                    .collect::<Vec<_>>();
+                if windows.is_empty() {
+                    return None;
+                }
+
+                let mut container = Container::default();
+                container.windows_mut().extend(windows);
+                container.focus_window(saved.focused_window_idx.min(container.windows().len() - 1));
+
+                Some(container)
+            };
+
+        for ((monitor_idx, saved_monitor), workspace_idxs) in targets.iter().zip(&workspace_targets)
+        {
+            let monitor = self
+                .monitors_mut()
+                .get_mut(*monitor_idx)
+                .ok_or_eyre("there is no monitor at this index")?;
+
+            for (saved, workspace_idx) in saved_monitor.workspaces.iter().zip(workspace_idxs) {
+                let workspace = monitor
+                    .workspaces_mut()
+                    .get_mut(*workspace_idx)
+                    .ok_or_eyre("there is no workspace at this index")?;
+
+                workspace.layout = saved.layout.clone();
+                workspace.custom_layout = saved.custom_layout.clone();
+                workspace.layout_flip = saved.layout_flip;
+                workspace.tile = saved.tile;
+                workspace.layer = saved.layer;
+
+                // restored containers take the saved positions, and containers which were left
+                // in place follow them
+                let kept = std::mem::take(workspace.containers_mut());
+                for saved_container in &saved.containers {
+                    if let Some(container) = container_from(saved_container, &mut next_window) {
+                        workspace.containers_mut().push_back(container);
+                    }
+                }
+
+                let restored_count = workspace.containers().len();
+
+                if let Some(saved_container) = &saved.monocle_container
+                    && let Some(container) = container_from(saved_container, &mut next_window)
+                {
+                    if workspace.monocle_container.is_none() {
+                        workspace.monocle_container = Some(container);
+                        workspace.monocle_container_restore_idx = Some(
+                            saved
+                                .monocle_container_restore_idx
+                                .unwrap_or_default()
+                                .min(restored_count),
+                        );
+                    } else {
+                        workspace.containers_mut().push_back(container);
+                    }
+                }
+
+                let container_count = workspace.containers().len() + kept.len();
+                workspace.containers_mut().extend(kept);
+
+                for saved_window in &saved.floating_windows {
+                    if let Some(mut window) = next_window() {
+                        window.sticky = saved_window.sticky;
+                        workspace.floating_windows_mut().push_back(window);
+                    }
+                }
+
+                // resize adjustments only make sense if every container could be restored
+                workspace.resize_dimensions = if saved.resize_dimensions.len() == container_count {
+                    saved.resize_dimensions.clone()
+                } else {
+                    vec![]
+                };
+
+                workspace.focus_container(
+                    saved
+                        .focused_container_idx
+                        .min(container_count.saturating_sub(1)),
+                );
+            }
+
+            if let Some(workspace_idx) = workspace_idxs.get(saved_monitor.focused_workspace_idx) {
+                monitor.focus_workspace(*workspace_idx)?;
+            }
+
+            monitor.load_focused_workspace(mouse_follows_focus)?;
+        }
+
+        self.update_known_window_ids();
+        self.retile_all(true)?;
+        self.update_focused_workspace(mouse_follows_focus, true)?;
+
+        Ok(())
+    }
+
+    /// Replaces the configured scratchpads, keeping the windows captured by scratchpads whose
+    /// names are still configured
+    ///
+    /// Hidden windows of scratchpads which are no longer configured are restored and released
+    pub fn set_scratchpads(&mut self, configs: Vec<ScratchpadConfig>) {
+        let mut previous = std::mem::take(&mut self.scratchpads);
+
+        for config in configs {
+            let scratchpad = match previous.remove(&config.name) {
+                Some(existing) => Scratchpad { config, ..existing },
+                None => Scratchpad::from(config),
+            };
+
+            self.scratchpads
+                .insert(scratchpad.config.name.clone(), scratchpad);
+        }
+
+        for (name, scratchpad) in previous {
+            if let Some(mut window) = scratchpad.hidden_window {
+                tracing::info!("releasing the window captured by scratchpad {name}");
+                if let Err(error) = window.restore() {
+                    tracing::error!("failed to restore scratchpad window: {}", error);
+                }
+            }
+        }
+    }
+
+    /// The name of the first scratchpad which can capture this window, if any
+    pub fn scratchpad_for_window(&self, window: &Window) -> Option<String> {
+        self.scratchpads
+            .values()
+            .find(|scratchpad| {
+                scratchpad.can_capture(&self.known_window_ids) && scratchpad.matches(window)
+            })
+            .map(|scratchpad| scratchpad.config.name.clone())
+    }
+
+    /// Whether this window is hidden away in a scratchpad
+    pub fn is_hidden_scratchpad_window(&self, window_id: u32) -> bool {
+        self.scratchpads.values().any(|scratchpad| {
+            scratchpad
+                .hidden_window
+                .as_ref()
+                .is_some_and(|window| window.id == window_id)
+        })
+    }
+
+    /// Captures a newly managed window for a scratchpad and shows it on the focused workspace
+    #[tracing::instrument(skip(self, window))]
+    pub fn capture_scratchpad_window(&mut self, name: &str, window: Window) -> eyre::Result<()> {
+        tracing::info!("capturing window for scratchpad");
+
+        let scratchpad = self
+            .scratchpads
+            .get_mut(name)
+            .ok_or_eyre("there is no scratchpad with this name")?;
+
+        scratchpad.window_id = Some(window.id);
+        scratchpad.hidden_window = None;
+
+        self.show_scratchpad_window(name, window)
+    }
+
+    /// Shows a scratchpad over the focused workspace, or hides it if it is already shown there
+    ///
+    /// A scratchpad which is shown on another workspace is moved over to the focused workspace
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_scratchpad(&mut self, name: &str) -> eyre::Result<()> {
+        tracing::info!("toggling scratchpad");
+
+        let mouse_follows_focus = self.mouse_follows_focus;
+        let focused_location = (self.focused_monitor_idx(), self.focused_workspace_idx()?);
+
+        let scratchpad = self
+            .scratchpads
+            .get_mut(name)
+            .ok_or_eyre("there is no scratchpad with this name")?;
+
+        let Some(window_id) = scratchpad.window_id else {
+            bail!("scratchpad {name} has not captured a window yet");
+        };
+
+        if let Some(mut window) = scratchpad.hidden_window.take() {
+            if let Err(error) = window.restore() {
+                // the window has most likely been closed while it was hidden
+                scratchpad.window_id = None;
+                return Err(eyre::Report::from(error)
+                    .wrap_err(format!("could not show the window of scratchpad {name}")));
+            }
+
+            return self.show_scratchpad_window(name, window);
+        }
+
+        let Some(&location) = self.known_window_ids.get(&window_id) else {
+            scratchpad.window_id = None;
+            bail!("the window captured by scratchpad {name} is no longer managed");
+        };
+
+        let (monitor_idx, workspace_idx) = location;
+        let workspace = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .and_then(|monitor| monitor.workspaces_mut().get_mut(workspace_idx))
+            .ok_or_eyre("there is no workspace at this index")?;
+
+        let mut window = match workspace
+            .floating_windows()
+            .iter()
+            .position(|window| window.id == window_id)
+        {
+            Some(idx) => workspace
+                .floating_windows_mut()
+                .remove(idx)
+                .ok_or_eyre("there is no floating window at this index")?,
+            None => workspace.remove_window(window_id)?,
+        };
+
+        if location == focused_location {
+            window.hide(workspace.globals.window_hiding_position)?;
+            if workspace.floating_windows().is_empty() {
+                workspace.layer = WorkspaceLayer::Tiling;
+            }
+
+            if let Some(scratchpad) = self.scratchpads.get_mut(name) {
+                scratchpad.hidden_window = Some(window);
+            }
+
+            self.update_known_window_ids();
+            self.update_focused_workspace(mouse_follows_focus, true)
+        } else {
+            // the window was hidden along with the workspace it was shown on
+            window.restore()?;
+            self.show_scratchpad_window(name, window)
+        }
+    }
+
+    fn show_scratchpad_window(&mut self, name: &str, mut window: Window) -> eyre::Result<()> {
+        let mouse_follows_focus = self.mouse_follows_focus;
+        let work_area = self.focused_monitor_work_area()?;
+
+        self.scratchpads
+            .get(name)
+            .ok_or_eyre("there is no scratchpad with this name")?
+            .place(&mut window, &work_area)?;
+
+        let workspace = self.focused_workspace_mut()?;
+        workspace.floating_windows_mut().push_back(window.clone());
+        workspace.layer = WorkspaceLayer::Floating;
+
+        self.update_known_window_ids();
+        self.update_focused_workspace(false, false)?;
+        window.focus(mouse_follows_focus)?;
+
+        Ok(())
+    }
+
+    /// Focuses the window at `location` on the workspace at `workspace_idx` of the monitor at
+    /// `monitor_idx`, focusing that monitor and workspace first if needed
+    #[tracing::instrument(skip(self))]
+    pub fn focus_window_location(
+        &mut self,
+        monitor_idx: usize,
+        workspace_idx: usize,
+        location: WorkspaceWindowLocation,
+    ) -> eyre::Result<()> {
+        let focused_monitor_idx = self.focused_monitor_idx();
+        let mouse_follows_focus = self.mouse_follows_focus;
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_eyre("there is no monitor at this index")?;
+
+        // Focus workspace if it is not already the focused one, without loading it so that we
+        // don't give focus to the wrong window, we will load it later after focusing the wanted
+        // window
+        let mut needs_workspace_loading = false;
+        let focused_ws_idx = monitor.focused_workspace_idx();
+        if focused_ws_idx != workspace_idx {
+            monitor.last_focused_workspace = Option::from(focused_ws_idx);
+            monitor.focus_workspace(workspace_idx)?;
+            needs_workspace_loading = true;
+        }
+
+        if monitor_idx != focused_monitor_idx {
+            self.focus_monitor(monitor_idx)?;
+        }
+
+        match location {
+            WorkspaceWindowLocation::Monocle(window_idx) => {
+                self.focus_container_window(window_idx)?;
+            }
+            WorkspaceWindowLocation::Maximized => {
+                if let Some(window) = &mut self.focused_workspace_mut()?.maximized_window {
+                    window.focus(mouse_follows_focus)?;
+                }
+            }
+            WorkspaceWindowLocation::Container(container_idx, window_idx) => {
+                let focused_container_idx = self.focused_container_idx()?;
+                if container_idx != focused_container_idx {
+                    self.focused_workspace_mut()?.focus_container(container_idx);
+                }
+
+                self.focus_container_window(window_idx)?;
+            }
+            WorkspaceWindowLocation::Floating(window_idx) => {
+                if let Some(window) = self
+                    .focused_workspace_mut()?
+                    .floating_windows_mut()
+                    .get_mut(window_idx)
+                {
+                    window.focus(mouse_follows_focus)?;
+                }
+            }
+        }
+
+        if needs_workspace_loading && let Some(monitor) = self.focused_monitor_mut() {
+            monitor.load_focused_workspace(mouse_follows_focus)?;
+        }
+
+        Ok(())
+    }
+
+    /// Attaches a mark to a window, taking it away from any other window which had it
+    pub fn mark_window(&mut self, window_id: u32, mark: &str) {
+        self.marks.retain(|_, existing| existing != mark);
+        self.marks.insert(window_id, mark.to_string());
+    }
+
+    pub fn marked_window_id(&self, mark: &str) -> Option<u32> {
+        self.marks
+            .iter()
+            .find(|(_, existing)| existing.as_str() == mark)
+            .map(|(window_id, _)| *window_id)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn mark_focused_window(&mut self, mark: &str) -> eyre::Result<()> {
+        tracing::info!("marking focused window");
+
+        let window_id = platform::backend()
+            .foreground_window_id()
+            .ok_or_eyre("there is no foreground window")?;
+
+        if !self.known_window_ids.contains_key(&window_id) {
+            bail!("cannot mark a window which is not managed by komorebi");
+        }
+
+        self.mark_window(window_id, mark);
+
+        Ok(())
+    }
+
+    /// Finds the window with this mark, dropping the mark if the window is no longer managed
+    fn marked_window_location(
+        &mut self,
+        mark: &str,
+    ) -> eyre::Result<(usize, usize, WorkspaceWindowLocation)> {
+        let Some(window_id) = self.marked_window_id(mark) else {
+            bail!("there is no window marked {mark}");
+        };
+
+        let Some(&(monitor_idx, workspace_idx)) = self.known_window_ids.get(&window_id) else {
+            self.marks.remove(&window_id);
+            bail!("the window marked {mark} is no longer managed");
+        };
+
+        let location = self
+            .monitors()
+            .get(monitor_idx)
+            .and_then(|monitor| monitor.workspaces().get(workspace_idx))
+            .and_then(|workspace| workspace.location_from_window_id(window_id))
+            .ok_or_eyre("there is no window with this id")?;
+
+        Ok((monitor_idx, workspace_idx, location))
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn focus_mark(&mut self, mark: &str) -> eyre::Result<()> {
+        tracing::info!("focusing marked window");
+
+        let (monitor_idx, workspace_idx, location) = self.marked_window_location(mark)?;
+        self.focus_window_location(monitor_idx, workspace_idx, location)
+    }
+
+    /// Swaps the focused container with the container of the marked window, which takes over the
+    /// position and focus of the focused container
+    #[tracing::instrument(skip(self))]
+    pub fn swap_with_mark(&mut self, mark: &str) -> eyre::Result<()> {
+        tracing::info!("swapping focused container with marked window");
+
+        let (target_monitor_idx, target_workspace_idx, location) =
+            self.marked_window_location(mark)?;
+
+        let WorkspaceWindowLocation::Container(target_container_idx, _) = location else {
+            bail!("the window marked {mark} is not in a tiled container");
+        };
+
+        if self.focused_workspace()?.monocle_container.is_some() {
+            bail!("cannot swap containers while the focused workspace is in monocle mode");
+        }
+
+        let origin = (
+            self.focused_monitor_idx(),
+            self.focused_workspace_idx()?,
+            self.focused_container_idx()?,
+        );
+        let target = (
+            target_monitor_idx,
+            target_workspace_idx,
+            target_container_idx,
+        );
+
+        if origin == target {
+            return Ok(());
+        }
+
+        if (origin.0, origin.1) == (target.0, target.1) {
+            let workspace = self.focused_workspace_mut()?;
+            workspace.swap_containers(origin.2, target.2);
+            workspace.focus_container(origin.2);
+        } else {
+            self.swap_containers(origin, target)?;
+
+            for (monitor_idx, workspace_idx, container_idx) in [origin, target] {
+                let monitor = self
+                    .monitors_mut()
+                    .get_mut(monitor_idx)
+                    .ok_or_eyre("there is no monitor at this index")?;
+
+                let hiding_position = monitor.window_hiding_position;
+                let is_visible = monitor.focused_workspace_idx() == workspace_idx;
+
+                let workspace = monitor
+                    .workspaces_mut()
+                    .get_mut(workspace_idx)
+                    .ok_or_eyre("there is no workspace at this index")?;
+
+                workspace.focus_container(container_idx);
+
+                let container = workspace
+                    .focused_container_mut()
+                    .ok_or_eyre("there is no container at this index")?;
+
+                if is_visible {
+                    container.restore()?;
+                } else {
+                    container.hide(hiding_position, None)?;
+                }
+            }
+        }
+
+        self.update_known_window_ids();
+        self.retile_all(true)?;
+
+        let mouse_follows_focus = self.mouse_follows_focus;
+        self.focused_window_mut()?.focus(mouse_follows_focus)?;
+
+        Ok(())
+    }
+
+    /// Restores the arrangement from before the most recent layout-altering command
+    #[tracing::instrument(skip(self))]
+    pub fn undo(&mut self) -> eyre::Result<()> {
+        let current = LayoutSnapshot::from(&*self);
+        let Some(previous) = self.history.undo(current) else {
+            tracing::info!("there is nothing to undo");
+            return Ok(());
+        };
+
+        self.restore_layout_snapshot(&previous)
     }
 
+    /// Restores the arrangement from before the most recent undo
     #[tracing::instrument(skip(self))]
-    pub fn restore_all_windows(&mut self, ignore_restore: bool) -> eyre::Result<()> {
-        tracing::info!("restoring all hidden windows");
+    pub fn redo(&mut self) -> eyre::Result<()> {
+        let current = LayoutSnapshot::from(&*self);
+        let Some(next) = self.history.redo(current) else {
+            tracing::info!("there is nothing to redo");
+            return Ok(());
+        };
+
+        self.restore_layout_snapshot(&next)
+    }
+
+    #[tracing::instrument(skip_all)]
+    fn restore_layout_snapshot(&mut self, snapshot: &LayoutSnapshot) -> eyre::Result<()> {
+        let mouse_follows_focus = self.mouse_follows_focus;
+
+        snapshot.restore(&mut self.monitors)?;
 
         for monitor in self.monitors_mut() {
-            for workspace in monitor.workspaces_mut() {
-                for containers in workspace.containers_mut() {
-                    for window in containers.windows_mut() {
-                        if !ignore_restore && let Err(error) = window.restore() {
-                            tracing::error!("failed to restore window: {}", error);
-                        }
-                    }
-                }
-            }
+            monitor.load_focused_workspace(mouse_follows_focus)?;
         }
 
-        Ok(())
+        self.update_known_window_ids();
+        self.retile_all(true)?;
+        self.update_focused_workspace(mouse_follows_focus, true)
     }
 
     #[tracing::instrument(skip(self))]
@@ -1312,6 +2108,78 @@ impl WindowManager {
         self.update_focused_workspace(false, true)
     }
 
+    /// Focuses every workspace of the workspace set named `name` on its monitor, retiling all
+    /// monitors once after every member has been loaded
+    #[tracing::instrument(skip(self))]
+    pub fn focus_workspace_set(&mut self, name: &str) -> eyre::Result<()> {
+        tracing::info!("focusing workspace set");
+
+        let members = self
+            .workspace_sets
+            .iter()
+            .find(|set| set.name == name)
+            .ok_or_eyre("there is no workspace set with this name")?
+            .resolve(&self.monitors, &self.monitor_usr_idx_map)?;
+
+        let focused_monitor_idx = self.focused_monitor_idx();
+        let mouse_follows_focus = self.mouse_follows_focus;
+
+        for (monitor_idx, workspace_idx) in members {
+            let monitor = self
+                .monitors_mut()
+                .get_mut(monitor_idx)
+                .ok_or_eyre("there is no monitor at this index")?;
+
+            if monitor.focused_workspace_idx() != workspace_idx {
+                monitor.focus_workspace(workspace_idx)?;
+                monitor.load_focused_workspace(
+                    mouse_follows_focus && monitor_idx == focused_monitor_idx,
+                )?;
+            }
+        }
+
+        self.last_focused_workspace_set = Some(name.to_string());
+
+        self.retile_all(true)
+    }
+
+    /// Focuses the workspace set before or after the active one, or the first or last set if no
+    /// set is active
+    #[tracing::instrument(skip(self))]
+    pub fn cycle_workspace_set(&mut self, direction: CycleDirection) -> eyre::Result<()> {
+        let len = NonZeroUsize::new(self.workspace_sets.len())
+            .ok_or_eyre("there are no workspace sets")?;
+
+        let active_idx = self.active_workspace_set().and_then(|active| {
+            self.workspace_sets
+                .iter()
+                .position(|set| set.name == active)
+        });
+
+        let idx = match (active_idx, direction) {
+            (Some(idx), direction) => direction.next_idx(idx, len),
+            (None, CycleDirection::Previous) => len.get() - 1,
+            (None, CycleDirection::Next) => 0,
+        };
+
+        let name = self.workspace_sets[idx].name.clone();
+        self.focus_workspace_set(&name)
+    }
+
+    /// The name of the workspace set whose workspaces are all focused, preferring the set which
+    /// was focused last when more than one set is fully focused
+    pub fn active_workspace_set(&self) -> Option<String> {
+        let is_focused =
+            |set: &&WorkspaceSetConfig| set.is_focused(&self.monitors, &self.monitor_usr_idx_map);
+
+        self.workspace_sets
+            .iter()
+            .filter(is_focused)
+            .find(|set| self.last_focused_workspace_set.as_ref() == Some(&set.name))
+            .or_else(|| self.workspace_sets.iter().find(is_focused))
+            .map(|set| set.name.clone())
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn move_container_to_workspace(
         &mut self,
@@ -1404,7 +2272,9 @@ impl WindowManager {
 
     #[tracing::instrument(skip(self))]
     pub fn toggle_float(&mut self, force_float: bool) -> eyre::Result<()> {
-        let window_id = MacosApi::foreground_window_id().ok_or_eyre("no foreground window")?;
+        let window_id = platform::backend()
+            .foreground_window_id()
+            .ok_or_eyre("no foreground window")?;
 
         let workspace = self.focused_workspace_mut()?;
         if workspace.monocle_container.is_some() {
@@ -1479,8 +2349,9 @@ impl WindowManager {
         match workspace.layer {
             WorkspaceLayer::Floating => {
                 let workspace = self.focused_workspace()?;
-                let focused_window_id =
-                    MacosApi::foreground_window_id().ok_or_eyre("no foreground window")?;
+                let focused_window_id = platform::backend()
+                    .foreground_window_id()
+                    .ok_or_eyre("no foreground window")?;
 
                 let border_offset = 0;
                 let border_width = 0;
@@ -1495,7 +2366,7 @@ impl WindowManager {
 
                 for window in workspace.floating_windows().iter() {
                     if window.id == focused_window_id {
-                        let mut rect = Rect::from(MacosApi::window_rect(&window.element)?);
+                        let mut rect = window.rect()?;
                         match (direction, sizing) {
                             (OperationDirection::Left, Sizing::Increase) => {
                                 if rect.left - delta < focused_monitor_work_area.left {
@@ -1552,7 +2423,7 @@ impl WindowManager {
                         window.set_position(&rect)?;
 
                         if mouse_follows_focus {
-                            MacosApi::center_cursor_in_rect(&rect)?;
+                            platform::backend().center_cursor_in_rect(&rect)?;
                         }
 
                         break;
@@ -1803,6 +2674,7 @@ impl WindowManager {
 
                 tracing::info!("next layout: {new_layout}");
                 workspace.layout = Layout::Default(new_layout);
+                workspace.custom_layout = None;
             }
         }
 
@@ -1830,12 +2702,93 @@ impl WindowManager {
     pub fn focus_monitor(&mut self, idx: usize) -> eyre::Result<()> {
         tracing::info!("focusing monitor");
 
+        let previous_idx = self.focused_monitor_idx();
+
         if self.monitors().get(idx).is_some() {
             self.monitors.focus(idx);
         } else {
             eyre::bail!("this is not a valid monitor index");
         }
 
+        if self.sticky_windows_follow_monitor_focus && previous_idx != idx {
+            self.move_sticky_windows_to_monitor(previous_idx, idx)?;
+        }
+
+        Ok(())
+    }
+
+    fn move_sticky_windows_to_monitor(
+        &mut self,
+        origin_idx: usize,
+        target_idx: usize,
+    ) -> eyre::Result<()> {
+        let origin = self
+            .monitors_mut()
+            .get_mut(origin_idx)
+            .ok_or_eyre("there is no monitor at this index")?;
+
+        let origin_area = origin.work_area_size;
+        let sticky_windows = origin
+            .focused_workspace_mut()
+            .ok_or_eyre("there is no workspace")?
+            .remove_sticky_windows();
+
+        if sticky_windows.is_empty() {
+            return Ok(());
+        }
+
+        let target = self
+            .monitors_mut()
+            .get_mut(target_idx)
+            .ok_or_eyre("there is no monitor at this index")?;
+
+        for window in &sticky_windows {
+            window.move_to_area(&origin_area, &target.work_area_size)?;
+        }
+
+        target
+            .focused_workspace_mut()
+            .ok_or_eyre("there is no workspace")?
+            .add_sticky_windows(sticky_windows);
+
+        self.update_known_window_ids();
+
+        Ok(())
+    }
+
+    pub fn is_sticky_window(&self, window_id: u32) -> bool {
+        self.monitors()
+            .iter()
+            .flat_map(|monitor| monitor.workspaces())
+            .flat_map(|workspace| workspace.floating_windows())
+            .any(|window| window.id == window_id && window.sticky)
+    }
+
+    /// Makes the focused floating window stay visible when switching workspaces, or stops it
+    /// from doing so
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_sticky(&mut self) -> eyre::Result<()> {
+        let window_id = platform::backend()
+            .foreground_window_id()
+            .ok_or_eyre("there is no foreground window")?;
+
+        let Some(window) = self
+            .focused_workspace_mut()?
+            .floating_windows_mut()
+            .iter_mut()
+            .find(|window| window.id == window_id)
+        else {
+            bail!("only floating windows can be made sticky");
+        };
+
+        if window.sticky {
+            tracing::info!("unsticking window");
+        } else {
+            tracing::info!("sticking window");
+        }
+
+        window.sticky = !window.sticky;
+
         Ok(())
     }
 
@@ -1871,8 +2824,74 @@ impl WindowManager {
         None
     }
 
+    pub fn set_focus_follows_mouse(
+        &mut self,
+        implementation: Option<FocusFollowsMouseImplementation>,
+    ) {
+        self.focus_follows_mouse = implementation;
+        input_event_listener::FOCUS_FOLLOWS_MOUSE.store(implementation.is_some(), Ordering::SeqCst);
+    }
+
+    /// Focuses the floating or tiled window under the cursor, if it isn't already focused,
+    /// returning what was decided
+    #[tracing::instrument(skip(self))]
+    pub fn focus_window_under_cursor(&mut self) -> eyre::Result<FocusFollowsMouseAction> {
+        if self.is_paused || self.focus_follows_mouse.is_none() {
+            return Ok(FocusFollowsMouseAction::Ignore);
+        }
+
+        // don't interfere with drags and resizes
+        if platform::backend().left_mouse_button_is_pressed() {
+            return Ok(FocusFollowsMouseAction::Ignore);
+        }
+
+        let Some(monitor_idx) = self.monitor_idx_from_current_pos() else {
+            return Ok(FocusFollowsMouseAction::Ignore);
+        };
+
+        let workspace = self
+            .monitors()
+            .get(monitor_idx)
+            .ok_or_eyre("there is no monitor at this index")?
+            .focused_workspace()
+            .ok_or_eyre("there is no workspace")?;
+
+        let point = platform::backend().cursor_pos();
+
+        // floating windows sit above the tiling layout, so they take precedence
+        let floating = workspace
+            .floating_windows()
+            .iter()
+            .find(|w| w.rect().is_ok_and(|rect| rect.contains_point(point)));
+
+        let target = match floating {
+            Some(window) => Some(window),
+            None if workspace.monocle_container.is_some() => None,
+            None => workspace
+                .container_idx_from_current_point()
+                .and_then(|idx| workspace.containers().get(idx))
+                .and_then(|container| container.focused_window()),
+        };
+
+        let action = input_event_listener::current_focus_follows_mouse_action(
+            target.map(|window| window.id),
+            platform::backend().foreground_window_id(),
+        );
+
+        if let (FocusFollowsMouseAction::Focus(id), Some(window)) = (action, target) {
+            tracing::debug!("focusing window {id} under the cursor");
+
+            // our internal focus state is reconciled when we receive the resulting focus event,
+            // exactly the same way as if the user had clicked on the window
+            window.focus(false)?;
+        }
+
+        Ok(action)
+    }
+
     pub fn monitor_idx_from_current_pos(&mut self) -> Option<usize> {
-        let monitor_id = MacosApi::monitor_from_point(MacosApi::cursor_pos())?;
+        let backend = platform::backend();
+        let monitor_id = backend.monitor_from_point(backend.cursor_pos())?;
 
         for (i, monitor) in self.monitors().iter().enumerate() {
             if monitor.id == monitor_id {
@@ -1976,8 +2995,9 @@ impl WindowManager {
             eyre::bail!("cannot move native maximized window to another monitor or workspace");
         }
 
-        let foreground_window_id =
-            MacosApi::foreground_window_id().ok_or_eyre("no foreground window")?;
+        let foreground_window_id = platform::backend()
+            .foreground_window_id()
+            .ok_or_eyre("no foreground window")?;
         let floating_window_index = workspace
             .floating_windows()
             .iter()
@@ -2242,8 +3262,9 @@ impl WindowManager {
         let len = focused_workspace.floating_windows().len();
 
         if len > 1 {
-            let focused_window_id =
-                MacosApi::foreground_window_id().ok_or_eyre("no foreground window")?;
+            let focused_window_id = platform::backend()
+                .foreground_window_id()
+                .ok_or_eyre("no foreground window")?;
             for (idx, window) in focused_workspace.floating_windows().iter().enumerate() {
                 if window.id == focused_window_id {
                     match direction {
@@ -2665,7 +3686,134 @@ impl WindowManager {
     #[tracing::instrument(skip(self))]
     pub fn reload_static_configuration(&mut self, pathbuf: &PathBuf) -> eyre::Result<()> {
         tracing::info!("reloading static configuration");
-        StaticConfig::reload(pathbuf, self)
+        let result = StaticConfig::reload(pathbuf, self);
+
+        // later reloads and watches should follow the configuration which is now in effect
+        if result.is_ok() {
+            self.static_config_path = Some(pathbuf.clone());
+        }
+
+        // the set of application-specific configuration files may have changed, and some editors
+        // replace files on save instead of writing to them, which drops any existing watches
+        if !self.watched_configuration_paths.is_empty() {
+            self.watch_configuration(true)?;
+        }
+
+        result
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn reload_configuration(&mut self) -> eyre::Result<()> {
+        let path = self
+            .static_config_path
+            .clone()
+            .ok_or_eyre("komorebi was not started with a static configuration file")?;
+
+        self.reload_static_configuration(&path)
+    }
+
+    /// Applies the overrides of the profile named `name` on top of the static configuration,
+    /// without reloading any rules or re-enumerating windows; when `name` is `None` the active
+    /// profile is deactivated and the settings of the static configuration are restored
+    #[tracing::instrument(skip(self))]
+    pub fn activate_profile(&mut self, name: Option<&str>) -> eyre::Result<()> {
+        let path = self
+            .static_config_path
+            .clone()
+            .ok_or_eyre("komorebi was not started with a static configuration file")?;
+
+        let config = StaticConfig::read(&path)?;
+        config.validate()?;
+
+        let Some(name) = name else {
+            tracing::info!("deactivating profile");
+            config.apply_profile_settings(self)?;
+            self.active_profile = None;
+
+            return Ok(());
+        };
+
+        tracing::info!("activating profile");
+
+        let profile = config
+            .profiles
+            .as_ref()
+            .and_then(|profiles| profiles.get(name))
+            .ok_or_eyre("there is no profile with this name")?;
+
+        profile.merge(&config).apply_profile_settings(self)?;
+        self.active_profile = Some(name.to_string());
+
+        Ok(())
+    }
+
+    /// Watches the static configuration file, the files it includes and any application-specific
+    /// configuration files it references, reloading the static configuration whenever one of them changes
+    #[tracing::instrument(skip(self))]
+    pub fn watch_configuration(&mut self, enable: bool) -> eyre::Result<()> {
+        for path in std::mem::take(&mut self.watched_configuration_paths) {
+            if let Err(error) = self.hotwatch.unwatch(&path) {
+                tracing::warn!("could not stop watching {}: {error}", path.display());
+            }
+        }
+
+        if !enable {
+            tracing::info!("no longer watching configuration files for changes");
+            return Ok(());
+        }
+
+        let static_config_path = self
+            .static_config_path
+            .clone()
+            .ok_or_eyre("komorebi was not started with a static configuration file")?;
+
+        let mut paths = vec![static_config_path.clone()];
+
+        // if the file can't be read right now we still want to watch it, so that fixing the
+        // problem results in the configuration being reloaded
+        if let Ok(layered) = LayeredConfig::read(&static_config_path) {
+            paths.clone_from(&layered.files);
+
+            if let Ok(config) = layered.deserialize::<StaticConfig>() {
+                paths.extend(config.app_specific_configuration_paths());
+            }
+        }
+
+        let bytes = SocketMessage::ReloadConfiguration.as_bytes()?;
+
+        for path in paths {
+            let bytes = bytes.clone();
+            let watched = self.hotwatch.watch(&path, move |event| match event.kind {
+                // Some editors write to the file in place while others (such as (Neo)Vim) remove
+                // the original and move a new file into its place
+                EventKind::Modify(_) | EventKind::Remove(_) => {
+                    let socket = DATA_DIR.join("komorebi.sock");
+                    match UnixStream::connect(socket) {
+                        Ok(mut stream) => {
+                            if let Err(error) = stream.write_all(&bytes) {
+                                tracing::error!("could not write to komorebi.sock: {error}");
+                            }
+                        }
+                        Err(error) => {
+                            tracing::error!("could not connect to komorebi.sock: {error}");
+                        }
+                    }
+                }
+                _ => {}
+            });
+
+            match watched {
+                Ok(()) => {
+                    tracing::info!("watching {} for changes", path.display());
+                    self.watched_configuration_paths.push(path);
+                }
+                Err(error) => {
+                    tracing::warn!("could not watch {}: {error}", path.display());
+                }
+            }
+        }
+
+        Ok(())
     }
 
     #[tracing::instrument(skip(self))]
@@ -2675,8 +3823,9 @@ impl WindowManager {
             OperationBehaviour::NoOp
         ) {
             let workspace = self.focused_workspace()?;
-            let focused_hwnd =
-                MacosApi::foreground_window_id().ok_or_eyre("there is no foreground window")?;
+            let focused_hwnd = platform::backend()
+                .foreground_window_id()
+                .ok_or_eyre("there is no foreground window")?;
             if !workspace.contains_managed_window(focused_hwnd) {
                 bail!("ignoring commands while active window is not managed by komorebi");
             }
@@ -2916,6 +4065,56 @@ impl WindowManager {
         rules.push((at_container_count, Layout::Default(layout)));
         rules.sort_by(|a, b| a.0.cmp(&b.0));
 
+        workspace
+            .custom_layout_rules
+            .retain(|pair| pair.0 != at_container_count);
+
+        // If this is the focused workspace on a non-focused screen, let's update it
+        if focused_monitor_idx != monitor_idx && focused_workspace_idx == workspace_idx {
+            workspace.update()?;
+            Ok(())
+        } else {
+            Ok(self.update_focused_workspace(false, false)?)
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn add_workspace_custom_layout_rule<P>(
+        &mut self,
+        monitor_idx: usize,
+        workspace_idx: usize,
+        at_container_count: usize,
+        path: P,
+    ) -> eyre::Result<()>
+    where
+        P: AsRef<Path> + std::fmt::Debug,
+    {
+        tracing::info!("setting workspace layout");
+
+        let layout = CustomLayout::from_path(path)?;
+        let focused_monitor_idx = self.focused_monitor_idx();
+
+        let monitor = self
+            .monitors_mut()
+            .get_mut(monitor_idx)
+            .ok_or_eyre("there is no monitor")?;
+
+        let focused_workspace_idx = monitor.focused_workspace_idx();
+
+        let workspace = monitor
+            .workspaces_mut()
+            .get_mut(workspace_idx)
+            .ok_or_eyre("there is no workspace")?;
+
+        let rules: &mut Vec<(usize, CustomLayout)> = &mut workspace.custom_layout_rules;
+        rules.retain(|pair| pair.0 != at_container_count);
+        rules.push((at_container_count, layout));
+        rules.sort_by(|a, b| a.0.cmp(&b.0));
+
+        workspace
+            .layout_rules
+            .retain(|pair| pair.0 != at_container_count);
+
         // If this is the focused workspace on a non-focused screen, let's update it
         if focused_monitor_idx != monitor_idx && focused_workspace_idx == workspace_idx {
             workspace.update()?;
@@ -2949,6 +4148,7 @@ impl WindowManager {
 
         let rules: &mut Vec<(usize, Layout)> = &mut workspace.layout_rules;
         rules.clear();
+        workspace.custom_layout_rules.clear();
 
         // If this is the focused workspace on a non-focused screen, let's update it
         if focused_monitor_idx != monitor_idx && focused_workspace_idx == workspace_idx {
@@ -3080,18 +4280,15 @@ impl WindowManager {
 
     #[tracing::instrument(skip(self))]
     pub fn manage_focused_window(&mut self) -> eyre::Result<()> {
-        let element = MacosApi::foreground_window().ok_or_eyre("there is no foreground window")?;
-        let window_id = MacosApi::foreground_window_id();
-        if let Some(process_id) = AdhocWindow::process_id(&element)
-            && let Some(event) = WindowManagerEvent::from_system_notification(
-                SystemNotification::Manual(ManualNotification::Manage),
-                process_id,
-                window_id,
-            )
-        {
-            UNMANAGED_WINDOW_IDS
-                .lock()
-                .retain(|id| *id != window_id.unwrap_or_default());
+        let window = platform::backend()
+            .foreground_window()
+            .ok_or_eyre("there is no foreground window")?;
+        if let Some(event) = WindowManagerEvent::from_system_notification(
+            SystemNotification::Manual(ManualNotification::Manage),
+            window.application.process_id,
+            Some(window.id),
+        ) {
+            UNMANAGED_WINDOW_IDS.lock().retain(|id| *id != window.id);
 
             window_manager_event_listener::send_notification(event);
         }
@@ -3101,15 +4298,14 @@ impl WindowManager {
 
     #[tracing::instrument(skip(self))]
     pub fn unmanage_focused_window(&mut self) -> eyre::Result<()> {
-        let element = MacosApi::foreground_window().ok_or_eyre("there is no foreground window")?;
-        let window_id = MacosApi::foreground_window_id();
-        if let Some(process_id) = AdhocWindow::process_id(&element)
-            && let Some(event) = WindowManagerEvent::from_system_notification(
-                SystemNotification::Manual(ManualNotification::Unmanage),
-                process_id,
-                window_id,
-            )
-        {
+        let window = platform::backend()
+            .foreground_window()
+            .ok_or_eyre("there is no foreground window")?;
+        if let Some(event) = WindowManagerEvent::from_system_notification(
+            SystemNotification::Manual(ManualNotification::Unmanage),
+            window.application.process_id,
+            Some(window.id),
+        ) {
             window_manager_event_listener::send_notification(event);
         }
 
@@ -3179,7 +4375,7 @@ impl WindowManager {
             .unwrap_or_default()
         {
             // Moving monocle container
-            if let Some(monocle_idx) = origin_workspace.monocle_container_restore_idx {
+            if origin_workspace.monocle_container_restore_idx.is_some() {
                 let origin_workspace = self
                     .monitors_mut()
                     .get_mut(origin_monitor_idx)
@@ -3194,6 +4390,10 @@ impl WindowManager {
 
                 origin_workspace.reintegrate_monocle_container()?;
 
+                // the restore index is clamped if containers were closed while the monocle was
+                // active, so we look up where the reintegrated container actually ended up
+                let monocle_idx = origin_workspace.focused_container_idx();
+
                 self.transfer_container(
                     (origin_monitor_idx, origin_workspace_idx, monocle_idx),
                     (
@@ -3274,7 +4474,7 @@ impl WindowManager {
             .get_mut(target_workspace_idx)
             .ok_or_eyre("there is no workspace at this index")?
             .containers()
-            .get(origin_container_idx)
+            .get(target_container_idx)
             .is_some();
 
         if origin_container_is_valid && target_container_is_valid {
@@ -3380,10 +4580,59 @@ impl WindowManager {
     }
 }
 
+/// Finds the workspace on `monitor` for each saved workspace, by name where possible and by
+/// index otherwise, creating workspaces if there aren't enough of them
+fn resolve_session_workspaces(monitor: &mut Monitor, saved: &MonitorSession) -> Vec<usize> {
+    let mut resolved = vec![];
+    for workspace in &saved.workspaces {
+        let by_name = workspace.name.as_ref().and_then(|name| {
+            monitor
+                .workspaces()
+                .iter()
+                .position(|w| w.name.as_ref() == Some(name))
+        });
+
+        let by_name = by_name.filter(|idx| !resolved.contains(&Some(*idx)));
+        resolved.push(by_name);
+    }
+
+    for saved_idx in 0..resolved.len() {
+        if resolved[saved_idx].is_some() {
+            continue;
+        }
+
+        let workspace_idx = (saved_idx..)
+            .chain(0..saved_idx)
+            .find(|idx| !resolved.contains(&Some(*idx)))
+            .unwrap_or(saved_idx);
+
+        monitor.ensure_workspace_count(workspace_idx + 1);
+        resolved[saved_idx] = Some(workspace_idx);
+
+        let name = monitor.workspaces()[workspace_idx].name.as_ref();
+        if let Some(saved_name) = &saved.workspaces[saved_idx].name
+            && name != Some(saved_name)
+        {
+            tracing::warn!(
+                "there is no workspace named {saved_name}, restoring it onto workspace {workspace_idx}{}",
+                name.map(|name| format!(" ({name})")).unwrap_or_default()
+            );
+        }
+    }
+
+    resolved.into_iter().flatten().collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::accessibility::notification_constants::AccessibilityNotification;
     use crate::monitor;
+    use crate::platform::FakeBackend;
+    use crate::platform::PlatformCall;
+    use crate::platform::ScopedBackendGuard;
+    use crate::platform::StubWindow;
+    use crate::state::State;
     use crossbeam_channel::Sender;
     use crossbeam_channel::bounded;
     use std::path::PathBuf;
@@ -3391,6 +4640,8 @@ mod tests {
 
     struct TestContext {
         socket_path: Option<PathBuf>,
+        backend: Arc<FakeBackend>,
+        _backend: ScopedBackendGuard,
     }
 
     impl Drop for TestContext {
@@ -3403,6 +4654,10 @@ mod tests {
     }
 
     fn setup_window_manager() -> (WindowManager, TestContext) {
+        // the backend has to be installed before the window manager asks it for the space id
+        let backend = Arc::new(FakeBackend::default());
+        let guard = platform::scoped_backend(backend.clone());
+
         let (_sender, receiver): (Sender<WindowManagerEvent>, Receiver<WindowManagerEvent>) =
             bounded(1);
 
@@ -3424,6 +4679,8 @@ mod tests {
             wm.unwrap(),
             TestContext {
                 socket_path: Some(socket_path),
+                backend,
+                _backend: guard,
             },
         )
     }
@@ -3455,25 +4712,28 @@ mod tests {
         // add the monitor to the window manager
         wm.monitors_mut().push_back(m);
 
-        {
-            // focusing a workspace which doesn't yet exist should create it
-            let monitor = wm.focused_monitor_mut().unwrap();
-            monitor.focus_workspace(new_workspace_index).unwrap();
-            assert_eq!(monitor.workspaces().len(), 2);
-        }
+        // focusing a workspace which doesn't yet exist should create it
+        wm.process_command(
+            SocketMessage::FocusWorkspaceNumber(new_workspace_index),
+            std::io::sink(),
+        )
+        .unwrap();
+        assert_eq!(wm.focused_monitor().unwrap().workspaces().len(), 2);
         assert_eq!(wm.focused_workspace_idx().unwrap(), 1);
 
-        {
-            // focusing a workspace many indices ahead should create all workspaces
-            // required along the way
-            let monitor = wm.focused_monitor_mut().unwrap();
-            monitor.focus_workspace(new_workspace_index + 2).unwrap();
-            assert_eq!(monitor.workspaces().len(), 4);
-        }
+        // focusing a workspace many indices ahead should create all workspaces
+        // required along the way
+        wm.process_command(
+            SocketMessage::FocusWorkspaceNumber(new_workspace_index + 2),
+            std::io::sink(),
+        )
+        .unwrap();
+        assert_eq!(wm.focused_monitor().unwrap().workspaces().len(), 4);
         assert_eq!(wm.focused_workspace_idx().unwrap(), 3);
 
         // we should be able to successfully focus an existing workspace too
-        wm.focus_workspace(0).unwrap();
+        wm.process_command(SocketMessage::FocusWorkspaceNumber(0), std::io::sink())
+            .unwrap();
         assert_eq!(wm.focused_workspace_idx().unwrap(), 0);
     }
 
@@ -3499,10 +4759,15 @@ mod tests {
         // add the monitor to the window manager
         wm.monitors_mut().push_back(m);
 
+        // focus a workspace which doesn't yet exist should create it
+        wm.process_command(
+            SocketMessage::FocusWorkspaceNumber(new_workspace_index + 1),
+            std::io::sink(),
+        )
+        .unwrap();
+
         {
-            // focus a workspace which doesn't yet exist should create it
-            let monitor = wm.focused_monitor_mut().unwrap();
-            monitor.focus_workspace(new_workspace_index + 1).unwrap();
+            let monitor = wm.focused_monitor().unwrap();
 
             // Monitor focused workspace should be 2
             assert_eq!(monitor.focused_workspace_idx(), 2);
@@ -3511,12 +4776,12 @@ mod tests {
             assert_eq!(monitor.workspaces().len(), 3);
         }
 
-        // Remove the focused workspace
-        wm.remove_focused_workspace().unwrap();
+        // Close the focused workspace, which is empty and unnamed
+        wm.process_command(SocketMessage::CloseWorkspace, std::io::sink())
+            .unwrap();
 
         {
-            let monitor = wm.focused_monitor_mut().unwrap();
-            monitor.focus_workspace(new_workspace_index).unwrap();
+            let monitor = wm.focused_monitor().unwrap();
 
             // Should be focused on workspace 1
             assert_eq!(monitor.focused_workspace_idx(), 1);
@@ -3548,17 +4813,21 @@ mod tests {
         // add the monitor to the window manager
         wm.monitors_mut().push_back(m);
 
-        {
-            // focusing a workspace which doesn't yet exist should create it
-            let monitor = wm.focused_monitor_mut().unwrap();
-            monitor.focus_workspace(new_workspace_index).unwrap();
-            assert_eq!(monitor.workspaces().len(), 2);
-        }
+        // focusing a workspace which doesn't yet exist should create it
+        wm.process_command(
+            SocketMessage::FocusWorkspaceNumber(new_workspace_index),
+            std::io::sink(),
+        )
+        .unwrap();
+        assert_eq!(wm.focused_monitor().unwrap().workspaces().len(), 2);
         assert_eq!(wm.focused_workspace_idx().unwrap(), 1);
 
         // set the name of the first workspace
-        wm.set_workspace_name(0, 0, "workspace1".to_string())
-            .unwrap();
+        wm.process_command(
+            SocketMessage::WorkspaceName(0, 0, "workspace1".to_string()),
+            std::io::sink(),
+        )
+        .unwrap();
 
         // monitor_workspace_index_by_name should return the index of the workspace with the name "workspace1"
         let workspace_index = wm.monitor_workspace_index_by_name("workspace1").unwrap();
@@ -3627,27 +4896,31 @@ mod tests {
 
         {
             // Set the first monitor as focused and check if it is focused
-            wm.focus_monitor(0).unwrap();
+            wm.process_command(SocketMessage::FocusMonitorNumber(0), std::io::sink())
+                .unwrap();
             let current_monitor_idx = wm.monitors.focused_idx();
             assert_eq!(current_monitor_idx, 0);
         }
 
         {
             // Set the second monitor as focused and check if it is focused
-            wm.focus_monitor(1).unwrap();
+            wm.process_command(SocketMessage::FocusMonitorNumber(1), std::io::sink())
+                .unwrap();
             let current_monitor_idx = wm.monitors.focused_idx();
             assert_eq!(current_monitor_idx, 1);
         }
 
         {
             // Set the third monitor as focused and check if it is focused
-            wm.focus_monitor(2).unwrap();
+            wm.process_command(SocketMessage::FocusMonitorNumber(2), std::io::sink())
+                .unwrap();
             let current_monitor_idx = wm.monitors.focused_idx();
             assert_eq!(current_monitor_idx, 2);
         }
 
         // Switch back to the first monitor
-        wm.focus_monitor(0).unwrap();
+        wm.process_command(SocketMessage::FocusMonitorNumber(0), std::io::sink())
+            .unwrap();
         let current_monitor_idx = wm.monitors.focused_idx();
         assert_eq!(current_monitor_idx, 0);
     }
@@ -3678,7 +4951,7 @@ mod tests {
         assert_eq!(wm.focused_monitor_idx(), 0);
 
         // Should receive an error when trying to focus a non-existent monitor
-        let result = wm.focus_monitor(1);
+        let result = wm.process_command(SocketMessage::FocusMonitorNumber(1), std::io::sink());
         assert!(
             result.is_err(),
             "Expected an error when focusing a non-existent monitor"
@@ -3712,7 +4985,8 @@ mod tests {
 
         {
             // Set the first monitor as focused and check if it is focused
-            wm.focus_monitor(0).unwrap();
+            wm.process_command(SocketMessage::FocusMonitorNumber(0), std::io::sink())
+                .unwrap();
             let current_monitor_size = wm.focused_monitor_size().unwrap();
             assert_eq!(current_monitor_size, Rect::default());
         }
@@ -3747,23 +5021,35 @@ mod tests {
         wm.monitors_mut().push_back(m);
 
         // container focus should be on the second container
-        wm.focus_container_in_cycle_direction(CycleDirection::Next)
-            .ok();
+        wm.process_command(
+            SocketMessage::CycleFocusWindow(CycleDirection::Next),
+            std::io::sink(),
+        )
+        .ok();
         assert_eq!(wm.focused_container_idx().unwrap(), 1);
 
         // container focus should be on the third container
-        wm.focus_container_in_cycle_direction(CycleDirection::Next)
-            .ok();
+        wm.process_command(
+            SocketMessage::CycleFocusWindow(CycleDirection::Next),
+            std::io::sink(),
+        )
+        .ok();
         assert_eq!(wm.focused_container_idx().unwrap(), 2);
 
         // container focus should be on the second container
-        wm.focus_container_in_cycle_direction(CycleDirection::Previous)
-            .ok();
+        wm.process_command(
+            SocketMessage::CycleFocusWindow(CycleDirection::Previous),
+            std::io::sink(),
+        )
+        .ok();
         assert_eq!(wm.focused_container_idx().unwrap(), 1);
 
         // container focus should be on the first container
-        wm.focus_container_in_cycle_direction(CycleDirection::Previous)
-            .ok();
+        wm.process_command(
+            SocketMessage::CycleFocusWindow(CycleDirection::Previous),
+            std::io::sink(),
+        )
+        .ok();
         assert_eq!(wm.focused_container_idx().unwrap(), 0);
     }
 
@@ -4054,7 +5340,8 @@ mod tests {
         }
 
         // Remove the focused window from the container
-        wm.remove_window_from_container().ok();
+        wm.process_command(SocketMessage::UnstackWindow, std::io::sink())
+            .ok();
 
         {
             // Should have 2 containers in the workspace
@@ -4106,7 +5393,7 @@ mod tests {
         }
 
         // Should receive an error when trying to remove a window from an empty container
-        let result = wm.remove_window_from_container();
+        let result = wm.process_command(SocketMessage::UnstackWindow, std::io::sink());
         assert!(
             result.is_err(),
             "Expected an error when trying to remove a window from an empty container"
@@ -4147,8 +5434,11 @@ mod tests {
         }
 
         // Cycle to the next window
-        wm.cycle_container_window_in_direction(CycleDirection::Next)
-            .ok();
+        wm.process_command(
+            SocketMessage::CycleStack(CycleDirection::Next),
+            std::io::sink(),
+        )
+        .ok();
 
         {
             // Should be on Window 1
@@ -4158,8 +5448,11 @@ mod tests {
         }
 
         // Cycle to the next window
-        wm.cycle_container_window_in_direction(CycleDirection::Next)
-            .ok();
+        wm.process_command(
+            SocketMessage::CycleStack(CycleDirection::Next),
+            std::io::sink(),
+        )
+        .ok();
 
         {
             // Should be on Window 2
@@ -4169,8 +5462,11 @@ mod tests {
         }
 
         // Cycle to the previous window
-        wm.cycle_container_window_in_direction(CycleDirection::Previous)
-            .ok();
+        wm.process_command(
+            SocketMessage::CycleStack(CycleDirection::Previous),
+            std::io::sink(),
+        )
+        .ok();
 
         {
             // Should be on Window 1
@@ -4209,7 +5505,10 @@ mod tests {
         }
 
         // Should return an error when trying to cycle through windows in an empty container
-        let result = wm.cycle_container_window_in_direction(CycleDirection::Next);
+        let result = wm.process_command(
+            SocketMessage::CycleStack(CycleDirection::Next),
+            std::io::sink(),
+        );
         assert!(
             result.is_err(),
             "Expected an error when cycling through windows in an empty container"
@@ -4250,8 +5549,11 @@ mod tests {
         }
 
         // Cycle to the next window
-        wm.cycle_container_window_index_in_direction(CycleDirection::Next)
-            .ok();
+        wm.process_command(
+            SocketMessage::CycleStackIndex(CycleDirection::Next),
+            std::io::sink(),
+        )
+        .ok();
 
         {
             // Should be on Window 1
@@ -4261,8 +5563,11 @@ mod tests {
         }
 
         // Cycle to the next window
-        wm.cycle_container_window_index_in_direction(CycleDirection::Next)
-            .ok();
+        wm.process_command(
+            SocketMessage::CycleStackIndex(CycleDirection::Next),
+            std::io::sink(),
+        )
+        .ok();
 
         {
             // Should be on Window 2
@@ -4272,8 +5577,11 @@ mod tests {
         }
 
         // Cycle to the Previous window
-        wm.cycle_container_window_index_in_direction(CycleDirection::Previous)
-            .ok();
+        wm.process_command(
+            SocketMessage::CycleStackIndex(CycleDirection::Previous),
+            std::io::sink(),
+        )
+        .ok();
 
         {
             // Should be on Window 1
@@ -4368,6 +5676,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_swap_containers_at_different_indices() {
+        let (mut wm, _context) = setup_window_manager();
+
+        let mut m = monitor::new(
+            0,
+            Rect::default(),
+            Rect::default(),
+            "TestMonitor".to_string(),
+            "TestDeviceID".to_string(),
+        );
+
+        {
+            let workspace = m.focused_workspace_mut().unwrap();
+            for i in 0..2 {
+                let mut container = Container::default();
+                container.windows_mut().push_back(Window::from(i));
+                workspace.add_container_to_back(container);
+            }
+        }
+
+        m.focus_workspace(1).unwrap();
+
+        {
+            let workspace = m.focused_workspace_mut().unwrap();
+            let mut container = Container::default();
+            container.windows_mut().push_back(Window::from(2));
+            workspace.add_container_to_back(container);
+        }
+
+        m.focus_workspace(0).unwrap();
+        wm.monitors_mut().push_back(m);
+
+        // the target workspace only has a container at index 0, so the target index has to be
+        // validated against the target workspace rather than the origin index
+        wm.swap_containers((0, 0, 1), (0, 1, 0)).unwrap();
+
+        let window_ids = |workspace_idx: usize| {
+            wm.monitors()[0].workspaces()[workspace_idx]
+                .containers()
+                .iter()
+                .map(|container| container.focused_window().unwrap().id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(window_ids(0), vec![0, 2]);
+        assert_eq!(window_ids(1), vec![1]);
+    }
+
     // #[test]
     // fn test_swap_container_with_nonexistent_container() {
     //     let (mut wm, _context) = setup_window_manager();
@@ -4490,7 +5847,11 @@ mod tests {
         }
 
         // Swap the workspaces between Monitor 0 and Monitor 1
-        wm.swap_monitor_workspaces(0, 1).ok();
+        wm.process_command(
+            SocketMessage::SwapWorkspacesToMonitorNumber(1),
+            std::io::sink(),
+        )
+        .ok();
 
         {
             // The focused workspace container in Monitor 0 should contain 3 containers
@@ -4536,7 +5897,10 @@ mod tests {
         }
 
         // Should be an error since Monitor 1 does not exist
-        let result = wm.swap_monitor_workspaces(1, 0);
+        let result = wm.process_command(
+            SocketMessage::SwapWorkspacesToMonitorNumber(1),
+            std::io::sink(),
+        );
         assert!(
             result.is_err(),
             "Expected an error when swapping with a non-existent monitor"
@@ -4599,7 +5963,11 @@ mod tests {
         assert_eq!(wm.monitors().len(), 2);
 
         // Move a workspace from Monitor 0 to Monitor 1
-        wm.move_workspace_to_monitor(1).ok();
+        wm.process_command(
+            SocketMessage::MoveWorkspaceToMonitorNumber(1),
+            std::io::sink(),
+        )
+        .ok();
 
         {
             // Should be focused on Monitor 1
@@ -4645,7 +6013,10 @@ mod tests {
         }
 
         // Attempt to move a workspace to a non-existent monitor
-        let result = wm.move_workspace_to_monitor(1);
+        let result = wm.process_command(
+            SocketMessage::MoveWorkspaceToMonitorNumber(1),
+            std::io::sink(),
+        );
 
         // Should be an error since Monitor 1 does not exist
         assert!(
@@ -4680,14 +6051,16 @@ mod tests {
 
         {
             // Tiling state should be false
-            wm.toggle_tiling().unwrap();
+            wm.process_command(SocketMessage::ToggleTiling, std::io::sink())
+                .unwrap();
             let workspace = wm.focused_workspace_mut().unwrap();
             assert!(!workspace.tile);
         }
 
         {
             // Tiling state should be true
-            wm.toggle_tiling().unwrap();
+            wm.process_command(SocketMessage::ToggleTiling, std::io::sink())
+                .unwrap();
             let workspace = wm.focused_workspace_mut().unwrap();
             assert!(workspace.tile);
         }
@@ -4726,7 +6099,8 @@ mod tests {
         }
 
         // Toggle lock on focused container
-        wm.toggle_lock().unwrap();
+        wm.process_command(SocketMessage::ToggleLock, std::io::sink())
+            .unwrap();
 
         {
             // Ensure container 2 is locked
@@ -4735,7 +6109,8 @@ mod tests {
         }
 
         // Toggle lock on focused container
-        wm.toggle_lock().unwrap();
+        wm.process_command(SocketMessage::ToggleLock, std::io::sink())
+            .unwrap();
 
         {
             // Ensure container 2 is not locked
@@ -4746,7 +6121,7 @@ mod tests {
 
     #[test]
     fn test_float_window() {
-        let (mut wm, _context) = setup_window_manager();
+        let (mut wm, context) = setup_window_manager();
 
         {
             // Create a monitor
@@ -4777,8 +6152,10 @@ mod tests {
             wm.monitors_mut().push_back(m);
         }
 
-        // Add focused window to floating window list
-        wm.float_window().ok();
+        // Float the focused window
+        context.backend.set_foreground_window(Some(0));
+        wm.process_command(SocketMessage::ToggleFloat, std::io::sink())
+            .ok();
 
         {
             let workspace = wm.focused_workspace().unwrap();
@@ -4798,8 +6175,10 @@ mod tests {
             assert_eq!(container.focused_window().unwrap().id, 1);
         }
 
-        // Add focused window to floating window list
-        wm.float_window().ok();
+        // Float the next window in the container
+        context.backend.set_foreground_window(Some(1));
+        wm.process_command(SocketMessage::ToggleFloat, std::io::sink())
+            .ok();
 
         {
             let workspace = wm.focused_workspace().unwrap();
@@ -4822,7 +6201,7 @@ mod tests {
 
     #[test]
     fn test_float_nonexistent_window() {
-        let (mut wm, _context) = setup_window_manager();
+        let (mut wm, context) = setup_window_manager();
 
         {
             let mut m = monitor::new(
@@ -4845,7 +6224,8 @@ mod tests {
         }
 
         // Should return an error when trying to float a non-existent window
-        let result = wm.float_window();
+        context.backend.set_foreground_window(Some(0));
+        let result = wm.process_command(SocketMessage::ToggleFloat, std::io::sink());
         assert!(
             result.is_err(),
             "Expected an error when trying to float a non-existent window"
@@ -5165,7 +6545,8 @@ mod tests {
         }
 
         // Toggle monocle on
-        wm.toggle_monocle().ok();
+        wm.process_command(SocketMessage::ToggleMonocle, std::io::sink())
+            .ok();
 
         {
             // Container should be a monocle container
@@ -5186,7 +6567,8 @@ mod tests {
         }
 
         // Toggle monocle off
-        wm.toggle_monocle().ok();
+        wm.process_command(SocketMessage::ToggleMonocle, std::io::sink())
+            .ok();
 
         {
             // Should have 1 container in the workspace
@@ -5221,7 +6603,7 @@ mod tests {
         }
 
         // Should return an error when trying to toggle monocle on a non-existent container
-        let result = wm.toggle_monocle();
+        let result = wm.process_command(SocketMessage::ToggleMonocle, std::io::sink());
         assert!(
             result.is_err(),
             "Expected an error when trying to toggle monocle on a non-existent container"
@@ -5264,8 +6646,11 @@ mod tests {
         let mut workspace_names = vec!["Workspace".to_string(), "Workspace1".to_string()];
 
         // Ensure workspaces for monitor 1
-        wm.ensure_named_workspaces_for_monitor(1, &workspace_names)
-            .ok();
+        wm.process_command(
+            SocketMessage::EnsureNamedWorkspaces(1, workspace_names.clone()),
+            std::io::sink(),
+        )
+        .ok();
 
         {
             // Monitor 1 should have 2 workspaces with names "Workspace" and "Workspace1"
@@ -5282,8 +6667,11 @@ mod tests {
         workspace_names.push("Workspace3".to_string());
 
         // Ensure workspaces for monitor 0
-        wm.ensure_named_workspaces_for_monitor(0, &workspace_names)
-            .ok();
+        wm.process_command(
+            SocketMessage::EnsureNamedWorkspaces(0, workspace_names.clone()),
+            std::io::sink(),
+        )
+        .ok();
 
         {
             // Monitor 0 should have 4 workspaces with names "Workspace", "Workspace1",
@@ -5335,4 +6723,448 @@ mod tests {
         assert_eq!(op.target_workspace_idx, target_workspace_idx); // 3
         assert_eq!(op.floating, floating); // false
     }
+
+    #[test]
+    fn test_focus_workspace_with_fake_backend() {
+        let (mut wm, context) = setup_window_manager();
+
+        let size = Rect {
+            left: 0,
+            top: 0,
+            right: 1920,
+            bottom: 1080,
+        };
+
+        let mut m = monitor::new(
+            0,
+            size,
+            size,
+            "TestMonitor".to_string(),
+            "TestDeviceID".to_string(),
+        );
+
+        {
+            let workspace = m.focused_workspace_mut().unwrap();
+            for window_id in [9001, 9002] {
+                let mut container = Container::default();
+                container.windows_mut().push_back(Window::from(window_id));
+                workspace.add_container_to_back(container);
+            }
+        }
+
+        wm.monitors_mut().push_back(m);
+        wm.update_known_window_ids();
+
+        // switching to another workspace should hide the windows on the first one
+        wm.process_command(SocketMessage::FocusWorkspaceNumber(1), std::io::sink())
+            .unwrap();
+
+        for window_id in [9001, 9002] {
+            let calls = context.backend.calls_for(window_id);
+            assert!(calls.contains(&PlatformCall::Hide(window_id)));
+            assert!(!calls.contains(&PlatformCall::Restore(window_id)));
+        }
+
+        context.backend.clear_calls();
+
+        // switching back should restore and tile them again
+        wm.process_command(SocketMessage::FocusWorkspaceNumber(0), std::io::sink())
+            .unwrap();
+
+        for window_id in [9001, 9002] {
+            let calls = context.backend.calls_for(window_id);
+            assert!(calls.contains(&PlatformCall::Restore(window_id)));
+            assert!(
+                calls.iter().any(
+                    |call| matches!(call, PlatformCall::SetPosition(id, _) if *id == window_id)
+                )
+            );
+        }
+
+        // the window positions can be read back from the backend
+        let first = Window::from(9001).rect().unwrap();
+        let second = Window::from(9002).rect().unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_mark_window() {
+        let (mut wm, _context) = setup_window_manager();
+
+        wm.mark_window(1, "browser");
+        wm.mark_window(2, "editor");
+        assert_eq!(wm.marked_window_id("browser"), Some(1));
+        assert_eq!(wm.marked_window_id("editor"), Some(2));
+
+        // a mark can only be attached to one window at a time
+        wm.mark_window(3, "browser");
+        assert_eq!(wm.marked_window_id("browser"), Some(3));
+        assert!(!wm.marks.contains_key(&1));
+
+        // and a window only carries one mark
+        wm.mark_window(3, "notes");
+        assert_eq!(wm.marked_window_id("browser"), None);
+        assert_eq!(wm.marks.len(), 2);
+    }
+
+    #[test]
+    fn test_swap_with_mark_with_fake_backend() {
+        let (mut wm, context) = setup_window_manager();
+
+        let size = Rect {
+            left: 0,
+            top: 0,
+            right: 1920,
+            bottom: 1080,
+        };
+
+        let mut m = monitor::new(
+            0,
+            size,
+            size,
+            "TestMonitor".to_string(),
+            "TestDeviceID".to_string(),
+        );
+
+        m.focus_workspace(1).unwrap();
+        {
+            let workspace = m.focused_workspace_mut().unwrap();
+            let mut container = Container::default();
+            container.windows_mut().push_back(Window::from(9103));
+            workspace.add_container_to_back(container);
+        }
+
+        m.focus_workspace(0).unwrap();
+        {
+            let workspace = m.focused_workspace_mut().unwrap();
+            for window_id in [9101, 9102] {
+                let mut container = Container::default();
+                container.windows_mut().push_back(Window::from(window_id));
+                workspace.add_container_to_back(container);
+            }
+
+            workspace.focus_container(0);
+        }
+
+        wm.monitors_mut().push_back(m);
+        wm.update_known_window_ids();
+        wm.mark_window(9103, "notes");
+
+        // the marked window on the hidden workspace takes over the focused container's position
+        wm.process_command(
+            SocketMessage::SwapWithMark(String::from("notes")),
+            std::io::sink(),
+        )
+        .unwrap();
+
+        assert_eq!(wm.focused_workspace_idx().unwrap(), 0);
+        assert_eq!(wm.focused_window().unwrap().id, 9103);
+        assert_eq!(wm.known_window_ids.get(&9101), Some(&(0, 1)));
+        assert!(
+            context
+                .backend
+                .calls_for(9101)
+                .contains(&PlatformCall::Hide(9101))
+        );
+        assert!(
+            context
+                .backend
+                .calls_for(9103)
+                .contains(&PlatformCall::Restore(9103))
+        );
+        assert!(
+            context
+                .backend
+                .calls_for(9103)
+                .contains(&PlatformCall::Focus(9103))
+        );
+
+        // focusing a mark follows the window to the workspace it now lives on
+        wm.mark_window(9101, "browser");
+        wm.process_command(
+            SocketMessage::FocusMark(String::from("browser")),
+            std::io::sink(),
+        )
+        .unwrap();
+
+        assert_eq!(wm.focused_workspace_idx().unwrap(), 1);
+        assert_eq!(wm.focused_window().unwrap().id, 9101);
+
+        // marks on windows which are no longer managed are dropped
+        wm.mark_window(9999, "gone");
+        assert!(
+            wm.process_command(
+                SocketMessage::FocusMark(String::from("gone")),
+                std::io::sink()
+            )
+            .is_err()
+        );
+        assert_eq!(wm.marked_window_id("gone"), None);
+    }
+
+    #[test]
+    fn test_sticky_windows_follow_monitor_focus_with_fake_backend() {
+        let (mut wm, context) = setup_window_manager();
+
+        for (id, left) in [(0, 0), (1, 1920)] {
+            let size = Rect {
+                left,
+                top: 0,
+                right: 1920,
+                bottom: 1080,
+            };
+
+            wm.monitors_mut().push_back(monitor::new(
+                id,
+                size,
+                size,
+                format!("TestMonitor{id}"),
+                format!("TestDeviceID{id}"),
+            ));
+        }
+
+        wm.focused_workspace_mut()
+            .unwrap()
+            .floating_windows_mut()
+            .push_back(Window::from(9301));
+
+        context.backend.set_foreground_window(Some(9301));
+        wm.process_command(SocketMessage::ToggleSticky, std::io::sink())
+            .unwrap();
+        assert!(wm.is_sticky_window(9301));
+
+        // the flag is part of the window, so it shows up in the state
+        let state = serde_json::to_value(State::from(&wm)).unwrap();
+        assert_eq!(
+            state["monitors"]["elements"][0]["workspaces"]["elements"][0]["floating_windows"]["elements"]
+                [0]["sticky"],
+            true
+        );
+
+        // sticky windows stay on their monitor unless they are configured to follow focus
+        wm.process_command(SocketMessage::FocusMonitorNumber(1), std::io::sink())
+            .unwrap();
+        assert!(wm.monitors()[0].workspaces()[0].contains_window(9301));
+
+        wm.process_command(SocketMessage::FocusMonitorNumber(0), std::io::sink())
+            .unwrap();
+        wm.sticky_windows_follow_monitor_focus = true;
+        wm.process_command(SocketMessage::FocusMonitorNumber(1), std::io::sink())
+            .unwrap();
+
+        assert!(!wm.monitors()[0].workspaces()[0].contains_window(9301));
+        assert!(wm.focused_workspace().unwrap().contains_window(9301));
+        assert_eq!(wm.known_window_ids.get(&9301), Some(&(1, 0)));
+        assert!(
+            context.backend.calls_for(9301).iter().any(
+                |call| matches!(call, PlatformCall::SetPosition(_, rect) if rect.left >= 1920)
+            )
+        );
+
+        wm.process_command(SocketMessage::ToggleSticky, std::io::sink())
+            .unwrap();
+        assert!(!wm.is_sticky_window(9301));
+    }
+
+    #[test]
+    fn test_workspace_sets_with_fake_backend() {
+        let (mut wm, context) = setup_window_manager();
+
+        for (id, names) in [(0, ["web", "code"]), (1, ["chat", "docs"])] {
+            let size = Rect {
+                left: id as i32 * 1920,
+                top: 0,
+                right: 1920,
+                bottom: 1080,
+            };
+
+            let mut monitor = monitor::new(
+                id,
+                size,
+                size,
+                format!("TestMonitor{id}"),
+                format!("TestDeviceID{id}"),
+            );
+
+            *monitor.workspaces_mut() = names
+                .iter()
+                .map(|name| Workspace {
+                    name: Some(String::from(*name)),
+                    ..Default::default()
+                })
+                .collect();
+
+            wm.monitors_mut().push_back(monitor);
+        }
+
+        let mut container = Container::default();
+        container.windows_mut().push_back(Window::from(9401));
+        wm.monitors_mut()[1].workspaces_mut()[1].add_container_to_back(container);
+
+        let mut container = Container::default();
+        container.windows_mut().push_back(Window::from(9402));
+        wm.monitors_mut()[0].workspaces_mut()[1].add_container_to_back(container);
+
+        wm.workspace_sets = serde_json::from_str(
+            r#"[
+                {
+                    "name": "coding",
+                    "workspaces": [
+                        { "monitor": 0, "workspace": "code" },
+                        { "monitor": 1, "workspace": "docs" }
+                    ]
+                },
+                {
+                    "name": "browsing",
+                    "workspaces": [{ "monitor": 0, "workspace": "web" }]
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(wm.active_workspace_set(), Some(String::from("browsing")));
+        assert!(
+            wm.process_command(
+                SocketMessage::FocusWorkspaceSet(String::from("missing")),
+                std::io::sink()
+            )
+            .is_err()
+        );
+
+        wm.process_command(
+            SocketMessage::FocusWorkspaceSet(String::from("coding")),
+            std::io::sink(),
+        )
+        .unwrap();
+
+        assert_eq!(wm.monitors()[0].focused_workspace_idx(), 1);
+        assert_eq!(wm.monitors()[1].focused_workspace_idx(), 1);
+        assert_eq!(wm.active_workspace_set(), Some(String::from("coding")));
+        assert!(
+            context
+                .backend
+                .calls_for(9401)
+                .iter()
+                .any(|call| matches!(call, PlatformCall::Restore(_)))
+        );
+
+        // cycling past the last set wraps around to the first
+        wm.process_command(
+            SocketMessage::CycleFocusWorkspaceSet(CycleDirection::Next),
+            std::io::sink(),
+        )
+        .unwrap();
+
+        assert_eq!(wm.monitors()[0].focused_workspace_idx(), 0);
+        assert_eq!(wm.monitors()[1].focused_workspace_idx(), 1);
+        assert_eq!(wm.active_workspace_set(), Some(String::from("browsing")));
+        assert!(
+            context
+                .backend
+                .calls_for(9402)
+                .iter()
+                .any(|call| matches!(call, PlatformCall::Hide(_)))
+        );
+    }
+
+    #[test]
+    fn test_scratchpad_with_fake_backend() {
+        let (mut wm, context) = setup_window_manager();
+
+        let size = Rect {
+            left: 0,
+            top: 0,
+            right: 1920,
+            bottom: 1080,
+        };
+
+        wm.monitors_mut().push_back(monitor::new(
+            0,
+            size,
+            size,
+            "TestMonitor".to_string(),
+            "TestDeviceID".to_string(),
+        ));
+
+        wm.set_scratchpads(vec![
+            serde_json::from_str(
+                r#"{ "name": "terminal", "match_rules": [{ "kind": "Exe", "id": "Terminal" }] }"#,
+            )
+            .unwrap(),
+        ]);
+
+        let created = SystemNotification::Accessibility(AccessibilityNotification::AXWindowCreated);
+        let destroyed =
+            SystemNotification::Accessibility(AccessibilityNotification::AXUIElementDestroyed);
+        let toggle = || SocketMessage::ToggleScratchpad(String::from("terminal"));
+
+        context
+            .backend
+            .add_window(StubWindow::new(9601, 600, "Safari", "Start Page"));
+        context
+            .backend
+            .add_window(StubWindow::new(9602, 700, "Terminal", "zsh"));
+
+        // windows which don't match are managed as usual, and the first match is captured
+        wm.process_event(WindowManagerEvent::Show(created, 600))
+            .unwrap();
+        wm.process_event(WindowManagerEvent::Show(created, 700))
+            .unwrap();
+
+        {
+            let workspace = wm.focused_workspace().unwrap();
+            assert_eq!(workspace.containers().len(), 1);
+            assert!(workspace.floating_windows().iter().any(|w| w.id == 9602));
+            assert!(wm.scratchpads["terminal"].is_open());
+        }
+
+        // toggling hides the captured window, and it isn't managed while it is hidden
+        wm.process_command(toggle(), std::io::sink()).unwrap();
+        assert!(wm.is_hidden_scratchpad_window(9602));
+        assert!(!wm.focused_workspace().unwrap().contains_window(9602));
+        assert!(
+            context
+                .backend
+                .calls_for(9602)
+                .contains(&PlatformCall::Hide(9602))
+        );
+
+        wm.process_event(WindowManagerEvent::Show(created, 700))
+            .unwrap();
+        assert!(!wm.focused_workspace().unwrap().contains_window(9602));
+
+        // toggling again shows it over whichever workspace is focused
+        wm.process_command(SocketMessage::FocusWorkspaceNumber(1), std::io::sink())
+            .unwrap();
+        context.backend.clear_calls();
+        wm.process_command(toggle(), std::io::sink()).unwrap();
+
+        assert_eq!(wm.focused_workspace_idx().unwrap(), 1);
+        assert!(wm.focused_workspace().unwrap().contains_window(9602));
+        assert_eq!(wm.known_window_ids.get(&9602), Some(&(0, 1)));
+        assert!(
+            context
+                .backend
+                .calls_for(9602)
+                .contains(&PlatformCall::Restore(9602))
+        );
+
+        // a hidden window which is closed is released, so that the next match can be captured
+        wm.process_command(toggle(), std::io::sink()).unwrap();
+        context.backend.destroy_window(9602);
+        wm.process_event(WindowManagerEvent::Destroy(destroyed, 700))
+            .unwrap();
+
+        assert!(wm.process_command(toggle(), std::io::sink()).is_err());
+        assert_eq!(wm.scratchpads["terminal"].window_id, None);
+
+        context
+            .backend
+            .add_window(StubWindow::new(9603, 700, "Terminal", "zsh"));
+        wm.process_event(WindowManagerEvent::Show(created, 700))
+            .unwrap();
+
+        assert_eq!(wm.scratchpads["terminal"].window_id, Some(9603));
+        assert!(wm.focused_workspace().unwrap().contains_window(9603));
+    }
 }
@@ -2,7 +2,7 @@
 
 use crate::accessibility::notification_constants::AccessibilityNotification;
 use crate::app_kit_notification_constants::AppKitWorkspaceNotification;
-use crate::macos_api::MacosApi;
+use crate::platform;
 use serde::Deserialize;
 use serde::Serialize;
 use strum::Display;
@@ -108,7 +108,7 @@ impl WindowManagerEvent {
                 })
             }
             SystemNotification::Accessibility(AccessibilityNotification::AXWindowMoved) => {
-                if MacosApi::left_mouse_button_is_pressed() {
+                if platform::backend().left_mouse_button_is_pressed() {
                     window_id.map(|window_id| {
                         WindowManagerEvent::MoveStart(notification, process_id, window_id)
                     })
@@ -119,7 +119,7 @@ impl WindowManagerEvent {
                 }
             }
             SystemNotification::Accessibility(AccessibilityNotification::AXWindowResized) => {
-                if MacosApi::left_mouse_button_is_pressed() {
+                if platform::backend().left_mouse_button_is_pressed() {
                     window_id.map(|window_id| {
                         WindowManagerEvent::ResizeStart(notification, process_id, window_id)
                     })
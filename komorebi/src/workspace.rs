@@ -12,14 +12,17 @@ use crate::core::Rect;
 use crate::core::SocketMessage;
 use crate::core::WindowContainerBehaviour;
 use crate::core::WindowHidingPosition;
+use crate::core::custom_layout;
+use crate::core::custom_layout::CustomLayout;
 use crate::lockable_sequence::LockableSequence;
-use crate::macos_api::MacosApi;
+use crate::platform;
 use crate::ring::Ring;
-use crate::skylight::CGSMainConnectionID;
-use crate::skylight::SLSDisableUpdate;
-use crate::skylight::SLSReenableUpdate;
+use crate::stackbar_manager;
+use crate::stackbar_manager::geometry;
 use crate::static_config::Wallpaper;
 use crate::static_config::WorkspaceConfig;
+use crate::theme_manager;
+use crate::theme_manager::StackbarThemeOptions;
 use crate::window::Window;
 use crate::window::WindowDetails;
 use color_eyre::eyre;
@@ -30,12 +33,14 @@ use komorebi_themes::KomorebiThemeCustom;
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::ffi::OsStr;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::io::Write;
 use std::num::NonZeroUsize;
 use std::os::unix::net::UnixStream;
+use std::sync::atomic::Ordering;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -50,8 +55,14 @@ pub struct Workspace {
     pub maximized_window_restore_idx: Option<usize>,
     pub floating_windows: Ring<Window>,
     pub layout: Layout,
+    /// Column-based layout loaded from a file, which takes precedence over `layout` when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_layout: Option<CustomLayout>,
     pub layout_options: Option<LayoutOptions>,
     pub layout_rules: Vec<(usize, Layout)>,
+    /// Threshold-based custom layout rules, sorted by threshold ascending
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_layout_rules: Vec<(usize, CustomLayout)>,
     /// Threshold-based layout options rules (container_count >= threshold -> use these options).
     /// Sorted by threshold ascending at load time.
     #[serde(default)]
@@ -118,12 +129,14 @@ impl Default for Workspace {
             container_padding: None,
             resize_dimensions: vec![],
             layout: Layout::Default(DefaultLayout::BSP),
+            custom_layout: None,
             work_area_offset: None,
             work_area_offset_rules: vec![],
             latest_layout: vec![],
             layout_flip: None,
             layout_options: None,
             layout_rules: vec![],
+            custom_layout_rules: vec![],
             layout_options_rules: vec![],
             layout_defaults_cache: HashMap::new(),
             globals: Default::default(),
@@ -219,8 +232,14 @@ impl Workspace {
             self.layout = Layout::Default(*layout);
         }
 
-        self.tile = !(/* config.custom_layout.is_none() && */config.layout.is_none() && config.tile.is_none()
-            || config.tile.is_some_and(|tile| !tile));
+        self.custom_layout = match &config.custom_layout {
+            Some(path) => Some(CustomLayout::from_path(path)?),
+            None => None,
+        };
+
+        self.tile =
+            !(config.custom_layout.is_none() && config.layout.is_none() && config.tile.is_none()
+                || config.tile.is_some_and(|tile| !tile));
 
         let mut all_layout_rules = vec![];
         if let Some(layout_rules) = &config.layout_rules {
@@ -234,6 +253,18 @@ impl Workspace {
 
         self.layout_rules = all_layout_rules.clone();
 
+        let mut all_custom_layout_rules = vec![];
+        if let Some(custom_layout_rules) = &config.custom_layout_rules {
+            for (count, path) in custom_layout_rules {
+                all_custom_layout_rules.push((*count, CustomLayout::from_path(path)?));
+            }
+
+            all_custom_layout_rules.sort_by_key(|(i, _)| *i);
+            self.tile = true;
+        }
+
+        self.custom_layout_rules = all_custom_layout_rules;
+
         self.work_area_offset = config.work_area_offset;
 
         let mut all_work_area_offset_rules = vec![];
@@ -369,6 +400,14 @@ impl Workspace {
     pub fn new_idx_for_direction(&self, direction: OperationDirection) -> Option<usize> {
         let len = NonZeroUsize::new(self.containers().len())?;
 
+        if self.custom_layout.is_some() {
+            return custom_layout::idx_in_direction(
+                &self.latest_layout,
+                self.focused_container_idx(),
+                direction,
+            );
+        }
+
         direction.destination(
             self.layout.as_boxed_direction().as_ref(),
             self.layout_flip,
@@ -535,7 +574,7 @@ impl Workspace {
                 && focused_window.application.process_id == process_id
             {
                 if !valid_window_ids.contains(&focused_window.id) {
-                    if let Ok(rect) = MacosApi::window_rect(&focused_window.element) {
+                    if let Ok(rect) = focused_window.rect() {
                         tracing::debug!(
                             "reaping window {} ({}): rect = {:?}",
                             focused_window.id,
@@ -560,7 +599,7 @@ impl Workspace {
             if focused_window.application.process_id == process_id
                 && !valid_window_ids.contains(&focused_window.id)
             {
-                if let Ok(rect) = MacosApi::window_rect(&focused_window.element) {
+                if let Ok(rect) = focused_window.rect() {
                     tracing::debug!(
                         "reaping monocle window {} ({}): rect = {:?}",
                         focused_window.id,
@@ -581,7 +620,7 @@ impl Workspace {
         for window in self.floating_windows() {
             if window.application.process_id == process_id {
                 if !valid_window_ids.contains(&window.id) {
-                    if let Ok(rect) = MacosApi::window_rect(&window.element) {
+                    if let Ok(rect) = window.rect() {
                         tracing::debug!(
                             "reaping floating window {} ({}): rect = {:?}",
                             window.id,
@@ -691,7 +730,9 @@ impl Workspace {
     }
 
     pub fn is_focused_window_monocle_or_maximized(&self) -> eyre::Result<bool> {
-        let window_id = MacosApi::foreground_window_id().ok_or_eyre("no foreground window")?;
+        let window_id = platform::backend()
+            .foreground_window_id()
+            .ok_or_eyre("no foreground window")?;
         if let Some(window) = &self.maximized_window
             && window_id == window.id
         {
@@ -776,6 +817,34 @@ impl Workspace {
         None
     }
 
+    pub fn location_from_window_id(&self, window_id: u32) -> Option<WorkspaceWindowLocation> {
+        for (container_idx, container) in self.containers().iter().enumerate() {
+            if let Some(window_idx) = container.idx_for_window(window_id) {
+                return Some(WorkspaceWindowLocation::Container(
+                    container_idx,
+                    window_idx,
+                ));
+            }
+        }
+
+        if let Some(window) = &self.maximized_window
+            && window.id == window_id
+        {
+            return Some(WorkspaceWindowLocation::Maximized);
+        }
+
+        if let Some(container) = &self.monocle_container
+            && let Some(window_idx) = container.idx_for_window(window_id)
+        {
+            return Some(WorkspaceWindowLocation::Monocle(window_idx));
+        }
+
+        self.floating_windows()
+            .iter()
+            .position(|window| window.id == window_id)
+            .map(WorkspaceWindowLocation::Floating)
+    }
+
     pub fn contains_managed_window(&self, window_id: u32) -> bool {
         for container in self.containers() {
             if container.contains_window(window_id) {
@@ -869,6 +938,11 @@ impl Workspace {
         let window_hiding_position = self.globals.window_hiding_position;
 
         for window in self.floating_windows_mut().iter_mut().rev() {
+            // sticky windows follow focus to the next workspace instead of being hidden
+            if window.sticky {
+                continue;
+            }
+
             let mut should_hide = omit.is_none();
 
             if !should_hide
@@ -886,7 +960,8 @@ impl Workspace {
         }
 
         let mut resize_dimensions = None;
-        if matches!(self.layout, Layout::Default(DefaultLayout::Scrolling))
+        if self.custom_layout.is_none()
+            && matches!(self.layout, Layout::Default(DefaultLayout::Scrolling))
             && self.resize_dimensions.iter().any(|r| r.is_some())
         {
             resize_dimensions = Some(self.resize_dimensions.clone());
@@ -915,6 +990,30 @@ impl Workspace {
         Ok(())
     }
 
+    /// Removes the sticky floating windows so that they can follow focus to another workspace
+    pub fn remove_sticky_windows(&mut self) -> Vec<Window> {
+        let (sticky_windows, floating_windows): (VecDeque<_>, VecDeque<_>) =
+            std::mem::take(self.floating_windows_mut())
+                .into_iter()
+                .partition(|window| window.sticky);
+
+        *self.floating_windows_mut() = floating_windows;
+
+        if self.floating_windows().is_empty() && matches!(self.layer, WorkspaceLayer::Floating) {
+            self.layer = WorkspaceLayer::Tiling;
+        }
+
+        sticky_windows.into()
+    }
+
+    pub fn add_sticky_windows(&mut self, windows: Vec<Window>) {
+        for window in windows {
+            if !self.contains_window(window.id) {
+                self.floating_windows_mut().push_back(window);
+            }
+        }
+    }
+
     pub fn restore(
         &mut self,
         mouse_follows_focus: bool,
@@ -993,20 +1092,42 @@ impl Workspace {
     }
 
     pub fn promote_container(&mut self) -> eyre::Result<()> {
-        let focused_idx = self.focused_container_idx();
         let container = self
-            .containers_mut()
-            .remove_respecting_locks(focused_idx)
+            .focused_container()
+            .cloned()
             .ok_or_eyre("there is no container")?;
 
+        if container.locked {
+            eyre::bail!("cannot promote a locked container");
+        }
+
         let primary_idx = match &self.layout {
             Layout::Default(_) => 0,
         };
 
+        // inserting before removing means the sequence never gets shorter than it was, so locked
+        // containers at the end of the workspace aren't pulled off their indices in between
+        let container_id = container.id.clone();
         let insertion_idx = self
             .containers_mut()
             .insert_respecting_locks(primary_idx, container);
-        self.focus_container(insertion_idx);
+
+        let original_idx = self
+            .containers()
+            .iter()
+            .enumerate()
+            .position(|(idx, container)| idx != insertion_idx && container.id == container_id)
+            .ok_or_eyre("there is no container")?;
+
+        self.containers_mut().remove_respecting_locks(original_idx);
+
+        let promoted_idx = self
+            .containers()
+            .iter()
+            .position(|container| container.id == container_id)
+            .ok_or_eyre("there is no container")?;
+
+        self.focus_container(promoted_idx);
 
         Ok(())
     }
@@ -1086,10 +1207,10 @@ impl Workspace {
             .ok_or_eyre("there is no monocle container")?;
 
         let container = container.clone();
-        if restore_idx >= self.containers().len() {
-            self.containers_mut()
-                .resize(restore_idx, Container::default());
-        }
+
+        // containers may have been closed while the monocle was active, and padding the
+        // workspace with empty containers to reach the old index would leave gaps in the layout
+        let restore_idx = restore_idx.min(self.containers().len());
 
         // we shouldn't use insert_container_at_index here because it doesn't make sense for
         // monocle and maximized toggles which take over the whole screen before being reinserted
@@ -1166,7 +1287,7 @@ impl Workspace {
     }
 
     pub fn remove_focused_floating_window(&mut self) -> Option<Window> {
-        let window_id = MacosApi::foreground_window_id()?;
+        let window_id = platform::backend().foreground_window_id()?;
         self.remove_floating_window(window_id)
     }
 
@@ -1334,17 +1455,40 @@ impl Workspace {
 
         self.enforce_resize_constraints();
 
-        if !self.layout_rules.is_empty() {
+        if !self.layout_rules.is_empty() || !self.custom_layout_rules.is_empty() {
             let mut updated_layout = None;
+            let mut updated_custom_layout = None;
 
             for (threshold, layout) in &self.layout_rules {
                 if self.containers().len() >= *threshold {
-                    updated_layout = Option::from(layout.clone());
+                    updated_layout = Option::from((*threshold, layout.clone()));
                 }
             }
 
-            if let Some(updated_layout) = updated_layout {
-                self.layout = updated_layout;
+            for (threshold, custom_layout) in &self.custom_layout_rules {
+                if self.containers().len() >= *threshold {
+                    updated_custom_layout = Option::from((*threshold, custom_layout.clone()));
+                }
+            }
+
+            // when both kinds of rule match, the one with the higher threshold wins
+            match (updated_layout, updated_custom_layout) {
+                (Some((layout_threshold, layout)), Some((custom_threshold, custom_layout))) => {
+                    if custom_threshold >= layout_threshold {
+                        self.custom_layout = Some(custom_layout);
+                    } else {
+                        self.layout = layout;
+                        self.custom_layout = None;
+                    }
+                }
+                (Some((_, layout)), None) => {
+                    self.layout = layout;
+                    self.custom_layout = None;
+                }
+                (None, Some((_, custom_layout))) => {
+                    self.custom_layout = Some(custom_layout);
+                }
+                (None, None) => {}
             }
         }
 
@@ -1361,8 +1505,8 @@ impl Workspace {
 
         if self.tile {
             // Batch screen updates for flicker-free window positioning
-            let connection_id = unsafe { CGSMainConnectionID() };
-            unsafe { SLSDisableUpdate(connection_id) };
+            let backend = platform::backend();
+            backend.disable_screen_updates();
 
             let result = (|| -> eyre::Result<()> {
                 if let Some(container) = self.monocle_container.as_mut() {
@@ -1382,22 +1526,34 @@ impl Workspace {
                         self.layout_options,
                         self.layout_options_rules.len(),
                     );
-                    let mut layouts = self.layout.as_boxed_arrangement().calculate(
-                        &adjusted_work_area,
-                        NonZeroUsize::new(self.containers().len()).ok_or_eyre(
-                            "there must be at least one container to calculate a workspace layout",
-                        )?,
-                        Some(container_padding),
-                        self.layout_flip,
-                        &self.resize_dimensions,
-                        self.focused_container_idx(),
-                        effective_layout_options,
-                        &self.latest_layout,
-                    );
+                    let len = NonZeroUsize::new(self.containers().len()).ok_or_eyre(
+                        "there must be at least one container to calculate a workspace layout",
+                    )?;
 
-                    let is_scrolling =
-                        matches!(self.layout, Layout::Default(DefaultLayout::Scrolling));
+                    let mut layouts = match &self.custom_layout {
+                        Some(custom_layout) => custom_layout.calculate(
+                            &adjusted_work_area,
+                            len,
+                            Some(container_padding),
+                            self.layout_flip,
+                        ),
+                        None => self.layout.as_boxed_arrangement().calculate(
+                            &adjusted_work_area,
+                            len,
+                            Some(container_padding),
+                            self.layout_flip,
+                            &self.resize_dimensions,
+                            self.focused_container_idx(),
+                            effective_layout_options,
+                            &self.latest_layout,
+                        ),
+                    };
+
+                    let is_scrolling = self.custom_layout.is_none()
+                        && matches!(self.layout, Layout::Default(DefaultLayout::Scrolling));
                     let resize_dimensions_is_empty = self.resize_dimensions.is_empty();
+                    let stackbar_height =
+                        stackbar_manager::STACKBAR_TAB_HEIGHT.load(Ordering::SeqCst);
 
                     let containers = self.containers_mut();
 
@@ -1431,13 +1587,22 @@ impl Workspace {
                             layout.add_padding(border_offset);
                             layout.add_padding(border_width);
 
+                            // the stackbar is drawn along the top of the container, so the
+                            // windows in the container have to be shifted down to make room
+                            let window_layout = if stackbar_manager::should_have_stackbar(
+                                container.windows().len(),
+                            ) {
+                                geometry::split_container(layout, stackbar_height).1
+                            } else {
+                                *layout
+                            };
+
                             for window in container.windows_mut() {
-                                let current_rect =
-                                    MacosApi::window_rect(&window.element).unwrap_or_default();
-                                let current_percentage = work_area
-                                    .percentage_within_horizontal_bounds(&Rect::from(current_rect));
+                                let current_rect = window.rect().unwrap_or_default();
+                                let current_percentage =
+                                    work_area.percentage_within_horizontal_bounds(&current_rect);
                                 let proposed_percentage =
-                                    work_area.percentage_within_horizontal_bounds(layout);
+                                    work_area.percentage_within_horizontal_bounds(&window_layout);
 
                                 let percentage_override = proposed_percentage == 0.0
                                     && current_percentage > 0.1
@@ -1464,7 +1629,7 @@ impl Workspace {
                                     }
                                 } else if percentage_override {
                                     window.center(&work_area, false)?;
-                                } else if let Err(error) = window.set_position(layout) {
+                                } else if let Err(error) = window.set_position(&window_layout) {
                                     tracing::warn!("failed to set window position: {error}")
                                 }
                             }
@@ -1477,7 +1642,7 @@ impl Workspace {
             })();
 
             // Always re-enable updates, even if there was an error
-            unsafe { SLSReenableUpdate(connection_id) };
+            backend.reenable_screen_updates();
 
             // Propagate any error that occurred
             result?;
@@ -1504,11 +1669,11 @@ impl Workspace {
     pub fn container_idx_from_current_point(&self) -> Option<usize> {
         let mut idx = None;
 
-        let point = MacosApi::cursor_pos();
+        let point = platform::backend().cursor_pos();
 
         for (i, _container) in self.containers().iter().enumerate() {
             if let Some(rect) = self.latest_layout.get(i)
-                && rect.contains_point((point.x as i32, point.y as i32))
+                && rect.contains_point(point)
             {
                 idx = Option::from(i);
             }
@@ -1528,7 +1693,7 @@ impl Workspace {
         monitor_wp: &Option<Wallpaper>,
     ) -> eyre::Result<()> {
         if let Some(wallpaper) = self.wallpaper.as_ref().or(monitor_wp.as_ref()) {
-            if let Err(error) = MacosApi::set_wallpaper(&wallpaper.path, monitor_id) {
+            if let Err(error) = platform::backend().set_wallpaper(&wallpaper.path, monitor_id) {
                 tracing::error!("failed to set wallpaper: {error}");
             }
 
@@ -1581,6 +1746,13 @@ impl Workspace {
                 }
 
                 if let Some(palette) = base16_palette {
+                    let theme_options = wallpaper.theme_options.as_ref();
+                    theme_manager::STACKBAR_THEME_OPTIONS.store(StackbarThemeOptions {
+                        focused_text: theme_options.and_then(|o| o.stackbar_focused_text),
+                        unfocused_text: theme_options.and_then(|o| o.stackbar_unfocused_text),
+                        background: theme_options.and_then(|o| o.stackbar_background),
+                    });
+
                     let komorebi_theme = KomorebiTheme::Custom(KomorebiThemeCustom {
                         colours: Box::new(palette),
                         single_border: wallpaper
@@ -1633,6 +1805,10 @@ impl Workspace {
 
 impl Workspace {
     fn enforce_resize_constraints(&mut self) {
+        if self.custom_layout.is_some() {
+            return self.enforce_no_resize();
+        }
+
         match self.layout {
             Layout::Default(DefaultLayout::BSP) => self.enforce_resize_constraints_for_bsp(),
             Layout::Default(DefaultLayout::Columns) => self.enforce_resize_for_columns(),
@@ -1916,7 +2092,10 @@ mod tests {
     use super::*;
     use crate::container::Container;
     use crate::window::Window;
+    use proptest::prelude::*;
+    use proptest::sample::Index;
     use std::collections::HashMap;
+    use std::collections::HashSet;
 
     #[test]
     fn test_locked_containers_with_new_window() {
@@ -2077,6 +2256,95 @@ mod tests {
         assert!(!workspace.is_empty())
     }
 
+    #[test]
+    fn test_location_from_window_id() {
+        let mut workspace = Workspace::default();
+
+        for i in 0..2 {
+            let mut container = Container::default();
+            container.windows_mut().push_back(Window::from(i));
+            workspace.add_container_to_back(container);
+        }
+
+        workspace.containers_mut()[1]
+            .windows_mut()
+            .push_back(Window::from(2));
+        workspace.floating_windows_mut().push_back(Window::from(3));
+
+        assert!(matches!(
+            workspace.location_from_window_id(2),
+            Some(WorkspaceWindowLocation::Container(1, 1))
+        ));
+        assert!(matches!(
+            workspace.location_from_window_id(3),
+            Some(WorkspaceWindowLocation::Floating(0))
+        ));
+        assert!(workspace.location_from_window_id(4).is_none());
+    }
+
+    #[test]
+    fn test_promote_container_with_locked_container_at_the_end() {
+        let mut ws = Workspace::default();
+
+        for i in 0..3 {
+            let mut container = Container::default();
+            container.windows_mut().push_back(Window::from(i));
+            if i == 2 {
+                container.locked = true;
+            }
+            ws.add_container_to_back(container);
+        }
+
+        // promoting the middle container must not pull the locked container off index 2
+        ws.focus_container(1);
+        ws.promote_container().unwrap();
+
+        let window_ids = ws
+            .containers()
+            .iter()
+            .map(|container| container.focused_window().unwrap().id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(window_ids, vec![1, 0, 2]);
+        assert!(ws.containers()[2].locked);
+        assert_eq!(ws.focused_container_idx(), 0);
+
+        // the locked container itself can't be promoted
+        ws.focus_container(2);
+        assert!(ws.promote_container().is_err());
+        assert_eq!(ws.containers()[2].focused_window().unwrap().id, 2);
+    }
+
+    #[test]
+    fn test_reintegrate_monocle_container_after_containers_closed() {
+        let mut ws = Workspace::default();
+
+        for i in 0..3 {
+            let mut container = Container::default();
+            container.windows_mut().push_back(Window::from(i));
+            ws.add_container_to_back(container);
+        }
+
+        ws.focus_container(2);
+        ws.new_monocle_container().unwrap();
+        assert_eq!(ws.monocle_container_restore_idx, Some(2));
+
+        // a container is closed while the monocle is active, so its restore index is now past
+        // the end of the workspace
+        ws.remove_container(1);
+        ws.reintegrate_monocle_container().unwrap();
+
+        let window_ids = ws
+            .containers()
+            .iter()
+            .map(|container| container.windows().iter().map(|w| w.id).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        assert_eq!(window_ids, vec![vec![0], vec![2]]);
+        assert_eq!(ws.focused_container_idx(), 1);
+        assert!(ws.monocle_container.is_none());
+    }
+
     #[test]
     fn test_add_container_to_back() {
         let mut workspace = Workspace::default();
@@ -2824,4 +3092,215 @@ mod tests {
             container1_right_edge
         );
     }
+
+    #[derive(Debug, Clone)]
+    enum WorkspaceOperation {
+        InsertContainer(Index),
+        RemoveContainer(Index),
+        SwapContainers(Index, Index),
+        MoveWindowToContainer(Index),
+        NewContainerForFocusedWindow,
+        NewMonocleContainer,
+        ReintegrateMonocleContainer,
+        NewFloatingWindow,
+        PromoteContainer,
+        ToggleLock(Index),
+        FocusContainer(Index),
+    }
+
+    fn arb_workspace_operation() -> impl Strategy<Value = WorkspaceOperation> {
+        prop_oneof![
+            4 => any::<Index>().prop_map(WorkspaceOperation::InsertContainer),
+            1 => any::<Index>().prop_map(WorkspaceOperation::RemoveContainer),
+            1 => (any::<Index>(), any::<Index>())
+                .prop_map(|(i, j)| WorkspaceOperation::SwapContainers(i, j)),
+            2 => any::<Index>().prop_map(WorkspaceOperation::MoveWindowToContainer),
+            1 => Just(WorkspaceOperation::NewContainerForFocusedWindow),
+            1 => Just(WorkspaceOperation::NewMonocleContainer),
+            1 => Just(WorkspaceOperation::ReintegrateMonocleContainer),
+            1 => Just(WorkspaceOperation::NewFloatingWindow),
+            1 => Just(WorkspaceOperation::PromoteContainer),
+            2 => any::<Index>().prop_map(WorkspaceOperation::ToggleLock),
+            1 => any::<Index>().prop_map(WorkspaceOperation::FocusContainer),
+        ]
+    }
+
+    fn managed_window_ids(workspace: &Workspace) -> Vec<u32> {
+        let mut window_ids = workspace
+            .containers()
+            .iter()
+            .flat_map(|container| container.windows().iter().map(|window| window.id))
+            .collect::<Vec<_>>();
+
+        if let Some(monocle) = &workspace.monocle_container {
+            window_ids.extend(monocle.windows().iter().map(|window| window.id));
+        }
+
+        window_ids.extend(workspace.floating_windows().iter().map(|window| window.id));
+
+        window_ids
+    }
+
+    fn locked_container_positions(workspace: &Workspace) -> Vec<(usize, String)> {
+        workspace
+            .containers()
+            .iter()
+            .enumerate()
+            .filter(|(_, container)| container.locked)
+            .map(|(idx, container)| (idx, container.id.clone()))
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn test_workspace_operation_invariants(
+            operations in proptest::collection::vec(arb_workspace_operation(), 1..50)
+        ) {
+            let mut ws = Workspace::default();
+            let mut next_window_id = 1;
+            let mut live_window_ids = HashSet::new();
+
+            for operation in operations {
+                let len = ws.containers().len();
+                let monocle = ws.monocle_container.is_some();
+                let locked = locked_container_positions(&ws);
+
+                // the window manager only reintegrates or floats while a monocle is active
+                if monocle
+                    && !matches!(
+                        operation,
+                        WorkspaceOperation::ReintegrateMonocleContainer
+                            | WorkspaceOperation::NewFloatingWindow
+                    )
+                {
+                    continue;
+                }
+
+                let mut should_respect_locks = true;
+
+                match &operation {
+                    WorkspaceOperation::InsertContainer(idx) => {
+                        let mut container = Container::default();
+                        container
+                            .add_window(
+                                &Window::from(next_window_id),
+                                WindowHidingPosition::BottomLeft,
+                            )
+                            .unwrap();
+
+                        ws.insert_container_at_idx(idx.index(len + 1), container);
+                        live_window_ids.insert(next_window_id);
+                        next_window_id += 1;
+                    }
+                    WorkspaceOperation::RemoveContainer(idx) => {
+                        if len == 0 {
+                            continue;
+                        }
+
+                        if let Some(container) = ws.remove_container(idx.index(len)) {
+                            for window in container.windows() {
+                                live_window_ids.remove(&window.id);
+                            }
+                        }
+                    }
+                    WorkspaceOperation::SwapContainers(i, j) => {
+                        if len == 0 {
+                            continue;
+                        }
+
+                        ws.swap_containers(i.index(len), j.index(len));
+                        should_respect_locks = false;
+                    }
+                    WorkspaceOperation::MoveWindowToContainer(idx) => {
+                        if len < 2 || idx.index(len) == ws.focused_container_idx() {
+                            continue;
+                        }
+
+                        ws.move_window_to_container(idx.index(len)).ok();
+                    }
+                    WorkspaceOperation::NewContainerForFocusedWindow => {
+                        ws.new_container_for_focused_window().ok();
+                    }
+                    WorkspaceOperation::NewMonocleContainer => {
+                        ws.new_monocle_container().ok();
+                        should_respect_locks = false;
+                    }
+                    WorkspaceOperation::ReintegrateMonocleContainer => {
+                        ws.reintegrate_monocle_container().ok();
+                        should_respect_locks = false;
+                    }
+                    WorkspaceOperation::NewFloatingWindow => {
+                        ws.new_floating_window().ok();
+                        should_respect_locks = !monocle;
+                    }
+                    WorkspaceOperation::PromoteContainer => {
+                        ws.promote_container().ok();
+                    }
+                    WorkspaceOperation::ToggleLock(idx) => {
+                        if len == 0 {
+                            continue;
+                        }
+
+                        let container = &mut ws.containers_mut()[idx.index(len)];
+                        container.locked = !container.locked;
+                        should_respect_locks = false;
+                    }
+                    WorkspaceOperation::FocusContainer(idx) => {
+                        if len == 0 {
+                            continue;
+                        }
+
+                        ws.focus_container(idx.index(len));
+                    }
+                }
+
+                let window_ids = managed_window_ids(&ws);
+                let unique_window_ids = window_ids.iter().copied().collect::<HashSet<_>>();
+
+                prop_assert_eq!(
+                    window_ids.len(),
+                    unique_window_ids.len(),
+                    "duplicate window ids after {:?}",
+                    operation
+                );
+                prop_assert_eq!(
+                    &unique_window_ids,
+                    &live_window_ids,
+                    "windows lost after {:?}",
+                    operation
+                );
+
+                let len = ws.containers().len();
+                prop_assert!(
+                    len == 0 || ws.focused_container_idx() < len,
+                    "focused container {} out of bounds for {} containers after {:?}",
+                    ws.focused_container_idx(),
+                    len,
+                    operation
+                );
+                prop_assert!(
+                    ws.containers().iter().all(|c| !c.windows().is_empty()),
+                    "empty container after {:?}",
+                    operation
+                );
+
+                // locked containers can only be pulled off their indices when the workspace
+                // becomes too short to hold them there
+                if should_respect_locks && locked.iter().all(|(idx, _)| *idx < len) {
+                    for (idx, id) in &locked {
+                        if let Some(current_idx) =
+                            ws.containers().iter().position(|c| &c.id == id)
+                        {
+                            prop_assert_eq!(
+                                current_idx,
+                                *idx,
+                                "locked container moved after {:?}",
+                                operation
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
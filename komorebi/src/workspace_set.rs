@@ -0,0 +1,189 @@
+//! Named groups of workspaces across monitors which are focused together
+//!
+//! A [`WorkspaceSetConfig`] names one workspace on each of any number of monitors. Focusing the
+//! set switches every member monitor to its workspace at once, and the set is considered active
+//! for as long as each of its members remains the focused workspace on its monitor.
+
+use crate::monitor::Monitor;
+use crate::ring::Ring;
+use color_eyre::eyre;
+use color_eyre::eyre::OptionExt;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// A named group of workspaces on different monitors which are focused together
+pub struct WorkspaceSetConfig {
+    /// Name used to focus the set
+    pub name: String,
+    /// Workspaces which are focused when the set is focused
+    pub workspaces: Vec<WorkspaceSetMember>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// A workspace which belongs to a workspace set
+pub struct WorkspaceSetMember {
+    /// Index of the monitor in the monitors of the static configuration
+    pub monitor: usize,
+    /// Name of the workspace on that monitor
+    pub workspace: String,
+}
+
+impl WorkspaceSetConfig {
+    /// Resolves every member of the set to a pair of monitor and workspace indices, mapping the
+    /// configured monitor indices through `monitor_usr_idx_map`
+    pub fn resolve(
+        &self,
+        monitors: &Ring<Monitor>,
+        monitor_usr_idx_map: &HashMap<usize, usize>,
+    ) -> eyre::Result<Vec<(usize, usize)>> {
+        self.workspaces
+            .iter()
+            .map(|member| {
+                let monitor_idx = monitor_usr_idx_map
+                    .get(&member.monitor)
+                    .copied()
+                    .unwrap_or(member.monitor);
+
+                let monitor = monitors
+                    .elements()
+                    .get(monitor_idx)
+                    .ok_or_eyre(format!("there is no monitor at index {}", member.monitor))?;
+
+                let workspace_idx = monitor
+                    .workspaces()
+                    .iter()
+                    .position(|workspace| workspace.name.as_ref() == Some(&member.workspace))
+                    .ok_or_eyre(format!(
+                        "there is no workspace named {} on monitor {}",
+                        member.workspace, member.monitor
+                    ))?;
+
+                Ok((monitor_idx, workspace_idx))
+            })
+            .collect()
+    }
+
+    /// Whether every member of the set is the focused workspace on its monitor
+    pub fn is_focused(
+        &self,
+        monitors: &Ring<Monitor>,
+        monitor_usr_idx_map: &HashMap<usize, usize>,
+    ) -> bool {
+        self.resolve(monitors, monitor_usr_idx_map)
+            .is_ok_and(|members| {
+                !members.is_empty()
+                    && members.iter().all(|(monitor_idx, workspace_idx)| {
+                        monitors
+                            .elements()
+                            .get(*monitor_idx)
+                            .is_some_and(|monitor| {
+                                monitor.focused_workspace_idx() == *workspace_idx
+                            })
+                    })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Rect;
+    use crate::monitor;
+    use crate::workspace::Workspace;
+    use std::collections::VecDeque;
+
+    fn monitors() -> Ring<Monitor> {
+        let mut monitors = Ring::default();
+
+        for (id, names) in [(1, ["code", "web"]), (2, ["docs", "chat"])] {
+            let mut monitor = monitor::new(
+                id,
+                Rect::default(),
+                Rect::default(),
+                String::from("Test Monitor"),
+                String::from("TestMonitor"),
+            );
+
+            *monitor.workspaces_mut() = names
+                .iter()
+                .map(|name| Workspace {
+                    name: Some(String::from(*name)),
+                    ..Default::default()
+                })
+                .collect::<VecDeque<_>>();
+
+            monitors.elements_mut().push_back(monitor);
+        }
+
+        monitors
+    }
+
+    fn coding() -> WorkspaceSetConfig {
+        serde_json::from_str(
+            r#"{
+                "name": "coding",
+                "workspaces": [
+                    { "monitor": 0, "workspace": "code" },
+                    { "monitor": 1, "workspace": "docs" }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve() {
+        let monitors = monitors();
+        let set = coding();
+
+        assert_eq!(
+            set.resolve(&monitors, &HashMap::new()).unwrap(),
+            vec![(0, 0), (1, 0)]
+        );
+
+        // configured monitor indices are mapped to the actual monitor indices
+        let monitor_usr_idx_map = HashMap::from([(0, 1), (1, 0)]);
+        let set = WorkspaceSetConfig {
+            name: String::from("swapped"),
+            workspaces: vec![
+                WorkspaceSetMember {
+                    monitor: 0,
+                    workspace: String::from("chat"),
+                },
+                WorkspaceSetMember {
+                    monitor: 1,
+                    workspace: String::from("web"),
+                },
+            ],
+        };
+
+        assert_eq!(
+            set.resolve(&monitors, &monitor_usr_idx_map).unwrap(),
+            vec![(1, 1), (0, 1)]
+        );
+
+        let set = WorkspaceSetConfig {
+            name: String::from("missing"),
+            workspaces: vec![WorkspaceSetMember {
+                monitor: 0,
+                workspace: String::from("docs"),
+            }],
+        };
+
+        assert!(set.resolve(&monitors, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_is_focused() {
+        let mut monitors = monitors();
+        let set = coding();
+        assert!(set.is_focused(&monitors, &HashMap::new()));
+
+        monitors.elements_mut()[1].focus_workspace(1).unwrap();
+        assert!(!set.is_focused(&monitors, &HashMap::new()));
+    }
+}
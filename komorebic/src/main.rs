@@ -8,8 +8,10 @@ use color_eyre::eyre;
 use fs_tail::TailedFile;
 use komorebi_client::ApplicationIdentifier;
 use komorebi_client::Axis;
+use komorebi_client::ClientError;
 use komorebi_client::CycleDirection;
 use komorebi_client::DefaultLayout;
+use komorebi_client::MetricsFormat;
 use komorebi_client::MoveBehaviour;
 use komorebi_client::OperationBehaviour;
 use komorebi_client::OperationDirection;
@@ -17,19 +19,25 @@ use komorebi_client::PathExt;
 use komorebi_client::Rect;
 use komorebi_client::Sizing;
 use komorebi_client::SocketMessage;
+use komorebi_client::StatePath;
 use komorebi_client::StateQuery;
+use komorebi_client::SubscribeOptions;
 use komorebi_client::WindowKind;
+use komorebi_client::config_check;
+use komorebi_client::explain;
+use komorebi_client::recorder;
 use komorebi_client::replace_env_in_path;
-use komorebi_client::send_message;
-use komorebi_client::send_query;
+use komorebi_client::send_request;
 use komorebi_client::splash;
 use komorebi_client::splash::ValidationFeedback;
 use lazy_static::lazy_static;
 use std::fs::File;
 use std::fs::OpenOptions;
 use std::io::BufRead;
+use std::io::Read;
 use std::io::Write;
 use std::num::NonZeroUsize;
+use std::os::unix::net::UnixListener;
 use std::path::PathBuf;
 use std::process::Command;
 use std::process::Stdio;
@@ -104,6 +112,7 @@ gen_enum_subcommand_args! {
     CycleMonitor: CycleDirection,
     CycleWorkspace: CycleDirection,
     CycleEmptyWorkspace: CycleDirection,
+    CycleSet: CycleDirection,
     CycleMoveWorkspaceToMonitor: CycleDirection,
     Stack: OperationDirection,
     CycleStack: CycleDirection,
@@ -111,9 +120,9 @@ gen_enum_subcommand_args! {
     FlipLayout: Axis,
     ChangeLayout: DefaultLayout,
     CycleLayout: CycleDirection,
-    // WatchConfiguration: BooleanState,
+    WatchConfiguration: BooleanState,
     MouseFollowsFocus: BooleanState,
-    Query: StateQuery,
+    Transparency: BooleanState,
     // WindowHidingBehaviour: HidingBehaviour,
     CrossMonitorMoveBehaviour: MoveBehaviour,
     UnmanagedWindowOperationBehaviour: OperationBehaviour,
@@ -146,6 +155,12 @@ gen_target_subcommand_args! {
     FocusStackWindow,
 }
 
+#[derive(Parser)]
+struct ToggleScratchpad {
+    /// Name of a scratchpad from the static configuration
+    name: String,
+}
+
 #[derive(Parser)]
 struct MonocleFocusBehaviour {
     /// Desired monocle focus behaviour
@@ -442,6 +457,76 @@ struct UnsubscribeSocket {
     socket: String,
 }
 
+#[derive(Parser)]
+struct SubscribePipe {
+    /// Name of the fifo to send event notifications to
+    named_pipe: String,
+}
+
+#[derive(Parser)]
+struct UnsubscribePipe {
+    /// Name of the fifo to stop sending event notifications to
+    named_pipe: String,
+}
+
+#[derive(Parser)]
+struct Subscribe {
+    /// Name of the subscriber socket (default: komorebic-<pid>)
+    name: Option<String>,
+    /// Keep printing notifications until komorebi stops or the command is cancelled
+    #[clap(short, long)]
+    follow: bool,
+    /// Only print notifications when the window manager state has changed
+    #[clap(long)]
+    filter_state_changes: bool,
+}
+
+#[derive(Parser)]
+struct Check {
+    /// Path to a static configuration JSON file (default: komorebi.json)
+    #[clap(value_parser = replace_env_in_path)]
+    path: Option<PathBuf>,
+    /// Print the problems as JSON
+    #[clap(long)]
+    json: bool,
+}
+
+#[derive(Parser)]
+struct Explain {
+    /// Executable name of the window
+    #[clap(long)]
+    exe: String,
+    /// Title of the window
+    #[clap(long)]
+    title: Option<String>,
+    /// Role of the window
+    #[clap(long, default_value = "AXWindow")]
+    role: String,
+    /// Subrole of the window
+    #[clap(long, default_value = "AXStandardWindow")]
+    subrole: String,
+    /// Executable path of the window
+    #[clap(long)]
+    path: Option<String>,
+    /// Path to a static configuration JSON file (default: komorebi.json)
+    #[clap(short, long)]
+    #[clap(value_parser = replace_env_in_path)]
+    config: Option<PathBuf>,
+    /// Print the full rule report as JSON
+    #[clap(long)]
+    json: bool,
+}
+
+#[derive(Parser)]
+struct Replay {
+    /// File written by komorebi --record
+    #[clap(value_parser = replace_env_in_path)]
+    path: PathBuf,
+    /// Print the checkpoint reports as JSON
+    #[clap(long)]
+    json: bool,
+}
+
 #[derive(Parser)]
 struct GlobalWorkAreaOffset {
     /// Size of the left work area offset (set right to left * 2 to maintain right padding)
@@ -535,6 +620,58 @@ pub struct NamedWorkspaceLayoutRule {
     layout: DefaultLayout,
 }
 
+#[derive(Parser)]
+pub struct WorkspaceCustomLayoutRule {
+    /// Monitor index (zero-indexed)
+    monitor: usize,
+
+    /// Workspace index on the specified monitor (zero-indexed)
+    workspace: usize,
+
+    /// The number of window containers on-screen required to trigger this layout rule
+    at_container_count: usize,
+
+    /// JSON or YAML file from which the custom layout definition should be loaded
+    #[clap(value_parser = replace_env_in_path)]
+    path: PathBuf,
+}
+
+#[derive(Parser)]
+pub struct NamedWorkspaceCustomLayoutRule {
+    /// Target workspace name
+    workspace: String,
+
+    /// The number of window containers on-screen required to trigger this layout rule
+    at_container_count: usize,
+
+    /// JSON or YAML file from which the custom layout definition should be loaded
+    #[clap(value_parser = replace_env_in_path)]
+    path: PathBuf,
+}
+
+#[derive(Parser)]
+pub struct WorkspaceCustomLayout {
+    /// Monitor index (zero-indexed)
+    monitor: usize,
+
+    /// Workspace index on the specified monitor (zero-indexed)
+    workspace: usize,
+
+    /// JSON or YAML file from which the custom layout definition should be loaded
+    #[clap(value_parser = replace_env_in_path)]
+    path: PathBuf,
+}
+
+#[derive(Parser)]
+pub struct NamedWorkspaceCustomLayout {
+    /// Target workspace name
+    workspace: String,
+
+    /// JSON or YAML file from which the custom layout definition should be loaded
+    #[clap(value_parser = replace_env_in_path)]
+    path: PathBuf,
+}
+
 #[derive(Parser)]
 struct DisplayIndexPreference {
     /// Preferred monitor index (zero-indexed)
@@ -574,6 +711,26 @@ struct Start {
     // clean_state: bool,
 }
 
+#[derive(Parser)]
+struct Query {
+    /// A built-in query
+    #[clap(value_enum, required_unless_present = "path")]
+    state_query: Option<StateQuery>,
+    /// A path to select from the window manager state, e.g. "monitors[focused].workspaces[*].name"
+    #[clap(long, conflicts_with = "state_query")]
+    path: Option<StatePath>,
+    /// Print the result as JSON
+    #[clap(long)]
+    json: bool,
+}
+
+#[derive(Parser)]
+struct Metrics {
+    /// Output format
+    #[clap(value_enum, short, long, default_value = "json")]
+    format: MetricsFormat,
+}
+
 #[derive(Parser)]
 struct Stop {
     /// Do not restore windows after stopping komorebi
@@ -585,15 +742,22 @@ struct Stop {
 }
 
 #[derive(Parser)]
-struct SaveResize {
-    /// File to which the resize layout dimensions should be saved
+struct Save {
+    /// File to which the session should be saved
+    #[clap(value_parser = replace_env_in_path)]
+    path: PathBuf,
+}
+
+#[derive(Parser)]
+struct Load {
+    /// File from which the session should be loaded
     #[clap(value_parser = replace_env_in_path)]
     path: PathBuf,
 }
 
 #[derive(Parser)]
-struct LoadResize {
-    /// File from which the resize layout dimensions should be loaded
+struct LoadCustomLayout {
+    /// JSON or YAML file from which the custom layout definition should be loaded
     #[clap(value_parser = replace_env_in_path)]
     path: PathBuf,
 }
@@ -605,6 +769,12 @@ struct ReplaceConfiguration {
     path: PathBuf,
 }
 
+#[derive(Parser)]
+struct ActivateProfile {
+    /// Name of a profile from the static configuration file, or nothing to deactivate the active profile
+    profile: Option<String>,
+}
+
 #[derive(Parser)]
 struct EnableAutostart {
     /// Path to a static configuration JSON file
@@ -622,6 +792,30 @@ struct EagerFocus {
     exe: String,
 }
 
+#[derive(Parser)]
+struct FocusSet {
+    /// Name of the workspace set to focus
+    set: String,
+}
+
+#[derive(Parser)]
+struct Mark {
+    /// Label to attach to the focused window
+    mark: String,
+}
+
+#[derive(Parser)]
+struct FocusMark {
+    /// Label attached to the window to focus
+    mark: String,
+}
+
+#[derive(Parser)]
+struct SwapWithMark {
+    /// Label attached to the window to swap with
+    mark: String,
+}
+
 #[derive(Parser)]
 struct ScrollingLayoutColumns {
     /// Desired number of visible columns
@@ -668,6 +862,36 @@ struct BorderOffset {
     offset: i32,
 }
 
+#[derive(Parser)]
+struct StackbarMode {
+    #[clap(value_enum)]
+    mode: komorebi_client::StackbarMode,
+}
+
+#[derive(Parser)]
+struct StackbarLabel {
+    #[clap(value_enum)]
+    label: komorebi_client::StackbarLabel,
+}
+
+#[derive(Parser)]
+struct StackbarHeight {
+    /// Desired height of the stackbar
+    height: i32,
+}
+
+#[derive(Parser)]
+struct StackbarTabWidth {
+    /// Desired width of a stackbar tab
+    width: i32,
+}
+
+#[derive(Parser)]
+struct TransparencyAlpha {
+    /// Alpha
+    alpha: u8,
+}
+
 #[derive(Parser)]
 struct Animation {
     #[clap(value_enum)]
@@ -677,6 +901,22 @@ struct Animation {
     animation_type: Option<komorebi_client::AnimationPrefix>,
 }
 
+#[derive(Parser)]
+struct FocusFollowsMouse {
+    #[clap(value_enum)]
+    boolean_state: BooleanState,
+    /// Implementation of focus follows mouse to use
+    #[clap(value_enum, short, long, default_value = "komorebi")]
+    implementation: komorebi_client::FocusFollowsMouseImplementation,
+}
+
+#[derive(Parser)]
+struct ToggleFocusFollowsMouse {
+    /// Implementation of focus follows mouse to use
+    #[clap(value_enum, short, long, default_value = "komorebi")]
+    implementation: komorebi_client::FocusFollowsMouseImplementation,
+}
+
 #[derive(Parser)]
 struct AnimationDuration {
     /// Desired animation durations in ms
@@ -755,8 +995,11 @@ enum SubCommand {
     Stop(Stop),
     // /// Kill background processes started by komorebic
     // Kill(Kill),
-    // /// Check komorebi configuration and related files for common errors
-    // Check(Check),
+    /// Check komorebi configuration and related files for common errors
+    Check(Check),
+    /// Explain how a window with the given identifiers would be handled by the configured rules
+    #[clap(arg_required_else_help = true)]
+    Explain(Explain),
     /// Show the path to komorebi.json
     #[clap(alias = "config")]
     Configuration,
@@ -783,6 +1026,8 @@ enum SubCommand {
     /// Show information about connected monitors
     #[clap(alias = "monitor-info")]
     MonitorInformation,
+    /// Show command and event latencies, lock wait times and subscriber fan-out times
+    Metrics(Metrics),
     /// Query the current window manager state
     #[clap(arg_required_else_help = true)]
     Query(Query),
@@ -792,30 +1037,33 @@ enum SubCommand {
     /// Unsubscribe from komorebi events
     #[clap(arg_required_else_help = true)]
     UnsubscribeSocket(UnsubscribeSocket),
-    // /// Subscribe to komorebi events using a Named Pipe
-    // #[clap(arg_required_else_help = true)]
-    // #[clap(alias = "subscribe")]
-    // SubscribePipe(SubscribePipe),
-    // /// Unsubscribe from komorebi events
-    // #[clap(arg_required_else_help = true)]
-    // #[clap(alias = "unsubscribe")]
-    // UnsubscribePipe(UnsubscribePipe),
+    /// Subscribe to komorebi events using a FIFO, which is created if it does not exist
+    #[clap(arg_required_else_help = true)]
+    SubscribePipe(SubscribePipe),
+    /// Unsubscribe from komorebi events
+    #[clap(arg_required_else_help = true)]
+    UnsubscribePipe(UnsubscribePipe),
+    /// Print komorebi events to stdout as newline-delimited JSON
+    Subscribe(Subscribe),
     /// Tail komorebi's process logs (cancel with Ctrl-C)
     Log,
-    /// Quicksave the current resize layout dimensions
-    #[clap(alias = "quick-save")]
-    QuickSaveResize,
-    /// Load the last quicksaved resize layout dimensions
-    #[clap(alias = "quick-load")]
-    QuickLoadResize,
-    /// Save the current resize layout dimensions to a file
-    #[clap(arg_required_else_help = true)]
-    #[clap(alias = "save")]
-    SaveResize(SaveResize),
-    /// Load the resize layout dimensions from a file
-    #[clap(arg_required_else_help = true)]
-    #[clap(alias = "load")]
-    LoadResize(LoadResize),
+    /// Replay a recording made with komorebi --record and compare the result with its checkpoints
+    #[clap(arg_required_else_help = true)]
+    Replay(Replay),
+    /// Quicksave the arrangement of windows on every monitor and workspace
+    #[clap(alias = "quick-save-resize")]
+    QuickSave,
+    /// Move windows back into the last quicksaved arrangement
+    #[clap(alias = "quick-load-resize")]
+    QuickLoad,
+    /// Save the arrangement of windows on every monitor and workspace to a file
+    #[clap(arg_required_else_help = true)]
+    #[clap(alias = "save-resize")]
+    Save(Save),
+    /// Move windows back into an arrangement saved to a file
+    #[clap(arg_required_else_help = true)]
+    #[clap(alias = "load-resize")]
+    Load(Load),
     /// Change focus to the window in the specified direction
     #[clap(arg_required_else_help = true)]
     Focus(Focus),
@@ -842,6 +1090,15 @@ enum SubCommand {
     /// Focus the first managed window matching the given executable
     #[clap(arg_required_else_help = true)]
     EagerFocus(EagerFocus),
+    /// Attach a mark to the focused window, taking it from any other window
+    #[clap(arg_required_else_help = true)]
+    Mark(Mark),
+    /// Focus the window with the given mark
+    #[clap(arg_required_else_help = true)]
+    FocusMark(FocusMark),
+    /// Swap the focused container with the container of the window with the given mark
+    #[clap(arg_required_else_help = true)]
+    SwapWithMark(SwapWithMark),
     /// Stack the focused window in the specified direction
     #[clap(arg_required_else_help = true)]
     Stack(Stack),
@@ -926,6 +1183,9 @@ enum SubCommand {
     /// Focus the specified workspace
     #[clap(arg_required_else_help = true)]
     FocusNamedWorkspace(FocusNamedWorkspace),
+    /// Focus every workspace of the specified workspace set
+    #[clap(arg_required_else_help = true)]
+    FocusSet(FocusSet),
     /// Close the focused workspace (must be empty and unnamed)
     CloseWorkspace,
     /// Focus the monitor in the given cycle direction
@@ -937,6 +1197,9 @@ enum SubCommand {
     /// Focus the next empty workspace in the given cycle direction (if one exists)
     #[clap(arg_required_else_help = true)]
     CycleEmptyWorkspace(CycleWorkspace),
+    /// Focus the workspace set in the given cycle direction
+    #[clap(arg_required_else_help = true)]
+    CycleSet(CycleSet),
     /// Move the focused workspace to the specified monitor
     #[clap(arg_required_else_help = true)]
     MoveWorkspaceToMonitor(MoveWorkspaceToMonitor),
@@ -988,10 +1251,9 @@ enum SubCommand {
     ScrollingLayoutColumns(ScrollingLayoutColumns),
     /// Set the layout column and row ratios for the focused workspace
     LayoutRatios(LayoutRatios),
-    // /// Load a custom layout from file for the focused workspace
-    // #[clap(hide = true)]
-    // #[clap(arg_required_else_help = true)]
-    // LoadCustomLayout(LoadCustomLayout),
+    /// Load a custom layout from file for the focused workspace
+    #[clap(arg_required_else_help = true)]
+    LoadCustomLayout(LoadCustomLayout),
     /// Flip the layout on the focused workspace
     #[clap(arg_required_else_help = true)]
     FlipLayout(FlipLayout),
@@ -1005,6 +1267,10 @@ enum SubCommand {
     PromoteWindow(PromoteWindow),
     /// Force the retiling of all managed windows
     Retile,
+    /// Undo the most recent layout-altering command
+    Undo,
+    /// Redo the most recently undone layout-altering command
+    Redo,
     // /// Set the monitor index preference for a monitor identified using its size
     // #[clap(arg_required_else_help = true)]
     // MonitorIndexPreference(MonitorIndexPreference),
@@ -1035,28 +1301,24 @@ enum SubCommand {
     /// Set the layout for the specified workspace
     #[clap(arg_required_else_help = true)]
     NamedWorkspaceLayout(NamedWorkspaceLayout),
-    // /// Set a custom layout for the specified workspace
-    // #[clap(hide = true)]
-    // #[clap(arg_required_else_help = true)]
-    // WorkspaceCustomLayout(WorkspaceCustomLayout),
-    // /// Set a custom layout for the specified workspace
-    // #[clap(hide = true)]
-    // #[clap(arg_required_else_help = true)]
-    // NamedWorkspaceCustomLayout(NamedWorkspaceCustomLayout),
+    /// Set a custom layout for the specified workspace
+    #[clap(arg_required_else_help = true)]
+    WorkspaceCustomLayout(WorkspaceCustomLayout),
+    /// Set a custom layout for the specified workspace
+    #[clap(arg_required_else_help = true)]
+    NamedWorkspaceCustomLayout(NamedWorkspaceCustomLayout),
     /// Add a dynamic layout rule for the specified workspace
     #[clap(arg_required_else_help = true)]
     WorkspaceLayoutRule(WorkspaceLayoutRule),
     /// Add a dynamic layout rule for the specified workspace
     #[clap(arg_required_else_help = true)]
     NamedWorkspaceLayoutRule(NamedWorkspaceLayoutRule),
-    // /// Add a dynamic custom layout for the specified workspace
-    // #[clap(hide = true)]
-    // #[clap(arg_required_else_help = true)]
-    // WorkspaceCustomLayoutRule(WorkspaceCustomLayoutRule),
-    // /// Add a dynamic custom layout for the specified workspace
-    // #[clap(hide = true)]
-    // #[clap(arg_required_else_help = true)]
-    // NamedWorkspaceCustomLayoutRule(NamedWorkspaceCustomLayoutRule),
+    /// Add a dynamic custom layout for the specified workspace
+    #[clap(arg_required_else_help = true)]
+    WorkspaceCustomLayoutRule(WorkspaceCustomLayoutRule),
+    /// Add a dynamic custom layout for the specified workspace
+    #[clap(arg_required_else_help = true)]
+    NamedWorkspaceCustomLayoutRule(NamedWorkspaceCustomLayoutRule),
     /// Clear all dynamic layout rules for the specified workspace
     #[clap(arg_required_else_help = true)]
     ClearWorkspaceLayoutRules(ClearWorkspaceLayoutRules),
@@ -1092,8 +1354,13 @@ enum SubCommand {
     ToggleTiling,
     /// Toggle floating mode for the focused window
     ToggleFloat,
+    /// Toggle whether the focused floating window stays visible when switching workspaces
+    ToggleSticky,
     /// Toggle monocle mode for the focused container
     ToggleMonocle,
+    /// Show the specified scratchpad over the focused workspace, or hide it if it is already shown
+    #[clap(arg_required_else_help = true)]
+    ToggleScratchpad(ToggleScratchpad),
     // /// Toggle native maximization for the focused window
     // ToggleMaximize,
     /// Toggle a lock for the focused container, ensuring it will not be displaced by any new windows
@@ -1107,11 +1374,13 @@ enum SubCommand {
     /// Replace the configuration of a running instance of komorebi from a static configuration file
     #[clap(arg_required_else_help = true)]
     ReplaceConfiguration(ReplaceConfiguration),
-    // /// Reload legacy komorebi.ahk or komorebi.ps1 configurations (if they exist)
-    // ReloadConfiguration,
-    // /// Enable or disable watching of legacy komorebi.ahk or komorebi.ps1 configurations (if they exist)
-    // #[clap(arg_required_else_help = true)]
-    // WatchConfiguration(WatchConfiguration),
+    /// Reload the static configuration file komorebi was started with
+    ReloadConfiguration,
+    /// Apply the overrides of a profile on top of the static configuration file
+    ActivateProfile(ActivateProfile),
+    /// Enable or disable reloading the static configuration file and any referenced applications.json files when they change
+    #[clap(arg_required_else_help = true)]
+    WatchConfiguration(WatchConfiguration),
     // /// For legacy komorebi.ahk or komorebi.ps1 configurations, signal that the final configuration option has been sent
     // CompleteConfiguration,
     // /// DEPRECATED since v0.1.22
@@ -1209,17 +1478,26 @@ enum SubCommand {
     // /// Set the border implementation
     // #[clap(arg_required_else_help = true)]
     // BorderImplementation(BorderImplementation),
-    // /// Set the stackbar mode
-    // #[clap(arg_required_else_help = true)]
-    // StackbarMode(StackbarMode),
-    // /// Enable or disable transparency for unfocused windows
-    // #[clap(arg_required_else_help = true)]
-    // Transparency(Transparency),
-    // /// Set the alpha value for unfocused window transparency
-    // #[clap(arg_required_else_help = true)]
-    // TransparencyAlpha(TransparencyAlpha),
-    // /// Toggle transparency for unfocused windows
-    // ToggleTransparency,
+    /// Set the stackbar mode
+    #[clap(arg_required_else_help = true)]
+    StackbarMode(StackbarMode),
+    /// Set the stackbar tab label
+    #[clap(arg_required_else_help = true)]
+    StackbarLabel(StackbarLabel),
+    /// Set the stackbar height
+    #[clap(arg_required_else_help = true)]
+    StackbarHeight(StackbarHeight),
+    /// Set the stackbar tab width
+    #[clap(arg_required_else_help = true)]
+    StackbarTabWidth(StackbarTabWidth),
+    /// Enable or disable transparency for unfocused windows
+    #[clap(arg_required_else_help = true)]
+    Transparency(Transparency),
+    /// Set the alpha value for unfocused window transparency
+    #[clap(arg_required_else_help = true)]
+    TransparencyAlpha(TransparencyAlpha),
+    /// Toggle transparency for unfocused windows
+    ToggleTransparency,
     /// Enable or disable movement animations
     #[clap(arg_required_else_help = true)]
     Animation(Animation),
@@ -1232,14 +1510,11 @@ enum SubCommand {
     /// Set the ease function for movement animations
     #[clap(arg_required_else_help = true)]
     AnimationStyle(AnimationStyle),
-    // /// Enable or disable focus follows mouse for the operating system
-    // #[clap(hide = true)]
-    // #[clap(arg_required_else_help = true)]
-    // FocusFollowsMouse(FocusFollowsMouse),
-    // /// Toggle focus follows mouse for the operating system
-    // #[clap(hide = true)]
-    // #[clap(arg_required_else_help = true)]
-    // ToggleFocusFollowsMouse(ToggleFocusFollowsMouse),
+    /// Enable or disable focus follows mouse on all workspaces
+    #[clap(arg_required_else_help = true)]
+    FocusFollowsMouse(FocusFollowsMouse),
+    /// Toggle focus follows mouse on all workspaces
+    ToggleFocusFollowsMouse(ToggleFocusFollowsMouse),
     /// Enable or disable mouse follows focus on all workspaces
     #[clap(arg_required_else_help = true)]
     MouseFollowsFocus(MouseFollowsFocus),
@@ -1282,11 +1557,38 @@ enum SubCommand {
     DisableAutostart,
 }
 
-fn print_query(message: &SocketMessage) {
-    match send_query(message) {
-        Ok(response) => println!("{response}"),
-        Err(error) => panic!("{}", error),
+fn send_message(message: &SocketMessage) -> Result<(), ClientError> {
+    send_request(message).map(|_| ())
+}
+
+fn print_query(message: &SocketMessage) -> Result<(), ClientError> {
+    println!("{}", send_request(message)?);
+    Ok(())
+}
+
+/// Prints every notification pushed to a subscriber socket as a line of JSON, stopping after the
+/// first one unless following or when komorebi shuts down
+fn print_notifications(listener: &UnixListener, follow: bool) -> eyre::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+
+    for incoming in listener.incoming() {
+        let mut notification = String::new();
+        incoming?.read_to_string(&mut notification)?;
+
+        // komorebi connects without sending anything when it is shutting down
+        if notification.is_empty() {
+            break;
+        }
+
+        writeln!(stdout, "{}", notification.trim_end())?;
+        stdout.flush()?;
+
+        if !follow {
+            break;
+        }
     }
+
+    Ok(())
 }
 
 fn main() -> eyre::Result<()> {
@@ -1305,11 +1607,6 @@ fn main() -> eyre::Result<()> {
                 "splash",
                 "alt-focus-hack",
                 "identify-border-overflow-application",
-                "load-custom-layout",
-                "workspace-custom-layout",
-                "named-workspace-custom-layout",
-                "workspace-custom-layout-rule",
-                "named-workspace-custom-layout-rule",
                 "focus-follows-mouse",
                 "toggle-focus-follows-mouse",
                 "format-app-specific-configuration",
@@ -1873,10 +2170,11 @@ exit 1
             );
         }
         SubCommand::Stop(args) => {
+            // komorebi exits before it can respond to these
             if args.ignore_restore {
-                send_message(&SocketMessage::StopIgnoreRestore)?;
+                komorebi_client::send_message(&SocketMessage::StopIgnoreRestore)?;
             } else {
-                send_message(&SocketMessage::Stop)?;
+                komorebi_client::send_message(&SocketMessage::Stop)?;
             }
 
             if args.bar {
@@ -1900,6 +2198,106 @@ exit 1
                 println!("{}", static_config.display());
             }
         }
+        SubCommand::Check(args) => {
+            let path = args.path.unwrap_or_else(|| HOME_DIR.join("komorebi.json"));
+            let diagnostics = config_check::check(&path);
+
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&diagnostics)?);
+            } else if diagnostics.is_empty() {
+                println!("no problems found in {}", path.display());
+            } else {
+                for diagnostic in &diagnostics {
+                    println!("{diagnostic}");
+                }
+            }
+
+            let errors = diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.severity == config_check::Severity::Error)
+                .count();
+
+            if errors > 0 {
+                eyre::bail!(
+                    "found {errors} errors and {} warnings in {}",
+                    diagnostics.len() - errors,
+                    path.display()
+                );
+            }
+        }
+        SubCommand::Explain(args) => {
+            let path = args
+                .config
+                .unwrap_or_else(|| HOME_DIR.join("komorebi.json"));
+            let config = komorebi_client::StaticConfig::read(&path)?;
+
+            let window = explain::HypotheticalWindow {
+                exe: args.exe,
+                title: args.title,
+                role: args.role,
+                subrole: args.subrole,
+                path: args.path.unwrap_or_default(),
+            };
+
+            let explanation = explain::explain(&config, &window)?;
+            let rule_debug = &explanation.rule_debug;
+
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&explanation)?);
+            } else {
+                println!(
+                    "{} would {}be managed",
+                    window.exe,
+                    if rule_debug.should_manage { "" } else { "not " }
+                );
+
+                if !rule_debug.has_title && rule_debug.matches_titleless_applications.is_none() {
+                    println!("  the window has no title and is not a titleless application");
+                }
+
+                if let Some(class) = &rule_debug.matches_permaignore_class {
+                    println!("  windows with the role {class} are never managed");
+                }
+
+                if let Some(exe) = &rule_debug.matches_titleless_applications {
+                    println!("  titleless application: {exe}");
+                }
+
+                if let Some(exe) = &rule_debug.matches_tabbed_applications {
+                    println!("  tabbed application: {exe}");
+                }
+
+                for (label, rule) in [
+                    ("ignore rule", &rule_debug.matches_ignore_identifier),
+                    ("manage rule", &rule_debug.matches_managed_override),
+                    ("floating rule", &rule_debug.matches_floating_applications),
+                ] {
+                    if let Some(rule) = rule {
+                        println!("  {label}: {}", serde_json::to_string(rule)?);
+                    }
+                }
+
+                if !rule_debug.should_manage && !explanation.workspace_rules.is_empty() {
+                    println!("  workspace rules are only enforced for managed windows");
+                }
+
+                for (idx, workspace_rule) in explanation.workspace_rules.iter().enumerate() {
+                    println!(
+                        "  {} {} (monitor {}, workspace {}){}: {}",
+                        if idx == 0 { "moved to" } else { "also matches" },
+                        workspace_rule.workspace,
+                        workspace_rule.rule.monitor_index,
+                        workspace_rule.rule.workspace_index,
+                        if workspace_rule.rule.initial_only {
+                            " when it opens"
+                        } else {
+                            ""
+                        },
+                        serde_json::to_string(&workspace_rule.rule.matching_rule)?
+                    );
+                }
+            }
+        }
         SubCommand::DataDirectory => {
             let dir = &*DATA_DIR;
             if dir.exists() {
@@ -1916,6 +2314,46 @@ exit 1
                 println!("{line}");
             }
         }
+        SubCommand::Replay(args) => {
+            let reports = recorder::replay(&args.path)?;
+
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&reports)?);
+            } else {
+                for report in &reports {
+                    println!(
+                        "checkpoint at entry {} ({}ms): {} commands replayed ({} failed), {} events replayed ({} failed, {} skipped), {} differences",
+                        report.entry,
+                        report.elapsed_ms,
+                        report.commands_replayed,
+                        report.commands_failed,
+                        report.events_replayed,
+                        report.events_failed,
+                        report.events_skipped,
+                        report.differences.len()
+                    );
+
+                    for difference in &report.differences {
+                        println!(
+                            "  {}: recorded {}, replayed {}",
+                            difference.path, difference.recorded, difference.replayed
+                        );
+                    }
+                }
+            }
+
+            let diverged = reports
+                .iter()
+                .filter(|report| !report.differences.is_empty())
+                .count();
+
+            if diverged > 0 {
+                eyre::bail!(
+                    "the replay diverged from {diverged} of {} checkpoints",
+                    reports.len()
+                );
+            }
+        }
         SubCommand::Focus(args) => {
             send_message(&SocketMessage::FocusWindow(args.operation_direction))?;
         }
@@ -1943,6 +2381,9 @@ exit 1
         SubCommand::CycleLayout(args) => {
             send_message(&SocketMessage::CycleLayout(args.cycle_direction))?;
         }
+        SubCommand::LoadCustomLayout(args) => {
+            send_message(&SocketMessage::ChangeLayoutCustom(args.path))?;
+        }
         SubCommand::FlipLayout(args) => {
             send_message(&SocketMessage::FlipLayout(args.axis))?;
         }
@@ -1973,9 +2414,15 @@ exit 1
         SubCommand::ToggleMonocle => {
             send_message(&SocketMessage::ToggleMonocle)?;
         }
+        SubCommand::ToggleScratchpad(args) => {
+            send_message(&SocketMessage::ToggleScratchpad(args.name))?;
+        }
         SubCommand::ToggleFloat => {
             send_message(&SocketMessage::ToggleFloat)?;
         }
+        SubCommand::ToggleSticky => {
+            send_message(&SocketMessage::ToggleSticky)?;
+        }
         SubCommand::ToggleWorkspaceLayer => {
             send_message(&SocketMessage::ToggleWorkspaceLayer)?;
         }
@@ -1988,6 +2435,12 @@ exit 1
         SubCommand::Retile => {
             send_message(&SocketMessage::Retile)?;
         }
+        SubCommand::Undo => {
+            send_message(&SocketMessage::Undo)?;
+        }
+        SubCommand::Redo => {
+            send_message(&SocketMessage::Redo)?;
+        }
         SubCommand::Close => {
             send_message(&SocketMessage::Close)?;
         }
@@ -2060,6 +2513,9 @@ exit 1
         SubCommand::FocusNamedWorkspace(args) => {
             send_message(&SocketMessage::FocusNamedWorkspace(args.workspace))?;
         }
+        SubCommand::FocusSet(args) => {
+            send_message(&SocketMessage::FocusWorkspaceSet(args.set))?;
+        }
         SubCommand::CloseWorkspace => {
             send_message(&SocketMessage::CloseWorkspace)?;
         }
@@ -2074,6 +2530,9 @@ exit 1
                 args.cycle_direction,
             ))?;
         }
+        SubCommand::CycleSet(args) => {
+            send_message(&SocketMessage::CycleFocusWorkspaceSet(args.cycle_direction))?;
+        }
         SubCommand::MoveToMonitor(args) => {
             send_message(&SocketMessage::MoveContainerToMonitorNumber(args.target))?;
         }
@@ -2146,19 +2605,37 @@ exit 1
             send_message(&SocketMessage::SwapWorkspacesToMonitorNumber(args.target))?;
         }
         SubCommand::State => {
-            print_query(&SocketMessage::State);
+            print_query(&SocketMessage::State)?;
         }
         SubCommand::GlobalState => {
-            print_query(&SocketMessage::GlobalState);
+            print_query(&SocketMessage::GlobalState)?;
         }
         SubCommand::Query(args) => {
-            print_query(&SocketMessage::Query(args.state_query));
+            if let Some(path) = args.path {
+                let response = send_request(&SocketMessage::QueryPath(path.to_string()))?;
+
+                // strings are printed without quotes unless json output was requested, like `jq -r`
+                match serde_json::from_str::<serde_json::Value>(&response) {
+                    Ok(serde_json::Value::String(value)) if !args.json => println!("{value}"),
+                    _ => println!("{response}"),
+                }
+            } else if let Some(state_query) = args.state_query {
+                if args.json {
+                    print_query(&SocketMessage::QueryJson(state_query))?;
+                } else {
+                    print_query(&SocketMessage::Query(state_query))?;
+                }
+            }
         }
         SubCommand::VisibleWindows => {
-            print_query(&SocketMessage::VisibleWindows);
+            print_query(&SocketMessage::VisibleWindows)?;
         }
         SubCommand::MonitorInformation => {
-            print_query(&SocketMessage::MonitorInformation);
+            print_query(&SocketMessage::MonitorInformation)?;
+        }
+        SubCommand::Metrics(args) => {
+            let response = send_request(&SocketMessage::Metrics(args.format))?;
+            println!("{}", response.trim_end());
         }
         SubCommand::FetchAppSpecificConfiguration => {
             let content = reqwest::blocking::get("https://raw.githubusercontent.com/LGUG2Z/komorebi-application-specific-configuration/master/applications.mac.json")?
@@ -2186,7 +2663,7 @@ exit 1
             send_message(&SocketMessage::SessionFloatRule)?;
         }
         SubCommand::SessionFloatRules => {
-            print_query(&SocketMessage::SessionFloatRules);
+            print_query(&SocketMessage::SessionFloatRules)?;
         }
         SubCommand::ClearSessionFloatRules => {
             send_message(&SocketMessage::ClearSessionFloatRules)?;
@@ -2245,6 +2722,15 @@ exit 1
         SubCommand::EagerFocus(args) => {
             send_message(&SocketMessage::EagerFocus(args.exe))?;
         }
+        SubCommand::Mark(args) => {
+            send_message(&SocketMessage::Mark(args.mark))?;
+        }
+        SubCommand::FocusMark(args) => {
+            send_message(&SocketMessage::FocusMark(args.mark))?;
+        }
+        SubCommand::SwapWithMark(args) => {
+            send_message(&SocketMessage::SwapWithMark(args.mark))?;
+        }
         SubCommand::NewWorkspace => {
             send_message(&SocketMessage::NewWorkspace)?;
         }
@@ -2347,6 +2833,19 @@ exit 1
                 args.value,
             ))?;
         }
+        SubCommand::WorkspaceCustomLayout(args) => {
+            send_message(&SocketMessage::WorkspaceLayoutCustom(
+                args.monitor,
+                args.workspace,
+                args.path,
+            ))?;
+        }
+        SubCommand::NamedWorkspaceCustomLayout(args) => {
+            send_message(&SocketMessage::NamedWorkspaceLayoutCustom(
+                args.workspace,
+                args.path,
+            ))?;
+        }
 
         SubCommand::WorkspaceTiling(args) => {
             send_message(&SocketMessage::WorkspaceTiling(
@@ -2411,6 +2910,21 @@ exit 1
                 args.layout,
             ))?;
         }
+        SubCommand::WorkspaceCustomLayoutRule(args) => {
+            send_message(&SocketMessage::WorkspaceLayoutCustomRule(
+                args.monitor,
+                args.workspace,
+                args.at_container_count,
+                args.path,
+            ))?;
+        }
+        SubCommand::NamedWorkspaceCustomLayoutRule(args) => {
+            send_message(&SocketMessage::NamedWorkspaceLayoutCustomRule(
+                args.workspace,
+                args.at_container_count,
+                args.path,
+            ))?;
+        }
         SubCommand::CrossMonitorMoveBehaviour(args) => {
             send_message(&SocketMessage::CrossMonitorMoveBehaviour(
                 args.move_behaviour,
@@ -2424,6 +2938,15 @@ exit 1
                 args.operation_behaviour,
             ))?;
         }
+        SubCommand::FocusFollowsMouse(args) => {
+            send_message(&SocketMessage::FocusFollowsMouse(
+                args.implementation,
+                args.boolean_state.into(),
+            ))?;
+        }
+        SubCommand::ToggleFocusFollowsMouse(args) => {
+            send_message(&SocketMessage::ToggleFocusFollowsMouse(args.implementation))?;
+        }
         SubCommand::ToggleMouseFollowsFocus => {
             send_message(&SocketMessage::ToggleMouseFollowsFocus)?;
         }
@@ -2468,19 +2991,45 @@ exit 1
         SubCommand::UnsubscribeSocket(args) => {
             send_message(&SocketMessage::RemoveSubscriberSocket(args.socket))?;
         }
+        SubCommand::SubscribePipe(args) => {
+            send_message(&SocketMessage::AddSubscriberPipe(args.named_pipe))?;
+        }
+        SubCommand::UnsubscribePipe(args) => {
+            send_message(&SocketMessage::RemoveSubscriberPipe(args.named_pipe))?;
+        }
+        SubCommand::Subscribe(args) => {
+            let name = args
+                .name
+                .unwrap_or_else(|| format!("komorebic-{}", std::process::id()));
+
+            let listener = komorebi_client::subscribe_with_options(
+                &name,
+                SubscribeOptions {
+                    filter_state_changes: args.filter_state_changes,
+                },
+            )?;
+
+            let printed = print_notifications(&listener, args.follow);
+
+            // best effort, komorebi will also clean up subscriber sockets it can no longer connect to
+            let _ = send_message(&SocketMessage::RemoveSubscriberSocket(name.clone()));
+            let _ = std::fs::remove_file(DATA_DIR.join(&name));
+
+            printed?;
+        }
         SubCommand::GenerateStaticConfig => {
-            print_query(&SocketMessage::GenerateStaticConfig);
+            print_query(&SocketMessage::GenerateStaticConfig)?;
         }
-        SubCommand::QuickSaveResize => {
+        SubCommand::QuickSave => {
             send_message(&SocketMessage::QuickSave)?;
         }
-        SubCommand::QuickLoadResize => {
+        SubCommand::QuickLoad => {
             send_message(&SocketMessage::QuickLoad)?;
         }
-        SubCommand::SaveResize(args) => {
+        SubCommand::Save(args) => {
             send_message(&SocketMessage::Save(args.path))?;
         }
-        SubCommand::LoadResize(args) => {
+        SubCommand::Load(args) => {
             send_message(&SocketMessage::Load(args.path))?;
         }
         SubCommand::DisplayIndexPreference(args) => {
@@ -2492,6 +3041,17 @@ exit 1
         SubCommand::ReplaceConfiguration(args) => {
             send_message(&SocketMessage::ReplaceConfiguration(args.path))?;
         }
+        SubCommand::ReloadConfiguration => {
+            send_message(&SocketMessage::ReloadConfiguration)?;
+        }
+        SubCommand::ActivateProfile(args) => {
+            send_message(&SocketMessage::ActivateProfile(args.profile))?;
+        }
+        SubCommand::WatchConfiguration(args) => {
+            send_message(&SocketMessage::WatchConfiguration(
+                args.boolean_state.into(),
+            ))?;
+        }
         SubCommand::Border(args) => {
             send_message(&SocketMessage::Border(args.boolean_state.into()))?;
         }
@@ -2509,6 +3069,27 @@ exit 1
         SubCommand::BorderOffset(args) => {
             send_message(&SocketMessage::BorderOffset(args.offset))?;
         }
+        SubCommand::StackbarMode(args) => {
+            send_message(&SocketMessage::StackbarMode(args.mode))?;
+        }
+        SubCommand::StackbarLabel(args) => {
+            send_message(&SocketMessage::StackbarLabel(args.label))?;
+        }
+        SubCommand::StackbarHeight(args) => {
+            send_message(&SocketMessage::StackbarHeight(args.height))?;
+        }
+        SubCommand::StackbarTabWidth(args) => {
+            send_message(&SocketMessage::StackbarTabWidth(args.width))?;
+        }
+        SubCommand::Transparency(args) => {
+            send_message(&SocketMessage::Transparency(args.boolean_state.into()))?;
+        }
+        SubCommand::TransparencyAlpha(args) => {
+            send_message(&SocketMessage::TransparencyAlpha(args.alpha))?;
+        }
+        SubCommand::ToggleTransparency => {
+            send_message(&SocketMessage::ToggleTransparency)?;
+        }
         SubCommand::Animation(args) => {
             send_message(&SocketMessage::Animation(
                 args.boolean_state.into(),